@@ -109,6 +109,13 @@ pub fn enum_from_str_derive(input: TokenStream) -> TokenStream {
 ///             C(field_id) => self.c.query(field_id),
 ///         }
 ///     }
+///     fn field_type(field_id: &Self::FieldId) -> FieldType {
+///         match field_id {
+///             A => <f64 as HasFieldType>::FIELD_TYPE,
+///             B => <String as HasFieldType>::FIELD_TYPE,
+///             C(field_id) => <Bar as Queriable>::field_type(field_id),
+///         }
+///     }
 /// }
 #[proc_macro_derive(Queriable, attributes(queriable))]
 pub fn queriable_derive(input: TokenStream) -> TokenStream {
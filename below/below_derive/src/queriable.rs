@@ -137,6 +137,8 @@ struct QueriableFieldProps {
     pub ident: Ident,
     pub variant_name: Ident,
     pub option_type: Option<syn::Type>,
+    /// Type of the field, or the wrapped type if it's an Option.
+    pub base_type: syn::Type,
 }
 
 fn get_queriable_field_props(field: &Field) -> syn::Result<QueriableFieldProps> {
@@ -183,12 +185,14 @@ fn get_queriable_field_props(field: &Field) -> syn::Result<QueriableFieldProps>
         .ok_or_else(|| syn::Error::new(field.span(), "This macro only support named fields"))?;
     let preferred_name = preferred_name.unwrap_or_else(|| ident.clone());
     let variant_name = to_camelcase(&preferred_name);
+    let base_type = option_type.clone().unwrap_or_else(|| field.ty.clone());
     Ok(QueriableFieldProps {
         ignore,
         subquery,
         ident,
         variant_name,
         option_type,
+        base_type,
     })
 }
 
@@ -250,6 +254,21 @@ pub fn queriable_derive_impl(ast: &DeriveInput) -> syn::Result<TokenStream> {
         }
     });
 
+    let field_type_match_arms = all_field_props.iter().map(|field_props| {
+        let variant_name = &field_props.variant_name;
+        let base_type = &field_props.base_type;
+        if field_props.subquery.is_some() {
+            quote! {
+                Self::FieldId::#variant_name(field_id) =>
+                    <#base_type as Queriable>::field_type(field_id),
+            }
+        } else {
+            quote! {
+                Self::FieldId::#variant_name => <#base_type as HasFieldType>::FIELD_TYPE,
+            }
+        }
+    });
+
     Ok(quote! {
         #[derive(
             Clone,
@@ -275,6 +294,12 @@ pub fn queriable_derive_impl(ast: &DeriveInput) -> syn::Result<TokenStream> {
                     _ => unreachable!(),
                 }
             }
+            fn field_type(field_id: &Self::FieldId) -> FieldType {
+                match field_id {
+                    #(#field_type_match_arms)*
+                    _ => unreachable!(),
+                }
+            }
         }
     })
 }
@@ -33,6 +33,9 @@ use thiserror::Error;
 mod types;
 pub use types::*;
 
+mod v1;
+pub use v1::CgroupV1Reader;
+
 #[cfg(test)]
 mod test;
 
@@ -48,6 +51,8 @@ pub enum Error {
     UnexpectedLine(PathBuf, String),
     #[error("Not cgroup2 filesystem: {0:?}")]
     NotCgroup2(PathBuf),
+    #[error("No cgroup1 controller hierarchies found: {0:?}")]
+    NotCgroup1(PathBuf),
     #[error("Pressure metrics not supported: {0:?}")]
     PressureNotSupported(PathBuf),
 }
@@ -324,6 +329,25 @@ impl CgroupReader {
         }
     }
 
+    /// Read a file that has one pid per line, e.g. cgroup.procs. An empty file
+    /// yields an empty list.
+    fn read_pid_list_file(&self, file_name: &str) -> Result<Vec<u32>> {
+        let file = self
+            .dir
+            .open_file(file_name)
+            .map_err(|e| self.io_error(file_name, e))?;
+        let buf_reader = BufReader::new(file);
+        let mut pids = Vec::new();
+        for line in buf_reader.lines() {
+            let line = line.map_err(|e| self.io_error(file_name, e))?;
+            pids.push(
+                line.parse::<u32>()
+                    .map_err(|_| self.unexpected_line(file_name, line.clone()))?,
+            );
+        }
+        Ok(pids)
+    }
+
     /// Read a single line from a file representing a space separated list of
     /// cgroup controllers.
     fn read_singleline_controllers(&self, file_name: &str) -> Result<BTreeSet<String>> {
@@ -344,6 +368,16 @@ impl CgroupReader {
         self.read_singleline_controllers("cgroup.subtree_control")
     }
 
+    /// Read cgroup.procs - returning the pids of processes in the cgroup
+    pub fn read_cgroup_procs(&self) -> Result<Vec<u32>> {
+        self.read_pid_list_file("cgroup.procs")
+    }
+
+    /// Read cgroup.threads - returning the tids of threads in the cgroup
+    pub fn read_cgroup_threads(&self) -> Result<Vec<u32>> {
+        self.read_pid_list_file("cgroup.threads")
+    }
+
     /// Read pids.current - returning current cgroup number of processes
     pub fn read_pids_current(&self) -> Result<u64> {
         self.read_singleline_file("pids.current")
@@ -623,9 +657,10 @@ trait KVRead: Sized {
 // This macro generates the read() method for the given struct, file
 // name, and keys. If a line does not exist in the file then the
 // corresponding field is left as `None`. If lines include fields that
-// are not listed, they are ignored.
+// are not listed, they are ignored, or collected into the given map
+// field if one is specified.
 macro_rules! key_values_format {
-    ($struct:ident; $file:expr; [ $( $field:ident ),+ ]) => (
+    ($struct:ident; $file:expr; [ $( $field:ident ),+ ] $(; others => $others:ident)?) => (
         impl KVRead for $struct {
             fn read(r: &CgroupReader) -> Result<$struct> {
                 let mut s = $struct::default();
@@ -642,7 +677,9 @@ macro_rules! key_values_format {
                     let val = items[1].parse::<_>().map_err(|_| r.unexpected_line(file_name, line.clone()))?;
                     match key.as_ref() {
                         $(stringify!($field) => s.$field = Some(val),)*
-                        _ => (),
+                        _ => {
+                            $(s.$others.insert(key.to_owned(), val);)?
+                        }
                     };
                 }
                 if s == $struct::default() {
@@ -702,8 +739,17 @@ key_values_format!(MemoryStat; memory.stat; [
     pglazyfree,
     pglazyfreed,
     thp_fault_alloc,
-    thp_collapse_alloc
-]);
+    thp_collapse_alloc,
+    pgscan_kswapd,
+    pgscan_direct,
+    pgsteal_khugepaged,
+    thp_swpout,
+    zswpin,
+    zswpout,
+    percpu,
+    sec_pagetables,
+    hugetlb
+]; others => raw_stats);
 
 key_values_format!(MemoryEvents; memory.events; [
     low,
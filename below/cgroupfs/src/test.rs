@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::ffi::OsStr;
 use std::fs::File;
@@ -25,6 +26,7 @@ use paste::paste;
 use tempfile::TempDir;
 
 use crate::CgroupReader;
+use crate::CgroupV1Reader;
 use crate::CpuMax;
 use crate::Cpuset;
 use crate::Error;
@@ -59,6 +61,11 @@ impl TestCgroup {
         CgroupReader::new(self.path().to_path_buf())
     }
 
+    fn get_v1_reader(&self) -> CgroupV1Reader {
+        CgroupV1Reader::new_inner(self.path().to_path_buf(), false)
+            .expect("Failed to construct v1 reader")
+    }
+
     fn create_file_with_content<P: AsRef<Path>>(&self, p: P, content: &[u8]) {
         let path = self.path().join(p);
         let mut file =
@@ -148,6 +155,22 @@ singleline_integer_or_max_test!(read_memory_swap_max, "memory.swap.max");
 singleline_integer_or_max_test!(read_memory_zswap_max, "memory.zswap.max");
 
 test_success!(read_pids_current, "pids.current", b"10000\n", 10000);
+test_success!(
+    read_cgroup_procs,
+    "cgroup.procs",
+    b"1\n42\n1000\n",
+    vec![1, 42, 1000],
+    1
+);
+test_success!(read_cgroup_procs, "cgroup.procs", b"", Vec::<u32>::new(), 2);
+test_failure!(read_cgroup_procs, "cgroup.procs", b"abc\n");
+test_success!(
+    read_cgroup_threads,
+    "cgroup.threads",
+    b"1\n42\n43\n",
+    vec![1, 42, 43]
+);
+test_failure!(read_cgroup_threads, "cgroup.threads", b"-1\n");
 test_success!(read_cpu_weight, "cpu.weight", b"10000\n", 10000);
 test_failure!(read_cpu_weight, "cpu.weight", b"5000000000\n");
 test_success!(
@@ -343,6 +366,27 @@ fn test_memory_stat_success() {
     assert_eq!(val.slab.expect("Failed to populate slab field"), 1234);
 }
 
+#[test]
+fn test_memory_stat_unknown_keys() {
+    let cgroup = TestCgroup::new();
+    cgroup.create_file_with_content(
+        "memory.stat",
+        b"slab 1234\npgscan_kswapd 10\nzswpout 20\nsome_future_key 30\n",
+    );
+
+    let cgroup_reader = cgroup.get_reader();
+    let val = cgroup_reader
+        .read_memory_stat()
+        .expect("Failed to read memory.stat");
+    assert_eq!(val.slab, Some(1234));
+    assert_eq!(val.pgscan_kswapd, Some(10));
+    assert_eq!(val.zswpout, Some(20));
+    assert_eq!(
+        val.raw_stats,
+        BTreeMap::from([("some_future_key".to_owned(), 30)])
+    );
+}
+
 #[test]
 fn test_memory_stat_overflow() {
     let cgroup = TestCgroup::new();
@@ -951,3 +995,157 @@ fn test_memory_numa_stat_invalid_format() {
         _ => panic!("Got unexpected error type: {}", err),
     }
 }
+
+#[test]
+fn test_v1_no_hierarchies() {
+    let cgroup = TestCgroup::new();
+    let err = CgroupV1Reader::new_inner(cgroup.path().to_path_buf(), false)
+        .err()
+        .expect("Did not fail to construct v1 reader");
+    match err {
+        Error::NotCgroup1(_) => {}
+        _ => panic!("Got unexpected error type: {}", err),
+    }
+}
+
+#[test]
+fn test_v1_cpu_stat() {
+    let cgroup = TestCgroup::new();
+    cgroup.create_child("cpu");
+    cgroup.create_child("cpuacct");
+    cgroup.create_file_with_content("cpuacct/cpuacct.usage", b"1234567\n");
+    cgroup.create_file_with_content("cpuacct/cpuacct.stat", b"user 2\nsystem 3\n");
+    cgroup.create_file_with_content(
+        "cpu/cpu.stat",
+        b"nr_periods 10\nnr_throttled 4\nthrottled_time 5000\n",
+    );
+    cgroup.create_file_with_content("cpu/cpu.cfs_quota_us", b"-1\n");
+    cgroup.create_file_with_content("cpu/cpu.cfs_period_us", b"100000\n");
+
+    let cgroup_reader = cgroup.get_v1_reader();
+    let val = cgroup_reader
+        .read_cpu_stat()
+        .expect("Failed to read cpu stat");
+    let usec_per_tick = val.user_usec.expect("Failed to populate user_usec") / 2;
+    assert_eq!(val.usage_usec, Some(1234));
+    assert_eq!(val.system_usec, Some(3 * usec_per_tick));
+    assert_eq!(val.nr_periods, Some(10));
+    assert_eq!(val.nr_throttled, Some(4));
+    assert_eq!(val.throttled_usec, Some(5));
+    assert_eq!(
+        cgroup_reader
+            .read_cpu_max()
+            .expect("Failed to read cpu max"),
+        CpuMax {
+            max_usec: -1,
+            period_usec: 100000
+        }
+    );
+}
+
+#[test]
+fn test_v1_memory() {
+    let cgroup = TestCgroup::new();
+    cgroup.create_child("memory");
+    cgroup.create_file_with_content("memory/memory.usage_in_bytes", b"1000\n");
+    cgroup.create_file_with_content("memory/memory.memsw.usage_in_bytes", b"1500\n");
+    cgroup.create_file_with_content("memory/memory.limit_in_bytes", b"9223372036854771712\n");
+    cgroup.create_file_with_content(
+        "memory/memory.stat",
+        b"rss 1\ncache 2\ntotal_rss 10\ntotal_cache 20\ntotal_pgmajfault 30\n",
+    );
+    cgroup.create_file_with_content("memory/memory.failcnt", b"7\n");
+    cgroup.create_file_with_content(
+        "memory/memory.oom_control",
+        b"oom_kill_disable 0\nunder_oom 0\noom_kill 2\n",
+    );
+
+    let cgroup_reader = cgroup.get_v1_reader();
+    assert_eq!(cgroup_reader.read_memory_current().unwrap(), 1000);
+    assert_eq!(cgroup_reader.read_memory_swap_current().unwrap(), 500);
+    assert_eq!(cgroup_reader.read_memory_max().unwrap(), -1);
+    let stat = cgroup_reader
+        .read_memory_stat()
+        .expect("Failed to read memory.stat");
+    assert_eq!(stat.anon, Some(10));
+    assert_eq!(stat.file, Some(20));
+    assert_eq!(stat.pgmajfault, Some(30));
+    assert_eq!(stat.slab, None);
+    let events = cgroup_reader
+        .read_memory_events()
+        .expect("Failed to read memory events");
+    assert_eq!(events.max, Some(7));
+    assert_eq!(events.oom_kill, Some(2));
+    assert_eq!(events.oom, None);
+
+    // pids hierarchy is not mounted
+    match cgroup_reader.read_pids_current() {
+        Err(Error::IoError(_, e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+        res => panic!("Got unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn test_v1_io_stat() {
+    let cgroup = TestCgroup::new();
+    cgroup.create_child("blkio");
+    cgroup.create_file_with_content(
+        "blkio/blkio.throttle.io_service_bytes_recursive",
+        b"8:0 Read 100\n8:0 Write 200\n8:0 Sync 300\n8:0 Total 300\nTotal 300\n",
+    );
+    cgroup.create_file_with_content(
+        "blkio/blkio.throttle.io_serviced_recursive",
+        b"8:0 Read 1\n8:0 Write 2\n8:0 Discard 3\nTotal 6\n",
+    );
+
+    let cgroup_reader = cgroup.get_v1_reader();
+    let val = cgroup_reader
+        .read_io_stat()
+        .expect("Failed to read io stat");
+    let dev = &val["8:0"];
+    assert_eq!(val.len(), 1);
+    assert_eq!(dev.rbytes, Some(100));
+    assert_eq!(dev.wbytes, Some(200));
+    assert_eq!(dev.dbytes, None);
+    assert_eq!(dev.rios, Some(1));
+    assert_eq!(dev.wios, Some(2));
+    assert_eq!(dev.dios, Some(3));
+}
+
+#[test]
+fn test_v1_child_cgroup_iter() {
+    let cgroup = TestCgroup::new();
+    cgroup.create_child("memory");
+    cgroup.create_child("pids");
+    cgroup.create_child("memory/system.slice");
+    cgroup.create_child("memory/user.slice");
+    cgroup.create_child("pids/system.slice");
+    cgroup.create_file_with_content("pids/system.slice/pids.current", b"3\n");
+
+    let cgroup_reader = cgroup.get_v1_reader();
+    let children = cgroup_reader
+        .child_cgroup_iter()
+        .expect("Failed to iterate children")
+        .collect::<Vec<_>>();
+    assert_eq!(
+        children.iter().map(|c| c.name()).collect::<Vec<_>>(),
+        vec![Path::new("system.slice"), Path::new("user.slice")]
+    );
+    assert_eq!(children[0].read_pids_current().unwrap(), 3);
+    assert!(children[1].read_pids_current().is_err());
+}
+
+#[test]
+fn test_v1_cgroup_procs() {
+    let cgroup = TestCgroup::new();
+    cgroup.create_child("memory");
+    cgroup.create_file_with_content("memory/cgroup.procs", b"1\n20\n");
+    cgroup.create_file_with_content("memory/tasks", b"1\n20\n21\n");
+
+    let cgroup_reader = cgroup.get_v1_reader();
+    assert_eq!(cgroup_reader.read_cgroup_procs().unwrap(), vec![1, 20]);
+    assert_eq!(
+        cgroup_reader.read_cgroup_threads().unwrap(),
+        vec![1, 20, 21]
+    );
+}
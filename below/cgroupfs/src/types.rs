@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
 use serde::Deserialize;
@@ -88,6 +89,19 @@ pub struct MemoryStat {
     pub pglazyfreed: Option<u64>,
     pub thp_fault_alloc: Option<u64>,
     pub thp_collapse_alloc: Option<u64>,
+    pub pgscan_kswapd: Option<u64>,
+    pub pgscan_direct: Option<u64>,
+    pub pgsteal_khugepaged: Option<u64>,
+    pub thp_swpout: Option<u64>,
+    pub zswpin: Option<u64>,
+    pub zswpout: Option<u64>,
+    pub percpu: Option<u64>,
+    pub sec_pagetables: Option<u64>,
+    pub hugetlb: Option<u64>,
+    /// Keys not listed above, so that stats added by newer kernels are
+    /// still recorded. Defaulted for samples stored before it existed.
+    #[serde(default)]
+    pub raw_stats: BTreeMap<String, u64>,
 }
 
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
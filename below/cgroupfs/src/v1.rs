@@ -0,0 +1,379 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reader for cgroup1 (and hybrid) hierarchies.
+//!
+//! cgroup1 mounts each controller as a separate hierarchy under the cgroup
+//! root (e.g. `/sys/fs/cgroup/memory`). `CgroupV1Reader` reads the same
+//! relative cgroup path from each of the cpu, cpuacct, memory, blkio and pids
+//! hierarchies and translates the cgroup1 interface files into the cgroup2
+//! types returned by `CgroupReader`. Only values whose semantics match the
+//! cgroup2 interface are translated.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+
+use nix::sys::statfs::fstatfs;
+use nix::sys::statfs::CGROUP_SUPER_MAGIC;
+use nix::unistd::sysconf;
+use nix::unistd::SysconfVar;
+use openat::Dir;
+
+use crate::CgroupReader;
+use crate::CpuMax;
+use crate::CpuStat;
+use crate::Error;
+use crate::IoStat;
+use crate::MemoryEvents;
+use crate::MemoryStat;
+use crate::Result;
+use crate::DEFAULT_CG_ROOT;
+
+/// cgroup1 reports an unlimited memory limit as PAGE_COUNTER_MAX rounded
+/// down to the page size, which lands within one (possibly 64K) page of
+/// i64::MAX.
+const MEMORY_UNLIMITED_THRESHOLD: u64 = i64::MAX as u64 - (1 << 16);
+
+#[derive(Clone, Copy)]
+enum Controller {
+    Cpu,
+    Cpuacct,
+    Memory,
+    Blkio,
+    Pids,
+}
+
+/// Controller hierarchies read, by their directory name under the cgroup
+/// root. `cpu` and `cpuacct` are usually symlinks to a co-mounted
+/// `cpu,cpuacct` hierarchy.
+const CONTROLLERS: [(Controller, &str); 5] = [
+    (Controller::Cpu, "cpu"),
+    (Controller::Cpuacct, "cpuacct"),
+    (Controller::Memory, "memory"),
+    (Controller::Blkio, "blkio"),
+    (Controller::Pids, "pids"),
+];
+
+pub struct CgroupV1Reader {
+    relative_path: PathBuf,
+    controllers: [Option<CgroupReader>; CONTROLLERS.len()],
+}
+
+impl CgroupV1Reader {
+    pub fn new(root: PathBuf) -> Result<CgroupV1Reader> {
+        CgroupV1Reader::new_inner(root, true)
+    }
+
+    pub(crate) fn new_inner(root: PathBuf, validate: bool) -> Result<CgroupV1Reader> {
+        let mut controllers: [Option<CgroupReader>; CONTROLLERS.len()] = Default::default();
+        for (controller, name) in CONTROLLERS {
+            let path = root.join(name);
+            let dir = match Dir::open(&path) {
+                Ok(dir) => dir,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(Error::IoError(path, e)),
+            };
+
+            // Skip anything that is not a cgroup1 hierarchy, e.g. the
+            // cgroup2 `unified` mount of a hybrid setup
+            if validate {
+                match fstatfs(&dir) {
+                    Ok(s) if s.filesystem_type() == CGROUP_SUPER_MAGIC => {}
+                    Ok(_) => continue,
+                    Err(e) => {
+                        return Err(Error::IoError(
+                            path,
+                            std::io::Error::new(
+                                ErrorKind::Other,
+                                format!("Failed to fstatfs: {}", e),
+                            ),
+                        ));
+                    }
+                }
+            }
+
+            controllers[controller as usize] = Some(CgroupReader {
+                relative_path: PathBuf::new(),
+                dir,
+            });
+        }
+
+        if controllers.iter().all(Option::is_none) {
+            return Err(Error::NotCgroup1(root));
+        }
+
+        Ok(CgroupV1Reader {
+            relative_path: PathBuf::new(),
+            controllers,
+        })
+    }
+
+    pub fn root() -> Result<CgroupV1Reader> {
+        CgroupV1Reader::new(Path::new(DEFAULT_CG_ROOT).to_path_buf())
+    }
+
+    /// Returns the cgroup name (e.g. the path relative to the cgroup root)
+    /// Invoking this on the root cgroup will return an empty path
+    pub fn name(&self) -> &Path {
+        &self.relative_path
+    }
+
+    /// Returns the reader for a controller hierarchy, or a NotFound error
+    /// for `file_name` if the hierarchy is not mounted or does not contain
+    /// this cgroup.
+    fn controller(&self, controller: Controller, file_name: &str) -> Result<&CgroupReader> {
+        self.controllers[controller as usize]
+            .as_ref()
+            .ok_or_else(|| {
+                let mut p = self.relative_path.clone();
+                p.push(file_name);
+                Error::IoError(p, std::io::Error::from(ErrorKind::NotFound))
+            })
+    }
+
+    /// Returns the reader of any hierarchy containing this cgroup, for files
+    /// that exist in every hierarchy such as cgroup.procs.
+    fn any_controller(&self, file_name: &str) -> Result<&CgroupReader> {
+        match self.controllers.iter().flatten().next() {
+            Some(reader) => Ok(reader),
+            None => self.controller(Controller::Memory, file_name),
+        }
+    }
+
+    /// Read cgroup.procs - returning the pids of processes in the cgroup
+    pub fn read_cgroup_procs(&self) -> Result<Vec<u32>> {
+        self.any_controller("cgroup.procs")?
+            .read_pid_list_file("cgroup.procs")
+    }
+
+    /// Read tasks - returning the tids of threads in the cgroup
+    pub fn read_cgroup_threads(&self) -> Result<Vec<u32>> {
+        self.any_controller("tasks")?.read_pid_list_file("tasks")
+    }
+
+    /// Read cpuacct.usage, cpuacct.stat and cpu.stat - returning the cpu
+    /// consumption and throttling statistics in cgroup2 units
+    pub fn read_cpu_stat(&self) -> Result<CpuStat> {
+        let cpuacct = self.controller(Controller::Cpuacct, "cpuacct.usage")?;
+        let usage_nsec: u64 = cpuacct.read_singleline_file("cpuacct.usage")?;
+        // cpuacct.stat is in USER_HZ
+        let usec_per_tick = match sysconf(SysconfVar::CLK_TCK) {
+            Ok(Some(ticks)) if ticks > 0 => 1_000_000 / ticks as u64,
+            _ => 10_000,
+        };
+        let ticks = read_key_values(cpuacct, "cpuacct.stat")?;
+        let throttling = match self.controllers[Controller::Cpu as usize].as_ref() {
+            Some(cpu) => read_key_values(cpu, "cpu.stat")?,
+            None => BTreeMap::new(),
+        };
+        Ok(CpuStat {
+            usage_usec: Some(usage_nsec / 1000),
+            user_usec: ticks.get("user").map(|v| v * usec_per_tick),
+            system_usec: ticks.get("system").map(|v| v * usec_per_tick),
+            nr_periods: throttling.get("nr_periods").copied(),
+            nr_throttled: throttling.get("nr_throttled").copied(),
+            throttled_usec: throttling.get("throttled_time").map(|v| v / 1000),
+        })
+    }
+
+    /// Read cpu.cfs_quota_us and cpu.cfs_period_us
+    pub fn read_cpu_max(&self) -> Result<CpuMax> {
+        let cpu = self.controller(Controller::Cpu, "cpu.cfs_quota_us")?;
+        let quota: i64 = cpu.read_singleline_file("cpu.cfs_quota_us")?;
+        Ok(CpuMax {
+            max_usec: if quota < 0 { -1 } else { quota },
+            period_usec: cpu.read_singleline_file("cpu.cfs_period_us")?,
+        })
+    }
+
+    /// Read pids.current - returning current cgroup number of processes
+    pub fn read_pids_current(&self) -> Result<u64> {
+        self.controller(Controller::Pids, "pids.current")?
+            .read_pids_current()
+    }
+
+    /// Read pids.max - returning max cgroup number of processes
+    pub fn read_pids_max(&self) -> Result<i64> {
+        self.controller(Controller::Pids, "pids.max")?
+            .read_pids_max()
+    }
+
+    /// Read memory.usage_in_bytes - returning current cgroup memory
+    /// consumption in bytes
+    pub fn read_memory_current(&self) -> Result<u64> {
+        self.controller(Controller::Memory, "memory.usage_in_bytes")?
+            .read_singleline_file("memory.usage_in_bytes")
+    }
+
+    /// Read memory.memsw.usage_in_bytes - returning current cgroup swap
+    /// consumption in bytes. Only available with swap accounting enabled.
+    pub fn read_memory_swap_current(&self) -> Result<u64> {
+        let memory = self.controller(Controller::Memory, "memory.memsw.usage_in_bytes")?;
+        let memsw: u64 = memory.read_singleline_file("memory.memsw.usage_in_bytes")?;
+        Ok(memsw.saturating_sub(self.read_memory_current()?))
+    }
+
+    /// Read memory.limit_in_bytes - returning memory limit in bytes
+    /// Will return -1 if the cgroup is unlimited
+    pub fn read_memory_max(&self) -> Result<i64> {
+        let limit: u64 = self
+            .controller(Controller::Memory, "memory.limit_in_bytes")?
+            .read_singleline_file("memory.limit_in_bytes")?;
+        if limit >= MEMORY_UNLIMITED_THRESHOLD {
+            Ok(-1)
+        } else {
+            Ok(limit as i64)
+        }
+    }
+
+    /// Read memory.stat - returning the hierarchical (`total_`) memory
+    /// statistics that have a cgroup2 equivalent
+    pub fn read_memory_stat(&self) -> Result<MemoryStat> {
+        let file_name = "memory.stat";
+        let memory = self.controller(Controller::Memory, file_name)?;
+        let stat = read_key_values(memory, file_name)?;
+        let total = |key: &str| stat.get(&format!("total_{}", key)).copied();
+        let s = MemoryStat {
+            anon: total("rss"),
+            file: total("cache"),
+            shmem: total("shmem"),
+            file_mapped: total("mapped_file"),
+            file_dirty: total("dirty"),
+            file_writeback: total("writeback"),
+            anon_thp: total("rss_huge"),
+            inactive_anon: total("inactive_anon"),
+            active_anon: total("active_anon"),
+            inactive_file: total("inactive_file"),
+            active_file: total("active_file"),
+            unevictable: total("unevictable"),
+            pgfault: total("pgfault"),
+            pgmajfault: total("pgmajfault"),
+            ..Default::default()
+        };
+        if s == MemoryStat::default() {
+            return Err(memory.invalid_file_format(file_name));
+        }
+        Ok(s)
+    }
+
+    /// Read memory.failcnt and memory.oom_control - returning the number of
+    /// times the memory limit was hit and the number of OOM kills
+    pub fn read_memory_events(&self) -> Result<MemoryEvents> {
+        let memory = self.controller(Controller::Memory, "memory.failcnt")?;
+        let oom_control = read_key_values(memory, "memory.oom_control")?;
+        Ok(MemoryEvents {
+            max: Some(memory.read_singleline_file("memory.failcnt")?),
+            // Only reported since Linux 4.13
+            oom_kill: oom_control.get("oom_kill").copied(),
+            ..Default::default()
+        })
+    }
+
+    /// Read blkio.throttle.io_service_bytes_recursive and
+    /// blkio.throttle.io_serviced_recursive - returning a map from device
+    /// number to io statistics
+    pub fn read_io_stat(&self) -> Result<BTreeMap<String, IoStat>> {
+        let bytes_file = "blkio.throttle.io_service_bytes_recursive";
+        let ios_file = "blkio.throttle.io_serviced_recursive";
+        let blkio = self.controller(Controller::Blkio, bytes_file)?;
+        let mut map: BTreeMap<String, IoStat> = BTreeMap::new();
+        for (file_name, is_bytes) in [(bytes_file, true), (ios_file, false)] {
+            let file = blkio
+                .dir
+                .open_file(file_name)
+                .map_err(|e| blkio.io_error(file_name, e))?;
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(|e| blkio.io_error(file_name, e))?;
+                // e.g. "8:0 Read 1234", terminated by "Total 5678"
+                let items = line.split_whitespace().collect::<Vec<_>>();
+                if items.len() != 3 {
+                    continue;
+                }
+                let val = items[2]
+                    .parse::<u64>()
+                    .map_err(|_| blkio.unexpected_line(file_name, line.clone()))?;
+                let s = map.entry(items[0].to_owned()).or_default();
+                match (items[1], is_bytes) {
+                    ("Read", true) => s.rbytes = Some(val),
+                    ("Write", true) => s.wbytes = Some(val),
+                    ("Discard", true) => s.dbytes = Some(val),
+                    ("Read", false) => s.rios = Some(val),
+                    ("Write", false) => s.wios = Some(val),
+                    ("Discard", false) => s.dios = Some(val),
+                    _ => {}
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    /// Return an iterator over child cgroups. A child is included if it
+    /// exists in any of the controller hierarchies.
+    pub fn child_cgroup_iter(&self) -> Result<impl Iterator<Item = CgroupV1Reader>> {
+        let mut children: BTreeMap<OsString, CgroupV1Reader> = BTreeMap::new();
+        for (controller, _) in CONTROLLERS {
+            let reader = match self.controllers[controller as usize].as_ref() {
+                Some(reader) => reader,
+                None => continue,
+            };
+            for child in reader.child_cgroup_iter()? {
+                let name = match child.name().file_name() {
+                    Some(name) => name.to_owned(),
+                    None => continue,
+                };
+                let mut relative_path = self.relative_path.clone();
+                relative_path.push(&name);
+                children
+                    .entry(name)
+                    .or_insert_with(|| CgroupV1Reader {
+                        relative_path,
+                        controllers: Default::default(),
+                    })
+                    .controllers[controller as usize] = Some(child);
+            }
+        }
+        Ok(children.into_values())
+    }
+}
+
+/// Read a `key value` formatted file such as memory.stat into a map. Unlike
+/// cgroup2, the cgroup1 keys vary between controllers and kernel versions,
+/// so they are all returned and picked by the caller.
+fn read_key_values(r: &CgroupReader, file_name: &str) -> Result<BTreeMap<String, u64>> {
+    let mut map = BTreeMap::new();
+    let file = r
+        .dir
+        .open_file(file_name)
+        .map_err(|e| r.io_error(file_name, e))?;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| r.io_error(file_name, e))?;
+        let items = line.split_whitespace().collect::<Vec<_>>();
+        if items.len() != 2 {
+            return Err(r.unexpected_line(file_name, line));
+        }
+        let val = items[1]
+            .parse::<u64>()
+            .map_err(|_| r.unexpected_line(file_name, line.clone()))?;
+        map.insert(items[0].to_owned(), val);
+    }
+    if map.is_empty() {
+        return Err(r.invalid_file_format(file_name));
+    }
+    Ok(map)
+}
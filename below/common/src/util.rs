@@ -20,6 +20,7 @@ use std::time::UNIX_EPOCH;
 use chrono::prelude::*;
 
 const BELOW_RC: &str = "/.config/below/belowrc";
+const BELOW_CONFIG_DIR: &str = "/.config/below";
 
 /// Execute an expression every n times. For example
 /// `every_n!(1 + 2, println!("I'm mod 3")` will print on the 1st,
@@ -153,6 +154,16 @@ pub fn get_belowrc_filename() -> String {
     )
 }
 
+/// Get the directory of the belowrc file, which also holds the saved views
+/// and bookmarks of the below view.
+pub fn get_below_config_dir() -> String {
+    format!(
+        "{}{}",
+        std::env::var("HOME").expect("Fail to obtain HOME env var"),
+        BELOW_CONFIG_DIR
+    )
+}
+
 /// The dump section key for belowrc
 pub fn get_belowrc_dump_section_key() -> &'static str {
     "dump"
@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+
+use model::BtrfsModel;
 use model::BtrfsModelFieldId;
 
 use super::*;
@@ -20,6 +23,7 @@ pub struct Btrfs {
     opts: GeneralOpt,
     select: Option<BtrfsModelFieldId>,
     fields: Vec<BtrfsField>,
+    filter_expr: Option<FilterExpr<BtrfsModelFieldId>>,
 }
 
 impl Btrfs {
@@ -27,12 +31,59 @@ impl Btrfs {
         opts: &GeneralOpt,
         select: Option<BtrfsModelFieldId>,
         fields: Vec<BtrfsField>,
+        filter_expr: Option<FilterExpr<BtrfsModelFieldId>>,
     ) -> Self {
         Self {
             opts: opts.to_owned(),
             select,
             fields,
+            filter_expr,
+        }
+    }
+
+    /// Subvolumes to dump, after filtering, sorting and --top.
+    fn get_btrfs_items<'a>(
+        &self,
+        btrfs_items_ref: &'a BTreeMap<String, BtrfsModel>,
+    ) -> Vec<&'a BtrfsModel> {
+        let mut btrfs_items: Vec<_> = btrfs_items_ref
+            .iter()
+            .filter_map(
+                |(_, model)| match (self.select.as_ref(), self.opts.filter.as_ref()) {
+                    (Some(field_id), Some(filter))
+                        if !filter.is_match(
+                            &model
+                                .query(field_id)
+                                .map_or("?".to_owned(), |v| v.to_string()),
+                        ) =>
+                    {
+                        None
+                    }
+                    _ => Some(model),
+                },
+            )
+            .filter(|model| {
+                self.filter_expr
+                    .as_ref()
+                    .map_or(true, |expr| expr.eval(model))
+            })
+            .collect();
+
+        if let Some(field_id) = &self.select {
+            if self.opts.sort {
+                model::sort_queriables(&mut btrfs_items, field_id, false);
+            }
+
+            if self.opts.rsort {
+                model::sort_queriables(&mut btrfs_items, field_id, true);
+            }
+
+            if (self.opts.sort || self.opts.rsort) && self.opts.top != 0 {
+                btrfs_items.truncate(self.opts.top as usize);
+            }
         }
+
+        btrfs_items
     }
 }
 
@@ -47,37 +98,7 @@ impl Dumper for Btrfs {
     ) -> Result<IterExecResult> {
         match model.system.btrfs.as_ref() {
             Some(btrfs_items_ref) => {
-                let mut btrfs_items: Vec<_> = btrfs_items_ref
-                    .iter()
-                    .filter_map(|(_, model)| {
-                        match (self.select.as_ref(), self.opts.filter.as_ref()) {
-                            (Some(field_id), Some(filter))
-                                if !filter.is_match(
-                                    &model
-                                        .query(field_id)
-                                        .map_or("?".to_owned(), |v| v.to_string()),
-                                ) =>
-                            {
-                                None
-                            }
-                            _ => Some(model),
-                        }
-                    })
-                    .collect();
-
-                if let Some(field_id) = &self.select {
-                    if self.opts.sort {
-                        model::sort_queriables(&mut btrfs_items, field_id, false);
-                    }
-
-                    if self.opts.rsort {
-                        model::sort_queriables(&mut btrfs_items, field_id, true);
-                    }
-
-                    if (self.opts.sort || self.opts.rsort) && self.opts.top != 0 {
-                        btrfs_items.truncate(self.opts.top as usize);
-                    }
-                }
+                let btrfs_items = self.get_btrfs_items(btrfs_items_ref);
                 let mut json_output = json!([]);
 
                 btrfs_items
@@ -135,6 +156,14 @@ impl Dumper for Btrfs {
                                 "{}",
                                 print::dump_openmetrics(&self.fields, ctx, model)
                             )?,
+                            Some(OutputFormat::JsonLines) => write!(
+                                output,
+                                "{}",
+                                print::dump_jsonl(&self.fields, ctx, model, self.opts.raw)
+                            )?,
+                            Some(OutputFormat::Influx) => {
+                                write!(output, "{}", print::dump_influx(&self.fields, ctx, model))?
+                            }
                         }
                         *round += 1;
                         Ok(())
@@ -144,7 +173,9 @@ impl Dumper for Btrfs {
                 match (self.opts.output_format, comma_flag) {
                     (Some(OutputFormat::Json), true) => write!(output, ",{}", json_output)?,
                     (Some(OutputFormat::Json), false) => write!(output, "{}", json_output)?,
-                    (Some(OutputFormat::OpenMetrics), _) => (),
+                    (Some(OutputFormat::OpenMetrics), _)
+                    | (Some(OutputFormat::JsonLines), _)
+                    | (Some(OutputFormat::Influx), _) => (),
                     _ => write!(output, "\n")?,
                 };
 
@@ -154,3 +185,24 @@ impl Dumper for Btrfs {
         }
     }
 }
+
+impl Summarize for Btrfs {
+    type Model = BtrfsModel;
+
+    fn summary_fields(&self, _model: &model::Model) -> Vec<BtrfsField> {
+        self.fields.clone()
+    }
+
+    fn entities<'a>(&self, model: &'a model::Model) -> Vec<(String, &'a BtrfsModel)> {
+        model
+            .system
+            .btrfs
+            .as_ref()
+            .map_or_else(Vec::new, |btrfs_items_ref| {
+                self.get_btrfs_items(btrfs_items_ref)
+            })
+            .into_iter()
+            .map(|btrfs| (btrfs.name.clone().unwrap_or_default(), btrfs))
+            .collect()
+    }
+}
@@ -12,7 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use model::CgroupDiffModel;
+use model::CgroupModel;
 use model::CgroupModelFieldId;
+use model::Recursive;
+use model::SingleCgroupDiffModelFieldId;
+use model::SingleCgroupModel;
 use model::SingleCgroupModelFieldId;
 
 use super::*;
@@ -21,6 +26,7 @@ pub struct Cgroup {
     opts: GeneralOpt,
     select: Option<SingleCgroupModelFieldId>,
     fields: Vec<CgroupField>,
+    filter_expr: Option<FilterExpr<SingleCgroupModelFieldId>>,
 }
 
 impl Cgroup {
@@ -28,11 +34,66 @@ impl Cgroup {
         opts: &GeneralOpt,
         select: Option<SingleCgroupModelFieldId>,
         fields: Vec<CgroupField>,
+        filter_expr: Option<FilterExpr<SingleCgroupModelFieldId>>,
     ) -> Self {
         Self {
             opts: opts.to_owned(),
             select,
             fields,
+            filter_expr,
+        }
+    }
+
+    /// Whether the cgroup matches --filter and --filter-expr.
+    fn should_print(&self, cgroup: &SingleCgroupModel) -> bool {
+        let matches_filter = match (self.select.as_ref(), self.opts.filter.as_ref()) {
+            (Some(field_id), Some(filter)) => filter.is_match(
+                &cgroup
+                    .query(field_id)
+                    .map_or("?".to_owned(), |v| v.to_string()),
+            ),
+            _ => true,
+        };
+        matches_filter
+            && self
+                .filter_expr
+                .as_ref()
+                .map_or(true, |expr| expr.eval(cgroup))
+    }
+
+    /// Children to traverse, after sorting and --top.
+    fn get_children<'a>(&self, model: &'a CgroupModel) -> Vec<&'a CgroupModel> {
+        let mut children = Vec::from_iter(&model.children);
+        //sort
+        if let Some(field_id) = &self.select {
+            // field_id that queries its own data
+            let field_id = CgroupModelFieldId {
+                path: Some(vec![]),
+                subquery_id: field_id.to_owned(),
+            };
+            if self.opts.sort {
+                model::sort_queriables(&mut children, &field_id, false);
+            }
+
+            if self.opts.rsort {
+                model::sort_queriables(&mut children, &field_id, false);
+            }
+
+            if (self.opts.sort || self.opts.rsort) && self.opts.top != 0 {
+                children.truncate(self.opts.top as usize);
+            }
+        }
+
+        children
+    }
+
+    /// Cgroups to dump from the tree in pre-order.
+    fn get_cgroups<'a>(&self, model: &'a CgroupModel, cgroups: &mut Vec<&'a SingleCgroupModel>) {
+        if self.should_print(&model.data) {
+            cgroups.push(&model.data);
+        }
+        for child_cgroup in self.get_children(model) {
+            self.get_cgroups(child_cgroup, cgroups);
         }
     }
 }
@@ -56,87 +117,191 @@ impl Dumper for Cgroup {
             jval: &mut Value,
         ) -> Result<()> {
             let cgroup = &model.data;
-            //filter
+            if handle.should_print(cgroup) {
+                if let Some(value) = print::dump_model_indented(
+                    &handle.fields,
+                    ctx,
+                    cgroup,
+                    *round,
+                    &handle.opts,
+                    output,
+                )? {
+                    *jval = value;
+                    jval["children"] = json!([]);
+                }
+                *round += 1;
+            }
+
+            for child_cgroup in &handle.get_children(model) {
+                let mut child = json!({});
+                output_cgroup(handle, ctx, child_cgroup, output, round, json, &mut child)?;
+                if json && child["children"].is_array() {
+                    // Parent does not match, but child does, we should also render parent.
+                    if !jval["children"].is_array() {
+                        *jval = print::dump_json(&handle.fields, ctx, cgroup, handle.opts.raw);
+                        jval["children"] = json!([]);
+                    }
+                    jval["children"].as_array_mut().unwrap().push(child);
+                }
+            }
+
+            Ok(())
+        }
+        let json = self.opts.output_format == Some(OutputFormat::Json);
+        let mut jval = json!({});
+        output_cgroup(&self, ctx, &model.cgroup, output, round, json, &mut jval)?;
+        match (json, comma_flag) {
+            (true, true) => write!(output, ",{}", jval)?,
+            (true, false) => write!(output, "{}", jval)?,
+            _ => {}
+        };
+
+        Ok(IterExecResult::Success)
+    }
+}
+
+impl Summarize for Cgroup {
+    type Model = SingleCgroupModel;
+
+    fn summary_fields(&self, _model: &model::Model) -> Vec<CgroupField> {
+        self.fields.clone()
+    }
+
+    fn entities<'a>(&self, model: &'a model::Model) -> Vec<(String, &'a SingleCgroupModel)> {
+        let mut cgroups = Vec::new();
+        self.get_cgroups(&model.cgroup, &mut cgroups);
+        cgroups
+            .into_iter()
+            .map(|cgroup| (cgroup.full_path.clone(), cgroup))
+            .collect()
+    }
+
+    fn is_identity(field_id: &SingleCgroupModelFieldId) -> bool {
+        matches!(field_id, SingleCgroupModelFieldId::InodeNumber)
+    }
+
+    fn depth(cgroup: &SingleCgroupModel) -> usize {
+        cgroup.get_depth()
+    }
+}
+
+/// Turns cgroup dump fields into cgroup diff dump fields. Fields identifying
+/// the cgroup are kept, and every other field becomes its absolute and
+/// relative change.
+pub fn get_diff_fields(fields: &[CgroupField]) -> Vec<CgroupDiffField> {
+    let mut diff_fields = Vec::new();
+    for field in fields {
+        match field {
+            DumpField::Common(common) => diff_fields.push(DumpField::Common(common.clone())),
+            DumpField::FieldId(SingleCgroupModelFieldId::Name) => {
+                diff_fields.push(DumpField::FieldId(SingleCgroupDiffModelFieldId::Name));
+                diff_fields.push(DumpField::FieldId(SingleCgroupDiffModelFieldId::State));
+            }
+            DumpField::FieldId(SingleCgroupModelFieldId::FullPath) => {
+                diff_fields.push(DumpField::FieldId(SingleCgroupDiffModelFieldId::FullPath))
+            }
+            DumpField::FieldId(field_id @ SingleCgroupModelFieldId::InodeNumber) => diff_fields
+                .push(DumpField::FieldId(SingleCgroupDiffModelFieldId::After(
+                    field_id.clone(),
+                ))),
+            DumpField::FieldId(field_id) => {
+                diff_fields.push(DumpField::FieldId(SingleCgroupDiffModelFieldId::Delta(
+                    field_id.clone(),
+                )));
+                diff_fields.push(DumpField::FieldId(SingleCgroupDiffModelFieldId::DeltaPct(
+                    field_id.clone(),
+                )));
+            }
+        }
+    }
+    diff_fields
+}
+
+/// Dumps the difference between each Model's cgroup tree and a fixed one.
+pub struct CgroupDiff {
+    opts: GeneralOpt,
+    select: Option<SingleCgroupModelFieldId>,
+    fields: Vec<CgroupDiffField>,
+    filter_expr: Option<FilterExpr<SingleCgroupModelFieldId>>,
+    before: model::CgroupModel,
+}
+
+impl CgroupDiff {
+    pub fn new(
+        opts: &GeneralOpt,
+        select: Option<SingleCgroupModelFieldId>,
+        fields: Vec<CgroupDiffField>,
+        filter_expr: Option<FilterExpr<SingleCgroupModelFieldId>>,
+        before: model::CgroupModel,
+    ) -> Self {
+        Self {
+            opts: opts.to_owned(),
+            select,
+            fields,
+            filter_expr,
+            before,
+        }
+    }
+}
+
+impl Dumper for CgroupDiff {
+    fn dump_model(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output: &mut dyn Write,
+        round: &mut usize,
+        comma_flag: bool,
+    ) -> Result<IterExecResult> {
+        fn output_cgroup_diff(
+            handle: &CgroupDiff,
+            ctx: &CommonFieldContext,
+            model: &CgroupDiffModel,
+            output: &mut dyn Write,
+            round: &mut usize,
+            json: bool,
+            jval: &mut Value,
+        ) -> Result<()> {
+            let cgroup_diff = &model.data;
+            //filter on the current value, or the last one for removed cgroups
+            let cgroup = cgroup_diff.after.as_ref().or(cgroup_diff.before.as_ref());
             let should_print = match (handle.select.as_ref(), handle.opts.filter.as_ref()) {
                 (Some(field_id), Some(filter)) => filter.is_match(
                     &cgroup
-                        .query(&field_id)
+                        .and_then(|cgroup| cgroup.query(field_id))
                         .map_or("?".to_owned(), |v| v.to_string()),
                 ),
                 _ => true,
+            } && match (cgroup, handle.filter_expr.as_ref()) {
+                (Some(cgroup), Some(expr)) => expr.eval(cgroup),
+                _ => true,
             };
 
             if should_print {
-                match handle.opts.output_format {
-                    Some(OutputFormat::Raw) | None => write!(
-                        output,
-                        "{}",
-                        print::dump_raw_indented(
-                            &handle.fields,
-                            ctx,
-                            cgroup,
-                            *round,
-                            handle.opts.repeat_title,
-                            handle.opts.disable_title,
-                            handle.opts.raw,
-                        )
-                    )?,
-                    Some(OutputFormat::Csv) => write!(
-                        output,
-                        "{}",
-                        print::dump_csv(
-                            &handle.fields,
-                            ctx,
-                            cgroup,
-                            *round,
-                            handle.opts.disable_title,
-                            handle.opts.raw,
-                        )
-                    )?,
-                    Some(OutputFormat::Tsv) => write!(
-                        output,
-                        "{}",
-                        print::dump_tsv(
-                            &handle.fields,
-                            ctx,
-                            cgroup,
-                            *round,
-                            handle.opts.disable_title,
-                            handle.opts.raw,
-                        )
-                    )?,
-                    Some(OutputFormat::KeyVal) => write!(
-                        output,
-                        "{}",
-                        print::dump_kv(&handle.fields, ctx, cgroup, handle.opts.raw)
-                    )?,
-                    Some(OutputFormat::Json) => {
-                        *jval = print::dump_json(&handle.fields, ctx, cgroup, handle.opts.raw);
-                        jval["children"] = json!([]);
-                    }
-                    Some(OutputFormat::OpenMetrics) => write!(
-                        output,
-                        "{}",
-                        print::dump_openmetrics(&handle.fields, ctx, cgroup)
-                    )?,
-                };
+                if let Some(value) = print::dump_model_indented(
+                    &handle.fields,
+                    ctx,
+                    cgroup_diff,
+                    *round,
+                    &handle.opts,
+                    output,
+                )? {
+                    *jval = value;
+                    jval["children"] = json!([]);
+                }
                 *round += 1;
             }
 
             let mut children = Vec::from_iter(&model.children);
-            //sort
+            //sort by the size of the change
             if let Some(field_id) = &handle.select {
-                // field_id that queries its own data
-                let field_id = CgroupModelFieldId {
-                    path: Some(vec![]),
-                    subquery_id: field_id.to_owned(),
-                };
+                let field_id = SingleCgroupDiffModelFieldId::Delta(field_id.to_owned());
                 if handle.opts.sort {
-                    model::sort_queriables(&mut children, &field_id, false);
+                    model::sort_cgroup_diffs(&mut children, &field_id, false);
                 }
 
                 if handle.opts.rsort {
-                    model::sort_queriables(&mut children, &field_id, false);
+                    model::sort_cgroup_diffs(&mut children, &field_id, true);
                 }
 
                 if (handle.opts.sort || handle.opts.rsort) && handle.opts.top != 0 {
@@ -146,11 +311,11 @@ impl Dumper for Cgroup {
 
             for child_cgroup in &children {
                 let mut child = json!({});
-                output_cgroup(handle, ctx, child_cgroup, output, round, json, &mut child)?;
+                output_cgroup_diff(handle, ctx, child_cgroup, output, round, json, &mut child)?;
                 if json && child["children"].is_array() {
                     // Parent does not match, but child does, we should also render parent.
                     if !jval["children"].is_array() {
-                        *jval = print::dump_json(&handle.fields, ctx, cgroup, handle.opts.raw);
+                        *jval = print::dump_json(&handle.fields, ctx, cgroup_diff, handle.opts.raw);
                         jval["children"] = json!([]);
                     }
                     jval["children"].as_array_mut().unwrap().push(child);
@@ -159,9 +324,10 @@ impl Dumper for Cgroup {
 
             Ok(())
         }
+        let diff = CgroupDiffModel::new(&self.before, &model.cgroup);
         let json = self.opts.output_format == Some(OutputFormat::Json);
         let mut jval = json!({});
-        output_cgroup(&self, ctx, &model.cgroup, output, round, json, &mut jval)?;
+        output_cgroup_diff(self, ctx, &diff, output, round, json, &mut jval)?;
         match (json, comma_flag) {
             (true, true) => write!(output, ",{}", jval)?,
             (true, false) => write!(output, "{}", jval)?,
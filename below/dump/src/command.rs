@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::bail;
@@ -21,6 +22,10 @@ use clap::Parser;
 use model::BtrfsModelFieldId;
 use model::FieldId;
 use model::NetworkModelFieldId;
+use model::ProcessGroupBy;
+use model::ProcessGroupModelFieldId;
+use model::ResctrlCtrlMonGroupModelFieldId;
+use model::ResctrlMonGroupModelFieldId;
 use model::SingleCgroupModelFieldId;
 use model::SingleDiskModelFieldId;
 use model::SingleNetModelFieldId;
@@ -431,6 +436,161 @@ $ below dump btrfs -b "08:30:00" -e "08:30:30" -s disk_bytes --rsort --top 5
     )
 });
 
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    below_derive::EnumFromStr,
+    below_derive::EnumToString
+)]
+pub enum ResctrlAggField {
+    Mon,
+}
+
+impl AggField<ResctrlCtrlMonGroupModelFieldId> for ResctrlAggField {
+    fn expand(&self, _detail: bool) -> Vec<ResctrlCtrlMonGroupModelFieldId> {
+        use model::ResctrlCtrlMonGroupModelFieldId::Mon;
+        use model::ResctrlL3MonModelFieldId as L3;
+        use model::ResctrlMonModelFieldId::Total;
+
+        match self {
+            Self::Mon => enum_iterator::all::<L3>()
+                .map(|field_id| Mon(Total(field_id)))
+                .collect(),
+        }
+    }
+}
+
+pub type ResctrlOptionField = DumpOptionField<ResctrlCtrlMonGroupModelFieldId, ResctrlAggField>;
+
+pub static DEFAULT_RESCTRL_FIELDS: &[ResctrlOptionField] = &[
+    DumpOptionField::Unit(DumpField::Common(CommonField::Datetime)),
+    DumpOptionField::Unit(DumpField::FieldId(
+        ResctrlCtrlMonGroupModelFieldId::FullPath,
+    )),
+    DumpOptionField::Unit(DumpField::FieldId(ResctrlCtrlMonGroupModelFieldId::Mode)),
+    DumpOptionField::Agg(ResctrlAggField::Mon),
+    DumpOptionField::Unit(DumpField::Common(CommonField::Timestamp)),
+];
+
+const RESCTRL_ABOUT: &str = "Dump resctrl CTRL_MON group Stats";
+
+static RESCTRL_LONG_ABOUT: Lazy<String> = Lazy::new(|| {
+    format!(
+        r#"{about}
+
+Each CTRL_MON group is a row, including the root group named <root>. Their
+MON groups are dumped with `below dump resctrl-mon`.
+
+********************** Available fields **********************
+
+{common_fields}, {resctrl_fields}
+
+********************** Aggregated fields **********************
+
+* mon: includes [{agg_mon_fields}].
+
+* --detail: no effect.
+
+* --default: includes [{default_fields}].
+
+* --everything: includes everything (equivalent to --default --detail).
+
+********************** Example Commands **********************
+
+Simple example:
+
+$ below dump resctrl -b "08:30:00" -e "08:30:30" -f full_path cpuset mode mon -O csv
+
+Output stats for top 5 groups by L3 occupancy for each time slice from 08:30:00 to 08:30:30:
+
+$ below dump resctrl -b "08:30:00" -e "08:30:30" -s mon.total.llc_occupancy_bytes --rsort --top 5
+
+"#,
+        about = RESCTRL_ABOUT,
+        common_fields = join(enum_iterator::all::<CommonField>()),
+        resctrl_fields = join(enum_iterator::all::<ResctrlCtrlMonGroupModelFieldId>()),
+        agg_mon_fields = join(ResctrlAggField::Mon.expand(false)),
+        default_fields = join(DEFAULT_RESCTRL_FIELDS.to_owned()),
+    )
+});
+
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    below_derive::EnumFromStr,
+    below_derive::EnumToString
+)]
+pub enum ResctrlMonAggField {
+    Mon,
+}
+
+impl AggField<ResctrlMonGroupModelFieldId> for ResctrlMonAggField {
+    fn expand(&self, _detail: bool) -> Vec<ResctrlMonGroupModelFieldId> {
+        use model::ResctrlL3MonModelFieldId as L3;
+        use model::ResctrlMonGroupModelFieldId::Mon;
+        use model::ResctrlMonModelFieldId::Total;
+
+        match self {
+            Self::Mon => enum_iterator::all::<L3>()
+                .map(|field_id| Mon(Total(field_id)))
+                .collect(),
+        }
+    }
+}
+
+pub type ResctrlMonOptionField = DumpOptionField<ResctrlMonGroupModelFieldId, ResctrlMonAggField>;
+
+pub static DEFAULT_RESCTRL_MON_FIELDS: &[ResctrlMonOptionField] = &[
+    DumpOptionField::Unit(DumpField::Common(CommonField::Datetime)),
+    DumpOptionField::Unit(DumpField::FieldId(ResctrlMonGroupModelFieldId::FullPath)),
+    DumpOptionField::Agg(ResctrlMonAggField::Mon),
+    DumpOptionField::Unit(DumpField::Common(CommonField::Timestamp)),
+];
+
+const RESCTRL_MON_ABOUT: &str = "Dump resctrl MON group Stats";
+
+static RESCTRL_MON_LONG_ABOUT: Lazy<String> = Lazy::new(|| {
+    format!(
+        r#"{about}
+
+Each MON group is a row, MON groups of a CTRL_MON group other than the root
+group have its name as path prefix.
+
+********************** Available fields **********************
+
+{common_fields}, {resctrl_mon_fields}
+
+********************** Aggregated fields **********************
+
+* mon: includes [{agg_mon_fields}].
+
+* --detail: no effect.
+
+* --default: includes [{default_fields}].
+
+* --everything: includes everything (equivalent to --default --detail).
+
+********************** Example Commands **********************
+
+Simple example:
+
+$ below dump resctrl-mon -b "08:30:00" -e "08:30:30" -f full_path mon -O csv
+
+Output stats for top 5 groups by L3 occupancy for each time slice from 08:30:00 to 08:30:30:
+
+$ below dump resctrl-mon -b "08:30:00" -e "08:30:30" -s mon.total.llc_occupancy_bytes --rsort --top 5
+
+"#,
+        about = RESCTRL_MON_ABOUT,
+        common_fields = join(enum_iterator::all::<CommonField>()),
+        resctrl_mon_fields = join(enum_iterator::all::<ResctrlMonGroupModelFieldId>()),
+        agg_mon_fields = join(ResctrlMonAggField::Mon.expand(false)),
+        default_fields = join(DEFAULT_RESCTRL_MON_FIELDS.to_owned()),
+    )
+});
+
 /// Represents the four sub-model of ProcessModel.
 #[derive(
     Clone,
@@ -526,6 +686,14 @@ Output stats for top 5 CPU intense processes for each time slice from 08:30:00 t
 
 $ below dump process -b "08:30:00" -e "08:30:30" -s cpu.usage_pct --rsort --top 5
 
+Output stats for "below*" processes using more than 1G of memory or 10% of CPU from 08:30:00 to 08:30:30:
+
+$ below dump process -b "08:30:00" -e "08:30:30" --filter-expr "comm =~ /^below/ and (mem.rss_bytes > 1G or cpu.usage_pct > 10)"
+
+Output total CPU and RSS of the 5 most CPU intense commands for each time slice from 08:30:00 to 08:30:30:
+
+$ below dump process -b "08:30:00" -e "08:30:30" --group-by comm -f comm cpu.usage_pct mem.rss_bytes -s cpu.usage_pct --rsort --top 5
+
 "#,
         about = PROCESS_ABOUT,
         common_fields = join(enum_iterator::all::<CommonField>()),
@@ -551,11 +719,13 @@ pub enum CgroupAggField {
     Io,
     Pids,
     Pressure,
+    Exited,
 }
 
 impl AggField<SingleCgroupModelFieldId> for CgroupAggField {
     fn expand(&self, detail: bool) -> Vec<SingleCgroupModelFieldId> {
         use model::CgroupCpuModelFieldId as Cpu;
+        use model::CgroupExitedModelFieldId as Exited;
         use model::CgroupIoModelFieldId as Io;
         use model::CgroupMemoryModelFieldId as Mem;
         use model::CgroupPidsModelFieldId as Pid;
@@ -571,6 +741,9 @@ impl AggField<SingleCgroupModelFieldId> for CgroupAggField {
                 Self::Pressure => enum_iterator::all::<Pressure>()
                     .map(FieldId::Pressure)
                     .collect(),
+                Self::Exited => enum_iterator::all::<Exited>()
+                    .map(FieldId::Exited)
+                    .collect(),
             }
         } else {
             // Default fields for each group
@@ -584,6 +757,10 @@ impl AggField<SingleCgroupModelFieldId> for CgroupAggField {
                     FieldId::Pressure(Pressure::MemoryFullPct),
                     FieldId::Pressure(Pressure::IoFullPct),
                 ],
+                Self::Exited => vec![
+                    FieldId::Exited(Exited::Count),
+                    FieldId::Exited(Exited::CpuUsagePct),
+                ],
             }
         }
     }
@@ -623,6 +800,8 @@ static CGROUP_LONG_ABOUT: Lazy<String> = Lazy::new(|| {
 
 * pressure: includes [{agg_pressure_fields}].
 
+* exited: includes [{agg_exited_fields}].
+
 * --detail: includes [<agg_field>.*] for each given aggregated field.
 
 * --default: includes [{default_fields}].
@@ -645,6 +824,15 @@ from 08:30:00 to 08:30:30 recursively:
 
 $ below dump cgroup -b "08:30:00" -e "08:30:30" -s cpu.usage_pct --rsort --top 5
 
+Output the change of cgroup stats since 08:00:00 for each time slice from
+08:30:00 to 08:30:30, with the 5 largest changes in CPU usage first:
+
+$ below dump cgroup -b "08:30:00" -e "08:30:30" --compare-to "08:00:00" -s cpu.usage_pct --rsort --top 5
+
+Output stats for cgroups under workload.slice using more than 50% of CPU from 08:30:00 to 08:30:30:
+
+$ below dump cgroup -b "08:30:00" -e "08:30:30" --filter-expr "cpu.usage_pct > 50 and full_path =~ /workload.slice/"
+
 "#,
         about = CGROUP_ABOUT,
         common_fields = join(enum_iterator::all::<CommonField>()),
@@ -653,6 +841,7 @@ $ below dump cgroup -b "08:30:00" -e "08:30:30" -s cpu.usage_pct --rsort --top 5
         agg_memory_fields = join(CgroupAggField::Mem.expand(false)),
         agg_io_fields = join(CgroupAggField::Io.expand(false)),
         agg_pressure_fields = join(CgroupAggField::Pressure.expand(false)),
+        agg_exited_fields = join(CgroupAggField::Exited.expand(false)),
         default_fields = join(DEFAULT_CGROUP_FIELDS.to_owned()),
     )
 });
@@ -1043,6 +1232,46 @@ make_option! (OutputFormat {
     "json": Json,
     "kv": KeyVal,
     "openmetrics": OpenMetrics,
+    "jsonl": JsonLines,
+    "influx": Influx,
+});
+
+const MULTI_ABOUT: &str = "Dump several model types in a single pass";
+
+static MULTI_LONG_ABOUT: Lazy<String> = Lazy::new(|| {
+    format!(
+        r#"{about}
+
+Fields of each model type are given with its own option and accept the same
+fields and aggregated fields as the corresponding dump subcommand, e.g.
+--cgroup accepts the fields of `below dump cgroup`. The store is read once
+for all of them.
+
+JSON output has an object per sample with the Timestamp, the Datetime, and
+a key per model type: an object for system and network, an array of objects
+for cgroup, process, disk and iface.
+
+CSV, TSV, raw and key-value output print a single row per sample, with
+column titles prefixed by the model type, e.g. "system.Usage". As cgroup,
+process, disk and iface have several entities per sample, they are only
+supported by JSON, JSON Lines, OpenMetrics and influx output.
+
+--sort, --rsort, --top, --filter, --filter-expr and --summary are not
+supported, nor is --select.
+
+********************** Example Commands **********************
+
+System CPU and memory with the memory of each cgroup, one JSON object per line:
+
+$ below dump multi -b "08:30:00" -e "08:30:30" --system cpu mem --cgroup name mem -O jsonl
+
+System and network stats in a single CSV row per sample:
+
+$ below dump multi -b "08:30:00" -e "08:30:30" --system cpu --network tcp -O csv
+
+"#,
+        about = MULTI_ABOUT,
+    )
 });
 
 #[derive(Debug, Parser, Default, Clone)]
@@ -1071,6 +1300,13 @@ pub struct GeneralOpt {
     /// Take a regex and apply to --select selected field. See command level doc for example.
     #[clap(long, short = 'F')]
     pub filter: Option<Regex>,
+    /// Only dump entries matching a boolean expression over fields, e.g.
+    /// "cpu.usage_pct > 50 and name =~ /below/". Supports ==, !=, <, <=, >, >=,
+    /// =~ and !~ (regex), combined with and, or, not and parentheses. Numbers may
+    /// have a unit: K, M, G, T (powers of 1024 for sizes, of 1000 otherwise),
+    /// KiB, MiB, GiB, TiB (sizes) or us, ms, s, min, h (durations).
+    #[clap(long)]
+    pub filter_expr: Option<String>,
     /// Sort (lower to higher) by --select selected field. See command level doc for example.
     #[clap(long)]
     pub sort: bool,
@@ -1083,7 +1319,8 @@ pub struct GeneralOpt {
     /// Repeat title, for each N line, it will render a line of title. Only for raw output format.
     #[clap(long = "repeat-title")]
     pub repeat_title: Option<usize>,
-    /// Output format. Choose from raw, csv, tsv, kv, json, openmetrics. Default to raw
+    /// Output format. Choose from raw, csv, tsv, kv, json, jsonl, openmetrics,
+    /// influx. Default to raw
     #[clap(long, short = 'O')]
     pub output_format: Option<OutputFormat>,
     /// Output destination, default to stdout.
@@ -1101,6 +1338,16 @@ pub struct GeneralOpt {
     /// Dump raw data without units or conversion
     #[clap(long)]
     pub raw: bool,
+    /// Instead of a row per sample, dump a row per entity (e.g. cgroup or
+    /// process) with the min, max, avg, p50, p95 and p99 of each numeric
+    /// field over the time range. Other fields show their latest value.
+    #[clap(long)]
+    pub summary: bool,
+    /// Dump a row per interval, e.g. "5m", instead of per sample. Each row is
+    /// calculated from the first and last sample of the interval, so rates
+    /// cover the whole interval. The samples in between are skipped.
+    #[clap(long)]
+    pub every: Option<humantime::Duration>,
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -1144,6 +1391,34 @@ pub enum DumpCommand {
         #[clap(long, short, conflicts_with("fields"))]
         pattern: Option<String>,
     },
+    #[clap(about = RESCTRL_ABOUT, long_about = RESCTRL_LONG_ABOUT.as_str())]
+    Resctrl {
+        /// Select which fields to display and in what order.
+        #[clap(short, long, num_args = 1..)]
+        fields: Option<Vec<ResctrlOptionField>>,
+        #[clap(flatten)]
+        opts: GeneralOpt,
+        /// Select field for operation, use with --sort, --rsort, --filter, --top
+        #[clap(long, short)]
+        select: Option<ResctrlCtrlMonGroupModelFieldId>,
+        /// Saved pattern in the dumprc file under [resctrl] section.
+        #[clap(long, short, conflicts_with("fields"))]
+        pattern: Option<String>,
+    },
+    #[clap(about = RESCTRL_MON_ABOUT, long_about = RESCTRL_MON_LONG_ABOUT.as_str())]
+    ResctrlMon {
+        /// Select which fields to display and in what order.
+        #[clap(short, long, num_args = 1..)]
+        fields: Option<Vec<ResctrlMonOptionField>>,
+        #[clap(flatten)]
+        opts: GeneralOpt,
+        /// Select field for operation, use with --sort, --rsort, --filter, --top
+        #[clap(long, short)]
+        select: Option<ResctrlMonGroupModelFieldId>,
+        /// Saved pattern in the dumprc file under [resctrl_mon] section.
+        #[clap(long, short, conflicts_with("fields"))]
+        pattern: Option<String>,
+    },
     #[clap(about = PROCESS_ABOUT, long_about = PROCESS_LONG_ABOUT.as_str())]
     Process {
         /// Select which fields to display and in what order.
@@ -1151,12 +1426,23 @@ pub enum DumpCommand {
         fields: Option<Vec<ProcessOptionField>>,
         #[clap(flatten)]
         opts: GeneralOpt,
-        /// Select field for operation, use with --sort, --rsort, --filter, --top
+        /// Select field for operation, use with --sort, --rsort, --filter, --top.
+        /// With --group-by, "members" selects the number of processes in a group.
         #[clap(long, short)]
-        select: Option<SingleProcessModelFieldId>,
+        select: Option<ProcessGroupModelFieldId>,
         /// Saved pattern in the dumprc file under [process] section.
         #[clap(long, short, conflicts_with("fields"))]
         pattern: Option<String>,
+        /// Fold processes with the same comm, cgroup, exe_path or ppid into a
+        /// row per group with their stats summed up and a member count.
+        /// --filter, --filter-expr, --sort and --top apply to the groups.
+        #[clap(long)]
+        group_by: Option<ProcessGroupBy>,
+        /// Dump processes as trees built from ppid, with the name indented by
+        /// depth and io, mem and cpu summed up over each subtree. --filter,
+        /// --filter-expr, --sort and --top apply to the subtrees.
+        #[clap(long, conflicts_with("group_by"))]
+        tree: bool,
     },
     #[clap(about = CGROUP_ABOUT, long_about = CGROUP_LONG_ABOUT.as_str())]
     Cgroup {
@@ -1171,6 +1457,11 @@ pub enum DumpCommand {
         /// Saved pattern in the dumprc file under [cgroup] section.
         #[clap(long, short, conflicts_with("fields"))]
         pattern: Option<String>,
+        /// Compare against the sample at this time, same format as --begin.
+        /// Dumps added and removed cgroups and the change of each field. With
+        /// --sort or --rsort, cgroups are ordered by the size of the change.
+        #[clap(long)]
+        compare_to: Option<String>,
     },
     #[clap(about = IFACE_ABOUT, long_about = IFACE_LONG_ABOUT.as_str())]
     Iface {
@@ -1219,4 +1510,88 @@ pub enum DumpCommand {
         #[clap(long, short, conflicts_with("fields"))]
         pattern: Option<String>,
     },
+    #[clap(about = MULTI_ABOUT, long_about = MULTI_LONG_ABOUT.as_str())]
+    Multi {
+        #[clap(flatten)]
+        opts: GeneralOpt,
+        /// System fields to dump, see `below dump system --help`.
+        #[clap(long, num_args = 1..)]
+        system: Option<Vec<SystemOptionField>>,
+        /// Cgroup fields to dump, see `below dump cgroup --help`.
+        #[clap(long, num_args = 1..)]
+        cgroup: Option<Vec<CgroupOptionField>>,
+        /// Process fields to dump, see `below dump process --help`.
+        #[clap(long, num_args = 1..)]
+        process: Option<Vec<ProcessOptionField>>,
+        /// Disk fields to dump, see `below dump disk --help`.
+        #[clap(long, num_args = 1..)]
+        disk: Option<Vec<DiskOptionField>>,
+        /// Network interface fields to dump, see `below dump iface --help`.
+        #[clap(long, num_args = 1..)]
+        iface: Option<Vec<IfaceOptionField>>,
+        /// Network fields to dump, see `below dump network --help`.
+        #[clap(long, num_args = 1..)]
+        network: Option<Vec<NetworkOptionField>>,
+    },
+}
+
+make_option! (ExportFormat {
+    "parquet": Parquet,
+    "arrow": Arrow,
+});
+
+/// Options of `below export`.
+#[derive(Debug, Parser, Clone)]
+pub struct ExportOpts {
+    /// Begin time, same format as replay
+    #[clap(long, short)]
+    pub begin: String,
+    /// End time, same format as replay
+    #[clap(long, short, group = "time")]
+    pub end: Option<String>,
+    /// Time string specifying the duration, e.g. "10 min"{n}
+    /// Keywords: days min, h, sec{n}
+    /// Relative: {humantime}, e.g. "2 days 3 hr 15m 10sec"{n}
+    /// _
+    #[clap(long, group = "time")]
+    pub duration: Option<String>,
+    /// Days adjuster, same as -r option in replay.
+    #[clap(short = 'r')]
+    pub yesterdays: Option<String>,
+    /// File format of the tables. Choose from parquet, arrow (IPC file).
+    #[clap(long, short = 'O', default_value = "parquet")]
+    pub format: ExportFormat,
+    /// Directory to write the tables to, one file per table, e.g.
+    /// cgroup.parquet. Created if it does not exist.
+    #[clap(long, short)]
+    pub output: PathBuf,
+}
+
+/// Options of `below sql`.
+#[derive(Debug, Parser, Clone)]
+pub struct SqlOpts {
+    /// Begin time, same format as replay
+    #[clap(long, short)]
+    pub begin: String,
+    /// End time, same format as replay. No short option as -e is taken by
+    /// --execute.
+    #[clap(long, group = "time")]
+    pub end: Option<String>,
+    /// Time string specifying the duration, e.g. "10 min"{n}
+    /// Keywords: days min, h, sec{n}
+    /// Relative: {humantime}, e.g. "2 days 3 hr 15m 10sec"{n}
+    /// _
+    #[clap(long, group = "time")]
+    pub duration: Option<String>,
+    /// Days adjuster, same as -r option in replay.
+    #[clap(short = 'r')]
+    pub yesterdays: Option<String>,
+    /// SQLite database file to load the data into, kept after the queries
+    /// are run. An in-memory database is used if unspecified.
+    #[clap(long, short)]
+    pub database: Option<PathBuf>,
+    /// Query to run, can be repeated. Queries are read from stdin if none
+    /// is given.
+    #[clap(long, short)]
+    pub execute: Vec<String>,
 }
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use model::Recursive;
+use model::SingleDiskModel;
 use model::SingleDiskModelFieldId;
 
 use super::*;
@@ -20,6 +22,7 @@ pub struct Disk {
     opts: GeneralOpt,
     select: Option<SingleDiskModelFieldId>,
     fields: Vec<DiskField>,
+    filter_expr: Option<FilterExpr<SingleDiskModelFieldId>>,
 }
 
 impl Disk {
@@ -27,24 +30,18 @@ impl Disk {
         opts: &GeneralOpt,
         select: Option<SingleDiskModelFieldId>,
         fields: Vec<DiskField>,
+        filter_expr: Option<FilterExpr<SingleDiskModelFieldId>>,
     ) -> Self {
         Self {
             opts: opts.to_owned(),
             select,
             fields,
+            filter_expr,
         }
     }
-}
 
-impl Dumper for Disk {
-    fn dump_model(
-        &self,
-        ctx: &CommonFieldContext,
-        model: &model::Model,
-        output: &mut dyn Write,
-        round: &mut usize,
-        comma_flag: bool,
-    ) -> Result<IterExecResult> {
+    /// Disks to dump from the Model, after filtering, sorting and --top.
+    fn get_disks<'a>(&self, model: &'a model::Model) -> Vec<&'a SingleDiskModel> {
         let mut disks: Vec<_> = model
             .system
             .disks
@@ -63,6 +60,11 @@ impl Dumper for Disk {
                     _ => Some(model),
                 },
             )
+            .filter(|model| {
+                self.filter_expr
+                    .as_ref()
+                    .map_or(true, |expr| expr.eval(model))
+            })
             .collect();
 
         if let Some(field_id) = &self.select {
@@ -78,6 +80,21 @@ impl Dumper for Disk {
                 disks.truncate(self.opts.top as usize);
             }
         }
+
+        disks
+    }
+}
+
+impl Dumper for Disk {
+    fn dump_model(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output: &mut dyn Write,
+        round: &mut usize,
+        comma_flag: bool,
+    ) -> Result<IterExecResult> {
+        let disks = self.get_disks(model);
         let mut json_output = json!([]);
 
         disks
@@ -135,6 +152,14 @@ impl Dumper for Disk {
                         "{}",
                         print::dump_openmetrics(&self.fields, ctx, model)
                     )?,
+                    Some(OutputFormat::JsonLines) => write!(
+                        output,
+                        "{}",
+                        print::dump_jsonl(&self.fields, ctx, model, self.opts.raw)
+                    )?,
+                    Some(OutputFormat::Influx) => {
+                        write!(output, "{}", print::dump_influx(&self.fields, ctx, model))?
+                    }
                 }
                 *round += 1;
                 Ok(())
@@ -144,10 +169,38 @@ impl Dumper for Disk {
         match (self.opts.output_format, comma_flag) {
             (Some(OutputFormat::Json), true) => write!(output, ",{}", json_output)?,
             (Some(OutputFormat::Json), false) => write!(output, "{}", json_output)?,
-            (Some(OutputFormat::OpenMetrics), _) => (),
+            (Some(OutputFormat::OpenMetrics), _)
+            | (Some(OutputFormat::JsonLines), _)
+            | (Some(OutputFormat::Influx), _) => (),
             _ => write!(output, "\n")?,
         };
 
         Ok(IterExecResult::Success)
     }
 }
+
+impl Summarize for Disk {
+    type Model = SingleDiskModel;
+
+    fn summary_fields(&self, _model: &model::Model) -> Vec<DiskField> {
+        self.fields.clone()
+    }
+
+    fn entities<'a>(&self, model: &'a model::Model) -> Vec<(String, &'a SingleDiskModel)> {
+        self.get_disks(model)
+            .into_iter()
+            .map(|sdm| (sdm.name.clone().unwrap_or_default(), sdm))
+            .collect()
+    }
+
+    fn is_identity(field_id: &SingleDiskModelFieldId) -> bool {
+        matches!(
+            field_id,
+            SingleDiskModelFieldId::Major | SingleDiskModelFieldId::Minor
+        )
+    }
+
+    fn depth(sdm: &SingleDiskModel) -> usize {
+        sdm.get_depth()
+    }
+}
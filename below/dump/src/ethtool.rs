@@ -3,15 +3,38 @@ use super::*;
 pub struct EthtoolQueue {
     opts: GeneralOpt,
     fields: Vec<EthtoolQueueField>,
+    filter_expr: Option<FilterExpr<model::SingleQueueModelFieldId>>,
 }
 
 impl EthtoolQueue {
-    pub fn new(opts: &GeneralOpt, fields: Vec<EthtoolQueueField>) -> Self {
+    pub fn new(
+        opts: &GeneralOpt,
+        fields: Vec<EthtoolQueueField>,
+        filter_expr: Option<FilterExpr<model::SingleQueueModelFieldId>>,
+    ) -> Self {
         Self {
             opts: opts.to_owned(),
             fields,
+            filter_expr,
         }
     }
+
+    /// Queues of all interfaces to dump from the Model, after filtering.
+    fn get_queues<'a>(&self, model: &'a model::Model) -> Vec<&'a model::SingleQueueModel> {
+        let mut queues = Vec::new();
+        for nic in model.network.interfaces.values() {
+            for queue in &nic.queues {
+                if let Some(expr) = &self.filter_expr {
+                    if !expr.eval(queue) {
+                        continue;
+                    }
+                }
+                queues.push(queue);
+            }
+        }
+
+        queues
+    }
 }
 
 impl Dumper for EthtoolQueue {
@@ -23,12 +46,7 @@ impl Dumper for EthtoolQueue {
         round: &mut usize,
         comma_flag: bool,
     ) -> Result<IterExecResult> {
-        let mut queues = Vec::new();
-        for nic in model.network.interfaces.values() {
-            for queue in &nic.queues {
-                queues.push(queue);
-            }
-        }
+        let queues = self.get_queues(model);
 
         // Return if we filtered everything.
         if queues.is_empty() {
@@ -92,6 +110,14 @@ impl Dumper for EthtoolQueue {
                         "{}",
                         print::dump_openmetrics(&self.fields, ctx, queue)
                     )?,
+                    Some(OutputFormat::JsonLines) => write!(
+                        output,
+                        "{}",
+                        print::dump_jsonl(&self.fields, ctx, queue, self.opts.raw)
+                    )?,
+                    Some(OutputFormat::Influx) => {
+                        write!(output, "{}", print::dump_influx(&self.fields, ctx, queue))?
+                    }
                 }
                 *round += 1;
                 Ok(())
@@ -101,10 +127,31 @@ impl Dumper for EthtoolQueue {
         match (self.opts.output_format, comma_flag) {
             (Some(OutputFormat::Json), true) => write!(output, ",{}", json_output)?,
             (Some(OutputFormat::Json), false) => write!(output, "{}", json_output)?,
-            (Some(OutputFormat::OpenMetrics), _) => (),
+            (Some(OutputFormat::OpenMetrics), _)
+            | (Some(OutputFormat::JsonLines), _)
+            | (Some(OutputFormat::Influx), _) => (),
             _ => write!(output, "\n")?,
         };
 
         Ok(IterExecResult::Success)
     }
 }
+
+impl Summarize for EthtoolQueue {
+    type Model = model::SingleQueueModel;
+
+    fn summary_fields(&self, _model: &model::Model) -> Vec<EthtoolQueueField> {
+        self.fields.clone()
+    }
+
+    fn entities<'a>(&self, model: &'a model::Model) -> Vec<(String, &'a model::SingleQueueModel)> {
+        self.get_queues(model)
+            .into_iter()
+            .map(|queue| (format!("{} {}", queue.interface, queue.queue_id), queue))
+            .collect()
+    }
+
+    fn is_identity(field_id: &model::SingleQueueModelFieldId) -> bool {
+        matches!(field_id, model::SingleQueueModelFieldId::QueueId)
+    }
+}
@@ -0,0 +1,320 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::array::ArrayRef;
+use arrow::array::Float32Array;
+use arrow::array::Float64Array;
+use arrow::array::Int32Array;
+use arrow::array::Int64Array;
+use arrow::array::StringArray;
+use arrow::array::TimestampSecondArray;
+use arrow::array::UInt32Array;
+use arrow::array::UInt64Array;
+use arrow::datatypes::DataType;
+use arrow::datatypes::Field as ArrowField;
+use arrow::datatypes::Schema;
+use arrow::datatypes::SchemaRef;
+use arrow::datatypes::TimeUnit;
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use command::ExportFormat;
+use command::ExportOpts;
+use enum_iterator::Sequence;
+use model::FieldType;
+use parquet::arrow::ArrowWriter;
+use tmain::jump_to_initial_sample;
+
+use super::*;
+
+/// Number of rows buffered per table before writing them out.
+const BATCH_SIZE: usize = 8192;
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Parquet => "parquet",
+            Self::Arrow => "arrow",
+        }
+    }
+}
+
+/// Writes the record batches of a table in the export format.
+enum TableWriter {
+    Parquet(ArrowWriter<File>),
+    Arrow(FileWriter<File>),
+}
+
+impl TableWriter {
+    fn new(format: ExportFormat, path: &PathBuf, schema: SchemaRef) -> Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(match format {
+            ExportFormat::Parquet => Self::Parquet(ArrowWriter::try_new(file, schema, None)?),
+            ExportFormat::Arrow => Self::Arrow(FileWriter::try_new(file, &schema)?),
+        })
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match self {
+            Self::Parquet(writer) => writer.write(batch)?,
+            Self::Arrow(writer) => writer.write(batch)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Parquet(writer) => {
+                writer.close()?;
+            }
+            Self::Arrow(mut writer) => writer.finish()?,
+        }
+        Ok(())
+    }
+}
+
+/// Arrow type of the columns holding Fields of the given type.
+fn get_data_type(field_type: FieldType) -> DataType {
+    match field_type {
+        FieldType::U32 => DataType::UInt32,
+        FieldType::U64 => DataType::UInt64,
+        FieldType::I32 => DataType::Int32,
+        FieldType::I64 => DataType::Int64,
+        FieldType::F32 => DataType::Float32,
+        FieldType::F64 => DataType::Float64,
+        _ => DataType::Utf8,
+    }
+}
+
+/// Turns a column of Fields into an Arrow array of the given type. Values of
+/// an unexpected type are null.
+fn to_array(data_type: &DataType, values: Vec<Option<Field>>) -> ArrayRef {
+    macro_rules! primitive_array {
+        ($array:ty, $variant:ident) => {
+            Arc::new(
+                values
+                    .into_iter()
+                    .map(|value| match value {
+                        Some(Field::$variant(v)) => Some(v),
+                        _ => None,
+                    })
+                    .collect::<$array>(),
+            )
+        };
+    }
+
+    match data_type {
+        DataType::UInt32 => primitive_array!(UInt32Array, U32),
+        DataType::UInt64 => primitive_array!(UInt64Array, U64),
+        DataType::Int32 => primitive_array!(Int32Array, I32),
+        DataType::Int64 => primitive_array!(Int64Array, I64),
+        DataType::Float32 => primitive_array!(Float32Array, F32),
+        DataType::Float64 => primitive_array!(Float64Array, F64),
+        _ => Arc::new(
+            values
+                .into_iter()
+                .map(|value| value.map(|v| v.to_string()))
+                .collect::<StringArray>(),
+        ),
+    }
+}
+
+/// A table of the export, e.g. the process table.
+pub trait ExportTable {
+    /// Adds a row per entity of the Model.
+    fn add_model(&mut self, model: &model::Model) -> Result<()>;
+
+    /// Writes out the remaining rows and closes the file.
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+/// A table with a timestamp column followed by a column per field id of
+/// the entities, e.g. SingleProcessModel. Keys such as the pid or the cgroup
+/// path are columns as well.
+pub struct Table<Q: Queriable> {
+    entities: for<'a> fn(&'a model::Model) -> Vec<&'a Q>,
+    field_ids: Vec<Q::FieldId>,
+    schema: SchemaRef,
+    timestamps: Vec<i64>,
+    columns: Vec<Vec<Option<Field>>>,
+    writer: TableWriter,
+}
+
+impl<Q> Table<Q>
+where
+    Q: Queriable,
+    Q::FieldId: Sequence + ToString,
+{
+    /// Creates the table file in the output directory. Column types follow
+    /// the declared types of the fields.
+    pub fn new(
+        name: &str,
+        entities: for<'a> fn(&'a model::Model) -> Vec<&'a Q>,
+        opts: &ExportOpts,
+    ) -> Result<Self> {
+        // Variants for listing, e.g. cpus.<key>.usage_pct, don't hold values
+        let field_ids: Vec<_> = enum_iterator::all::<Q::FieldId>()
+            .filter(|field_id| !field_id.to_string().contains('<'))
+            .collect();
+
+        let mut columns = vec![ArrowField::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Second, None),
+            false,
+        )];
+        for field_id in &field_ids {
+            let data_type = get_data_type(Q::field_type(field_id));
+            columns.push(ArrowField::new(field_id.to_string(), data_type, true));
+        }
+        let schema = Arc::new(Schema::new(columns));
+
+        let path = opts
+            .output
+            .join(format!("{}.{}", name, opts.format.extension()));
+        let writer = TableWriter::new(opts.format, &path, schema.clone())?;
+
+        Ok(Self {
+            entities,
+            columns: vec![Vec::new(); field_ids.len()],
+            field_ids,
+            schema,
+            timestamps: Vec::new(),
+            writer,
+        })
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.timestamps.is_empty() {
+            return Ok(());
+        }
+        let mut arrays: Vec<ArrayRef> = vec![Arc::new(TimestampSecondArray::from(std::mem::take(
+            &mut self.timestamps,
+        )))];
+        for (idx, values) in self.columns.iter_mut().enumerate() {
+            arrays.push(to_array(
+                self.schema.field(idx + 1).data_type(),
+                std::mem::take(values),
+            ));
+        }
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        self.writer.write(&batch)
+    }
+}
+
+impl<Q> ExportTable for Table<Q>
+where
+    Q: Queriable,
+    Q::FieldId: Sequence + ToString,
+{
+    fn add_model(&mut self, model: &model::Model) -> Result<()> {
+        let timestamp = model
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs() as i64;
+        for entity in (self.entities)(model) {
+            self.timestamps.push(timestamp);
+            for (field_id, values) in self.field_ids.iter().zip(self.columns.iter_mut()) {
+                values.push(entity.query(field_id));
+            }
+        }
+        if self.timestamps.len() >= BATCH_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.flush()?;
+        self.writer.finish()
+    }
+}
+
+/// Creates the system, cgroup, process, disk, iface and network tables.
+pub fn get_tables(opts: &ExportOpts) -> Result<Vec<Box<dyn ExportTable>>> {
+    Ok(vec![
+        Box::new(Table::new("system", |model| vec![&model.system], opts)?),
+        Box::new(Table::new("cgroup", get_cgroups, opts)?),
+        Box::new(Table::new(
+            "process",
+            |model| model.process.processes.values().collect(),
+            opts,
+        )?),
+        Box::new(Table::new(
+            "disk",
+            |model| model.system.disks.values().collect(),
+            opts,
+        )?),
+        Box::new(Table::new(
+            "iface",
+            |model| model.network.interfaces.values().collect(),
+            opts,
+        )?),
+        Box::new(Table::new("network", |model| vec![&model.network], opts)?),
+    ])
+}
+
+/// Exports the samples of a time range into a table per model type.
+pub fn run(
+    logger: slog::Logger,
+    errs: Receiver<Error>,
+    dir: PathBuf,
+    host: Option<String>,
+    port: Option<u16>,
+    snapshot: Option<String>,
+    opts: ExportOpts,
+) -> Result<()> {
+    let general_opts = GeneralOpt {
+        begin: opts.begin.clone(),
+        end: opts.end.clone(),
+        duration: opts.duration.clone(),
+        yesterdays: opts.yesterdays.clone(),
+        ..Default::default()
+    };
+    let (time_begin, time_end, mut advance) =
+        get_advance(logger, dir, host, port, snapshot, &general_opts)?;
+    let mut model = jump_to_initial_sample(&mut advance, time_begin, time_end)?;
+
+    fs::create_dir_all(&opts.output)
+        .with_context(|| format!("Failed to create {}", opts.output.display()))?;
+    let mut tables = get_tables(&opts)?;
+
+    loop {
+        // Received external error, e.g. stop signal
+        if let Ok(e) = errs.try_recv() {
+            bail!(e);
+        }
+        for table in &mut tables {
+            table.add_model(&model)?;
+        }
+
+        if advance.get_next_ts() > time_end {
+            break;
+        }
+
+        model = match advance.advance(Direction::Forward) {
+            Some(m) => m,
+            None => break,
+        };
+    }
+
+    cliutil::check_final_sample_time_with_requested_time(model.timestamp, time_end);
+
+    for table in tables {
+        table.finish()?;
+    }
+
+    Ok(())
+}
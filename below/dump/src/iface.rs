@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use model::SingleNetModel;
 use model::SingleNetModelFieldId;
 
 use super::*;
@@ -20,6 +21,7 @@ pub struct Iface {
     opts: GeneralOpt,
     select: Option<SingleNetModelFieldId>,
     fields: Vec<IfaceField>,
+    filter_expr: Option<FilterExpr<SingleNetModelFieldId>>,
 }
 
 impl Iface {
@@ -27,26 +29,18 @@ impl Iface {
         opts: &GeneralOpt,
         select: Option<SingleNetModelFieldId>,
         fields: Vec<IfaceField>,
+        filter_expr: Option<FilterExpr<SingleNetModelFieldId>>,
     ) -> Self {
         Self {
             opts: opts.to_owned(),
             select,
             fields,
+            filter_expr,
         }
     }
-}
-
-impl Dumper for Iface {
-    fn dump_model(
-        &self,
-        ctx: &CommonFieldContext,
-        model: &model::Model,
-        output: &mut dyn Write,
-        round: &mut usize,
-        comma_flag: bool,
-    ) -> Result<IterExecResult> {
-        let mut json_output = json!([]);
 
+    /// Interfaces to dump from the Model, after filtering.
+    fn get_interfaces<'a>(&self, model: &'a model::Model) -> Vec<&'a SingleNetModel> {
         model
             .network
             .interfaces
@@ -65,7 +59,30 @@ impl Dumper for Iface {
                     _ => true,
                 },
             )
-            .map(|(_, model)| {
+            .filter(|(_, model)| {
+                self.filter_expr
+                    .as_ref()
+                    .map_or(true, |expr| expr.eval(model))
+            })
+            .map(|(_, model)| model)
+            .collect()
+    }
+}
+
+impl Dumper for Iface {
+    fn dump_model(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output: &mut dyn Write,
+        round: &mut usize,
+        comma_flag: bool,
+    ) -> Result<IterExecResult> {
+        let mut json_output = json!([]);
+
+        self.get_interfaces(model)
+            .into_iter()
+            .map(|model| {
                 match self.opts.output_format {
                     Some(OutputFormat::Raw) | None => write!(
                         output,
@@ -118,6 +135,14 @@ impl Dumper for Iface {
                         "{}",
                         print::dump_openmetrics(&self.fields, ctx, model)
                     )?,
+                    Some(OutputFormat::JsonLines) => write!(
+                        output,
+                        "{}",
+                        print::dump_jsonl(&self.fields, ctx, model, self.opts.raw)
+                    )?,
+                    Some(OutputFormat::Influx) => {
+                        write!(output, "{}", print::dump_influx(&self.fields, ctx, model))?
+                    }
                 }
                 *round += 1;
                 Ok(())
@@ -127,10 +152,27 @@ impl Dumper for Iface {
         match (self.opts.output_format, comma_flag) {
             (Some(OutputFormat::Json), true) => write!(output, ",{}", json_output)?,
             (Some(OutputFormat::Json), false) => write!(output, "{}", json_output)?,
-            (Some(OutputFormat::OpenMetrics), _) => (),
+            (Some(OutputFormat::OpenMetrics), _)
+            | (Some(OutputFormat::JsonLines), _)
+            | (Some(OutputFormat::Influx), _) => (),
             _ => write!(output, "\n")?,
         };
 
         Ok(IterExecResult::Success)
     }
 }
+
+impl Summarize for Iface {
+    type Model = SingleNetModel;
+
+    fn summary_fields(&self, _model: &model::Model) -> Vec<IfaceField> {
+        self.fields.clone()
+    }
+
+    fn entities<'a>(&self, model: &'a model::Model) -> Vec<(String, &'a SingleNetModel)> {
+        self.get_interfaces(model)
+            .into_iter()
+            .map(|snm| (snm.interface.clone(), snm))
+            .collect()
+    }
+}
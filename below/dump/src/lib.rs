@@ -19,6 +19,7 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::mpsc::Receiver;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use anyhow::bail;
@@ -31,6 +32,7 @@ use common::util::get_belowrc_filename;
 use common::util::timestamp_to_datetime;
 use model::Field;
 use model::FieldId;
+use model::FilterExpr;
 use model::Queriable;
 use serde_json::json;
 use serde_json::Value;
@@ -47,10 +49,18 @@ pub mod cgroup;
 pub mod command;
 pub mod disk;
 pub mod ethtool;
+#[cfg(feature = "export")]
+pub mod export;
 pub mod iface;
+pub mod metrics;
+pub mod multi;
 pub mod network;
 pub mod print;
 pub mod process;
+pub mod resctrl;
+#[cfg(feature = "sql")]
+pub mod sql;
+pub mod summary;
 pub mod system;
 pub mod tmain;
 pub mod transport;
@@ -63,6 +73,8 @@ pub use command::DumpCommand;
 use command::GeneralOpt;
 use command::OutputFormat;
 use render::HasRenderConfigForDump;
+use summary::dump_summary;
+use summary::Summarize;
 use tmain::dump_timeseries;
 use tmain::Dumper;
 use tmain::IterExecResult;
@@ -108,10 +120,15 @@ pub enum DumpField<F: FieldId> {
 }
 
 pub type CgroupField = DumpField<model::SingleCgroupModelFieldId>;
+pub type CgroupDiffField = DumpField<model::SingleCgroupDiffModelFieldId>;
 pub type ProcessField = DumpField<model::SingleProcessModelFieldId>;
+pub type ProcessGroupField = DumpField<model::ProcessGroupModelFieldId>;
+pub type ProcessTreeField = DumpField<model::ProcessTreeModelFieldId>;
 pub type SystemField = DumpField<model::SystemModelFieldId>;
 pub type DiskField = DumpField<model::SingleDiskModelFieldId>;
 pub type BtrfsField = DumpField<model::BtrfsModelFieldId>;
+pub type ResctrlField = DumpField<model::ResctrlCtrlMonGroupModelFieldId>;
+pub type ResctrlMonField = DumpField<model::ResctrlMonGroupModelFieldId>;
 pub type NetworkField = DumpField<model::NetworkModelFieldId>;
 pub type IfaceField = DumpField<model::SingleNetModelFieldId>;
 // Essentially the same as NetworkField
@@ -209,6 +226,71 @@ pub fn parse_pattern<T: FromStr>(
     )
 }
 
+/// Parses --filter-expr over the FieldId of the dumped model. Numbers with a
+/// unit are converted into the raw unit of the field as it is dumped.
+fn parse_filter_expr<F>(opts: &GeneralOpt) -> Result<Option<FilterExpr<F>>>
+where
+    F: FieldId + FromStr,
+    F::Queriable: HasRenderConfigForDump,
+{
+    opts.filter_expr
+        .as_ref()
+        .map(|expr| {
+            FilterExpr::parse(expr, |field_id| {
+                F::Queriable::get_render_config_for_dump(field_id).get_field_unit()
+            })
+            .with_context(|| format!("Failed to parse --filter-expr: {}", expr))
+        })
+        .transpose()
+}
+
+/// All cgroups of the Model, parents before their children.
+pub(crate) fn get_cgroups(model: &model::Model) -> Vec<&model::SingleCgroupModel> {
+    fn walk<'a>(cgroup: &'a model::CgroupModel, cgroups: &mut Vec<&'a model::SingleCgroupModel>) {
+        cgroups.push(&cgroup.data);
+        for child in &cgroup.children {
+            walk(child, cgroups);
+        }
+    }
+    let mut cgroups = Vec::new();
+    walk(&model.cgroup, &mut cgroups);
+    cgroups
+}
+
+/// Dumps each sample of the time range, or a summary of it with --summary.
+fn dump<D>(
+    advance: Advance,
+    time_begin: SystemTime,
+    time_end: SystemTime,
+    dumper: &D,
+    output: &mut dyn Write,
+    opts: &GeneralOpt,
+    errs: Receiver<Error>,
+) -> Result<()>
+where
+    D: Dumper + Summarize,
+    <D::Model as Queriable>::FieldId: ToString,
+{
+    if opts.summary {
+        if opts.output_format == Some(OutputFormat::Influx) {
+            bail!("--summary does not support influx output format");
+        }
+        dump_summary(advance, time_begin, time_end, dumper, output, opts, errs)
+    } else {
+        dump_timeseries(
+            advance,
+            time_begin,
+            time_end,
+            dumper,
+            output,
+            opts.output_format,
+            opts.br.clone(),
+            opts.every.map(Into::into),
+            errs,
+        )
+    }
+}
+
 pub fn run(
     logger: slog::Logger,
     errs: Receiver<Error>,
@@ -242,19 +324,18 @@ pub fn run(
                 },
                 detail,
             );
-            let system = system::System::new(&opts, fields);
+            let system = system::System::new(&opts, fields, parse_filter_expr(&opts)?);
             let mut output: Box<dyn Write> = match opts.output.as_ref() {
                 Some(file_path) => Box::new(File::create(file_path)?),
                 None => Box::new(io::stdout()),
             };
-            dump_timeseries(
+            dump(
                 advance,
                 time_begin,
                 time_end,
                 &system,
                 output.as_mut(),
-                opts.output_format,
-                opts.br,
+                &opts,
                 errs,
             )
         }
@@ -280,19 +361,18 @@ pub fn run(
                 },
                 detail,
             );
-            let disk = disk::Disk::new(&opts, select, fields);
+            let disk = disk::Disk::new(&opts, select, fields, parse_filter_expr(&opts)?);
             let mut output: Box<dyn Write> = match opts.output.as_ref() {
                 Some(file_path) => Box::new(File::create(file_path)?),
                 None => Box::new(io::stdout()),
             };
-            dump_timeseries(
+            dump(
                 advance,
                 time_begin,
                 time_end,
                 &disk,
                 output.as_mut(),
-                opts.output_format,
-                opts.br,
+                &opts,
                 errs,
             )
         }
@@ -318,19 +398,114 @@ pub fn run(
                 },
                 detail,
             );
-            let btrfs = btrfs::Btrfs::new(&opts, select, fields);
+            let btrfs = btrfs::Btrfs::new(&opts, select, fields, parse_filter_expr(&opts)?);
             let mut output: Box<dyn Write> = match opts.output.as_ref() {
                 Some(file_path) => Box::new(File::create(file_path)?),
                 None => Box::new(io::stdout()),
             };
-            dump_timeseries(
+            dump(
                 advance,
                 time_begin,
                 time_end,
                 &btrfs,
                 output.as_mut(),
+                &opts,
+                errs,
+            )
+        }
+        DumpCommand::Resctrl {
+            fields,
+            opts,
+            select,
+            pattern,
+        } => {
+            if opts.summary {
+                bail!("resctrl does not support --summary");
+            }
+            let (time_begin, time_end, advance) =
+                get_advance(logger, dir, host, port, snapshot, &opts)?;
+            let default = opts.everything || opts.default;
+            let detail = opts.everything || opts.detail;
+            let fields = if let Some(pattern_key) = pattern {
+                parse_pattern(filename, pattern_key, "resctrl")
+            } else {
+                fields
+            };
+            let fields = expand_fields(
+                match fields.as_ref() {
+                    Some(fields) if !default => fields,
+                    _ => command::DEFAULT_RESCTRL_FIELDS,
+                },
+                detail,
+            );
+            let resctrl = resctrl::Resctrl::new(
+                &opts,
+                model::ResctrlModel::get_ctrl_mon_groups,
+                select,
+                fields,
+                parse_filter_expr(&opts)?,
+            );
+            let mut output: Box<dyn Write> = match opts.output.as_ref() {
+                Some(file_path) => Box::new(File::create(file_path)?),
+                None => Box::new(io::stdout()),
+            };
+            dump_timeseries(
+                advance,
+                time_begin,
+                time_end,
+                &resctrl,
+                output.as_mut(),
+                opts.output_format,
+                opts.br,
+                opts.every.map(Into::into),
+                errs,
+            )
+        }
+        DumpCommand::ResctrlMon {
+            fields,
+            opts,
+            select,
+            pattern,
+        } => {
+            if opts.summary {
+                bail!("resctrl-mon does not support --summary");
+            }
+            let (time_begin, time_end, advance) =
+                get_advance(logger, dir, host, port, snapshot, &opts)?;
+            let default = opts.everything || opts.default;
+            let detail = opts.everything || opts.detail;
+            let fields = if let Some(pattern_key) = pattern {
+                parse_pattern(filename, pattern_key, "resctrl_mon")
+            } else {
+                fields
+            };
+            let fields = expand_fields(
+                match fields.as_ref() {
+                    Some(fields) if !default => fields,
+                    _ => command::DEFAULT_RESCTRL_MON_FIELDS,
+                },
+                detail,
+            );
+            let resctrl = resctrl::Resctrl::new(
+                &opts,
+                model::ResctrlModel::get_mon_groups,
+                select,
+                fields,
+                parse_filter_expr(&opts)?,
+            );
+            let mut output: Box<dyn Write> = match opts.output.as_ref() {
+                Some(file_path) => Box::new(File::create(file_path)?),
+                None => Box::new(io::stdout()),
+            };
+            dump_timeseries(
+                advance,
+                time_begin,
+                time_end,
+                &resctrl,
+                output.as_mut(),
                 opts.output_format,
                 opts.br,
+                opts.every.map(Into::into),
                 errs,
             )
         }
@@ -339,6 +514,8 @@ pub fn run(
             opts,
             select,
             pattern,
+            group_by,
+            tree,
         } => {
             let (time_begin, time_end, advance) =
                 get_advance(logger, dir, host, port, snapshot, &opts)?;
@@ -356,19 +533,71 @@ pub fn run(
                 },
                 detail,
             );
-            let process = process::Process::new(&opts, select, fields);
             let mut output: Box<dyn Write> = match opts.output.as_ref() {
                 Some(file_path) => Box::new(File::create(file_path)?),
                 None => Box::new(io::stdout()),
             };
-            dump_timeseries(
+            if let Some(group_by) = group_by {
+                if opts.summary {
+                    bail!("--group-by does not support --summary");
+                }
+                let process_group = process::ProcessGroup::new(
+                    &opts,
+                    group_by,
+                    select,
+                    process::get_group_fields(&fields, group_by),
+                    parse_filter_expr(&opts)?,
+                );
+                return dump_timeseries(
+                    advance,
+                    time_begin,
+                    time_end,
+                    &process_group,
+                    output.as_mut(),
+                    opts.output_format,
+                    opts.br,
+                    opts.every.map(Into::into),
+                    errs,
+                );
+            }
+            // Only groups have a member count
+            let select = match select {
+                Some(model::ProcessGroupModelFieldId::Members) => {
+                    bail!("--select members requires --group-by")
+                }
+                Some(model::ProcessGroupModelFieldId::Process(field_id)) => Some(field_id),
+                None => None,
+            };
+            if tree {
+                if opts.summary {
+                    bail!("--tree does not support --summary");
+                }
+                let process_tree = process::ProcessTree::new(
+                    &opts,
+                    select,
+                    process::get_tree_fields(&fields),
+                    parse_filter_expr(&opts)?,
+                );
+                return dump_timeseries(
+                    advance,
+                    time_begin,
+                    time_end,
+                    &process_tree,
+                    output.as_mut(),
+                    opts.output_format,
+                    opts.br,
+                    opts.every.map(Into::into),
+                    errs,
+                );
+            }
+            let process = process::Process::new(&opts, select, fields, parse_filter_expr(&opts)?);
+            dump(
                 advance,
                 time_begin,
                 time_end,
                 &process,
                 output.as_mut(),
-                opts.output_format,
-                opts.br,
+                &opts,
                 errs,
             )
         }
@@ -377,8 +606,9 @@ pub fn run(
             opts,
             select,
             pattern,
+            compare_to,
         } => {
-            let (time_begin, time_end, advance) =
+            let (time_begin, time_end, mut advance) =
                 get_advance(logger, dir, host, port, snapshot, &opts)?;
             let default = opts.everything || opts.default;
             let detail = opts.everything || opts.detail;
@@ -394,19 +624,58 @@ pub fn run(
                 },
                 detail,
             );
-            let cgroup = cgroup::Cgroup::new(&opts, select, fields);
             let mut output: Box<dyn Write> = match opts.output.as_ref() {
                 Some(file_path) => Box::new(File::create(file_path)?),
                 None => Box::new(io::stdout()),
             };
-            dump_timeseries(
+            if let Some(compare_to) = compare_to {
+                if opts.summary {
+                    bail!("--compare-to does not support --summary");
+                }
+                match opts.output_format {
+                    Some(OutputFormat::OpenMetrics) => {
+                        bail!("--compare-to does not support openmetrics output format")
+                    }
+                    Some(OutputFormat::Influx) => {
+                        bail!("--compare-to does not support influx output format")
+                    }
+                    _ => {}
+                }
+                let compare_time = cliutil::system_time_from_date_and_adjuster(
+                    compare_to.as_str(),
+                    opts.yesterdays.as_deref(),
+                )?;
+                let before = match advance.jump_sample_to(compare_time) {
+                    Some(model) => model.cgroup,
+                    None => bail!("No sample could be found to compare to at {}", compare_to),
+                };
+                let cgroup_diff = cgroup::CgroupDiff::new(
+                    &opts,
+                    select,
+                    cgroup::get_diff_fields(&fields),
+                    parse_filter_expr(&opts)?,
+                    before,
+                );
+                return dump_timeseries(
+                    advance,
+                    time_begin,
+                    time_end,
+                    &cgroup_diff,
+                    output.as_mut(),
+                    opts.output_format,
+                    opts.br,
+                    opts.every.map(Into::into),
+                    errs,
+                );
+            }
+            let cgroup = cgroup::Cgroup::new(&opts, select, fields, parse_filter_expr(&opts)?);
+            dump(
                 advance,
                 time_begin,
                 time_end,
                 &cgroup,
                 output.as_mut(),
-                opts.output_format,
-                opts.br,
+                &opts,
                 errs,
             )
         }
@@ -432,19 +701,18 @@ pub fn run(
                 },
                 detail,
             );
-            let iface = iface::Iface::new(&opts, select, fields);
+            let iface = iface::Iface::new(&opts, select, fields, parse_filter_expr(&opts)?);
             let mut output: Box<dyn Write> = match opts.output.as_ref() {
                 Some(file_path) => Box::new(File::create(file_path)?),
                 None => Box::new(io::stdout()),
             };
-            dump_timeseries(
+            dump(
                 advance,
                 time_begin,
                 time_end,
                 &iface,
                 output.as_mut(),
-                opts.output_format,
-                opts.br,
+                &opts,
                 errs,
             )
         }
@@ -469,19 +737,18 @@ pub fn run(
                 },
                 detail,
             );
-            let network = network::Network::new(&opts, fields);
+            let network = network::Network::new(&opts, fields, parse_filter_expr(&opts)?);
             let mut output: Box<dyn Write> = match opts.output.as_ref() {
                 Some(file_path) => Box::new(File::create(file_path)?),
                 None => Box::new(io::stdout()),
             };
-            dump_timeseries(
+            dump(
                 advance,
                 time_begin,
                 time_end,
                 &network,
                 output.as_mut(),
-                opts.output_format,
-                opts.br,
+                &opts,
                 errs,
             )
         }
@@ -506,19 +773,18 @@ pub fn run(
                 },
                 detail,
             );
-            let transport = transport::Transport::new(&opts, fields);
+            let transport = transport::Transport::new(&opts, fields, parse_filter_expr(&opts)?);
             let mut output: Box<dyn Write> = match opts.output.as_ref() {
                 Some(file_path) => Box::new(File::create(file_path)?),
                 None => Box::new(io::stdout()),
             };
-            dump_timeseries(
+            dump(
                 advance,
                 time_begin,
                 time_end,
                 &transport,
                 output.as_mut(),
-                opts.output_format,
-                opts.br,
+                &opts,
                 errs,
             )
         }
@@ -543,19 +809,105 @@ pub fn run(
                 },
                 detail,
             );
-            let ethtool = ethtool::EthtoolQueue::new(&opts, fields);
+            let ethtool = ethtool::EthtoolQueue::new(&opts, fields, parse_filter_expr(&opts)?);
             let mut output: Box<dyn Write> = match opts.output.as_ref() {
                 Some(file_path) => Box::new(File::create(file_path)?),
                 None => Box::new(io::stdout()),
             };
-            dump_timeseries(
+            dump(
                 advance,
                 time_begin,
                 time_end,
                 &ethtool,
                 output.as_mut(),
+                &opts,
+                errs,
+            )
+        }
+        DumpCommand::Multi {
+            opts,
+            system,
+            cgroup,
+            process,
+            disk,
+            iface,
+            network,
+        } => {
+            if opts.summary {
+                bail!("multi does not support --summary");
+            }
+            if opts.sort || opts.rsort || opts.top != 0 {
+                bail!("multi does not support --sort, --rsort or --top");
+            }
+            if opts.filter.is_some() || opts.filter_expr.is_some() {
+                bail!("multi does not support --filter or --filter-expr");
+            }
+            let detail = opts.everything || opts.detail;
+            let mut parts: Vec<Box<dyn multi::MultiPart>> = Vec::new();
+            if let Some(fields) = system {
+                parts.push(Box::new(multi::Part::new(
+                    "system",
+                    expand_fields(&fields, detail),
+                    |model| vec![&model.system],
+                    true,
+                )));
+            }
+            if let Some(fields) = cgroup {
+                parts.push(Box::new(multi::Part::new(
+                    "cgroup",
+                    expand_fields(&fields, detail),
+                    get_cgroups,
+                    false,
+                )));
+            }
+            if let Some(fields) = process {
+                parts.push(Box::new(multi::Part::new(
+                    "process",
+                    expand_fields(&fields, detail),
+                    |model| model.process.processes.values().collect(),
+                    false,
+                )));
+            }
+            if let Some(fields) = disk {
+                parts.push(Box::new(multi::Part::new(
+                    "disk",
+                    expand_fields(&fields, detail),
+                    |model| model.system.disks.values().collect(),
+                    false,
+                )));
+            }
+            if let Some(fields) = iface {
+                parts.push(Box::new(multi::Part::new(
+                    "iface",
+                    expand_fields(&fields, detail),
+                    |model| model.network.interfaces.values().collect(),
+                    false,
+                )));
+            }
+            if let Some(fields) = network {
+                parts.push(Box::new(multi::Part::new(
+                    "network",
+                    expand_fields(&fields, detail),
+                    |model| vec![&model.network],
+                    true,
+                )));
+            }
+            let multi = multi::Multi::new(&opts, parts)?;
+            let (time_begin, time_end, advance) =
+                get_advance(logger, dir, host, port, snapshot, &opts)?;
+            let mut output: Box<dyn Write> = match opts.output.as_ref() {
+                Some(file_path) => Box::new(File::create(file_path)?),
+                None => Box::new(io::stdout()),
+            };
+            dump_timeseries(
+                advance,
+                time_begin,
+                time_end,
+                &multi,
+                output.as_mut(),
                 opts.output_format,
                 opts.br,
+                opts.every.map(Into::into),
                 errs,
             )
         }
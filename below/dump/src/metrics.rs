@@ -0,0 +1,134 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use model::ProcessCpuModelFieldId;
+use model::SingleProcessModelFieldId;
+
+use super::*;
+
+/// Content type of the metrics returned by `get_metrics`.
+pub const METRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Renders the latest Model as a Prometheus scrape response. System, cgroup,
+/// process, disk, iface and network metrics are rendered with the same
+/// OpenMetrics configs as `below dump -O openmetrics`. Only the process_top
+/// processes using the most CPU are included.
+pub fn get_metrics(model: &model::Model, process_top: u32) -> Result<String> {
+    let opts = GeneralOpt {
+        output_format: Some(OutputFormat::OpenMetrics),
+        ..Default::default()
+    };
+    let process_opts = GeneralOpt {
+        rsort: true,
+        top: process_top,
+        ..opts.clone()
+    };
+    let ctx = CommonFieldContext {
+        timestamp: model
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs() as i64,
+        hostname: model.system.hostname.clone(),
+    };
+
+    let dumpers: Vec<Box<dyn Dumper>> = vec![
+        Box::new(system::System::new(
+            &opts,
+            expand_fields(command::DEFAULT_SYSTEM_FIELDS, false),
+            None,
+        )),
+        Box::new(cgroup::Cgroup::new(
+            &opts,
+            None,
+            expand_fields(command::DEFAULT_CGROUP_FIELDS, false),
+            None,
+        )),
+        Box::new(process::Process::new(
+            &process_opts,
+            Some(SingleProcessModelFieldId::Cpu(
+                ProcessCpuModelFieldId::UsagePct,
+            )),
+            expand_fields(command::DEFAULT_PROCESS_FIELDS, false),
+            None,
+        )),
+        Box::new(disk::Disk::new(
+            &opts,
+            None,
+            expand_fields(command::DEFAULT_DISK_FIELDS, false),
+            None,
+        )),
+        Box::new(iface::Iface::new(
+            &opts,
+            None,
+            expand_fields(command::DEFAULT_IFACE_FIELDS, false),
+            None,
+        )),
+        Box::new(network::Network::new(
+            &opts,
+            expand_fields(command::DEFAULT_NETWORK_FIELDS, false),
+            None,
+        )),
+    ];
+
+    let mut output = Vec::new();
+    for dumper in dumpers {
+        dumper.dump_model(&ctx, model, &mut output, &mut 0, false)?;
+    }
+
+    Ok(merge_metric_families(&String::from_utf8(output)?))
+}
+
+/// Turns OpenMetrics dump output into an OpenMetrics exposition.
+// The dump output repeats the metadata of a metric for each entity (e.g.
+// each cgroup), while an exposition must list each metric family once with
+// all of its samples and end with `# EOF`. Samples keep the timestamp of the
+// Model they were collected in.
+pub fn merge_metric_families(text: &str) -> String {
+    let mut families: Vec<(Vec<&str>, Vec<&str>)> = Vec::new();
+    let mut family_idx: HashMap<String, usize> = HashMap::new();
+    let mut current = None;
+
+    for line in text.lines() {
+        if let Some(metadata) = line.strip_prefix("# ") {
+            let mut tokens = metadata.splitn(3, ' ');
+            let (kind, key) = match (tokens.next(), tokens.next()) {
+                (Some(kind), Some(key)) => (kind, key),
+                _ => continue,
+            };
+            let idx = *family_idx.entry(key.to_owned()).or_insert_with(|| {
+                families.push((Vec::new(), Vec::new()));
+                families.len() - 1
+            });
+            current = Some(idx);
+            // Only the metadata before the first samples of a family is kept
+            if families[idx].1.is_empty() && matches!(kind, "TYPE" | "HELP" | "UNIT") {
+                families[idx].0.push(line);
+            }
+        } else if let Some(idx) = current {
+            families[idx].1.push(line);
+        }
+    }
+
+    let mut res = String::new();
+    for (metadata, samples) in families {
+        for line in metadata.into_iter().chain(samples) {
+            res.push_str(line);
+            res.push('\n');
+        }
+    }
+    res.push_str("# EOF\n");
+    res
+}
@@ -0,0 +1,285 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use model::Nameable;
+
+use super::*;
+
+/// A model type dumped by `dump multi`, e.g. cgroup, with its fields.
+pub trait MultiPart {
+    /// Name of the model type, used as JSON key and column prefix.
+    fn name(&self) -> &'static str;
+
+    /// Whether the model type has a single entity per sample, e.g. system.
+    /// Only those can be part of a wide row.
+    fn is_single(&self) -> bool;
+
+    /// An object for a single entity model type, an array of objects
+    /// otherwise.
+    fn dump_json(&self, ctx: &CommonFieldContext, model: &model::Model, raw: bool) -> Value;
+
+    /// Column titles prefixed with the model type name.
+    fn titles(&self, fixed_width: bool) -> Vec<String>;
+
+    /// Values of the single entity, empty if the sample doesn't have it.
+    fn values(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        raw: bool,
+        fixed_width: bool,
+    ) -> Vec<String>;
+
+    /// All entities in OpenMetrics or influx format.
+    fn dump_metrics(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output_format: OutputFormat,
+    ) -> String;
+}
+
+pub struct Part<T: Queriable> {
+    name: &'static str,
+    fields: Vec<DumpField<T::FieldId>>,
+    entities: for<'a> fn(&'a model::Model) -> Vec<&'a T>,
+    single: bool,
+}
+
+impl<T: Queriable> Part<T> {
+    pub fn new(
+        name: &'static str,
+        fields: Vec<DumpField<T::FieldId>>,
+        entities: for<'a> fn(&'a model::Model) -> Vec<&'a T>,
+        single: bool,
+    ) -> Self {
+        Self {
+            name,
+            fields,
+            entities,
+            single,
+        }
+    }
+}
+
+impl<T> MultiPart for Part<T>
+where
+    T: HasRenderConfigForDump + Nameable,
+    T::FieldId: ToString,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn is_single(&self) -> bool {
+        self.single
+    }
+
+    fn dump_json(&self, ctx: &CommonFieldContext, model: &model::Model, raw: bool) -> Value {
+        let mut entities = (self.entities)(model)
+            .into_iter()
+            .map(|entity| print::dump_json(&self.fields, ctx, entity, raw));
+        if self.single {
+            entities.next().unwrap_or(Value::Null)
+        } else {
+            Value::Array(entities.collect())
+        }
+    }
+
+    fn titles(&self, fixed_width: bool) -> Vec<String> {
+        self.fields
+            .iter()
+            .map(|field| {
+                let mut config = field.get_render_config();
+                config.title = Some(format!("{}.{}", self.name, config.render_title(false)));
+                config.render_title(fixed_width)
+            })
+            .collect()
+    }
+
+    fn values(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        raw: bool,
+        fixed_width: bool,
+    ) -> Vec<String> {
+        let entities = (self.entities)(model);
+        self.fields
+            .iter()
+            .map(|field| match entities.first() {
+                Some(entity) => field.dump_field(ctx, entity, raw, fixed_width),
+                None => String::new(),
+            })
+            .collect()
+    }
+
+    fn dump_metrics(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output_format: OutputFormat,
+    ) -> String {
+        (self.entities)(model)
+            .into_iter()
+            .map(|entity| match output_format {
+                OutputFormat::Influx => print::dump_influx(&self.fields, ctx, entity),
+                _ => print::dump_openmetrics(&self.fields, ctx, entity),
+            })
+            .collect()
+    }
+}
+
+/// Dumps several model types per sample, reading the store once. JSON
+/// output nests each model type under its name. Other formats print a wide
+/// row with prefixed column names, which is only possible for single
+/// entity model types. OpenMetrics and influx list all entities.
+pub struct Multi {
+    opts: GeneralOpt,
+    parts: Vec<Box<dyn MultiPart>>,
+}
+
+impl Multi {
+    pub fn new(opts: &GeneralOpt, parts: Vec<Box<dyn MultiPart>>) -> Result<Self> {
+        if parts.is_empty() {
+            bail!("dump multi needs fields of at least one model type, e.g. --system cpu");
+        }
+        match opts.output_format {
+            Some(OutputFormat::Json)
+            | Some(OutputFormat::JsonLines)
+            | Some(OutputFormat::OpenMetrics)
+            | Some(OutputFormat::Influx) => {}
+            _ => {
+                if let Some(part) = parts.iter().find(|part| !part.is_single()) {
+                    bail!(
+                        "{} has several entities per sample, use -O json or jsonl",
+                        part.name()
+                    );
+                }
+            }
+        }
+        Ok(Self {
+            opts: opts.to_owned(),
+            parts,
+        })
+    }
+
+    fn dump_object(&self, ctx: &CommonFieldContext, model: &model::Model) -> Value {
+        let mut res = json!({
+            "Timestamp": ctx.timestamp.to_string(),
+            "Datetime": timestamp_to_datetime(&ctx.timestamp),
+        });
+        for part in &self.parts {
+            res[part.name()] = part.dump_json(ctx, model, self.opts.raw);
+        }
+        res
+    }
+
+    fn dump_row(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        round: usize,
+        sep: &str,
+        fixed_width: bool,
+    ) -> String {
+        let common = [CommonField::Datetime, CommonField::Timestamp];
+        let mut res = String::new();
+        let repeat = self.opts.repeat_title.unwrap_or(0);
+        // Only raw output repeats titles
+        let repeat_title = fixed_width && repeat != 0 && round % repeat == 0;
+        if !self.opts.disable_title && (round == 0 || repeat_title) {
+            let titles = common
+                .iter()
+                .map(|field| field.get_render_config().render_title(fixed_width))
+                .chain(self.parts.iter().flat_map(|part| part.titles(fixed_width)));
+            for title in titles {
+                res.push_str(&title);
+                res.push_str(sep);
+            }
+            res.push('\n');
+        }
+        let values = common
+            .iter()
+            .map(|field| {
+                field
+                    .get_render_config()
+                    .render(field.get_field(ctx), fixed_width)
+            })
+            .chain(
+                self.parts
+                    .iter()
+                    .flat_map(|part| part.values(ctx, model, self.opts.raw, fixed_width)),
+            );
+        for value in values {
+            res.push_str(&value);
+            res.push_str(sep);
+        }
+        res.push('\n');
+        res
+    }
+
+    fn dump_kv(&self, ctx: &CommonFieldContext, model: &model::Model) -> String {
+        let mut res = String::new();
+        for part in &self.parts {
+            let values = part.values(ctx, model, self.opts.raw, false);
+            for (title, value) in part.titles(false).into_iter().zip(values) {
+                res.push_str(&format!("{}: {}\n", title, value));
+            }
+        }
+        res.push('\n');
+        res
+    }
+}
+
+impl Dumper for Multi {
+    fn dump_model(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output: &mut dyn Write,
+        round: &mut usize,
+        comma_flag: bool,
+    ) -> Result<IterExecResult> {
+        match self.opts.output_format {
+            Some(OutputFormat::Raw) | None => {
+                write!(output, "{}", self.dump_row(ctx, model, *round, " ", true))?
+            }
+            Some(OutputFormat::Csv) => {
+                write!(output, "{}", self.dump_row(ctx, model, *round, ",", false))?
+            }
+            Some(OutputFormat::Tsv) => {
+                write!(output, "{}", self.dump_row(ctx, model, *round, "\t", false))?
+            }
+            Some(OutputFormat::KeyVal) => write!(output, "{}", self.dump_kv(ctx, model))?,
+            Some(OutputFormat::Json) => {
+                let par = self.dump_object(ctx, model);
+                if comma_flag {
+                    write!(output, ",{}", par)?;
+                } else {
+                    write!(output, "{}", par)?;
+                }
+            }
+            Some(OutputFormat::JsonLines) => writeln!(output, "{}", self.dump_object(ctx, model))?,
+            Some(format @ OutputFormat::OpenMetrics) | Some(format @ OutputFormat::Influx) => {
+                for part in &self.parts {
+                    write!(output, "{}", part.dump_metrics(ctx, model, format))?;
+                }
+            }
+        }
+        *round += 1;
+
+        Ok(IterExecResult::Success)
+    }
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+
 use model::Field;
 use model::FieldId;
 use model::Nameable;
@@ -39,7 +41,7 @@ where
     F: FieldId,
     <<F as FieldId>::Queriable as Queriable>::FieldId: ToString,
 {
-    fn get_field_id_str(&self) -> String {
+    pub(crate) fn get_field_id_str(&self) -> String {
         match self {
             Self::Common(common) => common.to_string(),
             Self::FieldId(field_id) => field_id.to_string(),
@@ -163,6 +165,18 @@ pub fn dump_json<T: HasRenderConfigForDump>(
     res
 }
 
+/// Renders the fields as a JSON object on a single line, for JSON Lines.
+pub fn dump_jsonl<T: HasRenderConfigForDump>(
+    fields: &[DumpField<T::FieldId>],
+    ctx: &CommonFieldContext,
+    model: &T,
+    raw: bool,
+) -> String {
+    let mut line = dump_json(fields, ctx, model, raw).to_string();
+    line.push('\n');
+    line
+}
+
 fn dump_title_line<F>(fields: &[DumpField<F>], sep: &'static str, fixed_width: bool) -> String
 where
     F: FieldId,
@@ -285,3 +299,202 @@ where
         .flat_map(|s| s.chars().collect::<Vec<_>>().into_iter())
         .collect::<String>()
 }
+
+/// Escapes commas, equal signs and spaces in measurements, tag keys, tag
+/// values and field keys of the InfluxDB line protocol. Line breaks cannot be
+/// escaped and are turned into spaces, as they would end the line.
+fn escape_influx_key(key: &str) -> String {
+    let mut escaped = String::with_capacity(key.len());
+    for c in key.chars() {
+        match c {
+            ',' | '=' | ' ' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push_str("\\ "),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders a field value of the InfluxDB line protocol, or None if the value
+/// cannot be represented (e.g. NaN).
+fn influx_value(field: Field) -> Option<String> {
+    match field {
+        Field::U32(v) => Some(format!("{}i", v)),
+        Field::U64(v) => Some(format!("{}i", v)),
+        Field::I32(v) => Some(format!("{}i", v)),
+        Field::I64(v) => Some(format!("{}i", v)),
+        Field::F32(v) if v.is_finite() => Some(v.to_string()),
+        Field::F64(v) if v.is_finite() => Some(v.to_string()),
+        Field::F32(_) | Field::F64(_) => None,
+        field => Some(format!(
+            "\"{}\"",
+            field.to_string().replace('\\', "\\\\").replace('"', "\\\"")
+        )),
+    }
+}
+
+/// Renders the fields as a line of InfluxDB line protocol. The labels of the
+/// OpenMetrics config of each field (e.g. the cgroup or the pid) and the
+/// hostname become tags, and the fields that would be OpenMetrics metrics
+/// become fields. The timestamp is in nanoseconds.
+pub fn dump_influx<T>(
+    fields: &[DumpField<T::FieldId>],
+    ctx: &CommonFieldContext,
+    model: &T,
+) -> String
+where
+    T: HasRenderConfigForDump,
+    T: Nameable,
+    T::FieldId: ToString,
+{
+    let mut tags = BTreeMap::new();
+    tags.insert("hostname".to_owned(), ctx.hostname.clone());
+    let mut values = Vec::new();
+    for field in fields {
+        let (config, value) = match (
+            field.get_openmetrics_render_config(model),
+            field.get_field(ctx, model),
+        ) {
+            (Some(config), Some(value)) => (config.build(), value),
+            _ => continue,
+        };
+        tags.extend(config.labels().clone());
+        let key = field.get_field_id_str();
+        match value {
+            Field::StrU64Map(map) => {
+                for (k, v) in map {
+                    values.push(format!(
+                        "{}={}i",
+                        escape_influx_key(&format!("{}.{}", key, k)),
+                        v
+                    ));
+                }
+            }
+            value => {
+                if let Some(value) = influx_value(value) {
+                    values.push(format!("{}={}", escape_influx_key(&key), value));
+                }
+            }
+        }
+    }
+    // A line needs at least one field
+    if values.is_empty() {
+        return String::new();
+    }
+
+    let mut line = escape_influx_key(T::name());
+    for (k, v) in tags {
+        // Empty tag values are not allowed
+        if !v.is_empty() {
+            line.push_str(&format!(
+                ",{}={}",
+                escape_influx_key(&k),
+                escape_influx_key(&v)
+            ));
+        }
+    }
+    line.push_str(&format!(
+        " {} {}\n",
+        values.join(","),
+        ctx.timestamp * 1_000_000_000
+    ));
+    line
+}
+
+/// Writes the fields of a model in the output format of `opts`. JSON is
+/// returned instead of written, as each dumper nests the JSON of its models
+/// differently.
+pub fn dump_model<T>(
+    fields: &[DumpField<T::FieldId>],
+    ctx: &CommonFieldContext,
+    model: &T,
+    round: usize,
+    opts: &GeneralOpt,
+    output: &mut dyn Write,
+) -> Result<Option<Value>>
+where
+    T: HasRenderConfigForDump,
+    T: Nameable,
+    T::FieldId: ToString,
+{
+    dump_model_with(fields, ctx, model, round, opts, output, || {
+        dump_raw(
+            fields,
+            ctx,
+            model,
+            round,
+            opts.repeat_title,
+            opts.disable_title,
+            opts.raw,
+        )
+    })
+}
+
+/// Like `dump_model`, but indents the raw output by the depth of the model.
+pub fn dump_model_indented<T>(
+    fields: &[DumpField<T::FieldId>],
+    ctx: &CommonFieldContext,
+    model: &T,
+    round: usize,
+    opts: &GeneralOpt,
+    output: &mut dyn Write,
+) -> Result<Option<Value>>
+where
+    T: HasRenderConfigForDump + Recursive,
+    T: Nameable,
+    T::FieldId: ToString,
+{
+    dump_model_with(fields, ctx, model, round, opts, output, || {
+        dump_raw_indented(
+            fields,
+            ctx,
+            model,
+            round,
+            opts.repeat_title,
+            opts.disable_title,
+            opts.raw,
+        )
+    })
+}
+
+fn dump_model_with<T>(
+    fields: &[DumpField<T::FieldId>],
+    ctx: &CommonFieldContext,
+    model: &T,
+    round: usize,
+    opts: &GeneralOpt,
+    output: &mut dyn Write,
+    raw: impl FnOnce() -> String,
+) -> Result<Option<Value>>
+where
+    T: HasRenderConfigForDump,
+    T: Nameable,
+    T::FieldId: ToString,
+{
+    match opts.output_format {
+        Some(OutputFormat::Raw) | None => write!(output, "{}", raw())?,
+        Some(OutputFormat::Csv) => write!(
+            output,
+            "{}",
+            dump_csv(fields, ctx, model, round, opts.disable_title, opts.raw)
+        )?,
+        Some(OutputFormat::Tsv) => write!(
+            output,
+            "{}",
+            dump_tsv(fields, ctx, model, round, opts.disable_title, opts.raw)
+        )?,
+        Some(OutputFormat::KeyVal) => write!(output, "{}", dump_kv(fields, ctx, model, opts.raw))?,
+        Some(OutputFormat::Json) => return Ok(Some(dump_json(fields, ctx, model, opts.raw))),
+        Some(OutputFormat::OpenMetrics) => {
+            write!(output, "{}", dump_openmetrics(fields, ctx, model))?
+        }
+        Some(OutputFormat::JsonLines) => {
+            write!(output, "{}", dump_jsonl(fields, ctx, model, opts.raw))?
+        }
+        Some(OutputFormat::Influx) => write!(output, "{}", dump_influx(fields, ctx, model))?,
+    }
+    Ok(None)
+}
@@ -12,6 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use model::ProcessGroupBy;
+use model::ProcessGroupModel;
+use model::ProcessGroupModelFieldId;
+use model::ProcessTreeModel;
+use model::ProcessTreeModelFieldId;
+use model::SingleProcessModel;
 use model::SingleProcessModelFieldId;
 
 use super::*;
@@ -20,6 +26,7 @@ pub struct Process {
     opts: GeneralOpt,
     select: Option<SingleProcessModelFieldId>,
     fields: Vec<ProcessField>,
+    filter_expr: Option<FilterExpr<SingleProcessModelFieldId>>,
 }
 
 impl Process {
@@ -27,24 +34,18 @@ impl Process {
         opts: &GeneralOpt,
         select: Option<SingleProcessModelFieldId>,
         fields: Vec<ProcessField>,
+        filter_expr: Option<FilterExpr<SingleProcessModelFieldId>>,
     ) -> Self {
         Self {
             opts: opts.to_owned(),
             select,
             fields,
+            filter_expr,
         }
     }
-}
 
-impl Dumper for Process {
-    fn dump_model(
-        &self,
-        ctx: &CommonFieldContext,
-        model: &model::Model,
-        output: &mut dyn Write,
-        round: &mut usize,
-        comma_flag: bool,
-    ) -> Result<IterExecResult> {
+    /// Processes to dump from the Model, after filtering, sorting and --top.
+    fn get_processes<'a>(&self, model: &'a model::Model) -> Vec<&'a SingleProcessModel> {
         let mut processes: Vec<_> = model
             .process
             .processes
@@ -62,13 +63,13 @@ impl Dumper for Process {
                     _ => Some(spm),
                 },
             )
+            .filter(|spm| {
+                self.filter_expr
+                    .as_ref()
+                    .map_or(true, |expr| expr.eval(spm))
+            })
             .collect();
 
-        // Return if we filtered everything.
-        if processes.is_empty() {
-            return Ok(IterExecResult::Skip);
-        }
-
         if let Some(field_id) = self.select.as_ref() {
             if self.opts.sort {
                 model::sort_queriables(&mut processes, &field_id, false);
@@ -82,63 +83,36 @@ impl Dumper for Process {
                 processes.truncate(self.opts.top as usize);
             }
         }
+
+        processes
+    }
+}
+
+impl Dumper for Process {
+    fn dump_model(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output: &mut dyn Write,
+        round: &mut usize,
+        comma_flag: bool,
+    ) -> Result<IterExecResult> {
+        let processes = self.get_processes(model);
+
+        // Return if we filtered everything.
+        if processes.is_empty() {
+            return Ok(IterExecResult::Skip);
+        }
+
         let mut json_output = json!([]);
 
         processes
             .into_iter()
             .map(|spm| {
-                match self.opts.output_format {
-                    Some(OutputFormat::Raw) | None => write!(
-                        output,
-                        "{}",
-                        print::dump_raw(
-                            &self.fields,
-                            ctx,
-                            spm,
-                            *round,
-                            self.opts.repeat_title,
-                            self.opts.disable_title,
-                            self.opts.raw
-                        )
-                    )?,
-                    Some(OutputFormat::Csv) => write!(
-                        output,
-                        "{}",
-                        print::dump_csv(
-                            &self.fields,
-                            ctx,
-                            spm,
-                            *round,
-                            self.opts.disable_title,
-                            self.opts.raw
-                        )
-                    )?,
-                    Some(OutputFormat::Tsv) => write!(
-                        output,
-                        "{}",
-                        print::dump_tsv(
-                            &self.fields,
-                            ctx,
-                            spm,
-                            *round,
-                            self.opts.disable_title,
-                            self.opts.raw
-                        )
-                    )?,
-                    Some(OutputFormat::KeyVal) => write!(
-                        output,
-                        "{}",
-                        print::dump_kv(&self.fields, ctx, spm, self.opts.raw)
-                    )?,
-                    Some(OutputFormat::Json) => {
-                        let par = print::dump_json(&self.fields, ctx, spm, self.opts.raw);
-                        json_output.as_array_mut().unwrap().push(par);
-                    }
-                    Some(OutputFormat::OpenMetrics) => write!(
-                        output,
-                        "{}",
-                        print::dump_openmetrics(&self.fields, ctx, spm)
-                    )?,
+                if let Some(value) =
+                    print::dump_model(&self.fields, ctx, spm, *round, &self.opts, output)?
+                {
+                    json_output.as_array_mut().unwrap().push(value);
                 }
                 *round += 1;
                 Ok(())
@@ -148,7 +122,335 @@ impl Dumper for Process {
         match (self.opts.output_format, comma_flag) {
             (Some(OutputFormat::Json), true) => write!(output, ",{}", json_output)?,
             (Some(OutputFormat::Json), false) => write!(output, "{}", json_output)?,
-            (Some(OutputFormat::OpenMetrics), _) => (),
+            (Some(OutputFormat::OpenMetrics), _)
+            | (Some(OutputFormat::JsonLines), _)
+            | (Some(OutputFormat::Influx), _) => (),
+            _ => write!(output, "\n")?,
+        };
+
+        Ok(IterExecResult::Success)
+    }
+}
+
+impl Summarize for Process {
+    type Model = SingleProcessModel;
+
+    fn summary_fields(&self, _model: &model::Model) -> Vec<ProcessField> {
+        self.fields.clone()
+    }
+
+    fn entities<'a>(&self, model: &'a model::Model) -> Vec<(String, &'a SingleProcessModel)> {
+        self.get_processes(model)
+            .into_iter()
+            .map(|spm| {
+                let key = format!(
+                    "{} {}",
+                    spm.pid.map_or("?".to_owned(), |pid| pid.to_string()),
+                    spm.comm.as_deref().unwrap_or("?")
+                );
+                (key, spm)
+            })
+            .collect()
+    }
+
+    fn is_identity(field_id: &SingleProcessModelFieldId) -> bool {
+        matches!(
+            field_id,
+            SingleProcessModelFieldId::Pid | SingleProcessModelFieldId::Ppid
+        )
+    }
+}
+
+/// Turns process dump fields into process group dump fields. The grouped by
+/// field and the member count come first, followed by the fields that are
+/// summed up. Other fields cannot be folded and are dropped.
+pub fn get_group_fields(
+    fields: &[ProcessField],
+    group_by: ProcessGroupBy,
+) -> Vec<ProcessGroupField> {
+    let mut group_fields = Vec::new();
+    let mut key_added = false;
+    for field in fields {
+        match field {
+            DumpField::Common(common) => group_fields.push(DumpField::Common(common.clone())),
+            DumpField::FieldId(field_id) => {
+                if !key_added {
+                    group_fields.push(DumpField::FieldId(ProcessGroupModelFieldId::Process(
+                        group_by.field_id(),
+                    )));
+                    group_fields.push(DumpField::FieldId(ProcessGroupModelFieldId::Members));
+                    key_added = true;
+                }
+                if let SingleProcessModelFieldId::Io(_)
+                | SingleProcessModelFieldId::Mem(_)
+                | SingleProcessModelFieldId::Cpu(_) = field_id
+                {
+                    group_fields.push(DumpField::FieldId(ProcessGroupModelFieldId::Process(
+                        field_id.clone(),
+                    )));
+                }
+            }
+        }
+    }
+    if !key_added {
+        group_fields.push(DumpField::FieldId(ProcessGroupModelFieldId::Process(
+            group_by.field_id(),
+        )));
+        group_fields.push(DumpField::FieldId(ProcessGroupModelFieldId::Members));
+    }
+    group_fields
+}
+
+/// Dumps processes folded into groups sharing the value of a field.
+pub struct ProcessGroup {
+    opts: GeneralOpt,
+    group_by: ProcessGroupBy,
+    select: Option<ProcessGroupModelFieldId>,
+    fields: Vec<ProcessGroupField>,
+    filter_expr: Option<FilterExpr<ProcessGroupModelFieldId>>,
+}
+
+impl ProcessGroup {
+    pub fn new(
+        opts: &GeneralOpt,
+        group_by: ProcessGroupBy,
+        select: Option<ProcessGroupModelFieldId>,
+        fields: Vec<ProcessGroupField>,
+        filter_expr: Option<FilterExpr<ProcessGroupModelFieldId>>,
+    ) -> Self {
+        Self {
+            opts: opts.to_owned(),
+            group_by,
+            select,
+            fields,
+            filter_expr,
+        }
+    }
+
+    /// Groups to dump from the Model, after filtering, sorting and --top.
+    fn get_groups(&self, model: &model::Model) -> Vec<ProcessGroupModel> {
+        let groups = ProcessGroupModel::group(model.process.processes.values(), self.group_by);
+        let mut groups: Vec<_> = groups
+            .iter()
+            .filter(
+                |group| match (self.select.as_ref(), self.opts.filter.as_ref()) {
+                    (Some(field_id), Some(filter)) => filter.is_match(
+                        &group
+                            .query(field_id)
+                            .map_or("?".to_owned(), |v| v.to_string()),
+                    ),
+                    _ => true,
+                },
+            )
+            .filter(|group| {
+                self.filter_expr
+                    .as_ref()
+                    .map_or(true, |expr| expr.eval(group))
+            })
+            .collect();
+
+        if let Some(field_id) = self.select.as_ref() {
+            if self.opts.sort {
+                model::sort_queriables(&mut groups, field_id, false);
+            }
+
+            if self.opts.rsort {
+                model::sort_queriables(&mut groups, field_id, true);
+            }
+
+            if (self.opts.sort || self.opts.rsort) && self.opts.top != 0 {
+                groups.truncate(self.opts.top as usize);
+            }
+        }
+
+        groups.into_iter().cloned().collect()
+    }
+}
+
+impl Dumper for ProcessGroup {
+    fn dump_model(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output: &mut dyn Write,
+        round: &mut usize,
+        comma_flag: bool,
+    ) -> Result<IterExecResult> {
+        let groups = self.get_groups(model);
+
+        // Return if we filtered everything.
+        if groups.is_empty() {
+            return Ok(IterExecResult::Skip);
+        }
+
+        let mut json_output = json!([]);
+
+        groups
+            .iter()
+            .map(|group| {
+                if let Some(value) =
+                    print::dump_model(&self.fields, ctx, group, *round, &self.opts, output)?
+                {
+                    json_output.as_array_mut().unwrap().push(value);
+                }
+                *round += 1;
+                Ok(())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        match (self.opts.output_format, comma_flag) {
+            (Some(OutputFormat::Json), true) => write!(output, ",{}", json_output)?,
+            (Some(OutputFormat::Json), false) => write!(output, "{}", json_output)?,
+            (Some(OutputFormat::OpenMetrics), _)
+            | (Some(OutputFormat::JsonLines), _)
+            | (Some(OutputFormat::Influx), _) => (),
+            _ => write!(output, "\n")?,
+        };
+
+        Ok(IterExecResult::Success)
+    }
+}
+
+/// Turns process dump fields into process tree dump fields, which query the
+/// same fields with io, mem and cpu summed up over each subtree.
+pub fn get_tree_fields(fields: &[ProcessField]) -> Vec<ProcessTreeField> {
+    fields
+        .iter()
+        .map(|field| match field {
+            DumpField::Common(common) => DumpField::Common(common.clone()),
+            DumpField::FieldId(field_id) => {
+                DumpField::FieldId(ProcessTreeModelFieldId::Process(field_id.clone()))
+            }
+        })
+        .collect()
+}
+
+/// Dumps processes as trees built from ppid, in pre-order.
+pub struct ProcessTree {
+    opts: GeneralOpt,
+    select: Option<ProcessTreeModelFieldId>,
+    fields: Vec<ProcessTreeField>,
+    filter_expr: Option<FilterExpr<ProcessTreeModelFieldId>>,
+}
+
+impl ProcessTree {
+    pub fn new(
+        opts: &GeneralOpt,
+        select: Option<SingleProcessModelFieldId>,
+        fields: Vec<ProcessTreeField>,
+        filter_expr: Option<FilterExpr<ProcessTreeModelFieldId>>,
+    ) -> Self {
+        Self {
+            opts: opts.to_owned(),
+            select: select.map(ProcessTreeModelFieldId::Process),
+            fields,
+            filter_expr,
+        }
+    }
+
+    /// Whether a process is dumped. Its subtree is traversed either way.
+    fn should_print(&self, tree: &ProcessTreeModel) -> bool {
+        let matches_filter = match (self.select.as_ref(), self.opts.filter.as_ref()) {
+            (Some(field_id), Some(filter)) => filter.is_match(
+                &tree
+                    .query(field_id)
+                    .map_or("?".to_owned(), |v| v.to_string()),
+            ),
+            _ => true,
+        };
+        matches_filter
+            && self
+                .filter_expr
+                .as_ref()
+                .map_or(true, |expr| expr.eval(tree))
+    }
+
+    /// Trees to traverse, after sorting and --top.
+    fn sort_and_truncate<'a>(&self, trees: &'a [ProcessTreeModel]) -> Vec<&'a ProcessTreeModel> {
+        let mut trees = Vec::from_iter(trees);
+        if let Some(field_id) = self.select.as_ref() {
+            if self.opts.sort {
+                model::sort_queriables(&mut trees, field_id, false);
+            }
+
+            if self.opts.rsort {
+                model::sort_queriables(&mut trees, field_id, true);
+            }
+
+            if (self.opts.sort || self.opts.rsort) && self.opts.top != 0 {
+                trees.truncate(self.opts.top as usize);
+            }
+        }
+        trees
+    }
+}
+
+impl Dumper for ProcessTree {
+    fn dump_model(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output: &mut dyn Write,
+        round: &mut usize,
+        comma_flag: bool,
+    ) -> Result<IterExecResult> {
+        fn output_tree(
+            handle: &ProcessTree,
+            ctx: &CommonFieldContext,
+            tree: &ProcessTreeModel,
+            output: &mut dyn Write,
+            round: &mut usize,
+            json: bool,
+            jval: &mut Value,
+        ) -> Result<()> {
+            if handle.should_print(tree) {
+                if let Some(value) = print::dump_model_indented(
+                    &handle.fields,
+                    ctx,
+                    tree,
+                    *round,
+                    &handle.opts,
+                    output,
+                )? {
+                    *jval = value;
+                    jval["children"] = json!([]);
+                }
+                *round += 1;
+            }
+
+            for child_tree in handle.sort_and_truncate(&tree.children) {
+                let mut child = json!({});
+                output_tree(handle, ctx, child_tree, output, round, json, &mut child)?;
+                if json && child["children"].is_array() {
+                    // Parent does not match, but child does, we should also render parent.
+                    if !jval["children"].is_array() {
+                        *jval = print::dump_json(&handle.fields, ctx, tree, handle.opts.raw);
+                        jval["children"] = json!([]);
+                    }
+                    jval["children"].as_array_mut().unwrap().push(child);
+                }
+            }
+
+            Ok(())
+        }
+
+        let trees = ProcessTreeModel::build(model.process.processes.values());
+        let json = self.opts.output_format == Some(OutputFormat::Json);
+        let mut json_output = json!([]);
+        for tree in self.sort_and_truncate(&trees) {
+            let mut jval = json!({});
+            output_tree(self, ctx, tree, output, round, json, &mut jval)?;
+            if json && jval["children"].is_array() {
+                json_output.as_array_mut().unwrap().push(jval);
+            }
+        }
+
+        match (self.opts.output_format, comma_flag) {
+            (Some(OutputFormat::Json), true) => write!(output, ",{}", json_output)?,
+            (Some(OutputFormat::Json), false) => write!(output, "{}", json_output)?,
+            (Some(OutputFormat::OpenMetrics), _)
+            | (Some(OutputFormat::JsonLines), _)
+            | (Some(OutputFormat::Influx), _) => (),
             _ => write!(output, "\n")?,
         };
 
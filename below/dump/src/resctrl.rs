@@ -0,0 +1,124 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use model::Nameable;
+use model::ResctrlModel;
+
+use super::*;
+
+/// Dumps resctrl groups of one kind, i.e. CTRL_MON or MON groups.
+pub struct Resctrl<G: Queriable> {
+    opts: GeneralOpt,
+    get_groups: fn(&ResctrlModel) -> Vec<G>,
+    select: Option<G::FieldId>,
+    fields: Vec<DumpField<G::FieldId>>,
+    filter_expr: Option<FilterExpr<G::FieldId>>,
+}
+
+impl<G: Queriable> Resctrl<G> {
+    pub fn new(
+        opts: &GeneralOpt,
+        get_groups: fn(&ResctrlModel) -> Vec<G>,
+        select: Option<G::FieldId>,
+        fields: Vec<DumpField<G::FieldId>>,
+        filter_expr: Option<FilterExpr<G::FieldId>>,
+    ) -> Self {
+        Self {
+            opts: opts.to_owned(),
+            get_groups,
+            select,
+            fields,
+            filter_expr,
+        }
+    }
+
+    /// Groups to dump, after filtering, sorting and --top.
+    fn filter_groups<'a>(&self, all_groups: &'a [G]) -> Vec<&'a G> {
+        let mut groups: Vec<_> = all_groups
+            .iter()
+            .filter(
+                |model| match (self.select.as_ref(), self.opts.filter.as_ref()) {
+                    (Some(field_id), Some(filter)) => filter.is_match(
+                        &model
+                            .query(field_id)
+                            .map_or("?".to_owned(), |v| v.to_string()),
+                    ),
+                    _ => true,
+                },
+            )
+            .filter(|model| {
+                self.filter_expr
+                    .as_ref()
+                    .map_or(true, |expr| expr.eval(model))
+            })
+            .collect();
+
+        if let Some(field_id) = &self.select {
+            if self.opts.sort {
+                model::sort_queriables(&mut groups, field_id, false);
+            }
+
+            if self.opts.rsort {
+                model::sort_queriables(&mut groups, field_id, true);
+            }
+
+            if (self.opts.sort || self.opts.rsort) && self.opts.top != 0 {
+                groups.truncate(self.opts.top as usize);
+            }
+        }
+
+        groups
+    }
+}
+
+impl<G> Dumper for Resctrl<G>
+where
+    G: Queriable + HasRenderConfigForDump + Nameable,
+    G::FieldId: ToString,
+{
+    fn dump_model(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output: &mut dyn Write,
+        round: &mut usize,
+        comma_flag: bool,
+    ) -> Result<IterExecResult> {
+        let all_groups = match model.resctrl.as_ref() {
+            Some(resctrl) => (self.get_groups)(resctrl),
+            None => return Ok(IterExecResult::Skip),
+        };
+        let mut json_output = json!([]);
+
+        for model in self.filter_groups(&all_groups) {
+            if let Some(value) =
+                print::dump_model(&self.fields, ctx, model, *round, &self.opts, output)?
+            {
+                json_output.as_array_mut().unwrap().push(value);
+            }
+            *round += 1;
+        }
+
+        match (self.opts.output_format, comma_flag) {
+            (Some(OutputFormat::Json), true) => write!(output, ",{}", json_output)?,
+            (Some(OutputFormat::Json), false) => write!(output, "{}", json_output)?,
+            (Some(OutputFormat::OpenMetrics), _)
+            | (Some(OutputFormat::JsonLines), _)
+            | (Some(OutputFormat::Influx), _) => (),
+            _ => write!(output, "\n")?,
+        };
+
+        Ok(IterExecResult::Success)
+    }
+}
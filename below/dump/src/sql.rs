@@ -0,0 +1,271 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::BufRead;
+use std::io::IsTerminal;
+
+use command::SqlOpts;
+use enum_iterator::Sequence;
+use model::FieldType;
+use rusqlite::params_from_iter;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::types::ValueRef;
+use rusqlite::Connection;
+use tmain::jump_to_initial_sample;
+
+use super::*;
+
+/// SQLite type of the columns holding Fields of the given type.
+fn get_sql_type(field_type: FieldType) -> &'static str {
+    match field_type {
+        FieldType::U32 | FieldType::U64 | FieldType::I32 | FieldType::I64 => "INTEGER",
+        FieldType::F32 | FieldType::F64 => "REAL",
+        _ => "TEXT",
+    }
+}
+
+fn to_sql_value(field: Option<Field>) -> SqlValue {
+    match field {
+        None => SqlValue::Null,
+        Some(Field::U32(v)) => SqlValue::Integer(v as i64),
+        Some(Field::U64(v)) => SqlValue::Integer(v as i64),
+        Some(Field::I32(v)) => SqlValue::Integer(v as i64),
+        Some(Field::I64(v)) => SqlValue::Integer(v),
+        Some(Field::F32(v)) => SqlValue::Real(v as f64),
+        Some(Field::F64(v)) => SqlValue::Real(v),
+        Some(field) => SqlValue::Text(field.to_string()),
+    }
+}
+
+/// A table of the database, e.g. the process table.
+pub trait SqlTable {
+    /// Inserts a row per entity of the Model.
+    fn insert_model(&self, conn: &Connection, model: &model::Model) -> Result<()>;
+}
+
+/// A table with a timestamp column followed by a column per field id of
+/// the entities, named as in dump, e.g. "cpu.usage_pct".
+pub struct Table<Q: Queriable> {
+    entities: for<'a> fn(&'a model::Model) -> Vec<&'a Q>,
+    field_ids: Vec<Q::FieldId>,
+    insert: String,
+}
+
+impl<Q> Table<Q>
+where
+    Q: Queriable,
+    Q::FieldId: Sequence + ToString,
+{
+    /// Creates the table if it doesn't exist yet. Column types follow the
+    /// declared types of the fields, as in export.
+    pub fn new(
+        conn: &Connection,
+        name: &str,
+        entities: for<'a> fn(&'a model::Model) -> Vec<&'a Q>,
+    ) -> Result<Self> {
+        // Variants for listing, e.g. cpus.<key>.usage_pct, don't hold values
+        let field_ids: Vec<_> = enum_iterator::all::<Q::FieldId>()
+            .filter(|field_id| !field_id.to_string().contains('<'))
+            .collect();
+
+        let mut columns = vec!["\"timestamp\" INTEGER NOT NULL".to_owned()];
+        for field_id in &field_ids {
+            let sql_type = get_sql_type(Q::field_type(field_id));
+            columns.push(format!("\"{}\" {}", field_id.to_string(), sql_type));
+        }
+        conn.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS \"{}\" ({})",
+                name,
+                columns.join(", ")
+            ),
+            [],
+        )
+        .with_context(|| format!("Failed to create table {}", name))?;
+
+        let insert = format!(
+            "INSERT INTO \"{}\" VALUES ({})",
+            name,
+            vec!["?"; field_ids.len() + 1].join(", ")
+        );
+        Ok(Self {
+            entities,
+            field_ids,
+            insert,
+        })
+    }
+}
+
+impl<Q> SqlTable for Table<Q>
+where
+    Q: Queriable,
+    Q::FieldId: Sequence + ToString,
+{
+    fn insert_model(&self, conn: &Connection, model: &model::Model) -> Result<()> {
+        let timestamp = model
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs() as i64;
+        let mut stmt = conn.prepare_cached(&self.insert)?;
+        for entity in (self.entities)(model) {
+            let values = std::iter::once(SqlValue::Integer(timestamp)).chain(
+                self.field_ids
+                    .iter()
+                    .map(|field_id| to_sql_value(entity.query(field_id))),
+            );
+            stmt.execute(params_from_iter(values))?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates the system, cgroup, process, disk, iface and network tables.
+pub fn get_tables(conn: &Connection) -> Result<Vec<Box<dyn SqlTable>>> {
+    Ok(vec![
+        Box::new(Table::new(conn, "system", |model| vec![&model.system])?),
+        Box::new(Table::new(conn, "cgroup", get_cgroups)?),
+        Box::new(Table::new(conn, "process", |model| {
+            model.process.processes.values().collect()
+        })?),
+        Box::new(Table::new(conn, "disk", |model| {
+            model.system.disks.values().collect()
+        })?),
+        Box::new(Table::new(conn, "iface", |model| {
+            model.network.interfaces.values().collect()
+        })?),
+        Box::new(Table::new(conn, "network", |model| vec![&model.network])?),
+    ])
+}
+
+/// Runs a statement and writes the header and rows of its result, if any,
+/// with values separated by '|'.
+pub fn execute_query(conn: &Connection, query: &str, output: &mut dyn Write) -> Result<()> {
+    let mut stmt = conn.prepare(query)?;
+    let column_count = stmt.column_count();
+    if column_count > 0 {
+        writeln!(output, "{}", stmt.column_names().join("|"))?;
+    }
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let mut values = Vec::with_capacity(column_count);
+        for idx in 0..column_count {
+            values.push(match row.get_ref(idx)? {
+                ValueRef::Null => String::new(),
+                ValueRef::Integer(v) => v.to_string(),
+                ValueRef::Real(v) => v.to_string(),
+                ValueRef::Text(v) => String::from_utf8_lossy(v).into_owned(),
+                ValueRef::Blob(v) => format!("<{} bytes>", v.len()),
+            });
+        }
+        writeln!(output, "{}", values.join("|"))?;
+    }
+    Ok(())
+}
+
+/// Reads statements terminated by ';' from stdin and runs them until EOF.
+/// Errors are reported without stopping, to allow fixing a typo.
+fn run_interactive(conn: &Connection) -> Result<()> {
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    let prompt = |continuation: bool| {
+        if interactive {
+            eprint!("{}", if continuation { "   ...> " } else { "below> " });
+        }
+    };
+
+    let mut query = String::new();
+    prompt(false);
+    for line in stdin.lock().lines() {
+        let line = line?;
+        query.push_str(&line);
+        query.push('\n');
+        if !line.trim_end().ends_with(';') {
+            prompt(!query.trim().is_empty());
+            continue;
+        }
+        if let Err(e) = execute_query(conn, &query, &mut io::stdout()) {
+            eprintln!("Error: {:#}", e);
+        }
+        query.clear();
+        prompt(false);
+    }
+    if !query.trim().is_empty() {
+        execute_query(conn, &query, &mut io::stdout())?;
+    }
+    Ok(())
+}
+
+/// Loads the samples of a time range into a SQLite database and runs the
+/// queries on it.
+pub fn run(
+    logger: slog::Logger,
+    errs: Receiver<Error>,
+    dir: PathBuf,
+    host: Option<String>,
+    port: Option<u16>,
+    snapshot: Option<String>,
+    opts: SqlOpts,
+) -> Result<()> {
+    let general_opts = GeneralOpt {
+        begin: opts.begin.clone(),
+        end: opts.end.clone(),
+        duration: opts.duration.clone(),
+        yesterdays: opts.yesterdays.clone(),
+        ..Default::default()
+    };
+    let (time_begin, time_end, mut advance) =
+        get_advance(logger, dir, host, port, snapshot, &general_opts)?;
+    let mut model = jump_to_initial_sample(&mut advance, time_begin, time_end)?;
+
+    let mut conn = match &opts.database {
+        Some(path) => {
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?
+        }
+        None => Connection::open_in_memory().context("Failed to open in-memory database")?,
+    };
+    // A single transaction, as committing each insert is very slow
+    let tx = conn.transaction()?;
+    let tables = get_tables(&tx)?;
+
+    loop {
+        // Received external error, e.g. stop signal
+        if let Ok(e) = errs.try_recv() {
+            bail!(e);
+        }
+        for table in &tables {
+            table.insert_model(&tx, &model)?;
+        }
+
+        if advance.get_next_ts() > time_end {
+            break;
+        }
+
+        model = match advance.advance(Direction::Forward) {
+            Some(m) => m,
+            None => break,
+        };
+    }
+    tx.commit()?;
+
+    cliutil::check_final_sample_time_with_requested_time(model.timestamp, time_end);
+
+    if opts.execute.is_empty() {
+        run_interactive(&conn)
+    } else {
+        for query in &opts.execute {
+            execute_query(&conn, query, &mut io::stdout())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,459 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use model::Nameable;
+use render::OpenMetricsType;
+use render::RenderConfig;
+use render::RenderConfigBuilder;
+use render::RenderOpenMetricsConfigBuilder;
+use tmain::advance_model;
+use tmain::is_broken_pipe;
+use tmain::jump_to_initial_sample;
+
+use super::*;
+
+/// Statistics of a numeric field over a time range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SummaryStat {
+    Min,
+    Max,
+    Avg,
+    P50,
+    P95,
+    P99,
+}
+
+const SUMMARY_STATS: &[SummaryStat] = &[
+    SummaryStat::Min,
+    SummaryStat::Max,
+    SummaryStat::Avg,
+    SummaryStat::P50,
+    SummaryStat::P95,
+    SummaryStat::P99,
+];
+
+impl SummaryStat {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Avg => "avg",
+            Self::P50 => "p50",
+            Self::P95 => "p95",
+            Self::P99 => "p99",
+        }
+    }
+
+    /// Computes the statistic over numeric values sorted in ascending order.
+    /// Percentiles use the nearest-rank method, so like min and max they are
+    /// one of the values and keep its type.
+    fn compute(&self, sorted: &[Field]) -> Option<Field> {
+        if sorted.is_empty() {
+            return None;
+        }
+        let len = sorted.len();
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * len as f64).ceil() as usize;
+            sorted[rank.clamp(1, len) - 1].clone()
+        };
+        Some(match self {
+            Self::Min => sorted[0].clone(),
+            Self::Max => sorted[len - 1].clone(),
+            Self::Avg => {
+                let avg = sorted.iter().cloned().map(f64::from).sum::<f64>() / len as f64;
+                match sorted[0] {
+                    // Signed fields may render -1 as "max", which only works
+                    // on integers.
+                    Field::I32(_) | Field::I64(_) => Field::I64(avg.round() as i64),
+                    _ => Field::F64(avg),
+                }
+            }
+            Self::P50 => percentile(50.0),
+            Self::P95 => percentile(95.0),
+            Self::P99 => percentile(99.0),
+        })
+    }
+}
+
+/// Dumpers that can list the entities they dump from a Model, so that fields
+/// can be aggregated per entity over a time range with --summary.
+pub trait Summarize {
+    type Model: HasRenderConfigForDump + Nameable;
+
+    /// Fields to aggregate. The first Model of the time range is given for
+    /// dumpers that add fields based on its content.
+    fn summary_fields(
+        &self,
+        model: &model::Model,
+    ) -> Vec<DumpField<<Self::Model as Queriable>::FieldId>>;
+
+    /// Entities dumped from the Model, keyed by an identity that is stable
+    /// across samples, e.g. the cgroup path.
+    fn entities<'a>(&self, model: &'a model::Model) -> Vec<(String, &'a Self::Model)>;
+
+    /// Whether the numeric field identifies the entity, in which case its
+    /// latest value is dumped instead of its statistics.
+    fn is_identity(_field_id: &<Self::Model as Queriable>::FieldId) -> bool {
+        false
+    }
+
+    /// Indentation depth of the entity in raw output.
+    fn depth(_model: &Self::Model) -> usize {
+        0
+    }
+}
+
+/// Values of a single entity collected over the time range.
+struct EntitySummary {
+    samples: u64,
+    depth: usize,
+    /// Context of the latest sample with the entity.
+    timestamp: i64,
+    hostname: String,
+    /// Numeric values of each field. Sorted once all samples are added.
+    values: Vec<Vec<Field>>,
+    /// Latest value of each field.
+    latest: Vec<Option<Field>>,
+    /// Latest OpenMetrics config of each field, which carries its labels.
+    openmetrics: Vec<Option<RenderOpenMetricsConfigBuilder>>,
+}
+
+impl EntitySummary {
+    fn new(num_fields: usize) -> Self {
+        Self {
+            samples: 0,
+            depth: 0,
+            timestamp: 0,
+            hostname: String::new(),
+            values: vec![Vec::new(); num_fields],
+            latest: vec![None; num_fields],
+            openmetrics: vec![None; num_fields],
+        }
+    }
+}
+
+/// What a column of the summary shows for each entity.
+#[derive(Clone, Copy)]
+enum ColumnValue {
+    /// Number of samples the entity is in.
+    Samples,
+    /// Latest value of a field.
+    Latest(usize),
+    /// Statistic of a numeric field.
+    Stat(usize, SummaryStat),
+}
+
+struct Column {
+    value: ColumnValue,
+    config: RenderConfig,
+}
+
+impl Column {
+    fn get(&self, entity: &EntitySummary) -> Option<Field> {
+        match self.value {
+            ColumnValue::Samples => Some(Field::U64(entity.samples)),
+            ColumnValue::Latest(idx) => entity.latest[idx].clone(),
+            ColumnValue::Stat(idx, stat) => stat.compute(&entity.values[idx]),
+        }
+    }
+
+    fn render(&self, entity: &EntitySummary, fixed_width: bool) -> String {
+        // Like other dumps, only fixed width output is indented
+        let depth = if fixed_width { entity.depth } else { 0 };
+        self.config
+            .render_indented(self.get(entity), fixed_width, depth)
+    }
+}
+
+fn dump_title_line(columns: &[Column], sep: &str, fixed_width: bool) -> String {
+    let mut line = String::new();
+    for column in columns {
+        line.push_str(&column.config.render_title(fixed_width));
+        line.push_str(sep);
+    }
+    line.push('\n');
+    line
+}
+
+fn dump_row(columns: &[Column], entity: &EntitySummary, sep: &str, fixed_width: bool) -> String {
+    let mut line = String::new();
+    for column in columns {
+        line.push_str(&column.render(entity, fixed_width));
+        line.push_str(sep);
+    }
+    line.push('\n');
+    line
+}
+
+/// Aggregates the fields of each entity of a Summarize dumper over Models.
+pub struct Summary<'a, S: Summarize> {
+    summarizer: &'a S,
+    fields: Vec<DumpField<<S::Model as Queriable>::FieldId>>,
+    /// Entities in the order they first appear in.
+    entities: Vec<EntitySummary>,
+    index: HashMap<String, usize>,
+}
+
+impl<'a, S> Summary<'a, S>
+where
+    S: Summarize,
+    <S::Model as Queriable>::FieldId: ToString,
+{
+    pub fn new(summarizer: &'a S, model: &model::Model) -> Self {
+        Self {
+            summarizer,
+            fields: summarizer.summary_fields(model),
+            entities: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn add_model(&mut self, ctx: &CommonFieldContext, model: &model::Model) {
+        for (key, entity_model) in self.summarizer.entities(model) {
+            let idx = match self.index.get(&key) {
+                Some(idx) => *idx,
+                None => {
+                    self.entities.push(EntitySummary::new(self.fields.len()));
+                    self.index.insert(key, self.entities.len() - 1);
+                    self.entities.len() - 1
+                }
+            };
+            let entity = &mut self.entities[idx];
+            entity.samples += 1;
+            entity.depth = S::depth(entity_model);
+            entity.timestamp = ctx.timestamp;
+            entity.hostname = ctx.hostname.clone();
+            for (field_idx, field) in self.fields.iter().enumerate() {
+                let value = field.get_field(ctx, entity_model);
+                if let Some(value) = &value {
+                    if model::numeric_value(value.clone()).is_some() {
+                        entity.values[field_idx].push(value.clone());
+                    }
+                }
+                entity.latest[field_idx] = value;
+                entity.openmetrics[field_idx] = field.get_openmetrics_render_config(entity_model);
+            }
+        }
+    }
+
+    /// A field is aggregated if it is numeric for any entity, so all rows
+    /// have the same columns.
+    fn get_columns(&self, raw: bool) -> Vec<Column> {
+        let mut columns = vec![Column {
+            value: ColumnValue::Samples,
+            config: RenderConfigBuilder::new().title("Samples").get(),
+        }];
+        for (idx, field) in self.fields.iter().enumerate() {
+            let mut config = field.get_render_config();
+            if raw {
+                config.format = None;
+                config.suffix = None;
+            }
+            let aggregated = match field {
+                DumpField::Common(_) => false,
+                DumpField::FieldId(field_id) => {
+                    !S::is_identity(field_id)
+                        && self
+                            .entities
+                            .iter()
+                            .any(|entity| !entity.values[idx].is_empty())
+                }
+            };
+            if !aggregated {
+                columns.push(Column {
+                    value: ColumnValue::Latest(idx),
+                    config,
+                });
+                continue;
+            }
+            for stat in SUMMARY_STATS {
+                let title = format!("{} {}", config.get_title(), stat.name());
+                let mut config = config.clone();
+                config.width = config.width.map(|width| width.max(title.len() + 2));
+                config.title = Some(title);
+                columns.push(Column {
+                    value: ColumnValue::Stat(idx, *stat),
+                    config,
+                });
+            }
+        }
+        columns
+    }
+
+    fn dump_openmetrics(&self, column: &Column, entity: &EntitySummary) -> String {
+        let (idx, stat) = match column.value {
+            ColumnValue::Samples => return String::new(),
+            ColumnValue::Latest(idx) => (idx, None),
+            ColumnValue::Stat(idx, stat) => (idx, Some(stat)),
+        };
+        // Fields without a config, e.g. strings, cannot be dumped
+        let (builder, field) = match (&entity.openmetrics[idx], column.get(entity)) {
+            (Some(builder), Some(field)) => (builder.clone(), field),
+            _ => return String::new(),
+        };
+        // OpenMetrics forbids `.` in metric name
+        let key = format!(
+            "{}_{}",
+            <S::Model as Nameable>::name(),
+            self.fields[idx].get_field_id_str().replace('.', "_")
+        );
+        let builder = builder.label("hostname", &entity.hostname);
+        let builder = match stat {
+            // A statistic is a gauge even if the field is a counter
+            Some(stat) => builder
+                .ty(OpenMetricsType::Gauge)
+                .label("stat", stat.name()),
+            None => builder,
+        };
+        builder.build().render(&key, field, entity.timestamp)
+    }
+
+    pub fn dump(mut self, output: &mut dyn Write, opts: &GeneralOpt) -> Result<()> {
+        for entity in &mut self.entities {
+            for values in &mut entity.values {
+                values
+                    .sort_by(|lhs, rhs| f64::from(lhs.clone()).total_cmp(&f64::from(rhs.clone())));
+            }
+        }
+        let columns = self.get_columns(opts.raw);
+
+        let mut res = String::new();
+        match opts.output_format {
+            Some(OutputFormat::Raw) | None => {
+                if !opts.disable_title {
+                    res.push_str(&dump_title_line(&columns, " ", true));
+                }
+                for entity in &self.entities {
+                    res.push_str(&dump_row(&columns, entity, " ", true));
+                }
+            }
+            Some(OutputFormat::Csv) => {
+                if !opts.disable_title {
+                    res.push_str(&dump_title_line(&columns, ",", false));
+                }
+                for entity in &self.entities {
+                    res.push_str(&dump_row(&columns, entity, ",", false));
+                }
+            }
+            Some(OutputFormat::Tsv) => {
+                if !opts.disable_title {
+                    res.push_str(&dump_title_line(&columns, "\t", false));
+                }
+                for entity in &self.entities {
+                    res.push_str(&dump_row(&columns, entity, "\t", false));
+                }
+            }
+            Some(OutputFormat::KeyVal) => {
+                for entity in &self.entities {
+                    for column in &columns {
+                        res.push_str(&format!(
+                            "{}: {}\n",
+                            column.config.render_title(false),
+                            column.render(entity, false)
+                        ));
+                    }
+                    res.push('\n');
+                }
+            }
+            Some(OutputFormat::Json) => {
+                let mut json_output = json!([]);
+                for entity in &self.entities {
+                    let mut par = json!({});
+                    for column in &columns {
+                        par[column.config.render_title(false)] =
+                            json!(column.render(entity, false));
+                    }
+                    json_output.as_array_mut().unwrap().push(par);
+                }
+                res.push_str(&json_output.to_string());
+            }
+            Some(OutputFormat::JsonLines) => {
+                for entity in &self.entities {
+                    let mut par = json!({});
+                    for column in &columns {
+                        par[column.config.render_title(false)] =
+                            json!(column.render(entity, false));
+                    }
+                    res.push_str(&par.to_string());
+                    res.push('\n');
+                }
+            }
+            Some(OutputFormat::OpenMetrics) => {
+                for entity in &self.entities {
+                    for column in &columns {
+                        res.push_str(&self.dump_openmetrics(column, entity));
+                    }
+                }
+                res.push_str("# EOF\n");
+            }
+            // Rejected before summarizing, there is no timestamp per row
+            Some(OutputFormat::Influx) => {}
+        }
+        output.write_all(res.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Called by dump commands with --summary. Instead of dumping each Model of
+/// the time range, aggregates the fields of each entity over them and dumps
+/// one row per entity.
+pub fn dump_summary<S>(
+    mut advance: Advance,
+    time_begin: SystemTime,
+    time_end: SystemTime,
+    summarizer: &S,
+    output: &mut dyn Write,
+    opts: &GeneralOpt,
+    errs: Receiver<Error>,
+) -> Result<()>
+where
+    S: Summarize,
+    <S::Model as Queriable>::FieldId: ToString,
+{
+    let mut model = jump_to_initial_sample(&mut advance, time_begin, time_end)?;
+    let mut summary = Summary::new(summarizer, &model);
+
+    loop {
+        // Received external error, e.g. stop signal
+        if let Ok(e) = errs.try_recv() {
+            bail!(e);
+        }
+        let ctx = CommonFieldContext {
+            timestamp: model
+                .timestamp
+                .duration_since(SystemTime::UNIX_EPOCH)?
+                .as_secs() as i64,
+            hostname: model.system.hostname.clone(),
+        };
+        summary.add_model(&ctx, &model);
+
+        if advance.get_next_ts() > time_end {
+            break;
+        }
+
+        model = match advance_model(&mut advance, &model, opts.every.map(Into::into), time_end) {
+            Some(m) => m,
+            None => break,
+        };
+    }
+
+    cliutil::check_final_sample_time_with_requested_time(model.timestamp, time_end);
+
+    match summary.dump(output, opts) {
+        Err(e) if is_broken_pipe(&e) => Ok(()),
+        res => res,
+    }
+}
@@ -17,26 +17,24 @@ use super::*;
 pub struct System {
     opts: GeneralOpt,
     fields: Vec<SystemField>,
+    filter_expr: Option<FilterExpr<model::SystemModelFieldId>>,
 }
 
 impl System {
-    pub fn new(opts: &GeneralOpt, fields: Vec<SystemField>) -> Self {
+    pub fn new(
+        opts: &GeneralOpt,
+        fields: Vec<SystemField>,
+        filter_expr: Option<FilterExpr<model::SystemModelFieldId>>,
+    ) -> Self {
         Self {
             opts: opts.to_owned(),
             fields,
+            filter_expr,
         }
     }
-}
 
-impl Dumper for System {
-    fn dump_model(
-        &self,
-        ctx: &CommonFieldContext,
-        model: &model::Model,
-        output: &mut dyn Write,
-        round: &mut usize,
-        comma_flag: bool,
-    ) -> Result<IterExecResult> {
+    /// Fields to dump, which include per-cpu fields of the Model with --detail.
+    fn get_fields(&self, model: &model::Model) -> Vec<SystemField> {
         let mut fields = self.fields.clone();
 
         if self.opts.detail || self.opts.everything {
@@ -57,6 +55,27 @@ impl Dumper for System {
             }
         }
 
+        fields
+    }
+}
+
+impl Dumper for System {
+    fn dump_model(
+        &self,
+        ctx: &CommonFieldContext,
+        model: &model::Model,
+        output: &mut dyn Write,
+        round: &mut usize,
+        comma_flag: bool,
+    ) -> Result<IterExecResult> {
+        if let Some(expr) = &self.filter_expr {
+            if !expr.eval(&model.system) {
+                return Ok(IterExecResult::Skip);
+            }
+        }
+
+        let fields = self.get_fields(model);
+
         match self.opts.output_format {
             Some(OutputFormat::Raw) | None => write!(
                 output,
@@ -113,6 +132,16 @@ impl Dumper for System {
                 "{}",
                 print::dump_openmetrics(&fields, ctx, &model.system)
             )?,
+            Some(OutputFormat::JsonLines) => write!(
+                output,
+                "{}",
+                print::dump_jsonl(&fields, ctx, &model.system, self.opts.raw)
+            )?,
+            Some(OutputFormat::Influx) => write!(
+                output,
+                "{}",
+                print::dump_influx(&fields, ctx, &model.system)
+            )?,
         };
 
         *round += 1;
@@ -120,3 +149,18 @@ impl Dumper for System {
         Ok(IterExecResult::Success)
     }
 }
+
+impl Summarize for System {
+    type Model = model::SystemModel;
+
+    fn summary_fields(&self, model: &model::Model) -> Vec<SystemField> {
+        self.get_fields(model)
+    }
+
+    fn entities<'a>(&self, model: &'a model::Model) -> Vec<(String, &'a model::SystemModel)> {
+        match &self.filter_expr {
+            Some(expr) if !expr.eval(&model.system) => vec![],
+            _ => vec![(String::new(), &model.system)],
+        }
+    }
+}
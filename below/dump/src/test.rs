@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::time::Duration;
 
 use command::expand_fields;
@@ -48,7 +49,7 @@ fn test_dump_sys_content() {
         )));
     }
     opts.output_format = Some(OutputFormat::Json);
-    let system_dumper = system::System::new(&opts, fields.clone());
+    let system_dumper = system::System::new(&opts, fields.clone(), None);
 
     // update model again to populate cpu and io data
     let model = collector
@@ -207,7 +208,7 @@ fn test_dump_process_content() {
     let mut opts: GeneralOpt = Default::default();
     let fields = command::expand_fields(command::DEFAULT_PROCESS_FIELDS, true);
     opts.output_format = Some(OutputFormat::Json);
-    let process_dumper = process::Process::new(&opts, None, fields.clone());
+    let process_dumper = process::Process::new(&opts, None, fields.clone(), None);
 
     // update model again to populate cpu and io data
     let model = collector
@@ -335,6 +336,7 @@ fn test_dump_proc_select() {
         &opts,
         Some(model::SingleProcessModelFieldId::Pid),
         fields.clone(),
+        None,
     );
 
     let mut process_content: Vec<u8> = Vec::new();
@@ -360,6 +362,7 @@ fn test_dump_proc_select() {
         &opts,
         Some(model::SingleProcessModelFieldId::Pid),
         fields.clone(),
+        None,
     );
 
     process_content = Vec::new();
@@ -385,8 +388,12 @@ fn test_dump_proc_select() {
     // test select sort top
     opts.sort = false;
     opts.rsort = true;
-    let process_dumper =
-        process::Process::new(&opts, Some(model::SingleProcessModelFieldId::Pid), fields);
+    let process_dumper = process::Process::new(
+        &opts,
+        Some(model::SingleProcessModelFieldId::Pid),
+        fields,
+        None,
+    );
 
     process_content = Vec::new();
     round = 0;
@@ -409,6 +416,365 @@ fn test_dump_proc_select() {
     }
 }
 
+#[test]
+fn test_dump_process_jsonl_and_influx() {
+    let processes = [(1, "a b", Some(1024)), (2, "c\nd", None)]
+        .into_iter()
+        .map(|(pid, comm, rss_bytes)| {
+            (
+                pid,
+                model::SingleProcessModel {
+                    pid: Some(pid),
+                    comm: Some(comm.to_string()),
+                    cpu: Some(model::ProcessCpuModel {
+                        usage_pct: Some(pid as f64 * 2.5),
+                        ..Default::default()
+                    }),
+                    mem: rss_bytes.map(|rss_bytes| model::ProcessMemoryModel {
+                        rss_bytes: Some(rss_bytes),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+    let model = model::Model {
+        time_elapsed: Duration::from_secs(60),
+        timestamp: SystemTime::now(),
+        system: model::SystemModel::default(),
+        cgroup: model::CgroupModel::default(),
+        process: model::ProcessModel { processes },
+        network: model::NetworkModel::default(),
+        gpu: None,
+        resctrl: None,
+    };
+    let fields = vec![
+        DumpField::FieldId(model::SingleProcessModelFieldId::Pid),
+        DumpField::FieldId(model::SingleProcessModelFieldId::Comm),
+        DumpField::FieldId(model::SingleProcessModelFieldId::Cpu(
+            model::ProcessCpuModelFieldId::UsagePct,
+        )),
+        DumpField::FieldId(model::SingleProcessModelFieldId::Mem(
+            model::ProcessMemoryModelFieldId::RssBytes,
+        )),
+    ];
+    let ctx = CommonFieldContext {
+        timestamp: 60,
+        hostname: "h".to_string(),
+    };
+    let dump = |output_format| {
+        let opts = GeneralOpt {
+            output_format: Some(output_format),
+            raw: true,
+            ..Default::default()
+        };
+        let process_dumper = process::Process::new(&opts, None, fields.clone(), None);
+        let mut content: Vec<u8> = Vec::new();
+        let mut round = 0;
+        process_dumper
+            .dump_model(&ctx, &model, &mut content, &mut round, false)
+            .expect("Failed to dump process model");
+        String::from_utf8(content).expect("Output is not UTF-8")
+    };
+
+    let jsonl = dump(OutputFormat::JsonLines);
+    let rows = jsonl
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).expect("Fail parse json line"))
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["Pid"], "1");
+    assert_eq!(rows[0]["Comm"], "a b");
+    assert_eq!(rows[1]["Pid"], "2");
+
+    assert_eq!(
+        dump(OutputFormat::Influx),
+        "process,comm=a\\ b,hostname=h,pid=1 cpu.usage_pct=2.5,mem.rss_bytes=1024i 60000000000\n\
+         process,comm=c\\ d,hostname=h,pid=2 cpu.usage_pct=5 60000000000\n"
+    );
+}
+
+#[test]
+fn test_dump_proc_filter_expr() {
+    let logger = get_logger();
+    let mut collector = Collector::new(logger.clone(), Default::default());
+    let model = collector
+        .collect_and_update_model()
+        .expect("Fail to get model");
+
+    let mut pids = model.process.processes.keys();
+    let first = pids.next().unwrap();
+    let last = pids.last().unwrap();
+    let fields = command::expand_fields(command::DEFAULT_PROCESS_FIELDS, false);
+    let opts = GeneralOpt {
+        output_format: Some(OutputFormat::Json),
+        ..Default::default()
+    };
+    let ctx = CommonFieldContext {
+        timestamp: 0,
+        hostname: "h".to_string(),
+    };
+
+    for (expr, expected_pids) in [
+        (format!("pid == {}", first), vec![*first]),
+        (
+            format!(
+                "pid == {} or (pid >= {} and not pid != {})",
+                first, last, last
+            ),
+            vec![*first, *last],
+        ),
+        (format!("pid < {} and pid > {}", first, last), vec![]),
+    ] {
+        let process_dumper = process::Process::new(
+            &opts,
+            None,
+            fields.clone(),
+            Some(expr.parse().expect("Fail to parse filter expression")),
+        );
+        let mut process_content: Vec<u8> = Vec::new();
+        let mut round = 0;
+        let res = process_dumper
+            .dump_model(&ctx, &model, &mut process_content, &mut round, false)
+            .expect("Failed to dump process model");
+        if expected_pids.is_empty() {
+            assert!(
+                res == IterExecResult::Skip,
+                "{} should filter everything",
+                expr
+            );
+            continue;
+        }
+        let jval: Value =
+            serde_json::from_slice(&process_content).expect("Fail parse json of process dump");
+        let pids: Vec<i32> = jval
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["Pid"].as_str().unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(pids, expected_pids, "Unexpected pids for {}", expr);
+    }
+}
+
+#[test]
+fn test_dump_process_group_by() {
+    let processes = [
+        (1, 10, "worker", 10.0),
+        (2, 10, "worker", 5.0),
+        (3, 2, "server", 20.0),
+    ]
+    .into_iter()
+    .map(|(pid, ppid, comm, usage_pct)| {
+        (
+            pid,
+            model::SingleProcessModel {
+                pid: Some(pid),
+                ppid: Some(ppid),
+                comm: Some(comm.to_string()),
+                cpu: Some(model::ProcessCpuModel {
+                    usage_pct: Some(usage_pct),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+    })
+    .collect();
+    let model = model::Model {
+        time_elapsed: Duration::from_secs(60),
+        timestamp: SystemTime::now(),
+        system: model::SystemModel::default(),
+        cgroup: model::CgroupModel::default(),
+        process: model::ProcessModel { processes },
+        network: model::NetworkModel::default(),
+        gpu: None,
+        resctrl: None,
+    };
+    let fields = vec![
+        DumpField::FieldId(model::SingleProcessModelFieldId::Pid),
+        DumpField::FieldId(model::SingleProcessModelFieldId::Cpu(
+            model::ProcessCpuModelFieldId::UsagePct,
+        )),
+    ];
+    let ctx = CommonFieldContext {
+        timestamp: 0,
+        hostname: "h".to_string(),
+    };
+
+    let cpu = model::ProcessGroupModelFieldId::Process(model::SingleProcessModelFieldId::Cpu(
+        model::ProcessCpuModelFieldId::UsagePct,
+    ));
+    let dump_groups_by = |group_by,
+                          opts: &GeneralOpt,
+                          select: Option<&model::ProcessGroupModelFieldId>,
+                          filter_expr: Option<&str>| {
+        let process_group = process::ProcessGroup::new(
+            opts,
+            group_by,
+            select.cloned(),
+            process::get_group_fields(&fields, group_by),
+            filter_expr.map(|expr| expr.parse().expect("Fail to parse filter expression")),
+        );
+        let mut content: Vec<u8> = Vec::new();
+        let mut round = 0;
+        process_group
+            .dump_model(&ctx, &model, &mut content, &mut round, false)
+            .expect("Failed to dump process groups");
+        serde_json::from_slice::<Value>(&content).expect("Fail parse json of process groups")
+    };
+    let dump_groups =
+        |opts: &GeneralOpt, select: &model::ProcessGroupModelFieldId, filter_expr: Option<&str>| {
+            dump_groups_by(model::ProcessGroupBy::Comm, opts, Some(select), filter_expr)
+        };
+
+    let opts = GeneralOpt {
+        output_format: Some(OutputFormat::Json),
+        rsort: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        dump_groups(&opts, &cpu, None),
+        json!([
+            {"Comm": "server", "Members": "1", "CPU": "20.00%"},
+            {"Comm": "worker", "Members": "2", "CPU": "15.00%"}
+        ])
+    );
+    assert_eq!(
+        dump_groups(&opts, &model::ProcessGroupModelFieldId::Members, None),
+        json!([
+            {"Comm": "worker", "Members": "2", "CPU": "15.00%"},
+            {"Comm": "server", "Members": "1", "CPU": "20.00%"}
+        ])
+    );
+    assert_eq!(
+        dump_groups(&opts, &cpu, Some("members > 1")),
+        json!([{"Comm": "worker", "Members": "2", "CPU": "15.00%"}])
+    );
+
+    // Numeric keys are ordered as numbers
+    let opts = GeneralOpt {
+        output_format: Some(OutputFormat::Json),
+        ..Default::default()
+    };
+    assert_eq!(
+        dump_groups_by(model::ProcessGroupBy::Ppid, &opts, None, None),
+        json!([
+            {"Ppid": "2", "Members": "1", "CPU": "20.00%"},
+            {"Ppid": "10", "Members": "2", "CPU": "15.00%"}
+        ])
+    );
+}
+
+#[test]
+fn test_dump_process_tree() {
+    let processes = [
+        (1, 0, "init", 1.0),
+        (2, 1, "server", 10.0),
+        (3, 2, "worker", 5.0),
+        (4, 1, "shell", 20.0),
+    ]
+    .into_iter()
+    .map(|(pid, ppid, comm, usage_pct)| {
+        (
+            pid,
+            model::SingleProcessModel {
+                pid: Some(pid),
+                ppid: Some(ppid),
+                comm: Some(comm.to_string()),
+                cpu: Some(model::ProcessCpuModel {
+                    usage_pct: Some(usage_pct),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+    })
+    .collect();
+    let model = model::Model {
+        time_elapsed: Duration::from_secs(60),
+        timestamp: SystemTime::now(),
+        system: model::SystemModel::default(),
+        cgroup: model::CgroupModel::default(),
+        process: model::ProcessModel { processes },
+        network: model::NetworkModel::default(),
+        gpu: None,
+        resctrl: None,
+    };
+    let fields = process::get_tree_fields(&[
+        DumpField::FieldId(model::SingleProcessModelFieldId::Comm),
+        DumpField::FieldId(model::SingleProcessModelFieldId::Cpu(
+            model::ProcessCpuModelFieldId::UsagePct,
+        )),
+    ]);
+    let ctx = CommonFieldContext {
+        timestamp: 0,
+        hostname: "h".to_string(),
+    };
+
+    let dump_tree = |opts: &GeneralOpt| {
+        let process_tree = process::ProcessTree::new(
+            opts,
+            Some(model::SingleProcessModelFieldId::Cpu(
+                model::ProcessCpuModelFieldId::UsagePct,
+            )),
+            fields.clone(),
+            None,
+        );
+        let mut content: Vec<u8> = Vec::new();
+        let mut round = 0;
+        process_tree
+            .dump_model(&ctx, &model, &mut content, &mut round, false)
+            .expect("Failed to dump process tree");
+        String::from_utf8(content).expect("Fail to decode process tree")
+    };
+
+    // Children are sorted by the usage of their subtree
+    let opts = GeneralOpt {
+        rsort: true,
+        disable_title: true,
+        ..Default::default()
+    };
+    let lines: Vec<_> = dump_tree(&opts)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "init 36.00%",
+            "└─ shell 20.00%",
+            "└─ server 15.00%",
+            "└─ worker 5.00%",
+            "",
+        ]
+    );
+    // Each level indents the name by the width of the prefix
+    let content = dump_tree(&opts);
+    let indent = |comm: &str| {
+        let line = content.lines().find(|line| line.contains(comm)).unwrap();
+        line.chars().take_while(|c| *c == ' ').count()
+    };
+    assert_eq!(indent("worker"), indent("server") + "└─ ".chars().count());
+
+    let opts = GeneralOpt {
+        output_format: Some(OutputFormat::Json),
+        filter: Some(regex::Regex::new("^5$").expect("Fail to construct regex")),
+        ..Default::default()
+    };
+    assert_eq!(
+        serde_json::from_str::<Value>(&dump_tree(&opts)).expect("Fail parse json of process tree"),
+        json!([{
+            "Comm": "init", "CPU": "36.00%", "children": [{
+                "Comm": "server", "CPU": "15.00%", "children": [{
+                    "Comm": "worker", "CPU": "5.00%", "children": []
+                }]
+            }]
+        }])
+    );
+}
+
 fn traverse_cgroup_tree(model: &model::CgroupModel, jval: &Value) {
     for dump_field in expand_fields(command::DEFAULT_CGROUP_FIELDS, true) {
         match dump_field {
@@ -449,7 +815,7 @@ fn test_dump_cgroup_content() {
     let mut opts: GeneralOpt = Default::default();
     let fields = command::expand_fields(command::DEFAULT_CGROUP_FIELDS, true);
     opts.output_format = Some(OutputFormat::Json);
-    let cgroup_dumper = cgroup::Cgroup::new(&opts, None, fields);
+    let cgroup_dumper = cgroup::Cgroup::new(&opts, None, fields, None);
 
     // update model again to populate cpu and io data
     let model = collector
@@ -533,11 +899,21 @@ fn test_dump_cgroup_titles() {
         "Pglazyfreed",
         "THP Fault Alloc",
         "THP Collapse Alloc",
+        "Pgscan Kswapd",
+        "Pgscan Direct",
+        "Pgsteal Khugepaged",
+        "THP Swpout",
+        "Zswpin",
+        "Zswpout",
+        "Mem Percpu",
+        "Mem Sec Pagetables",
+        "Mem Hugetlb",
         "Events Low",
         "Events High",
         "Events Max",
         "Events OOM",
         "Events Kill",
+        "Raw Stats",
         "RBytes",
         "WBytes",
         "R I/O",
@@ -559,6 +935,31 @@ fn test_dump_cgroup_titles() {
     assert_eq!(titles, expected_titles);
 }
 
+#[test]
+fn test_dump_cgroup_diff_titles() {
+    let fields = vec![
+        DumpField::FieldId(model::SingleCgroupModelFieldId::Name),
+        DumpField::Common(CommonField::Datetime),
+        DumpField::FieldId(model::SingleCgroupModelFieldId::InodeNumber),
+        DumpField::FieldId(model::SingleCgroupModelFieldId::Cpu(
+            model::CgroupCpuModelFieldId::UsagePct,
+        )),
+    ];
+    let titles = cgroup::get_diff_fields(&fields)
+        .iter()
+        .map(|dump_field| dump_field.get_render_config().render_title(false))
+        .collect::<Vec<_>>();
+    let expected_titles = vec![
+        "Name",
+        "State",
+        "Datetime",
+        "Inode Number",
+        "Δ CPU Usage",
+        "Δ% CPU Usage",
+    ];
+    assert_eq!(titles, expected_titles);
+}
+
 #[test]
 // Test correctness of iface decoration
 // This test will also test JSON correctness.
@@ -572,7 +973,7 @@ fn test_dump_iface_content() {
     let mut opts: GeneralOpt = Default::default();
     let fields = command::expand_fields(command::DEFAULT_IFACE_FIELDS, true);
     opts.output_format = Some(OutputFormat::Json);
-    let iface_dumper = iface::Iface::new(&opts, None, fields.clone());
+    let iface_dumper = iface::Iface::new(&opts, None, fields.clone(), None);
 
     // update model again to populate net data
     let model = collector
@@ -692,7 +1093,7 @@ fn test_dump_network_content() {
     let mut opts: GeneralOpt = Default::default();
     let fields = command::expand_fields(command::DEFAULT_NETWORK_FIELDS, true);
     opts.output_format = Some(OutputFormat::Json);
-    let network_dumper = network::Network::new(&opts, fields.clone());
+    let network_dumper = network::Network::new(&opts, fields.clone(), None);
 
     // update model again to populate net data
     let model = collector
@@ -813,7 +1214,7 @@ fn test_dump_transport_content() {
     let mut opts: GeneralOpt = Default::default();
     let fields = command::expand_fields(command::DEFAULT_TRANSPORT_FIELDS, true);
     opts.output_format = Some(OutputFormat::Json);
-    let transport_dumper = transport::Transport::new(&opts, fields.clone());
+    let transport_dumper = transport::Transport::new(&opts, fields.clone(), None);
 
     // update model again to populate net data
     let model = collector
@@ -993,7 +1394,7 @@ fn test_dump_queue_content() {
     let fields = command::expand_fields(command::DEFAULT_ETHTOOL_QUEUE_FIELDS, true);
 
     opts.output_format = Some(OutputFormat::Json);
-    let queue_dumper = ethtool::EthtoolQueue::new(&opts, fields.clone());
+    let queue_dumper = ethtool::EthtoolQueue::new(&opts, fields.clone(), None);
 
     let mut queue_content: Vec<u8> = Vec::new();
     let mut round = 0;
@@ -1056,6 +1457,102 @@ fn test_dump_queue_content() {
     assert_eq!(jval, expected_json);
 }
 
+#[test]
+fn test_dump_queue_summary() {
+    let get_model = |queues: Vec<(u32, u64)>| {
+        let queues = queues
+            .into_iter()
+            .map(|(queue_id, rx_bytes_per_sec)| model::SingleQueueModel {
+                interface: "eth0".to_string(),
+                queue_id,
+                rx_bytes_per_sec: Some(rx_bytes_per_sec),
+                ..Default::default()
+            })
+            .collect();
+        let eth0_model = model::SingleNetModel {
+            interface: "eth0".to_string(),
+            queues,
+            ..Default::default()
+        };
+        model::Model {
+            time_elapsed: Duration::from_secs(60),
+            timestamp: SystemTime::now(),
+            system: model::SystemModel::default(),
+            cgroup: model::CgroupModel::default(),
+            process: model::ProcessModel::default(),
+            network: model::NetworkModel {
+                interfaces: BTreeMap::from([("eth0".to_string(), eth0_model)]),
+                ..Default::default()
+            },
+            gpu: None,
+            resctrl: None,
+        }
+    };
+
+    let opts = GeneralOpt {
+        output_format: Some(OutputFormat::Json),
+        summary: true,
+        ..Default::default()
+    };
+    let fields = vec![
+        DumpField::FieldId(model::SingleQueueModelFieldId::Interface),
+        DumpField::FieldId(model::SingleQueueModelFieldId::QueueId),
+        DumpField::FieldId(model::SingleQueueModelFieldId::RxBytesPerSec),
+        DumpField::Common(CommonField::Timestamp),
+    ];
+    let queue_dumper = ethtool::EthtoolQueue::new(&opts, fields, None);
+
+    // Queue 1 only shows up in the last two samples
+    let models = [
+        get_model(vec![(0, 40)]),
+        get_model(vec![(0, 10)]),
+        get_model(vec![(0, 30), (1, 200)]),
+        get_model(vec![(0, 20), (1, 100)]),
+    ];
+    let mut summary = summary::Summary::new(&queue_dumper, &models[0]);
+    for (idx, model) in models.iter().enumerate() {
+        let ctx = CommonFieldContext {
+            timestamp: idx as i64 * 60,
+            hostname: "h".to_string(),
+        };
+        summary.add_model(&ctx, model);
+    }
+    let mut queue_content: Vec<u8> = Vec::new();
+    summary
+        .dump(&mut queue_content, &opts)
+        .expect("Failed to dump queue summary");
+
+    let jval: Value =
+        serde_json::from_slice(&queue_content).expect("Fail parse json of queue summary");
+    let expected_json = json!([
+        {
+            "Samples": "4",
+            "Interface": "eth0",
+            "Queue": "0",
+            "RxBytes min": "10 B/s",
+            "RxBytes max": "40 B/s",
+            "RxBytes avg": "25 B/s",
+            "RxBytes p50": "20 B/s",
+            "RxBytes p95": "40 B/s",
+            "RxBytes p99": "40 B/s",
+            "Timestamp": "180"
+        },
+        {
+            "Samples": "2",
+            "Interface": "eth0",
+            "Queue": "1",
+            "RxBytes min": "100 B/s",
+            "RxBytes max": "200 B/s",
+            "RxBytes avg": "150 B/s",
+            "RxBytes p50": "100 B/s",
+            "RxBytes p95": "200 B/s",
+            "RxBytes p99": "200 B/s",
+            "Timestamp": "180"
+        }
+    ]);
+    assert_eq!(jval, expected_json);
+}
+
 #[test]
 // Test correctness of disk decoration
 // This test will also test JSON correctness.
@@ -1069,7 +1566,7 @@ fn test_dump_disk_content() {
     let mut opts: GeneralOpt = Default::default();
     let fields = command::expand_fields(command::DEFAULT_DISK_FIELDS, true);
     opts.output_format = Some(OutputFormat::Json);
-    let disk_dumper = disk::Disk::new(&opts, None, fields.clone());
+    let disk_dumper = disk::Disk::new(&opts, None, fields.clone(), None);
 
     // update model again to populate disk data
     let model = collector
@@ -1224,3 +1721,425 @@ proc = ["datetime", "mem.anon"]
         ))
     );
 }
+
+#[test]
+fn test_merge_metric_families() {
+    let text = r#"# TYPE cgroup_memory_total gauge
+cgroup_memory_total{cgroup="/a",hostname="h"} 10 1234
+# TYPE cgroup_cpu_usage_pct gauge
+cgroup_cpu_usage_pct{cgroup="/a",hostname="h"} 1.5 1234
+# TYPE cgroup_memory_total gauge
+cgroup_memory_total{cgroup="/b c",hostname="h"} 20 1234
+# TYPE process_io_rbytes_bytes counter
+# UNIT process_io_rbytes_bytes bytes
+process_io_rbytes_bytes{comm="a",hostname="h",pid="1"} 30 1234
+"#;
+    let expected = r#"# TYPE cgroup_memory_total gauge
+cgroup_memory_total{cgroup="/a",hostname="h"} 10 1234
+cgroup_memory_total{cgroup="/b c",hostname="h"} 20 1234
+# TYPE cgroup_cpu_usage_pct gauge
+cgroup_cpu_usage_pct{cgroup="/a",hostname="h"} 1.5 1234
+# TYPE process_io_rbytes_bytes counter
+# UNIT process_io_rbytes_bytes bytes
+process_io_rbytes_bytes{comm="a",hostname="h",pid="1"} 30 1234
+# EOF
+"#;
+    assert_eq!(metrics::merge_metric_families(text), expected);
+}
+
+#[test]
+fn test_get_metrics() {
+    let logger = get_logger();
+    let mut collector = Collector::new(logger.clone(), Default::default());
+    collector
+        .collect_and_update_model()
+        .expect("Fail to get model");
+    let model = collector
+        .collect_and_update_model()
+        .expect("Fail to get model");
+
+    let text = metrics::get_metrics(&model, 3).expect("Failed to get metrics");
+    assert!(text.contains("# TYPE system_"));
+    assert!(text.ends_with("# EOF\n"));
+    assert!(text.contains("# TYPE cgroup_"));
+    let mut families = HashSet::new();
+    for line in text.lines() {
+        if let Some(family) = line.strip_prefix("# TYPE ") {
+            assert!(families.insert(family), "{} is listed twice", family);
+        }
+    }
+    let pids = text
+        .lines()
+        .filter(|line| line.starts_with("process_"))
+        .filter_map(|line| line.split("pid=\"").nth(1))
+        .filter_map(|rest| rest.split('"').next())
+        .collect::<HashSet<_>>();
+    assert!(!pids.is_empty() && pids.len() <= 3, "{:?}", pids);
+}
+
+#[cfg(feature = "export")]
+#[test]
+fn test_export() {
+    use arrow::datatypes::DataType;
+
+    let tempdir = TempDir::with_prefix("below_export.").expect("Failed to create temp dir");
+    let model = model::get_sample_model();
+    let num_processes = model.process.processes.len();
+
+    for format in [command::ExportFormat::Parquet, command::ExportFormat::Arrow] {
+        let opts = command::ExportOpts {
+            begin: "".into(),
+            end: None,
+            duration: None,
+            yesterdays: None,
+            format,
+            output: tempdir.path().to_path_buf(),
+        };
+        let mut tables = export::get_tables(&opts).expect("Failed to create tables");
+        for _ in 0..2 {
+            for table in &mut tables {
+                table.add_model(&model).expect("Failed to add model");
+            }
+        }
+        for table in tables {
+            table.finish().expect("Failed to finish table");
+        }
+    }
+
+    let file = File::open(tempdir.path().join("process.parquet")).expect("No process table");
+    let batches = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+        .expect("Failed to read process table")
+        .build()
+        .expect("Failed to read process table")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Failed to read process table");
+    let num_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    assert_eq!(num_rows, 2 * num_processes);
+    let schema = batches[0].schema();
+    assert_eq!(schema.field(0).name(), "timestamp");
+    assert_eq!(
+        schema.field_with_name("pid").unwrap().data_type(),
+        &DataType::Int32
+    );
+    assert!(schema.field_with_name("cpu.usage_pct").is_ok());
+
+    // Column types follow the field types even if the sample has no value
+    let file = File::open(tempdir.path().join("cgroup.parquet")).expect("No cgroup table");
+    let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+        .expect("Failed to read cgroup table");
+    let schema = reader.schema();
+    for (name, data_type) in [
+        ("io.rbytes_per_sec", DataType::Float64),
+        ("io.cost_usage_pct", DataType::Float64),
+        ("props.memory_max", DataType::Int64),
+        ("props.cpu_weight", DataType::UInt32),
+        ("exited.count", DataType::UInt64),
+        ("exited.cpu_usage_pct", DataType::Float64),
+        ("exited.io_rbytes_per_sec", DataType::Float64),
+        ("name", DataType::Utf8),
+    ] {
+        assert_eq!(
+            schema.field_with_name(name).unwrap().data_type(),
+            &data_type,
+            "{}",
+            name
+        );
+    }
+
+    let file = File::open(tempdir.path().join("system.arrow")).expect("No system table");
+    let reader =
+        arrow::ipc::reader::FileReader::try_new(file, None).expect("Failed to read system table");
+    assert_eq!(
+        reader
+            .schema()
+            .field_with_name("mem.direct_map1g")
+            .unwrap()
+            .data_type(),
+        &DataType::UInt64
+    );
+    let num_rows: usize = reader
+        .map(|batch| batch.expect("Failed to read system table").num_rows())
+        .sum();
+    assert_eq!(num_rows, 2);
+}
+
+#[cfg(feature = "sql")]
+#[test]
+fn test_sql() {
+    let model = model::get_sample_model();
+    let conn = rusqlite::Connection::open_in_memory().expect("Failed to open database");
+    let tables = sql::get_tables(&conn).expect("Failed to create tables");
+    for _ in 0..2 {
+        for table in &tables {
+            table
+                .insert_model(&conn, &model)
+                .expect("Failed to insert model");
+        }
+    }
+
+    let num_processes: i64 = conn
+        .query_row("SELECT COUNT(*) FROM process", [], |row| row.get(0))
+        .expect("Failed to query process table");
+    assert_eq!(num_processes, 2 * model.process.processes.len() as i64);
+
+    let (pid, process) = model.process.processes.iter().next().unwrap();
+    let mut output = Vec::new();
+    sql::execute_query(
+        &conn,
+        &format!(
+            "SELECT pid, comm FROM process WHERE pid = {} GROUP BY pid",
+            pid
+        ),
+        &mut output,
+    )
+    .expect("Failed to run query");
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        format!("pid|comm\n{}|{}\n", pid, process.comm.as_ref().unwrap())
+    );
+
+    let mut output = Vec::new();
+    sql::execute_query(
+        &conn,
+        "SELECT COUNT(c.\"cpu.usage_pct\") AS n, MAX(s.\"cpu.usage_pct\") AS m \
+         FROM cgroup c JOIN system s USING (timestamp)",
+        &mut output,
+    )
+    .expect("Failed to run join");
+    assert!(String::from_utf8(output).unwrap().starts_with("n|m\n"));
+
+    // Column types follow the field types even if the sample has no value
+    for (table, column, sql_type) in [
+        ("cgroup", "io.rbytes_per_sec", "REAL"),
+        ("cgroup", "props.memory_max", "INTEGER"),
+        ("cgroup", "props.cpu_weight", "INTEGER"),
+        ("cgroup", "exited.count", "INTEGER"),
+        ("cgroup", "exited.cpu_usage_pct", "REAL"),
+        ("system", "mem.direct_map1g", "INTEGER"),
+        ("process", "comm", "TEXT"),
+    ] {
+        let declared: String = conn
+            .query_row(
+                &format!(
+                    "SELECT type FROM pragma_table_info('{}') WHERE name = ?",
+                    table
+                ),
+                [column],
+                |row| row.get(0),
+            )
+            .expect("Failed to query column type");
+        assert_eq!(declared, sql_type, "{}.{}", table, column);
+    }
+}
+
+#[test]
+fn test_dump_resctrl() {
+    let l3 = |llc_occupancy_bytes| model::ResctrlL3MonModel {
+        llc_occupancy_bytes: Some(llc_occupancy_bytes),
+        mbm_total_bytes_per_sec: Some(2048),
+        mbm_local_bytes_per_sec: Some(1024),
+    };
+    let mon = |llc_occupancy_bytes| model::ResctrlMonModel {
+        total: l3(llc_occupancy_bytes),
+        per_l3: BTreeMap::from([(0, l3(llc_occupancy_bytes))]),
+    };
+    let mon_group =
+        |name: &str, full_path: &str, llc_occupancy_bytes| model::ResctrlMonGroupModel {
+            name: name.to_owned(),
+            full_path: full_path.to_owned(),
+            mon: mon(llc_occupancy_bytes),
+        };
+    let model = model::Model {
+        time_elapsed: Duration::from_secs(60),
+        timestamp: SystemTime::now(),
+        system: model::SystemModel::default(),
+        cgroup: model::CgroupModel::default(),
+        process: model::ProcessModel::default(),
+        network: model::NetworkModel::default(),
+        gpu: None,
+        resctrl: Some(model::ResctrlModel {
+            cpuset: Some("0-3".parse().unwrap()),
+            mode: Some(resctrlfs::GroupMode::Shareable),
+            mon: Some(mon(100)),
+            mon_groups: BTreeMap::from([("mon1".to_owned(), mon_group("mon1", "mon1", 200))]),
+            ctrl_mon_groups: BTreeMap::from([(
+                "ctrl1".to_owned(),
+                model::ResctrlCtrlMonGroupModel {
+                    name: "ctrl1".to_owned(),
+                    full_path: "ctrl1".to_owned(),
+                    cpuset: Some("2-3".parse().unwrap()),
+                    mode: Some(resctrlfs::GroupMode::Exclusive),
+                    mon: mon(300),
+                    mon_groups: BTreeMap::from([(
+                        "mon2".to_owned(),
+                        mon_group("mon2", "ctrl1/mon2", 400),
+                    )]),
+                },
+            )]),
+        }),
+    };
+    let ctx = CommonFieldContext {
+        timestamp: 0,
+        hostname: "h".to_string(),
+    };
+    let opts = GeneralOpt {
+        output_format: Some(OutputFormat::Json),
+        raw: true,
+        ..Default::default()
+    };
+    let dump_groups = |dumper: &dyn Dumper| {
+        let mut content: Vec<u8> = Vec::new();
+        let mut round = 0;
+        dumper
+            .dump_model(&ctx, &model, &mut content, &mut round, false)
+            .expect("Failed to dump resctrl groups");
+        serde_json::from_slice::<Value>(&content).expect("Fail parse json of resctrl groups")
+    };
+
+    let llc_occupancy = model::ResctrlCtrlMonGroupModelFieldId::Mon(
+        model::ResctrlMonModelFieldId::Total(model::ResctrlL3MonModelFieldId::LlcOccupancyBytes),
+    );
+    let fields = vec![
+        DumpField::FieldId(model::ResctrlCtrlMonGroupModelFieldId::FullPath),
+        DumpField::FieldId(model::ResctrlCtrlMonGroupModelFieldId::Cpuset),
+        DumpField::FieldId(model::ResctrlCtrlMonGroupModelFieldId::Mode),
+        DumpField::FieldId(llc_occupancy.clone()),
+    ];
+    let ctrl_mon_groups = |opts: &GeneralOpt| {
+        dump_groups(&resctrl::Resctrl::new(
+            opts,
+            model::ResctrlModel::get_ctrl_mon_groups,
+            Some(llc_occupancy.clone()),
+            fields.clone(),
+            None,
+        ))
+    };
+    assert_eq!(
+        ctrl_mon_groups(&opts),
+        json!([
+            {"Full Path": "", "Cpuset": "0-3", "Mode": "shareable", "LLC Occupancy": "100"},
+            {"Full Path": "ctrl1", "Cpuset": "2-3", "Mode": "exclusive", "LLC Occupancy": "300"}
+        ])
+    );
+    assert_eq!(
+        ctrl_mon_groups(&GeneralOpt {
+            rsort: true,
+            top: 1,
+            ..opts.clone()
+        }),
+        json!([
+            {"Full Path": "ctrl1", "Cpuset": "2-3", "Mode": "exclusive", "LLC Occupancy": "300"}
+        ])
+    );
+
+    let llc_occupancy = model::ResctrlMonGroupModelFieldId::Mon(
+        model::ResctrlMonModelFieldId::Total(model::ResctrlL3MonModelFieldId::LlcOccupancyBytes),
+    );
+    let fields = vec![
+        DumpField::FieldId(model::ResctrlMonGroupModelFieldId::FullPath),
+        DumpField::FieldId(llc_occupancy.clone()),
+        DumpField::FieldId("mon.per_l3.0.mbm_total_bytes_per_sec".parse().unwrap()),
+    ];
+    let mon_groups = |opts: &GeneralOpt| {
+        dump_groups(&resctrl::Resctrl::new(
+            opts,
+            model::ResctrlModel::get_mon_groups,
+            Some(llc_occupancy.clone()),
+            fields.clone(),
+            None,
+        ))
+    };
+    assert_eq!(
+        mon_groups(&opts),
+        json!([
+            {"Full Path": "mon1", "LLC Occupancy": "200", "L3 0 MBM Total": "2048"},
+            {"Full Path": "ctrl1/mon2", "LLC Occupancy": "400", "L3 0 MBM Total": "2048"}
+        ])
+    );
+    assert_eq!(
+        mon_groups(&GeneralOpt {
+            rsort: true,
+            top: 1,
+            ..opts
+        }),
+        json!([
+            {"Full Path": "ctrl1/mon2", "LLC Occupancy": "400", "L3 0 MBM Total": "2048"}
+        ])
+    );
+}
+
+#[test]
+fn test_dump_multi() {
+    let model = model::get_sample_model();
+    let ctx = CommonFieldContext {
+        timestamp: 0,
+        hostname: "h".to_string(),
+    };
+    let system_part = || -> Box<dyn multi::MultiPart> {
+        Box::new(multi::Part::new(
+            "system",
+            vec![DumpField::FieldId(model::SystemModelFieldId::Hostname)],
+            |model| vec![&model.system],
+            true,
+        ))
+    };
+    let process_part = || -> Box<dyn multi::MultiPart> {
+        Box::new(multi::Part::new(
+            "process",
+            vec![DumpField::FieldId(model::SingleProcessModelFieldId::Pid)],
+            |model| model.process.processes.values().collect(),
+            false,
+        ))
+    };
+    let dump_multi = |multi: &multi::Multi| {
+        let mut content: Vec<u8> = Vec::new();
+        let mut round = 0;
+        multi
+            .dump_model(&ctx, &model, &mut content, &mut round, false)
+            .expect("Failed to dump multi");
+        assert_eq!(round, 1);
+        String::from_utf8(content).unwrap()
+    };
+
+    let opts = GeneralOpt {
+        output_format: Some(OutputFormat::Json),
+        ..Default::default()
+    };
+    let multi = multi::Multi::new(&opts, vec![system_part(), process_part()])
+        .expect("Failed to create multi dumper");
+    let jval: Value = serde_json::from_str(&dump_multi(&multi)).expect("Fail parse json of multi");
+    assert_eq!(jval["Timestamp"], "0");
+    assert_eq!(
+        jval["system"].as_object().unwrap().values().next().unwrap(),
+        &json!(model.system.hostname)
+    );
+    assert_eq!(
+        jval["process"].as_array().unwrap().len(),
+        model.process.processes.len()
+    );
+
+    // Processes don't fit in a single row
+    let opts = GeneralOpt {
+        output_format: Some(OutputFormat::Csv),
+        ..Default::default()
+    };
+    assert!(multi::Multi::new(&opts, vec![system_part(), process_part()]).is_err());
+
+    let multi =
+        multi::Multi::new(&opts, vec![system_part()]).expect("Failed to create multi dumper");
+    let content = dump_multi(&multi);
+    let mut lines = content.lines();
+    assert!(
+        lines
+            .next()
+            .unwrap()
+            .starts_with("Datetime,Timestamp,system.")
+    );
+    assert!(
+        lines
+            .next()
+            .unwrap()
+            .ends_with(&format!(",0,{},", model.system.hostname))
+    );
+    assert_eq!(lines.next(), None);
+}
@@ -35,6 +35,48 @@ pub trait Dumper {
     ) -> Result<IterExecResult>;
 }
 
+/// Returns the first Model of the time range, or an error if there is none.
+pub fn jump_to_initial_sample(
+    advance: &mut Advance,
+    time_begin: SystemTime,
+    time_end: SystemTime,
+) -> Result<model::Model> {
+    let model = match advance.jump_sample_to(time_begin) {
+        Some(m) => m,
+        None => bail!(
+            "No initial sample could be found!\n\
+            You may have provided a time in the future or no data was recorded during the provided time. \
+            Please check your input and timezone.\n\
+            If you are using remote, please make sure the below service on target host is running."
+        ),
+    };
+
+    cliutil::check_initial_sample_time_in_time_range(model.timestamp, time_begin, time_end)?;
+
+    Ok(model)
+}
+
+/// Returns the Model following the given one. With `every`, the Model covers
+/// the next `every` (up to time_end) instead of the next sample.
+pub fn advance_model(
+    advance: &mut Advance,
+    model: &model::Model,
+    every: Option<Duration>,
+    time_end: SystemTime,
+) -> Option<model::Model> {
+    match every {
+        Some(every) => advance.advance_until(std::cmp::min(model.timestamp + every, time_end)),
+        None => advance.advance(Direction::Forward),
+    }
+}
+
+/// Swallow BrokenPipe error for write. Rust runtime will ignore SIGPIPE by default and
+/// propagating EPIPE upwards to the application in the form of an IoError::BrokenPipe.
+pub fn is_broken_pipe(e: &Error) -> bool {
+    e.downcast_ref::<std::io::Error>()
+        .map_or(false, |e| e.kind() == std::io::ErrorKind::BrokenPipe)
+}
+
 /// Called by dump commands to dump Models in continuous time steps. The actual
 /// dump logic for different Models in each time step is handled by specific
 /// Dumper implementations. This function is responsible for retrieving Models
@@ -47,19 +89,10 @@ pub fn dump_timeseries(
     output: &mut dyn Write,
     output_format: Option<OutputFormat>,
     br: Option<String>,
+    every: Option<Duration>,
     errs: Receiver<Error>,
 ) -> Result<()> {
-    let mut model = match advance.jump_sample_to(time_begin) {
-        Some(m) => m,
-        None => bail!(
-            "No initial sample could be found!\n\
-            You may have provided a time in the future or no data was recorded during the provided time. \
-            Please check your input and timezone.\n\
-            If you are using remote, please make sure the below service on target host is running."
-        ),
-    };
-
-    cliutil::check_initial_sample_time_in_time_range(model.timestamp, time_begin, time_end)?;
+    let mut model = jump_to_initial_sample(&mut advance, time_begin, time_end)?;
 
     let json = output_format == Some(OutputFormat::Json);
     let csv = output_format == Some(OutputFormat::Csv);
@@ -88,11 +121,7 @@ pub fn dump_timeseries(
         let res = match dumper.dump_model(&ctx, &model, output, &mut round, comma_flag) {
             Ok(res) => res,
             Err(e) => {
-                // Swallow BrokenPipe error for write. Rust runtime will ignore SIGPIPE by default and
-                // propagating EPIPE upwards to the application in the form of an IoError::BrokenPipe.
-                if e.downcast_ref::<std::io::Error>()
-                    .map_or(false, |e| e.kind() == std::io::ErrorKind::BrokenPipe)
-                {
+                if is_broken_pipe(&e) {
                     return Ok(());
                 } else {
                     return Err(e);
@@ -104,7 +133,7 @@ pub fn dump_timeseries(
             break;
         }
 
-        model = match advance.advance(Direction::Forward) {
+        model = match advance_model(&mut advance, &model, every, time_end) {
             Some(m) => m,
             None => break,
         };
@@ -17,13 +17,19 @@ use super::*;
 pub struct Transport {
     opts: GeneralOpt,
     fields: Vec<TransportField>,
+    filter_expr: Option<FilterExpr<model::NetworkModelFieldId>>,
 }
 
 impl Transport {
-    pub fn new(opts: &GeneralOpt, fields: Vec<TransportField>) -> Self {
+    pub fn new(
+        opts: &GeneralOpt,
+        fields: Vec<TransportField>,
+        filter_expr: Option<FilterExpr<model::NetworkModelFieldId>>,
+    ) -> Self {
         Self {
             opts: opts.to_owned(),
             fields,
+            filter_expr,
         }
     }
 }
@@ -37,6 +43,12 @@ impl Dumper for Transport {
         round: &mut usize,
         comma_flag: bool,
     ) -> Result<IterExecResult> {
+        if let Some(expr) = &self.filter_expr {
+            if !expr.eval(&model.network) {
+                return Ok(IterExecResult::Skip);
+            }
+        }
+
         match self.opts.output_format {
             Some(OutputFormat::Raw) | None => write!(
                 output,
@@ -94,14 +106,42 @@ impl Dumper for Transport {
                 "{}",
                 print::dump_openmetrics(&self.fields, ctx, &model.network)
             )?,
+            Some(OutputFormat::JsonLines) => write!(
+                output,
+                "{}",
+                print::dump_jsonl(&self.fields, ctx, &model.network, self.opts.raw)
+            )?,
+            Some(OutputFormat::Influx) => write!(
+                output,
+                "{}",
+                print::dump_influx(&self.fields, ctx, &model.network)
+            )?,
         };
         *round += 1;
 
         match &self.opts.output_format {
-            Some(OutputFormat::Json) | Some(OutputFormat::OpenMetrics) => (),
+            Some(OutputFormat::Json)
+            | Some(OutputFormat::JsonLines)
+            | Some(OutputFormat::OpenMetrics)
+            | Some(OutputFormat::Influx) => (),
             _ => write!(output, "\n")?,
         }
 
         Ok(IterExecResult::Success)
     }
 }
+
+impl Summarize for Transport {
+    type Model = model::NetworkModel;
+
+    fn summary_fields(&self, _model: &model::Model) -> Vec<TransportField> {
+        self.fields.clone()
+    }
+
+    fn entities<'a>(&self, model: &'a model::Model) -> Vec<(String, &'a model::NetworkModel)> {
+        match &self.filter_expr {
+            Some(expr) if !expr.eval(&model.network) => vec![],
+            _ => vec![(String::new(), &model.network)],
+        }
+    }
+}
@@ -47,6 +47,10 @@ pub struct SingleCgroupModel {
     #[queriable(subquery)]
     #[queriable(preferred_name = mem_numa)]
     pub memory_numa_stat: Option<BTreeMap<u32, CgroupMemoryNumaModel>>,
+    /// Pids of the processes that are members of this cgroup, from cgroup.procs
+    pub procs: Option<Vec<u32>>,
+    #[queriable(subquery)]
+    pub exited: Option<CgroupExitedModel>,
 }
 
 /// A model that represents a cgroup subtree. Each instance is a node that uses
@@ -140,6 +144,9 @@ impl Queriable for CgroupModel {
         }
         model.data.query(&field_id.subquery_id)
     }
+    fn field_type(field_id: &Self::FieldId) -> FieldType {
+        SingleCgroupModel::field_type(&field_id.subquery_id)
+    }
 }
 
 impl core::borrow::Borrow<str> for CgroupModel {
@@ -290,6 +297,8 @@ impl CgroupModel {
                 depth,
                 cgroup_stat,
                 memory_numa_stat,
+                procs: sample.cgroup_procs.clone(),
+                exited: None,
             },
             children,
             count: nr_descendants + 1,
@@ -303,6 +312,48 @@ impl CgroupModel {
         });
         self
     }
+
+    /// Rolls up the usage of processes that exited during the interval into
+    /// the cgroup they exited in and all of its ancestors. Exited processes
+    /// are the ones reported by exitstat rather than read from procfs.
+    pub fn aggr_exited_processes(
+        self,
+        processes: &procfs::PidMap,
+        last: Option<(&procfs::PidMap, Duration)>,
+    ) -> Self {
+        let (last_processes, delta) = match last {
+            Some(last) => last,
+            None => return self,
+        };
+        let mut exited: BTreeMap<&str, CgroupExitedModel> = BTreeMap::new();
+        for (pid, pidinfo) in processes {
+            if pidinfo.stat.state != Some(procfs::PidState::Dead) {
+                continue;
+            }
+            let model = CgroupExitedModel::new(pidinfo, last_processes.get(pid), delta);
+            // /proc/<pid>/cgroup reports the root cgroup as "/"
+            let cgroup = pidinfo.cgroup.trim_end_matches('/');
+            let acc = exited
+                .remove(cgroup)
+                .unwrap_or_else(CgroupExitedModel::empty);
+            exited.insert(cgroup, acc + model);
+        }
+        self.set_exited(&exited)
+    }
+
+    fn set_exited(mut self, exited: &BTreeMap<&str, CgroupExitedModel>) -> Self {
+        self.children = std::mem::take(&mut self.children)
+            .into_iter()
+            .map(|child| child.set_exited(exited))
+            .collect();
+        // Cgroups without exited processes in their subtree are left None
+        let own = exited.get(self.data.full_path.as_str()).cloned();
+        self.data.exited = self
+            .children
+            .iter()
+            .fold(own, |acc, child| opt_add(acc, child.data.exited.clone()));
+        self
+    }
 }
 
 impl Nameable for CgroupModel {
@@ -513,17 +564,32 @@ pub struct CgroupMemoryModel {
     pub pglazyfreed: Option<u64>,
     pub thp_fault_alloc: Option<u64>,
     pub thp_collapse_alloc: Option<u64>,
+    pub pgscan_kswapd: Option<u64>,
+    pub pgscan_direct: Option<u64>,
+    pub pgsteal_khugepaged: Option<u64>,
+    pub thp_swpout: Option<u64>,
+    pub zswpin: Option<u64>,
+    pub zswpout: Option<u64>,
+    pub percpu: Option<u64>,
+    pub sec_pagetables: Option<u64>,
+    pub hugetlb: Option<u64>,
     pub events_low: Option<u64>,
     pub events_high: Option<u64>,
     pub events_max: Option<u64>,
     pub events_oom: Option<u64>,
     pub events_oom_kill: Option<u64>,
+    /// memory.stat keys without a dedicated field, as raw values
+    pub raw_stats: BTreeMap<String, u64>,
 }
 
 impl std::ops::Add for CgroupMemoryModel {
     type Output = Self;
 
     fn add(self, other: Self) -> Self::Output {
+        let mut raw_stats = self.raw_stats;
+        for (key, val) in other.raw_stats {
+            *raw_stats.entry(key).or_default() += val;
+        }
         Self {
             total: opt_add(self.total, other.total),
             swap: opt_add(self.swap, other.swap),
@@ -586,11 +652,21 @@ impl std::ops::Add for CgroupMemoryModel {
             pglazyfreed: opt_add(self.pglazyfreed, other.pglazyfreed),
             thp_fault_alloc: opt_add(self.thp_fault_alloc, other.thp_fault_alloc),
             thp_collapse_alloc: opt_add(self.thp_collapse_alloc, other.thp_collapse_alloc),
+            pgscan_kswapd: opt_add(self.pgscan_kswapd, other.pgscan_kswapd),
+            pgscan_direct: opt_add(self.pgscan_direct, other.pgscan_direct),
+            pgsteal_khugepaged: opt_add(self.pgsteal_khugepaged, other.pgsteal_khugepaged),
+            thp_swpout: opt_add(self.thp_swpout, other.thp_swpout),
+            zswpin: opt_add(self.zswpin, other.zswpin),
+            zswpout: opt_add(self.zswpout, other.zswpout),
+            percpu: opt_add(self.percpu, other.percpu),
+            sec_pagetables: opt_add(self.sec_pagetables, other.sec_pagetables),
+            hugetlb: opt_add(self.hugetlb, other.hugetlb),
             events_low: opt_add(self.events_low, other.events_low),
             events_high: opt_add(self.events_high, other.events_high),
             events_max: opt_add(self.events_max, other.events_max),
             events_oom: opt_add(self.events_oom, other.events_oom),
             events_oom_kill: opt_add(self.events_oom_kill, other.events_oom_kill),
+            raw_stats,
         }
     }
 }
@@ -637,6 +713,10 @@ impl CgroupMemoryModel {
             model.unevictable = stat.unevictable;
             model.slab_reclaimable = stat.slab_reclaimable;
             model.slab_unreclaimable = stat.slab_unreclaimable;
+            model.percpu = stat.percpu;
+            model.sec_pagetables = stat.sec_pagetables;
+            model.hugetlb = stat.hugetlb;
+            model.raw_stats = stat.raw_stats.clone();
 
             if let Some((
                 CgroupSample {
@@ -710,6 +790,20 @@ impl CgroupMemoryModel {
                     delta,
                     u64
                 );
+                model.pgscan_kswapd =
+                    count_per_sec!(last_stat.pgscan_kswapd, stat.pgscan_kswapd, delta, u64);
+                model.pgscan_direct =
+                    count_per_sec!(last_stat.pgscan_direct, stat.pgscan_direct, delta, u64);
+                model.pgsteal_khugepaged = count_per_sec!(
+                    last_stat.pgsteal_khugepaged,
+                    stat.pgsteal_khugepaged,
+                    delta,
+                    u64
+                );
+                model.thp_swpout =
+                    count_per_sec!(last_stat.thp_swpout, stat.thp_swpout, delta, u64);
+                model.zswpin = count_per_sec!(last_stat.zswpin, stat.zswpin, delta, u64);
+                model.zswpout = count_per_sec!(last_stat.zswpout, stat.zswpout, delta, u64);
             }
         }
 
@@ -728,6 +822,8 @@ impl CgroupMemoryModel {
 )]
 pub struct CgroupPidsModel {
     pub tids_current: Option<u64>,
+    pub nr_procs: Option<u64>,
+    pub nr_threads: Option<u64>,
 }
 
 impl std::ops::Add for CgroupPidsModel {
@@ -736,14 +832,19 @@ impl std::ops::Add for CgroupPidsModel {
     fn add(self, other: Self) -> Self::Output {
         Self {
             tids_current: opt_add(self.tids_current, other.tids_current),
+            nr_procs: opt_add(self.nr_procs, other.nr_procs),
+            nr_threads: opt_add(self.nr_threads, other.nr_threads),
         }
     }
 }
 
 impl CgroupPidsModel {
     pub fn new(sample: &CgroupSample) -> Self {
-        let tids_current = sample.tids_current;
-        CgroupPidsModel { tids_current }
+        CgroupPidsModel {
+            tids_current: sample.tids_current,
+            nr_procs: sample.cgroup_procs.as_ref().map(|procs| procs.len() as u64),
+            nr_threads: sample.nr_threads,
+        }
     }
 }
 
@@ -951,6 +1052,97 @@ impl CgroupProperties {
     }
 }
 
+/// Usage of processes in a cgroup subtree that exited during the interval.
+/// For processes that were already running at the start of the interval,
+/// only the usage since then is counted.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    below_derive::Queriable
+)]
+pub struct CgroupExitedModel {
+    pub count: Option<u64>,
+    pub cpu_usage_pct: Option<f64>,
+    pub cpu_user_pct: Option<f64>,
+    pub cpu_system_pct: Option<f64>,
+    pub io_rbytes_per_sec: Option<f64>,
+    pub io_wbytes_per_sec: Option<f64>,
+    pub minorfaults_per_sec: Option<f64>,
+    pub majorfaults_per_sec: Option<f64>,
+}
+
+impl std::ops::Add for CgroupExitedModel {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        Self {
+            count: opt_add(self.count, other.count),
+            cpu_usage_pct: opt_add(self.cpu_usage_pct, other.cpu_usage_pct),
+            cpu_user_pct: opt_add(self.cpu_user_pct, other.cpu_user_pct),
+            cpu_system_pct: opt_add(self.cpu_system_pct, other.cpu_system_pct),
+            io_rbytes_per_sec: opt_add(self.io_rbytes_per_sec, other.io_rbytes_per_sec),
+            io_wbytes_per_sec: opt_add(self.io_wbytes_per_sec, other.io_wbytes_per_sec),
+            minorfaults_per_sec: opt_add(self.minorfaults_per_sec, other.minorfaults_per_sec),
+            majorfaults_per_sec: opt_add(self.majorfaults_per_sec, other.majorfaults_per_sec),
+        }
+    }
+}
+
+impl CgroupExitedModel {
+    pub fn empty() -> Self {
+        Self {
+            count: Some(0),
+            cpu_usage_pct: Some(0.0),
+            cpu_user_pct: Some(0.0),
+            cpu_system_pct: Some(0.0),
+            io_rbytes_per_sec: Some(0.0),
+            io_wbytes_per_sec: Some(0.0),
+            minorfaults_per_sec: Some(0.0),
+            majorfaults_per_sec: Some(0.0),
+        }
+    }
+
+    fn new(end: &procfs::PidInfo, begin: Option<&procfs::PidInfo>, delta: Duration) -> Self {
+        // A process that is not in the last sample was started during the
+        // interval, so all of its usage is within the interval.
+        let since_begin = |end: Option<u64>, begin: Option<u64>| {
+            end.map(|end| end.saturating_sub(begin.unwrap_or_default()))
+        };
+        let usec_pct =
+            |usec: Option<u64>| usec.map(|v| v as f64 * 100.0 / delta.as_micros() as f64);
+        let per_sec = |count: Option<u64>| count.map(|v| v as f64 / delta.as_secs_f64());
+
+        let cpu_user_pct = usec_pct(since_begin(
+            end.stat.user_usecs,
+            begin.and_then(|b| b.stat.user_usecs),
+        ));
+        let cpu_system_pct = usec_pct(since_begin(
+            end.stat.system_usecs,
+            begin.and_then(|b| b.stat.system_usecs),
+        ));
+        Self {
+            count: Some(1),
+            cpu_usage_pct: opt_add(cpu_user_pct, cpu_system_pct),
+            cpu_user_pct,
+            cpu_system_pct,
+            io_rbytes_per_sec: per_sec(since_begin(end.io.rbytes, begin.and_then(|b| b.io.rbytes))),
+            io_wbytes_per_sec: per_sec(since_begin(end.io.wbytes, begin.and_then(|b| b.io.wbytes))),
+            minorfaults_per_sec: per_sec(since_begin(
+                end.stat.minflt,
+                begin.and_then(|b| b.stat.minflt),
+            )),
+            majorfaults_per_sec: per_sec(since_begin(
+                end.stat.majflt,
+                begin.and_then(|b| b.stat.majflt),
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -1038,4 +1230,104 @@ mod tests {
             Some(Field::F64(42.0))
         );
     }
+
+    fn exited_process(pid: i32, cgroup: &str, user_usecs: u64) -> procfs::PidInfo {
+        procfs::PidInfo {
+            stat: procfs::PidStat {
+                pid: Some(pid),
+                state: Some(procfs::PidState::Dead),
+                user_usecs: Some(user_usecs),
+                system_usecs: Some(0),
+                ..Default::default()
+            },
+            cgroup: cgroup.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn aggr_exited_processes() {
+        let model_json = r#"
+        {
+            "data": { "name": "<root>", "full_path": "", "depth": 0 },
+            "count": 4,
+            "recreate_flag": false,
+            "children": [
+                {
+                    "data": { "name": "system.slice", "full_path": "/system.slice", "depth": 1 },
+                    "count": 2,
+                    "recreate_flag": false,
+                    "children": [
+                        {
+                            "data": { "name": "foo.service", "full_path": "/system.slice/foo.service", "depth": 2 },
+                            "count": 1,
+                            "recreate_flag": false,
+                            "children": []
+                        }
+                    ]
+                },
+                {
+                    "data": { "name": "user.slice", "full_path": "/user.slice", "depth": 1 },
+                    "count": 1,
+                    "recreate_flag": false,
+                    "children": []
+                }
+            ]
+        }
+        "#;
+        let model: CgroupModel =
+            serde_json::from_str(model_json).expect("Failed to deserialize cgroup model JSON");
+
+        let mut running = exited_process(12, "/user.slice", 1_000_000);
+        running.stat.state = Some(procfs::PidState::Running);
+        let processes: procfs::PidMap = [
+            // Already running at the start of the interval
+            (
+                10,
+                exited_process(10, "/system.slice/foo.service", 2_000_000),
+            ),
+            // Started during the interval
+            (11, exited_process(11, "/system.slice", 500_000)),
+            (12, running.clone()),
+        ]
+        .into();
+        let last_processes: procfs::PidMap = [
+            (
+                10,
+                exited_process(10, "/system.slice/foo.service", 1_000_000),
+            ),
+            (12, running),
+        ]
+        .into();
+
+        let aggregated = model
+            .clone()
+            .aggr_exited_processes(&processes, Some((&last_processes, Duration::from_secs(1))));
+        let count_and_cpu = |path: &str| {
+            let field_id =
+                |f: &str| CgroupModelFieldId::from_str(&format!("path:{}/.{}", path, f)).unwrap();
+            (
+                aggregated.query(&field_id("exited.count")),
+                aggregated.query(&field_id("exited.cpu_user_pct")),
+            )
+        };
+        assert_eq!(
+            count_and_cpu("/system.slice/foo.service"),
+            (Some(Field::U64(1)), Some(Field::F64(100.0)))
+        );
+        assert_eq!(
+            count_and_cpu("/system.slice"),
+            (Some(Field::U64(2)), Some(Field::F64(150.0)))
+        );
+        assert_eq!(
+            count_and_cpu(""),
+            (Some(Field::U64(2)), Some(Field::F64(150.0)))
+        );
+        // No exited process in the subtree
+        assert_eq!(count_and_cpu("/user.slice"), (None, None));
+
+        // Nothing is rolled up without a previous sample
+        let first = model.aggr_exited_processes(&processes, None);
+        assert_eq!(first.data.exited, None);
+    }
 }
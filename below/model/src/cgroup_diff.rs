@@ -0,0 +1,408 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+/// Whether a cgroup exists in the `before` Model, the `after` Model or both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CgroupDiffState {
+    Added,
+    Removed,
+    #[default]
+    Common,
+}
+
+impl fmt::Display for CgroupDiffState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CgroupDiffState::Added => write!(f, "added"),
+            CgroupDiffState::Removed => write!(f, "removed"),
+            CgroupDiffState::Common => write!(f, "common"),
+        }
+    }
+}
+
+/// A single cgroup as seen in two Models, usually taken at different times.
+/// Either side is None if the cgroup only exists in the other one.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SingleCgroupDiffModel {
+    pub name: String,
+    pub full_path: String,
+    pub depth: u32,
+    pub state: CgroupDiffState,
+    pub before: Option<SingleCgroupModel>,
+    pub after: Option<SingleCgroupModel>,
+}
+
+/// Queries a SingleCgroupDiffModel. Its String representation looks like
+/// this:
+///     delta.cpu.usage_pct
+/// where the prefix is one of `before`, `after`, `delta` or `delta_pct` and
+/// the rest is a SingleCgroupModelFieldId.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SingleCgroupDiffModelFieldId {
+    Name,
+    FullPath,
+    State,
+    Before(SingleCgroupModelFieldId),
+    After(SingleCgroupModelFieldId),
+    Delta(SingleCgroupModelFieldId),
+    DeltaPct(SingleCgroupModelFieldId),
+}
+
+impl FieldId for SingleCgroupDiffModelFieldId {
+    type Queriable = SingleCgroupDiffModel;
+}
+
+impl std::string::ToString for SingleCgroupDiffModelFieldId {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Name => "name".to_owned(),
+            Self::FullPath => "full_path".to_owned(),
+            Self::State => "state".to_owned(),
+            Self::Before(field_id) => format!("before.{}", field_id.to_string()),
+            Self::After(field_id) => format!("after.{}", field_id.to_string()),
+            Self::Delta(field_id) => format!("delta.{}", field_id.to_string()),
+            Self::DeltaPct(field_id) => format!("delta_pct.{}", field_id.to_string()),
+        }
+    }
+}
+
+impl std::str::FromStr for SingleCgroupDiffModelFieldId {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "name" => return Ok(Self::Name),
+            "full_path" => return Ok(Self::FullPath),
+            "state" => return Ok(Self::State),
+            _ => {}
+        }
+        let (prefix, subquery_id_str) = s.split_once('.').ok_or_else(|| {
+            anyhow!(
+                "Unable to find a variant of the given enum matching string `{}`.",
+                s
+            )
+        })?;
+        let subquery_id = SingleCgroupModelFieldId::from_str(subquery_id_str)?;
+        match prefix {
+            "before" => Ok(Self::Before(subquery_id)),
+            "after" => Ok(Self::After(subquery_id)),
+            "delta" => Ok(Self::Delta(subquery_id)),
+            "delta_pct" => Ok(Self::DeltaPct(subquery_id)),
+            _ => Err(anyhow!(
+                "Unknown cgroup diff prefix `{}` in `{}`",
+                prefix,
+                s
+            )),
+        }
+    }
+}
+
+impl Queriable for SingleCgroupDiffModel {
+    type FieldId = SingleCgroupDiffModelFieldId;
+    fn query(&self, field_id: &Self::FieldId) -> Option<Field> {
+        match field_id {
+            SingleCgroupDiffModelFieldId::Name => Some(Field::from(self.name.clone())),
+            SingleCgroupDiffModelFieldId::FullPath => Some(Field::from(self.full_path.clone())),
+            SingleCgroupDiffModelFieldId::State => Some(Field::from(self.state.to_string())),
+            SingleCgroupDiffModelFieldId::Before(field_id) => self.before.as_ref()?.query(field_id),
+            SingleCgroupDiffModelFieldId::After(field_id) => self.after.as_ref()?.query(field_id),
+            SingleCgroupDiffModelFieldId::Delta(field_id) => self.delta(field_id).map(Field::F64),
+            SingleCgroupDiffModelFieldId::DeltaPct(field_id) => {
+                self.delta_pct(field_id).map(Field::F64)
+            }
+        }
+    }
+    fn field_type(field_id: &Self::FieldId) -> FieldType {
+        match field_id {
+            SingleCgroupDiffModelFieldId::Name
+            | SingleCgroupDiffModelFieldId::FullPath
+            | SingleCgroupDiffModelFieldId::State => FieldType::Str,
+            SingleCgroupDiffModelFieldId::Before(field_id)
+            | SingleCgroupDiffModelFieldId::After(field_id) => {
+                SingleCgroupModel::field_type(field_id)
+            }
+            SingleCgroupDiffModelFieldId::Delta(_) | SingleCgroupDiffModelFieldId::DeltaPct(_) => {
+                FieldType::F64
+            }
+        }
+    }
+}
+
+impl Recursive for SingleCgroupDiffModel {
+    fn get_depth(&self) -> usize {
+        self.depth as usize
+    }
+}
+
+impl Nameable for SingleCgroupDiffModel {
+    fn name() -> &'static str {
+        "cgroup_diff"
+    }
+}
+
+impl SingleCgroupDiffModel {
+    /// Absolute change of a numeric field from `before` to `after`. A cgroup
+    /// missing on one side counts as zero there, so added and removed cgroups
+    /// show their whole value as the change.
+    pub fn delta(&self, field_id: &SingleCgroupModelFieldId) -> Option<f64> {
+        delta(self.before.as_ref(), self.after.as_ref(), field_id)
+    }
+
+    /// Change of a numeric field relative to its `before` value, in percent.
+    /// None if the cgroup is not on both sides or the `before` value is zero.
+    pub fn delta_pct(&self, field_id: &SingleCgroupModelFieldId) -> Option<f64> {
+        delta_pct(self.before.as_ref(), self.after.as_ref(), field_id)
+    }
+}
+
+/// See `SingleCgroupDiffModel::delta`
+pub(crate) fn delta<T: Queriable>(
+    before: Option<&T>,
+    after: Option<&T>,
+    field_id: &T::FieldId,
+) -> Option<f64> {
+    let value = |model: Option<&T>| match model {
+        Some(model) => model.query(field_id).and_then(numeric_value),
+        None => Some(0.0),
+    };
+    Some(value(after)? - value(before)?)
+}
+
+/// See `SingleCgroupDiffModel::delta_pct`
+pub(crate) fn delta_pct<T: Queriable>(
+    before: Option<&T>,
+    after: Option<&T>,
+    field_id: &T::FieldId,
+) -> Option<f64> {
+    let before = before?.query(field_id).and_then(numeric_value)?;
+    let after = after?.query(field_id).and_then(numeric_value)?;
+    if before == 0.0 {
+        None
+    } else {
+        Some((after - before) / before.abs() * 100.0)
+    }
+}
+
+/// Difference between two cgroup trees, usually `Model::cgroup` of two
+/// samples taken at arbitrary times. Children are the union of both sides.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CgroupDiffModel {
+    pub data: SingleCgroupDiffModel,
+    pub children: BTreeSet<CgroupDiffModel>,
+}
+
+impl core::borrow::Borrow<str> for CgroupDiffModel {
+    fn borrow(&self) -> &str {
+        &self.data.name
+    }
+}
+
+// Same as CgroupModel, equality and ordering are based on the cgroup name
+// only so CgroupDiffModel can be stored in a BTreeSet
+impl Ord for CgroupDiffModel {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.data.name.cmp(&other.data.name)
+    }
+}
+
+impl PartialOrd for CgroupDiffModel {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for CgroupDiffModel {
+    fn eq(&self, other: &Self) -> bool {
+        self.data.name == other.data.name
+    }
+}
+
+impl Eq for CgroupDiffModel {}
+
+impl CgroupDiffModel {
+    pub fn new(before: &CgroupModel, after: &CgroupModel) -> CgroupDiffModel {
+        Self::new_node(Some(before), Some(after))
+    }
+
+    /// At least one of `before` and `after` must be Some.
+    fn new_node(before: Option<&CgroupModel>, after: Option<&CgroupModel>) -> CgroupDiffModel {
+        let (state, model) = match (before, after) {
+            (Some(_), Some(after)) => (CgroupDiffState::Common, after),
+            (None, Some(after)) => (CgroupDiffState::Added, after),
+            (Some(before), None) => (CgroupDiffState::Removed, before),
+            (None, None) => unreachable!("Cgroup must exist on at least one side"),
+        };
+        let names = before
+            .iter()
+            .chain(after.iter())
+            .flat_map(|model| model.children.iter().map(|child| child.data.name.as_str()))
+            .collect::<BTreeSet<_>>();
+        let children = names
+            .into_iter()
+            .map(|name| {
+                Self::new_node(
+                    before.and_then(|model| model.children.get(name)),
+                    after.and_then(|model| model.children.get(name)),
+                )
+            })
+            .collect();
+
+        CgroupDiffModel {
+            data: SingleCgroupDiffModel {
+                name: model.data.name.clone(),
+                full_path: model.data.full_path.clone(),
+                depth: model.data.depth,
+                state,
+                before: before.map(|model| model.data.clone()),
+                after: after.map(|model| model.data.clone()),
+            },
+            children,
+        }
+    }
+}
+
+/// Sorts cgroup diffs by a field. Numeric fields are compared by magnitude so
+/// that the largest changes in either direction end up together. Cgroups
+/// without the field are always last.
+pub fn sort_cgroup_diffs(
+    diffs: &mut [&CgroupDiffModel],
+    field_id: &SingleCgroupDiffModelFieldId,
+    reverse: bool,
+) {
+    sort_by_change(diffs, |diff| diff.data.query(field_id), reverse)
+}
+
+/// Sorts diffs by the field returned by `query`, see `sort_cgroup_diffs`.
+pub(crate) fn sort_by_change<D>(
+    diffs: &mut [D],
+    query: impl Fn(&D) -> Option<Field>,
+    reverse: bool,
+) {
+    let sort_key = |diff: &D| {
+        query(diff).map(|field| match numeric_value(field.clone()) {
+            Some(value) => Field::F64(value.abs()),
+            None => field,
+        })
+    };
+    diffs.sort_by(|lhs, rhs| match (sort_key(lhs), sort_key(rhs)) {
+        (Some(lhs), Some(rhs)) => {
+            let order = lhs.partial_cmp(&rhs).unwrap_or(std::cmp::Ordering::Equal);
+            if reverse { order.reverse() } else { order }
+        }
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn cgroup_model(json: &str) -> CgroupModel {
+        serde_json::from_str(json).expect("Failed to deserialize cgroup model JSON")
+    }
+
+    #[test]
+    fn diff_cgroup_trees() {
+        let before = cgroup_model(
+            r#"
+        {
+            "data": { "name": "<root>", "full_path": "", "depth": 0, "cpu": { "usage_pct": 20.0 } },
+            "count": 3,
+            "recreate_flag": false,
+            "children": [
+                {
+                    "data": { "name": "foo.service", "full_path": "/foo.service", "depth": 1, "cpu": { "usage_pct": 10.0 } },
+                    "count": 1,
+                    "recreate_flag": false,
+                    "children": []
+                },
+                {
+                    "data": { "name": "bar.service", "full_path": "/bar.service", "depth": 1, "cpu": { "usage_pct": 10.0 } },
+                    "count": 1,
+                    "recreate_flag": false,
+                    "children": []
+                }
+            ]
+        }
+        "#,
+        );
+        let after = cgroup_model(
+            r#"
+        {
+            "data": { "name": "<root>", "full_path": "", "depth": 0, "cpu": { "usage_pct": 50.0 } },
+            "count": 3,
+            "recreate_flag": false,
+            "children": [
+                {
+                    "data": { "name": "foo.service", "full_path": "/foo.service", "depth": 1, "cpu": { "usage_pct": 5.0 } },
+                    "count": 1,
+                    "recreate_flag": false,
+                    "children": []
+                },
+                {
+                    "data": { "name": "baz.service", "full_path": "/baz.service", "depth": 1, "cpu": { "usage_pct": 45.0 } },
+                    "count": 1,
+                    "recreate_flag": false,
+                    "children": []
+                }
+            ]
+        }
+        "#,
+        );
+        let diff = CgroupDiffModel::new(&before, &after);
+        let usage_pct = SingleCgroupModelFieldId::from_str("cpu.usage_pct").unwrap();
+        assert_eq!(diff.data.state, CgroupDiffState::Common);
+        assert_eq!(diff.data.delta(&usage_pct), Some(30.0));
+        assert_eq!(diff.data.delta_pct(&usage_pct), Some(150.0));
+
+        let child = |name: &str| &diff.children.get(name).unwrap().data;
+        assert_eq!(child("foo.service").state, CgroupDiffState::Common);
+        assert_eq!(child("foo.service").delta(&usage_pct), Some(-5.0));
+        assert_eq!(child("foo.service").delta_pct(&usage_pct), Some(-50.0));
+        assert_eq!(child("bar.service").state, CgroupDiffState::Removed);
+        assert_eq!(child("bar.service").delta(&usage_pct), Some(-10.0));
+        assert_eq!(child("bar.service").delta_pct(&usage_pct), None);
+        assert_eq!(child("baz.service").state, CgroupDiffState::Added);
+        assert_eq!(child("baz.service").delta(&usage_pct), Some(45.0));
+
+        let mut children = Vec::from_iter(&diff.children);
+        sort_cgroup_diffs(
+            &mut children,
+            &SingleCgroupDiffModelFieldId::Delta(usage_pct.clone()),
+            true,
+        );
+        assert_eq!(
+            children
+                .iter()
+                .map(|child| child.data.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["baz.service", "bar.service", "foo.service"]
+        );
+
+        assert_eq!(
+            child("foo.service")
+                .query(&SingleCgroupDiffModelFieldId::from_str("delta.cpu.usage_pct").unwrap()),
+            Some(Field::F64(-5.0))
+        );
+        assert_eq!(
+            child("foo.service").query(&SingleCgroupDiffModelFieldId::from_str("name").unwrap()),
+            Some(Field::Str("foo.service".to_owned()))
+        );
+    }
+}
@@ -136,18 +136,47 @@ pub fn get_os_release() -> Result<String> {
     ))
 }
 
+/// Returns the path of every cgroup in the sample, keyed by inode number.
+/// Paths are formatted the same way as `/proc/<pid>/cgroup`.
+fn get_cgroup_paths_by_inode(cgroup: &CgroupSample) -> BTreeMap<u64, String> {
+    fn visit(cgroup: &CgroupSample, path: String, paths: &mut BTreeMap<u64, String>) {
+        for (name, child) in cgroup.children.iter().flatten() {
+            visit(
+                child,
+                format!("{}/{}", path.trim_end_matches('/'), name),
+                paths,
+            );
+        }
+        if let Some(inode_number) = cgroup.inode_number {
+            paths.insert(inode_number as u64, path);
+        }
+    }
+
+    let mut paths = BTreeMap::new();
+    visit(cgroup, "/".to_owned(), &mut paths);
+    paths
+}
+
 fn merge_procfs_and_exit_data(
     mut procfs_data: procfs::PidMap,
     exit_data: procfs::PidMap,
+    cgroup: &CgroupSample,
 ) -> procfs::PidMap {
+    if exit_data.is_empty() {
+        return procfs_data;
+    }
+    let cgroup_paths = get_cgroup_paths_by_inode(cgroup);
     exit_data
-        .iter()
+        .into_iter()
         // If `procfs_data` already has the pid, then we use the procfs data because the time delta
         // between the two collection points is negligible and procfs collected data is more
         // complete.
-        .for_each(|entry| {
-            if !procfs_data.contains_key(entry.0) {
-                procfs_data.insert(*entry.0, entry.1.clone());
+        .for_each(|(pid, mut pidinfo)| {
+            if !procfs_data.contains_key(&pid) {
+                if let Some(path) = pidinfo.cgroup_id.and_then(|id| cgroup_paths.get(&id)) {
+                    pidinfo.cgroup = path.clone();
+                }
+                procfs_data.insert(pid, pidinfo);
             }
         });
 
@@ -187,13 +216,21 @@ fn collect_sample(logger: &slog::Logger, options: &CollectorOptions) -> Result<S
             .expect("tried to acquire poisoned lock"),
     );
 
-    Ok(Sample {
-        cgroup: collect_cgroup_sample(
-            &cgroupfs::CgroupReader::new(options.cgroup_root.to_owned())?,
+    let cgroup = match cgroupfs::CgroupReader::new(options.cgroup_root.to_owned()) {
+        Ok(reader) => {
+            collect_cgroup_sample(&reader, options.collect_io_stat, logger, &options.cgroup_re)?
+        }
+        // Fall back to the cgroup1 controller hierarchies on cgroup1 and
+        // hybrid hosts
+        Err(cgroupfs::Error::NotCgroup2(_)) => collect_cgroup_v1_sample(
+            &cgroupfs::CgroupV1Reader::new(options.cgroup_root.to_owned())?,
             options.collect_io_stat,
-            logger,
             &options.cgroup_re,
         )?,
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(Sample {
         processes: merge_procfs_and_exit_data(
             reader
                 .read_all_pids()?
@@ -201,7 +238,9 @@ fn collect_sample(logger: &slog::Logger, options: &CollectorOptions) -> Result<S
                 .map(|(k, v)| (k, v.into()))
                 .collect(),
             exit_pidmap,
+            &cgroup,
         ),
+        cgroup,
         netstats: match procfs::NetReader::new(logger.clone()).and_then(|v| v.read_netstat()) {
             Ok(ns) => ns.into(),
             Err(e) => {
@@ -362,6 +401,20 @@ fn pressure_wrap<S: Sized>(
     }
 }
 
+/// cgroup.threads (and cgroup.procs for domain cgroups in threaded mode)
+/// fail with EOPNOTSUPP when the cgroup is not in a mode that supports
+/// them. Treat that as the data being unavailable.
+fn procs_wrap<S: Sized>(
+    v: std::result::Result<S, cgroupfs::Error>,
+) -> std::result::Result<Option<S>, cgroupfs::Error> {
+    if let Err(cgroupfs::Error::IoError(_, ref e)) = v {
+        if e.raw_os_error() == Some(/* EOPNOTSUPP */ 95) {
+            return Ok(None);
+        }
+    }
+    wrap(v)
+}
+
 fn collect_cgroup_sample(
     reader: &cgroupfs::CgroupReader,
     collect_io_stat: bool,
@@ -443,6 +496,66 @@ fn collect_cgroup_sample(
         cpu_max: wrap(reader.read_cpu_max())?,
         cgroup_controllers: wrap(reader.read_cgroup_controllers())?,
         cgroup_subtree_control: wrap(reader.read_cgroup_subtree_control())?,
+        cgroup_procs: procs_wrap(reader.read_cgroup_procs())?,
+        nr_threads: procs_wrap(reader.read_cgroup_threads())?.map(|tids| tids.len() as u64),
+    })
+}
+
+/// Same as `collect_cgroup_sample`, but for cgroup1 hierarchies. Only the
+/// fields that have a cgroup1 equivalent are populated.
+fn collect_cgroup_v1_sample(
+    reader: &cgroupfs::CgroupV1Reader,
+    collect_io_stat: bool,
+    cgroup_re: &Option<Regex>,
+) -> Result<CgroupSample> {
+    let io_stat = if collect_io_stat {
+        io_stat_wrap(reader.read_io_stat())?
+    } else {
+        None
+    };
+    Ok(CgroupSample {
+        cpu_stat: wrap(reader.read_cpu_stat())?,
+        io_stat,
+        tids_current: wrap(reader.read_pids_current())?,
+        tids_max: wrap(reader.read_pids_max())?,
+        memory_current: wrap(reader.read_memory_current().map(|v| v as i64))?,
+        memory_stat: wrap(reader.read_memory_stat())?,
+        children: wrap(reader.child_cgroup_iter())
+            .context("Failed to get iterator over cgroup children")?
+            .map(|child_iter| {
+                child_iter
+                    .filter(|child| {
+                        if let Some(cgroup_re) = cgroup_re.as_ref() {
+                            !cgroup_re.is_match(&child.name().to_string_lossy())
+                        } else {
+                            true
+                        }
+                    })
+                    .map(|child| {
+                        collect_cgroup_v1_sample(&child, collect_io_stat, cgroup_re).map(
+                            |child_sample| {
+                                (
+                                    child
+                                        .name()
+                                        .file_name()
+                                        .expect("Unexpected .. in cgroup path")
+                                        .to_string_lossy()
+                                        .to_string(),
+                                    child_sample,
+                                )
+                            },
+                        )
+                    })
+                    .collect::<Result<BTreeMap<String, CgroupSample>>>()
+            })
+            .transpose()?,
+        memory_swap_current: wrap(reader.read_memory_swap_current().map(|v| v as i64))?,
+        memory_max: wrap(reader.read_memory_max())?,
+        memory_events: wrap(reader.read_memory_events())?,
+        cpu_max: wrap(reader.read_cpu_max())?,
+        cgroup_procs: wrap(reader.read_cgroup_procs())?,
+        nr_threads: wrap(reader.read_cgroup_threads())?.map(|tids| tids.len() as u64),
+        ..Default::default()
     })
 }
 
@@ -490,3 +603,77 @@ macro_rules! get_option_rate {
             .map(|s| s as u64)
     };
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cgroup_sample(inode_number: i64, children: Vec<(&str, CgroupSample)>) -> CgroupSample {
+        CgroupSample {
+            inode_number: Some(inode_number),
+            children: Some(
+                children
+                    .into_iter()
+                    .map(|(name, child)| (name.to_owned(), child))
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn pid_info(pid: i32, cgroup: &str, cgroup_id: Option<u64>) -> procfs::PidInfo {
+        procfs::PidInfo {
+            stat: procfs::PidStat {
+                pid: Some(pid),
+                ..Default::default()
+            },
+            cgroup: cgroup.to_owned(),
+            cgroup_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cgroup_paths_by_inode() {
+        let cgroup = cgroup_sample(
+            1,
+            vec![
+                (
+                    "system.slice",
+                    cgroup_sample(2, vec![("foo.service", cgroup_sample(3, vec![]))]),
+                ),
+                ("user.slice", CgroupSample::default()),
+            ],
+        );
+        assert_eq!(
+            get_cgroup_paths_by_inode(&cgroup),
+            BTreeMap::from([
+                (1, "/".to_owned()),
+                (2, "/system.slice".to_owned()),
+                (3, "/system.slice/foo.service".to_owned()),
+            ])
+        );
+
+        let procfs_data: procfs::PidMap = [(10, pid_info(10, "/system.slice", Some(2)))].into();
+        let exit_data: procfs::PidMap = [
+            // Also seen in procfs, which takes precedence
+            (10, pid_info(10, "", Some(3))),
+            (11, pid_info(11, "", Some(3))),
+            // Cgroup removed before the sample was collected
+            (12, pid_info(12, "", Some(4))),
+        ]
+        .into();
+        let merged = merge_procfs_and_exit_data(procfs_data, exit_data, &cgroup);
+        assert_eq!(
+            merged
+                .iter()
+                .map(|(pid, info)| (*pid, info.cgroup.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (10, "/system.slice"),
+                (11, "/system.slice/foo.service"),
+                (12, ""),
+            ]
+        );
+    }
+}
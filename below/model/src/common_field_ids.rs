@@ -23,7 +23,7 @@
 ///
 /// This list also servers as documentation for available field ids that could
 /// be used in other below crates. A test ensures that this list is up-to-date.
-pub const COMMON_MODEL_FIELD_IDS: [&str; 410] = [
+pub const COMMON_MODEL_FIELD_IDS: [&str; 431] = [
     "system.hostname",
     "system.kernel_version",
     "system.os_release",
@@ -154,6 +154,8 @@ pub const COMMON_MODEL_FIELD_IDS: [&str; 410] = [
     "cgroup.[path:/<cgroup_path>/.]cpu.nr_throttled_per_sec",
     "cgroup.[path:/<cgroup_path>/.]cpu.throttled_pct",
     "cgroup.[path:/<cgroup_path>/.]pids.tids_current",
+    "cgroup.[path:/<cgroup_path>/.]pids.nr_procs",
+    "cgroup.[path:/<cgroup_path>/.]pids.nr_threads",
     "cgroup.[path:/<cgroup_path>/.]mem.total",
     "cgroup.[path:/<cgroup_path>/.]mem.swap",
     "cgroup.[path:/<cgroup_path>/.]mem.anon",
@@ -194,11 +196,21 @@ pub const COMMON_MODEL_FIELD_IDS: [&str; 410] = [
     "cgroup.[path:/<cgroup_path>/.]mem.pglazyfreed",
     "cgroup.[path:/<cgroup_path>/.]mem.thp_fault_alloc",
     "cgroup.[path:/<cgroup_path>/.]mem.thp_collapse_alloc",
+    "cgroup.[path:/<cgroup_path>/.]mem.pgscan_kswapd",
+    "cgroup.[path:/<cgroup_path>/.]mem.pgscan_direct",
+    "cgroup.[path:/<cgroup_path>/.]mem.pgsteal_khugepaged",
+    "cgroup.[path:/<cgroup_path>/.]mem.thp_swpout",
+    "cgroup.[path:/<cgroup_path>/.]mem.zswpin",
+    "cgroup.[path:/<cgroup_path>/.]mem.zswpout",
+    "cgroup.[path:/<cgroup_path>/.]mem.percpu",
+    "cgroup.[path:/<cgroup_path>/.]mem.sec_pagetables",
+    "cgroup.[path:/<cgroup_path>/.]mem.hugetlb",
     "cgroup.[path:/<cgroup_path>/.]mem.events_low",
     "cgroup.[path:/<cgroup_path>/.]mem.events_high",
     "cgroup.[path:/<cgroup_path>/.]mem.events_max",
     "cgroup.[path:/<cgroup_path>/.]mem.events_oom",
     "cgroup.[path:/<cgroup_path>/.]mem.events_oom_kill",
+    "cgroup.[path:/<cgroup_path>/.]mem.raw_stats",
     "cgroup.[path:/<cgroup_path>/.]io_details.<key>.rbytes_per_sec",
     "cgroup.[path:/<cgroup_path>/.]io_details.<key>.wbytes_per_sec",
     "cgroup.[path:/<cgroup_path>/.]io_details.<key>.rios_per_sec",
@@ -256,6 +268,15 @@ pub const COMMON_MODEL_FIELD_IDS: [&str; 410] = [
     "cgroup.[path:/<cgroup_path>/.]mem_numa.<key>.workingset_restore_anon",
     "cgroup.[path:/<cgroup_path>/.]mem_numa.<key>.workingset_restore_file",
     "cgroup.[path:/<cgroup_path>/.]mem_numa.<key>.workingset_nodereclaim",
+    "cgroup.[path:/<cgroup_path>/.]procs",
+    "cgroup.[path:/<cgroup_path>/.]exited.count",
+    "cgroup.[path:/<cgroup_path>/.]exited.cpu_usage_pct",
+    "cgroup.[path:/<cgroup_path>/.]exited.cpu_user_pct",
+    "cgroup.[path:/<cgroup_path>/.]exited.cpu_system_pct",
+    "cgroup.[path:/<cgroup_path>/.]exited.io_rbytes_per_sec",
+    "cgroup.[path:/<cgroup_path>/.]exited.io_wbytes_per_sec",
+    "cgroup.[path:/<cgroup_path>/.]exited.minorfaults_per_sec",
+    "cgroup.[path:/<cgroup_path>/.]exited.majorfaults_per_sec",
     "resctrl.cpuset",
     "resctrl.ctrl_mon_groups.<key>.cpuset",
     "resctrl.ctrl_mon_groups.<key>.full_path",
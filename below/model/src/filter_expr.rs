@@ -0,0 +1,492 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A small boolean expression language for filtering Queriables, e.g.
+//! `cpu.usage_pct > 50 and full_path =~ /workload.slice/`.
+//!
+//! Grammar:
+//!
+//! ```text
+//! expr       := and_expr (("or" | "||") and_expr)*
+//! and_expr   := not_expr (("and" | "&&") not_expr)*
+//! not_expr   := ("not" | "!") not_expr | "(" expr ")" | comparison
+//! comparison := field_id op literal
+//! op         := "==" | "!=" | "<" | "<=" | ">" | ">=" | "=~" | "!~"
+//! literal    := number[unit] | "string" | 'string' | word
+//!             | /regex/ (only after =~ or !~)
+//! ```
+//!
+//! Numbers may carry a unit, which is converted into the raw unit of the
+//! Field as given by `FieldUnit`:
+//! * sizes: B, K, M, G, T or P, optionally followed by B or iB, are powers of
+//!   1024 bytes, as sizes are displayed.
+//! * durations: ns, us, ms, s, min or h.
+//! * anything else: k, K, M, G, T or P are powers of 1000, and % is ignored
+//!   as percentages are stored as is.
+//!
+//! A unit that does not apply to the Field is an error for <, <=, > and >=.
+//! `FilterExpr::from_str` knows nothing about Fields and treats all of them
+//! as `FieldUnit::Other`, use `FilterExpr::parse` to convert sizes and
+//! durations.
+
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+use anyhow::bail;
+use regex::Regex;
+
+use super::*;
+
+/// Unit of the raw value of a numeric Field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldUnit {
+    /// A size in multiples of the given number of bytes, e.g. 4096 for pages
+    Bytes(f64),
+    /// A duration in microseconds
+    Usecs,
+    /// Anything else, e.g. counts, rates or percentages
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+}
+
+#[derive(Clone, Debug)]
+enum Literal {
+    /// A number converted to the raw unit, along with the original text for
+    /// comparing with non-numeric Fields.
+    Number(f64, String),
+    Str(String),
+    Regex(Regex),
+}
+
+#[derive(Clone, Debug)]
+enum Node<F> {
+    Compare {
+        field_id: F,
+        op: CompareOp,
+        literal: Literal,
+    },
+    Not(Box<Node<F>>),
+    And(Box<Node<F>>, Box<Node<F>>),
+    Or(Box<Node<F>>, Box<Node<F>>),
+}
+
+/// A parsed filter expression over the FieldId of a Queriable.
+#[derive(Clone, Debug)]
+pub struct FilterExpr<F> {
+    root: Node<F>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Op(CompareOp),
+    Word(String),
+    Quoted(String),
+    Regex(String),
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !"()<>=!~&|\"'".contains(c)
+}
+
+/// Reads the text up to the closing `delimiter`.
+fn delimited(chars: &mut Peekable<std::str::Chars>, delimiter: char) -> Result<String> {
+    let mut text = String::new();
+    loop {
+        match chars.next() {
+            // Only the closing delimiter needs escaping, other backslashes
+            // are kept for the regex engine.
+            Some('\\') if chars.peek() == Some(&delimiter) => text.push(chars.next().unwrap()),
+            Some(next) if next == delimiter => return Ok(text),
+            Some(next) => text.push(next),
+            None => bail!("Missing closing {} in filter expression", delimiter),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        chars.next();
+        let token = match c {
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '=' if chars.next_if_eq(&'=').is_some() => Token::Op(CompareOp::Eq),
+            '=' if chars.next_if_eq(&'~').is_some() => Token::Op(CompareOp::Match),
+            '=' => Token::Op(CompareOp::Eq),
+            '!' if chars.next_if_eq(&'=').is_some() => Token::Op(CompareOp::Ne),
+            '!' if chars.next_if_eq(&'~').is_some() => Token::Op(CompareOp::NotMatch),
+            '!' => Token::Not,
+            '<' if chars.next_if_eq(&'=').is_some() => Token::Op(CompareOp::Le),
+            '<' => Token::Op(CompareOp::Lt),
+            '>' if chars.next_if_eq(&'=').is_some() => Token::Op(CompareOp::Ge),
+            '>' => Token::Op(CompareOp::Gt),
+            // A `/` anywhere else is part of a word, e.g. a cgroup path
+            '/' if matches!(
+                tokens.last(),
+                Some(Token::Op(CompareOp::Match | CompareOp::NotMatch))
+            ) =>
+            {
+                delimited(&mut chars, c).map(Token::Regex)?
+            }
+            '"' | '\'' => delimited(&mut chars, c).map(Token::Quoted)?,
+            c if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some(next) = chars.next_if(|c| is_word_char(*c)) {
+                    word.push(next);
+                }
+                match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Word(word),
+                }
+            }
+            c => bail!("Unexpected character in filter expression: {}", c),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Parses a number with an optional unit into the raw unit of a Field.
+/// Returns None if `s` is not a number, and an error if the unit does not
+/// apply to the Field.
+fn parse_number(s: &str, field_unit: FieldUnit) -> Result<Option<f64>> {
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value = match value.parse::<f64>() {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+    let bytes = |exp: i32| match field_unit {
+        FieldUnit::Bytes(size) => Some(1024.0_f64.powi(exp) / size),
+        _ => None,
+    };
+    // Sizes are displayed in powers of 1024
+    let power = |exp: i32| match field_unit {
+        FieldUnit::Other => Some(1000.0_f64.powi(exp)),
+        _ => bytes(exp),
+    };
+    let usecs = |usecs: f64| match field_unit {
+        FieldUnit::Usecs => Some(usecs),
+        _ => None,
+    };
+    let multiplier = match unit {
+        "" => Some(1.0),
+        "%" => match field_unit {
+            FieldUnit::Other => Some(1.0),
+            _ => None,
+        },
+        "B" => bytes(0),
+        "k" | "K" => power(1),
+        "M" => power(2),
+        "G" => power(3),
+        "T" => power(4),
+        "P" => power(5),
+        "KB" | "KiB" => bytes(1),
+        "MB" | "MiB" => bytes(2),
+        "GB" | "GiB" => bytes(3),
+        "TB" | "TiB" => bytes(4),
+        "PB" | "PiB" => bytes(5),
+        "ns" => usecs(1e-3),
+        "us" => usecs(1.0),
+        "ms" => usecs(1e3),
+        "s" => usecs(1e6),
+        "min" => usecs(60e6),
+        "h" => usecs(3600e6),
+        _ => return Ok(None),
+    };
+    match multiplier {
+        Some(multiplier) => Ok(Some(value * multiplier)),
+        None => bail!(
+            "Unit {} does not apply to a field of {:?}",
+            unit,
+            field_unit
+        ),
+    }
+}
+
+fn parse_literal(op: CompareOp, token: Option<Token>, field_unit: FieldUnit) -> Result<Literal> {
+    let matching = matches!(op, CompareOp::Match | CompareOp::NotMatch);
+    let ordering = matches!(
+        op,
+        CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge
+    );
+    match token {
+        Some(Token::Regex(re)) | Some(Token::Quoted(re)) | Some(Token::Word(re)) if matching => {
+            Ok(Literal::Regex(Regex::new(&re).with_context(|| {
+                format!("Invalid regex in filter expression: {}", re)
+            })?))
+        }
+        Some(Token::Word(word)) => match parse_number(&word, field_unit) {
+            Ok(Some(value)) => Ok(Literal::Number(value, word)),
+            // e.g. `name == 1s` compares the text
+            Ok(None) | Err(_) if !ordering => Ok(Literal::Str(word)),
+            Ok(None) => bail!("Expected a number but got: {}", word),
+            Err(e) => Err(e),
+        },
+        Some(Token::Quoted(text)) if !ordering => Ok(Literal::Str(text)),
+        Some(Token::Quoted(text)) => bail!("Expected a number but got: \"{}\"", text),
+        Some(token) => bail!("Expected a value but got: {:?}", token),
+        None => bail!("Expected a value at the end of filter expression"),
+    }
+}
+
+type UnitOf<'a, F> = &'a dyn Fn(&F) -> FieldUnit;
+
+fn parse_or<F: FromStr>(
+    tokens: &mut Peekable<IntoIter<Token>>,
+    unit_of: UnitOf<F>,
+) -> Result<Node<F>> {
+    let mut lhs = parse_and(tokens, unit_of)?;
+    while tokens.next_if_eq(&Token::Or).is_some() {
+        lhs = Node::Or(Box::new(lhs), Box::new(parse_and(tokens, unit_of)?));
+    }
+    Ok(lhs)
+}
+
+fn parse_and<F: FromStr>(
+    tokens: &mut Peekable<IntoIter<Token>>,
+    unit_of: UnitOf<F>,
+) -> Result<Node<F>> {
+    let mut lhs = parse_not(tokens, unit_of)?;
+    while tokens.next_if_eq(&Token::And).is_some() {
+        lhs = Node::And(Box::new(lhs), Box::new(parse_not(tokens, unit_of)?));
+    }
+    Ok(lhs)
+}
+
+fn parse_not<F: FromStr>(
+    tokens: &mut Peekable<IntoIter<Token>>,
+    unit_of: UnitOf<F>,
+) -> Result<Node<F>> {
+    match tokens.next() {
+        Some(Token::Not) => Ok(Node::Not(Box::new(parse_not(tokens, unit_of)?))),
+        Some(Token::LParen) => {
+            let node = parse_or(tokens, unit_of)?;
+            match tokens.next() {
+                Some(Token::RParen) => Ok(node),
+                _ => bail!("Missing closing ) in filter expression"),
+            }
+        }
+        Some(Token::Word(field)) => {
+            let field_id = F::from_str(&field).map_err(|_| anyhow!("Unknown field: {}", field))?;
+            let op = match tokens.next() {
+                Some(Token::Op(op)) => op,
+                _ => bail!("Expected a comparison operator after {}", field),
+            };
+            let literal = parse_literal(op, tokens.next(), unit_of(&field_id))?;
+            Ok(Node::Compare {
+                field_id,
+                op,
+                literal,
+            })
+        }
+        Some(token) => bail!("Expected a field but got: {:?}", token),
+        None => bail!("Unexpected end of filter expression"),
+    }
+}
+
+impl<F: FieldId + FromStr> FilterExpr<F> {
+    /// Parses an expression, converting numbers with a unit into the raw
+    /// unit of the compared Field as given by `unit_of`.
+    pub fn parse(s: &str, unit_of: impl Fn(&F) -> FieldUnit) -> Result<Self> {
+        let mut tokens = tokenize(s)?.into_iter().peekable();
+        let root = parse_or(&mut tokens, &unit_of)?;
+        if let Some(token) = tokens.next() {
+            bail!("Unexpected {:?} in filter expression", token);
+        }
+        Ok(Self { root })
+    }
+}
+
+impl<F: FieldId + FromStr> FromStr for FilterExpr<F> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s, |_| FieldUnit::Other)
+    }
+}
+
+fn compare(field: Field, op: CompareOp, literal: &Literal) -> bool {
+    match literal {
+        Literal::Regex(re) => re.is_match(&field.to_string()) == (op == CompareOp::Match),
+        Literal::Number(value, text) => match numeric_value(field.clone()) {
+            Some(v) => match op {
+                CompareOp::Eq => v == *value,
+                CompareOp::Ne => v != *value,
+                CompareOp::Lt => v < *value,
+                CompareOp::Le => v <= *value,
+                CompareOp::Gt => v > *value,
+                CompareOp::Ge => v >= *value,
+                CompareOp::Match | CompareOp::NotMatch => false,
+            },
+            // e.g. `comm == 123` compares the text
+            None => match op {
+                CompareOp::Eq => field.to_string() == *text,
+                CompareOp::Ne => field.to_string() != *text,
+                _ => false,
+            },
+        },
+        Literal::Str(text) => match op {
+            CompareOp::Eq => field.to_string() == *text,
+            CompareOp::Ne => field.to_string() != *text,
+            _ => false,
+        },
+    }
+}
+
+impl<F: FieldId> Node<F> {
+    fn eval(&self, model: &F::Queriable) -> bool {
+        match self {
+            // A field that is not available never matches
+            Node::Compare {
+                field_id,
+                op,
+                literal,
+            } => model
+                .query(field_id)
+                .map_or(false, |field| compare(field, *op, literal)),
+            Node::Not(node) => !node.eval(model),
+            Node::And(lhs, rhs) => lhs.eval(model) && rhs.eval(model),
+            Node::Or(lhs, rhs) => lhs.eval(model) || rhs.eval(model),
+        }
+    }
+}
+
+impl<F: FieldId> FilterExpr<F> {
+    /// Returns true if the model satisfies the expression.
+    pub fn eval(&self, model: &F::Queriable) -> bool {
+        self.root.eval(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cgroup_model() -> SingleCgroupModel {
+        serde_json::from_str(
+            r#"
+        {
+            "name": "foo.service",
+            "full_path": "/workload.slice/foo.service",
+            "inode_number": 42,
+            "depth": 2,
+            "cpu": { "usage_pct": 60.0 },
+            "memory": { "total": 2147483648, "raw_stats": {} }
+        }
+        "#,
+        )
+        .expect("Failed to deserialize cgroup model JSON")
+    }
+
+    fn unit_of(field_id: &SingleCgroupModelFieldId) -> FieldUnit {
+        if field_id.to_string().starts_with("mem.") {
+            FieldUnit::Bytes(1.0)
+        } else {
+            FieldUnit::Other
+        }
+    }
+
+    fn eval(expr: &str) -> bool {
+        FilterExpr::<SingleCgroupModelFieldId>::parse(expr, unit_of)
+            .expect("Failed to parse filter expression")
+            .eval(&cgroup_model())
+    }
+
+    #[test]
+    fn test_filter_expr_eval() {
+        assert!(eval("cpu.usage_pct > 50"));
+        assert!(!eval("cpu.usage_pct <= 50%"));
+        assert!(eval("mem.total >= 2G and mem.total < 3GiB"));
+        assert!(eval("full_path =~ /workload\\.slice/"));
+        assert!(eval("full_path !~ '^/system'"));
+        assert!(eval("full_path == /workload.slice/foo.service"));
+        assert!(eval("inode_number < 1k"));
+        assert!(eval("name == foo.service && !(cpu.usage_pct < 10)"));
+        assert!(eval("name == \"bar.service\" or cpu.usage_pct > 50"));
+        assert!(eval("not name != foo.service"));
+        assert!(eval(
+            "name == bar.service or name == baz.service or inode_number == 42"
+        ));
+        // Fields that are not available never match
+        assert!(!eval("io.rbytes_per_sec > 0"));
+        assert!(!eval("io.rbytes_per_sec <= 0"));
+    }
+
+    #[test]
+    fn test_filter_expr_units() {
+        let parse = |s, unit| parse_number(s, unit).ok().flatten();
+        assert_eq!(parse("1G", FieldUnit::Bytes(1.0)), Some(1073741824.0));
+        assert_eq!(parse("1.5KB", FieldUnit::Bytes(1.0)), Some(1536.0));
+        // Number of 4K pages
+        assert_eq!(parse("1MiB", FieldUnit::Bytes(4096.0)), Some(256.0));
+        assert_eq!(parse("500ms", FieldUnit::Usecs), Some(500000.0));
+        assert_eq!(parse("2min", FieldUnit::Usecs), Some(120000000.0));
+        assert_eq!(parse("1.5k", FieldUnit::Other), Some(1500.0));
+        assert_eq!(parse("50%", FieldUnit::Other), Some(50.0));
+        assert_eq!(parse("-3", FieldUnit::Other), Some(-3.0));
+        assert_eq!(parse("3parsecs", FieldUnit::Other), None);
+        assert!(parse_number("1GB", FieldUnit::Other).is_err());
+        assert!(parse_number("1s", FieldUnit::Bytes(1.0)).is_err());
+        assert!(parse_number("1k", FieldUnit::Usecs).is_err());
+    }
+
+    #[test]
+    fn test_filter_expr_errors() {
+        for expr in [
+            "",
+            "cpu.usage_pct >",
+            "cpu.usage_pct > foo",
+            "no_such_field == 1",
+            "(cpu.usage_pct > 1",
+            "cpu.usage_pct > 1 cpu.usage_pct < 2",
+            "mem.total > 1s",
+            "name =~ /[/",
+            "name =~ 'foo",
+        ] {
+            assert!(
+                FilterExpr::<SingleCgroupModelFieldId>::parse(expr, unit_of).is_err(),
+                "{} should fail to parse",
+                expr
+            );
+        }
+    }
+}
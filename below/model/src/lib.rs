@@ -31,11 +31,14 @@ use serde::Serialize;
 #[macro_use]
 pub mod collector;
 pub mod cgroup;
+pub mod cgroup_diff;
 pub mod collector_plugin;
 #[cfg(test)]
 mod common_field_ids;
+pub mod filter_expr;
 pub mod network;
 pub mod process;
+pub mod process_diff;
 pub mod resctrl;
 pub mod sample;
 mod sample_model;
@@ -44,9 +47,13 @@ pub mod system;
 open_source_shim!(pub);
 
 pub use cgroup::*;
+pub use cgroup_diff::*;
 pub use collector::*;
+pub use filter_expr::FieldUnit;
+pub use filter_expr::FilterExpr;
 pub use network::*;
 pub use process::*;
+pub use process_diff::*;
 pub use resctrl::*;
 pub use sample::*;
 pub use system::*;
@@ -113,6 +120,19 @@ impl From<Field> for f64 {
     }
 }
 
+/// Converts numeric Fields into f64 and returns None for everything else.
+pub fn numeric_value(field: Field) -> Option<f64> {
+    match field {
+        Field::U32(_)
+        | Field::U64(_)
+        | Field::I32(_)
+        | Field::I64(_)
+        | Field::F32(_)
+        | Field::F64(_) => Some(f64::from(field)),
+        _ => None,
+    }
+}
+
 impl From<Field> for String {
     fn from(field: Field) -> String {
         match field {
@@ -306,12 +326,68 @@ impl fmt::Display for Field {
     }
 }
 
+/// Variant of Field that a field holds, as declared by the type of the field.
+/// Unlike querying a Model, this is also known for fields without a value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldType {
+    U32,
+    U64,
+    I32,
+    I64,
+    F32,
+    F64,
+    Str,
+    PidState,
+    VecU32,
+    StrSet,
+    StrU64Map,
+    Cpuset,
+    MemNodes,
+    ResctrlCpuset,
+    ResctrlGroupMode,
+}
+
+/// Types of Model fields, i.e. types that convert into a Field.
+pub trait HasFieldType {
+    const FIELD_TYPE: FieldType;
+}
+
+macro_rules! impl_has_field_type {
+    ($($ty:ty => $variant:ident,)*) => {
+        $(
+            impl HasFieldType for $ty {
+                const FIELD_TYPE: FieldType = FieldType::$variant;
+            }
+        )*
+    };
+}
+
+impl_has_field_type! {
+    u32 => U32,
+    u64 => U64,
+    i32 => I32,
+    i64 => I64,
+    f32 => F32,
+    f64 => F64,
+    String => Str,
+    procfs::PidState => PidState,
+    Vec<u32> => VecU32,
+    BTreeSet<String> => StrSet,
+    BTreeMap<String, u64> => StrU64Map,
+    cgroupfs::Cpuset => Cpuset,
+    cgroupfs::MemNodes => MemNodes,
+    resctrlfs::Cpuset => ResctrlCpuset,
+    resctrlfs::GroupMode => ResctrlGroupMode,
+}
+
 /// Each Model is composed of Fields and optionally sub-Models. The Queriable
 /// trait let us query() a Model for a particular Field within the hierarchy
 /// with the given FieldId.
 pub trait Queriable {
     type FieldId: FieldId<Queriable = Self>;
     fn query(&self, field_id: &Self::FieldId) -> Option<Field>;
+    /// Type of the Field that query() returns for the given FieldId.
+    fn field_type(field_id: &Self::FieldId) -> FieldType;
 }
 
 /// Marker trait to bind FieldId back to Queriable for type inference.
@@ -438,6 +514,9 @@ impl<Q: Queriable> Queriable for Vec<Q> {
         self.get(field_id.idx?)
             .and_then(|f| f.query(&field_id.subquery_id))
     }
+    fn field_type(field_id: &Self::FieldId) -> FieldType {
+        Q::field_type(&field_id.subquery_id)
+    }
 }
 
 /// Type that makes BTreeMap Queriable if its value is Queriable. Uses `key`
@@ -510,6 +589,9 @@ impl<K: Ord, Q: Queriable> Queriable for BTreeMap<K, Q> {
         self.get(field_id.key.as_ref()?)
             .and_then(|f| f.query(&field_id.subquery_id))
     }
+    fn field_type(field_id: &Self::FieldId) -> FieldType {
+        Q::field_type(&field_id.subquery_id)
+    }
 }
 
 pub struct NetworkStats<'a> {
@@ -553,7 +635,8 @@ impl Model {
                 &sample.cgroup,
                 last.map(|(s, d)| (&s.cgroup, d)),
             )
-            .aggr_top_level_val(),
+            .aggr_top_level_val()
+            .aggr_exited_processes(&sample.processes, last.map(|(s, d)| (&s.processes, d))),
             process: ProcessModel::new(&sample.processes, last.map(|(s, d)| (&s.processes, d))),
             network: {
                 let sample = NetworkStats {
@@ -136,6 +136,286 @@ impl Nameable for SingleProcessModel {
     }
 }
 
+/// Field whose value processes are grouped by when folded together.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    below_derive::EnumFromStr,
+    below_derive::EnumToString
+)]
+pub enum ProcessGroupBy {
+    Comm,
+    Cgroup,
+    ExePath,
+    Ppid,
+}
+
+impl ProcessGroupBy {
+    pub fn field_id(&self) -> SingleProcessModelFieldId {
+        match self {
+            Self::Comm => SingleProcessModelFieldId::Comm,
+            Self::Cgroup => SingleProcessModelFieldId::Cgroup,
+            Self::ExePath => SingleProcessModelFieldId::ExePath,
+            Self::Ppid => SingleProcessModelFieldId::Ppid,
+        }
+    }
+
+    /// Copies the grouped by field, which `SingleProcessModel::fold` clears
+    /// as it differs between processes in general.
+    fn copy_key(&self, from: &SingleProcessModel, to: &mut SingleProcessModel) {
+        match self {
+            Self::Comm => to.comm = from.comm.clone(),
+            Self::Cgroup => to.cgroup = from.cgroup.clone(),
+            Self::ExePath => to.exe_path = from.exe_path.clone(),
+            Self::Ppid => to.ppid = from.ppid,
+        }
+    }
+}
+
+/// Processes sharing the same value of a field, e.g. all processes with the
+/// same comm, folded together with `SingleProcessModel::fold`.
+#[derive(Clone)]
+pub struct ProcessGroupModel {
+    pub group_by: ProcessGroupBy,
+    /// Value of the grouped by field, "?" for processes without it.
+    pub key: String,
+    pub members: u64,
+    /// Folded stats of the members. Only the grouped by field is kept from
+    /// the fields that cannot be summed.
+    pub process: SingleProcessModel,
+}
+
+impl ProcessGroupModel {
+    /// Groups processes by the value of a field. Groups are ordered by it,
+    /// e.g. numerically for ppid, with processes without it first.
+    pub fn group<'a>(
+        processes: impl IntoIterator<Item = &'a SingleProcessModel>,
+        group_by: ProcessGroupBy,
+    ) -> Vec<ProcessGroupModel> {
+        let field_id = group_by.field_id();
+        let mut groups: BTreeMap<String, ProcessGroupModel> = BTreeMap::new();
+        for spm in processes {
+            let key = spm
+                .query(&field_id)
+                .map_or("?".to_owned(), |v| v.to_string());
+            let group = groups
+                .entry(key.clone())
+                .or_insert_with(|| ProcessGroupModel {
+                    group_by,
+                    key,
+                    members: 0,
+                    process: SingleProcessModel::default(),
+                });
+            group.members += 1;
+            group.process = SingleProcessModel::fold(&group.process, spm);
+            group_by.copy_key(spm, &mut group.process);
+        }
+        // Keys are ordered as strings, which misorders numbers, e.g. "10" < "2"
+        let mut groups: Vec<_> = groups.into_values().collect();
+        groups.sort_by(|lhs, rhs| {
+            lhs.process
+                .query(&field_id)
+                .partial_cmp(&rhs.process.query(&field_id))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        groups
+    }
+}
+
+/// Queries a ProcessGroupModel. `members` is the number of processes in the
+/// group and any other string is a SingleProcessModelFieldId of the folded
+/// processes, e.g. `cpu.usage_pct`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProcessGroupModelFieldId {
+    Members,
+    Process(SingleProcessModelFieldId),
+}
+
+impl FieldId for ProcessGroupModelFieldId {
+    type Queriable = ProcessGroupModel;
+}
+
+impl std::string::ToString for ProcessGroupModelFieldId {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Members => "members".to_owned(),
+            Self::Process(field_id) => field_id.to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for ProcessGroupModelFieldId {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "members" => Ok(Self::Members),
+            _ => Ok(Self::Process(SingleProcessModelFieldId::from_str(s)?)),
+        }
+    }
+}
+
+impl Queriable for ProcessGroupModel {
+    type FieldId = ProcessGroupModelFieldId;
+    fn query(&self, field_id: &Self::FieldId) -> Option<Field> {
+        match field_id {
+            ProcessGroupModelFieldId::Members => Some(Field::U64(self.members)),
+            ProcessGroupModelFieldId::Process(field_id) => self.process.query(field_id),
+        }
+    }
+    fn field_type(field_id: &Self::FieldId) -> FieldType {
+        match field_id {
+            ProcessGroupModelFieldId::Members => FieldType::U64,
+            ProcessGroupModelFieldId::Process(field_id) => SingleProcessModel::field_type(field_id),
+        }
+    }
+}
+
+impl Nameable for ProcessGroupModel {
+    fn name() -> &'static str {
+        "process_group"
+    }
+}
+
+/// A process in the tree of processes built from ppid, with the stats of
+/// its subtree folded together with `SingleProcessModel::fold`.
+#[derive(Clone)]
+pub struct ProcessTreeModel {
+    /// Depth in the tree, 0 for processes whose parent is not in the tree.
+    pub depth: usize,
+    /// Number of processes in the subtree, including this one.
+    pub members: u64,
+    /// The process with its io, mem and cpu summed up over its subtree.
+    pub process: SingleProcessModel,
+    pub children: Vec<ProcessTreeModel>,
+}
+
+impl ProcessTreeModel {
+    /// Builds the trees of the given processes and returns their roots.
+    /// Processes whose parent is not given are roots. Roots and children
+    /// keep the order of the given processes.
+    pub fn build<'a>(
+        processes: impl IntoIterator<Item = &'a SingleProcessModel>,
+    ) -> Vec<ProcessTreeModel> {
+        let processes: Vec<_> = processes.into_iter().collect();
+        let pids: BTreeSet<i32> = processes.iter().filter_map(|spm| spm.pid).collect();
+        let mut children_of: BTreeMap<i32, Vec<&SingleProcessModel>> = BTreeMap::new();
+        let mut roots = Vec::new();
+        for spm in processes {
+            match spm.ppid {
+                Some(ppid) if pids.contains(&ppid) && spm.pid != Some(ppid) => {
+                    children_of.entry(ppid).or_default().push(spm)
+                }
+                _ => roots.push(spm),
+            }
+        }
+        let mut trees: Vec<_> = roots
+            .into_iter()
+            .map(|spm| Self::build_subtree(spm, 0, &mut children_of))
+            .collect();
+        // Processes in a ppid cycle, which only a racy read of procfs can
+        // produce, are not reachable from any root. Keep them as roots.
+        while let Some((_, orphans)) = children_of.pop_first() {
+            for spm in orphans {
+                trees.push(Self::build_subtree(spm, 0, &mut children_of));
+            }
+        }
+        trees
+    }
+
+    fn build_subtree(
+        spm: &SingleProcessModel,
+        depth: usize,
+        children_of: &mut BTreeMap<i32, Vec<&SingleProcessModel>>,
+    ) -> ProcessTreeModel {
+        let children: Vec<_> = spm
+            .pid
+            .and_then(|pid| children_of.remove(&pid))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::build_subtree(child, depth + 1, children_of))
+            .collect();
+        let mut tree = ProcessTreeModel {
+            depth,
+            members: 1,
+            process: spm.clone(),
+            children: Vec::new(),
+        };
+        for child in &children {
+            let folded = SingleProcessModel::fold(&tree.process, &child.process);
+            tree.process.io = folded.io;
+            tree.process.mem = folded.mem;
+            tree.process.cpu = folded.cpu;
+            tree.members += child.members;
+        }
+        tree.children = children;
+        tree
+    }
+}
+
+/// Queries a ProcessTreeModel. `members` is the number of processes in the
+/// subtree and any other string is a SingleProcessModelFieldId of the
+/// process, e.g. `cpu.usage_pct`, which is summed up over the subtree for
+/// io, mem and cpu.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProcessTreeModelFieldId {
+    Members,
+    Process(SingleProcessModelFieldId),
+}
+
+impl FieldId for ProcessTreeModelFieldId {
+    type Queriable = ProcessTreeModel;
+}
+
+impl std::string::ToString for ProcessTreeModelFieldId {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Members => "members".to_owned(),
+            Self::Process(field_id) => field_id.to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for ProcessTreeModelFieldId {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "members" => Ok(Self::Members),
+            _ => Ok(Self::Process(SingleProcessModelFieldId::from_str(s)?)),
+        }
+    }
+}
+
+impl Queriable for ProcessTreeModel {
+    type FieldId = ProcessTreeModelFieldId;
+    fn query(&self, field_id: &Self::FieldId) -> Option<Field> {
+        match field_id {
+            ProcessTreeModelFieldId::Members => Some(Field::U64(self.members)),
+            ProcessTreeModelFieldId::Process(field_id) => self.process.query(field_id),
+        }
+    }
+    fn field_type(field_id: &Self::FieldId) -> FieldType {
+        match field_id {
+            ProcessTreeModelFieldId::Members => FieldType::U64,
+            ProcessTreeModelFieldId::Process(field_id) => SingleProcessModel::field_type(field_id),
+        }
+    }
+}
+
+impl Recursive for ProcessTreeModel {
+    fn get_depth(&self) -> usize {
+        self.depth
+    }
+}
+
+impl Nameable for ProcessTreeModel {
+    fn name() -> &'static str {
+        "process"
+    }
+}
+
 #[derive(Clone, Default, Serialize, Deserialize, below_derive::Queriable)]
 pub struct ProcessIoModel {
     pub rbytes_per_sec: Option<f64>,
@@ -280,4 +560,113 @@ mod test {
             Some(Field::Str("systemd".to_owned()))
         );
     }
+
+    #[test]
+    fn group_processes() {
+        let model_json = r#"
+        {
+            "processes": {
+                "1": { "pid": 1, "ppid": 0, "comm": "worker", "cpu": { "usage_pct": 10.0 } },
+                "2": { "pid": 2, "ppid": 1, "comm": "worker", "cpu": { "usage_pct": 5.0 } },
+                "3": { "pid": 3, "ppid": 1, "comm": "server", "mem": { "rss_bytes": 1024 } }
+            }
+        }
+        "#;
+        let model: ProcessModel = serde_json::from_str(model_json).unwrap();
+
+        let groups = ProcessGroupModel::group(model.processes.values(), ProcessGroupBy::Comm);
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| (group.key.as_str(), group.members))
+                .collect::<Vec<_>>(),
+            vec![("server", 1), ("worker", 2)]
+        );
+        let query = |group: &ProcessGroupModel, field_id: &str| {
+            group.query(&ProcessGroupModelFieldId::from_str(field_id).unwrap())
+        };
+        assert_eq!(
+            query(&groups[1], "comm"),
+            Some(Field::Str("worker".to_owned()))
+        );
+        assert_eq!(query(&groups[1], "members"), Some(Field::U64(2)));
+        assert_eq!(query(&groups[1], "cpu.usage_pct"), Some(Field::F64(15.0)));
+        assert_eq!(query(&groups[1], "pid"), None);
+        assert_eq!(query(&groups[0], "mem.rss_bytes"), Some(Field::U64(1024)));
+
+        let groups = ProcessGroupModel::group(model.processes.values(), ProcessGroupBy::Ppid);
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| (group.key.as_str(), group.members))
+                .collect::<Vec<_>>(),
+            vec![("0", 1), ("1", 2)]
+        );
+        assert_eq!(query(&groups[1], "ppid"), Some(Field::I32(1)));
+        assert_eq!(query(&groups[1], "comm"), None);
+    }
+
+    #[test]
+    fn build_process_tree() {
+        let model_json = r#"
+        {
+            "processes": {
+                "1": { "pid": 1, "ppid": 0, "comm": "init", "cpu": { "usage_pct": 1.0 } },
+                "2": { "pid": 2, "ppid": 1, "comm": "server", "cpu": { "usage_pct": 10.0 } },
+                "3": { "pid": 3, "ppid": 2, "comm": "worker", "cpu": { "usage_pct": 5.0 } },
+                "4": { "pid": 4, "ppid": 1, "comm": "shell", "mem": { "rss_bytes": 1024 } },
+                "5": { "pid": 5, "ppid": 6, "comm": "racy" },
+                "6": { "pid": 6, "ppid": 5, "comm": "racy" }
+            }
+        }
+        "#;
+        let model: ProcessModel = serde_json::from_str(model_json).unwrap();
+
+        let trees = ProcessTreeModel::build(model.processes.values());
+        fn flatten(trees: &[ProcessTreeModel], res: &mut Vec<(i32, usize, u64)>) {
+            for tree in trees {
+                res.push((tree.process.pid.unwrap(), tree.depth, tree.members));
+                flatten(&tree.children, res);
+            }
+        }
+        let mut flattened = Vec::new();
+        flatten(&trees, &mut flattened);
+        assert_eq!(
+            flattened,
+            vec![
+                (1, 0, 4),
+                (2, 1, 2),
+                (3, 2, 1),
+                (4, 1, 1),
+                // The cycle is kept rather than lost
+                (6, 0, 2),
+                (5, 1, 1),
+            ]
+        );
+
+        let query = |tree: &ProcessTreeModel, field_id: &str| {
+            tree.query(&ProcessTreeModelFieldId::from_str(field_id).unwrap())
+        };
+        assert_eq!(query(&trees[0], "cpu.usage_pct"), Some(Field::F64(16.0)));
+        assert_eq!(query(&trees[0], "mem.rss_bytes"), Some(Field::U64(1024)));
+        assert_eq!(
+            query(&trees[0], "comm"),
+            Some(Field::Str("init".to_owned()))
+        );
+        assert_eq!(query(&trees[0], "members"), Some(Field::U64(4)));
+        assert_eq!(
+            query(&trees[0].children[0], "cpu.usage_pct"),
+            Some(Field::F64(15.0))
+        );
+
+        // Processes whose parent is filtered out become roots
+        let trees = ProcessTreeModel::build(model.processes.values().skip(1).take(3));
+        assert_eq!(
+            trees
+                .iter()
+                .map(|tree| (tree.process.pid.unwrap(), tree.members))
+                .collect::<Vec<_>>(),
+            vec![(2, 2), (4, 1)]
+        );
+    }
 }
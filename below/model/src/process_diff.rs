@@ -0,0 +1,267 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::cgroup_diff::delta;
+use crate::cgroup_diff::delta_pct;
+use crate::cgroup_diff::sort_by_change;
+
+/// Processes with the same comm as seen in two Models, folded together with
+/// `ProcessGroupModel::group`. Pids are not comparable between hosts or over
+/// long periods of time, so processes are matched by comm instead. Either
+/// side is None if no process has the comm there.
+#[derive(Clone, Default)]
+pub struct SingleProcessDiffModel {
+    pub comm: String,
+    pub state: CgroupDiffState,
+    pub before: Option<ProcessGroupModel>,
+    pub after: Option<ProcessGroupModel>,
+}
+
+/// Queries a SingleProcessDiffModel. Its String representation looks like
+/// this:
+///     delta.cpu.usage_pct
+/// where the prefix is one of `before`, `after`, `delta` or `delta_pct` and
+/// the rest is a ProcessGroupModelFieldId.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SingleProcessDiffModelFieldId {
+    Comm,
+    State,
+    Before(ProcessGroupModelFieldId),
+    After(ProcessGroupModelFieldId),
+    Delta(ProcessGroupModelFieldId),
+    DeltaPct(ProcessGroupModelFieldId),
+}
+
+impl FieldId for SingleProcessDiffModelFieldId {
+    type Queriable = SingleProcessDiffModel;
+}
+
+impl std::string::ToString for SingleProcessDiffModelFieldId {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Comm => "comm".to_owned(),
+            Self::State => "state".to_owned(),
+            Self::Before(field_id) => format!("before.{}", field_id.to_string()),
+            Self::After(field_id) => format!("after.{}", field_id.to_string()),
+            Self::Delta(field_id) => format!("delta.{}", field_id.to_string()),
+            Self::DeltaPct(field_id) => format!("delta_pct.{}", field_id.to_string()),
+        }
+    }
+}
+
+impl std::str::FromStr for SingleProcessDiffModelFieldId {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "comm" => return Ok(Self::Comm),
+            "state" => return Ok(Self::State),
+            _ => {}
+        }
+        let (prefix, subquery_id_str) = s.split_once('.').ok_or_else(|| {
+            anyhow!(
+                "Unable to find a variant of the given enum matching string `{}`.",
+                s
+            )
+        })?;
+        let subquery_id = ProcessGroupModelFieldId::from_str(subquery_id_str)?;
+        match prefix {
+            "before" => Ok(Self::Before(subquery_id)),
+            "after" => Ok(Self::After(subquery_id)),
+            "delta" => Ok(Self::Delta(subquery_id)),
+            "delta_pct" => Ok(Self::DeltaPct(subquery_id)),
+            _ => Err(anyhow!(
+                "Unknown process diff prefix `{}` in `{}`",
+                prefix,
+                s
+            )),
+        }
+    }
+}
+
+impl Queriable for SingleProcessDiffModel {
+    type FieldId = SingleProcessDiffModelFieldId;
+    fn query(&self, field_id: &Self::FieldId) -> Option<Field> {
+        match field_id {
+            SingleProcessDiffModelFieldId::Comm => Some(Field::from(self.comm.clone())),
+            SingleProcessDiffModelFieldId::State => Some(Field::from(self.state.to_string())),
+            SingleProcessDiffModelFieldId::Before(field_id) => {
+                self.before.as_ref()?.query(field_id)
+            }
+            SingleProcessDiffModelFieldId::After(field_id) => self.after.as_ref()?.query(field_id),
+            SingleProcessDiffModelFieldId::Delta(field_id) => {
+                delta(self.before.as_ref(), self.after.as_ref(), field_id).map(Field::F64)
+            }
+            SingleProcessDiffModelFieldId::DeltaPct(field_id) => {
+                delta_pct(self.before.as_ref(), self.after.as_ref(), field_id).map(Field::F64)
+            }
+        }
+    }
+    fn field_type(field_id: &Self::FieldId) -> FieldType {
+        match field_id {
+            SingleProcessDiffModelFieldId::Comm | SingleProcessDiffModelFieldId::State => {
+                FieldType::Str
+            }
+            SingleProcessDiffModelFieldId::Before(field_id)
+            | SingleProcessDiffModelFieldId::After(field_id) => {
+                ProcessGroupModel::field_type(field_id)
+            }
+            SingleProcessDiffModelFieldId::Delta(_)
+            | SingleProcessDiffModelFieldId::DeltaPct(_) => FieldType::F64,
+        }
+    }
+}
+
+impl Nameable for SingleProcessDiffModel {
+    fn name() -> &'static str {
+        "process_diff"
+    }
+}
+
+/// Difference between the processes of two Models, grouped by comm and
+/// ordered by it.
+#[derive(Clone, Default)]
+pub struct ProcessDiffModel {
+    pub processes: Vec<SingleProcessDiffModel>,
+}
+
+impl ProcessDiffModel {
+    pub fn new(before: &ProcessModel, after: &ProcessModel) -> ProcessDiffModel {
+        let group = |model: &ProcessModel| -> BTreeMap<String, ProcessGroupModel> {
+            ProcessGroupModel::group(model.processes.values(), ProcessGroupBy::Comm)
+                .into_iter()
+                .map(|group| (group.key.clone(), group))
+                .collect()
+        };
+        let mut before = group(before);
+        let mut after = group(after);
+        let comms = before
+            .keys()
+            .chain(after.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let processes = comms
+            .into_iter()
+            .map(|comm| {
+                let before = before.remove(&comm);
+                let after = after.remove(&comm);
+                let state = match (&before, &after) {
+                    (Some(_), Some(_)) => CgroupDiffState::Common,
+                    (None, _) => CgroupDiffState::Added,
+                    (_, None) => CgroupDiffState::Removed,
+                };
+                SingleProcessDiffModel {
+                    comm,
+                    state,
+                    before,
+                    after,
+                }
+            })
+            .collect();
+        ProcessDiffModel { processes }
+    }
+}
+
+/// Sorts process diffs by a field, see `sort_cgroup_diffs`.
+pub fn sort_process_diffs(
+    diffs: &mut [&SingleProcessDiffModel],
+    field_id: &SingleProcessDiffModelFieldId,
+    reverse: bool,
+) {
+    sort_by_change(diffs, |diff| diff.query(field_id), reverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn process_model(json: &str) -> ProcessModel {
+        serde_json::from_str(json).expect("Failed to deserialize process model JSON")
+    }
+
+    #[test]
+    fn diff_processes_by_comm() {
+        let before = process_model(
+            r#"
+        {
+            "processes": {
+                "1": { "pid": 1, "comm": "systemd", "cpu": { "usage_pct": 1.0 } },
+                "10": { "pid": 10, "comm": "httpd", "cpu": { "usage_pct": 10.0 } },
+                "11": { "pid": 11, "comm": "httpd", "cpu": { "usage_pct": 20.0 } },
+                "20": { "pid": 20, "comm": "cron", "cpu": { "usage_pct": 0.5 } }
+            }
+        }
+        "#,
+        );
+        let after = process_model(
+            r#"
+        {
+            "processes": {
+                "1": { "pid": 1, "comm": "systemd", "cpu": { "usage_pct": 1.0 } },
+                "42": { "pid": 42, "comm": "httpd", "cpu": { "usage_pct": 90.0 } },
+                "50": { "pid": 50, "comm": "java", "cpu": { "usage_pct": 40.0 } }
+            }
+        }
+        "#,
+        );
+        let diff = ProcessDiffModel::new(&before, &after);
+        assert_eq!(
+            diff.processes
+                .iter()
+                .map(|process| (process.comm.as_str(), process.state))
+                .collect::<Vec<_>>(),
+            vec![
+                ("cron", CgroupDiffState::Removed),
+                ("httpd", CgroupDiffState::Common),
+                ("java", CgroupDiffState::Added),
+                ("systemd", CgroupDiffState::Common),
+            ]
+        );
+
+        let field_id = |s: &str| SingleProcessDiffModelFieldId::from_str(s).unwrap();
+        let httpd = &diff.processes[1];
+        assert_eq!(
+            httpd.query(&field_id("before.members")),
+            Some(Field::U64(2))
+        );
+        assert_eq!(
+            httpd.query(&field_id("before.cpu.usage_pct")),
+            Some(Field::F64(30.0))
+        );
+        assert_eq!(
+            httpd.query(&field_id("delta.cpu.usage_pct")),
+            Some(Field::F64(60.0))
+        );
+        assert_eq!(
+            httpd.query(&field_id("delta_pct.cpu.usage_pct")),
+            Some(Field::F64(200.0))
+        );
+        assert_eq!(
+            diff.processes[2].query(&field_id("delta.members")),
+            Some(Field::F64(1.0))
+        );
+
+        let mut processes = Vec::from_iter(&diff.processes);
+        sort_process_diffs(&mut processes, &field_id("delta.cpu.usage_pct"), true);
+        assert_eq!(
+            processes
+                .iter()
+                .map(|process| process.comm.as_str())
+                .collect::<Vec<_>>(),
+            vec!["httpd", "java", "cron", "systemd"]
+        );
+    }
+}
@@ -39,6 +39,12 @@ pub struct ResctrlMonGroupModel {
     pub mon: ResctrlMonModel,
 }
 
+impl Nameable for ResctrlMonGroupModel {
+    fn name() -> &'static str {
+        "resctrl_mon"
+    }
+}
+
 /// Collection of all data about a single CTRL_MON group and descendents
 #[derive(Clone, Debug, Default, Serialize, Deserialize, below_derive::Queriable)]
 pub struct ResctrlCtrlMonGroupModel {
@@ -52,6 +58,12 @@ pub struct ResctrlCtrlMonGroupModel {
     pub mon_groups: BTreeMap<String, ResctrlMonGroupModel>,
 }
 
+impl Nameable for ResctrlCtrlMonGroupModel {
+    fn name() -> &'static str {
+        "resctrl"
+    }
+}
+
 /// All data about the entire resctrl filesystem
 #[derive(Clone, Debug, Default, Serialize, Deserialize, below_derive::Queriable)]
 pub struct ResctrlModel {
@@ -151,6 +163,40 @@ impl ResctrlModel {
                 .collect(),
         }
     }
+
+    /// All CTRL_MON groups: the root group, then the others.
+    pub fn get_ctrl_mon_groups(&self) -> Vec<ResctrlCtrlMonGroupModel> {
+        std::iter::once(self.get_root_group())
+            .chain(self.ctrl_mon_groups.values().cloned())
+            .collect()
+    }
+
+    /// All MON groups: those of the root group, then those of each other
+    /// CTRL_MON group.
+    pub fn get_mon_groups(&self) -> Vec<ResctrlMonGroupModel> {
+        self.mon_groups
+            .values()
+            .chain(
+                self.ctrl_mon_groups
+                    .values()
+                    .flat_map(|ctrl_mon_group| ctrl_mon_group.mon_groups.values()),
+            )
+            .cloned()
+            .collect()
+    }
+
+    /// The root group as a CTRL_MON group, with the top-level MON groups as
+    /// its MON groups.
+    pub fn get_root_group(&self) -> ResctrlCtrlMonGroupModel {
+        ResctrlCtrlMonGroupModel {
+            name: "<root>".to_owned(),
+            full_path: String::new(),
+            cpuset: self.cpuset.clone(),
+            mode: self.mode.clone(),
+            mon: self.mon.clone().unwrap_or_default(),
+            mon_groups: self.mon_groups.clone(),
+        }
+    }
 }
 
 impl ResctrlCtrlMonGroupModel {
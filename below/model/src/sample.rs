@@ -55,6 +55,8 @@ pub struct CgroupSample {
     pub cpu_max: Option<cgroupfs::CpuMax>,
     pub cgroup_controllers: Option<BTreeSet<String>>,
     pub cgroup_subtree_control: Option<BTreeSet<String>>,
+    pub cgroup_procs: Option<Vec<u32>>,
+    pub nr_threads: Option<u64>,
 }
 
 #[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize)]
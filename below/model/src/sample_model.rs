@@ -251,11 +251,21 @@ pub const SAMPLE_MODEL_JSON: &str = r#"
                 "pglazyfreed": 0,
                 "thp_fault_alloc": 0,
                 "thp_collapse_alloc": 0,
+                "pgscan_kswapd": 0,
+                "pgscan_direct": 0,
+                "pgsteal_khugepaged": 0,
+                "thp_swpout": 0,
+                "zswpin": 0,
+                "zswpout": 0,
+                "percpu": 0,
+                "sec_pagetables": 0,
+                "hugetlb": 0,
                 "events_low": 0,
                 "events_high": 300000,
                 "events_max": 300000,
                 "events_oom": 0,
-                "events_oom_kill": 0
+                "events_oom_kill": 0,
+                "raw_stats": {}
             },
             "io": null,
             "io_total": null,
@@ -343,11 +353,21 @@ pub const SAMPLE_MODEL_JSON: &str = r#"
                         "pglazyfreed": 0,
                         "thp_fault_alloc": 0,
                         "thp_collapse_alloc": 0,
+                        "pgscan_kswapd": 0,
+                        "pgscan_direct": 0,
+                        "pgsteal_khugepaged": 0,
+                        "thp_swpout": 0,
+                        "zswpin": 0,
+                        "zswpout": 0,
+                        "percpu": 0,
+                        "sec_pagetables": 0,
+                        "hugetlb": 0,
                         "events_low": 0,
                         "events_high": 0,
                         "events_max": 0,
                         "events_oom": 0,
-                        "events_oom_kill": 0
+                        "events_oom_kill": 0,
+                        "raw_stats": {}
                     },
                     "io": null,
                     "io_total": null,
@@ -430,11 +450,21 @@ pub const SAMPLE_MODEL_JSON: &str = r#"
                         "pglazyfreed": 0,
                         "thp_fault_alloc": 0,
                         "thp_collapse_alloc": 0,
+                        "pgscan_kswapd": 0,
+                        "pgscan_direct": 0,
+                        "pgsteal_khugepaged": 0,
+                        "thp_swpout": 0,
+                        "zswpin": 0,
+                        "zswpout": 0,
+                        "percpu": 0,
+                        "sec_pagetables": 0,
+                        "hugetlb": 0,
                         "events_low": 0,
                         "events_high": 0,
                         "events_max": 0,
                         "events_oom": 0,
-                        "events_oom_kill": 0
+                        "events_oom_kill": 0,
+                        "raw_stats": {}
                     },
                     "io": null,
                     "io_total": null,
@@ -525,11 +555,21 @@ pub const SAMPLE_MODEL_JSON: &str = r#"
                         "pglazyfreed": 0,
                         "thp_fault_alloc": 0,
                         "thp_collapse_alloc": 0,
+                        "pgscan_kswapd": 0,
+                        "pgscan_direct": 0,
+                        "pgsteal_khugepaged": 0,
+                        "thp_swpout": 0,
+                        "zswpin": 0,
+                        "zswpout": 0,
+                        "percpu": 0,
+                        "sec_pagetables": 0,
+                        "hugetlb": 0,
                         "events_low": 0,
                         "events_high": 0,
                         "events_max": 0,
                         "events_oom": 0,
-                        "events_oom_kill": 0
+                        "events_oom_kill": 0,
+                        "raw_stats": {}
                     },
                     "io": null,
                     "io_total": null,
@@ -617,11 +657,21 @@ pub const SAMPLE_MODEL_JSON: &str = r#"
                                 "pglazyfreed": 0,
                                 "thp_fault_alloc": 0,
                                 "thp_collapse_alloc": 0,
+                                "pgscan_kswapd": 0,
+                                "pgscan_direct": 0,
+                                "pgsteal_khugepaged": 0,
+                                "thp_swpout": 0,
+                                "zswpin": 0,
+                                "zswpout": 0,
+                                "percpu": 0,
+                                "sec_pagetables": 0,
+                                "hugetlb": 0,
                                 "events_low": 0,
                                 "events_high": 0,
                                 "events_max": 0,
                                 "events_oom": 0,
-                                "events_oom_kill": 0
+                                "events_oom_kill": 0,
+                                "raw_stats": {}
                             },
                             "io": null,
                             "io_total": null,
@@ -369,6 +369,9 @@ pub struct PidInfo {
     // This field was previously called "mem"
     #[serde(alias = "mem")]
     pub status: PidStatus,
+    // Only set for exited processes reported by exitstat, which do not have
+    // a cgroup path
+    pub cgroup_id: Option<u64>,
 }
 
 pub type PidMap = BTreeMap<i32, PidInfo>;
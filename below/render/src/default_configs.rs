@@ -24,6 +24,25 @@ use RenderFormat::SectorReadableSize;
 
 use super::*;
 
+impl HasRenderConfig for model::Model {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::ModelFieldId::*;
+        match field_id {
+            System(field_id) => model::SystemModel::get_render_config_builder(field_id),
+            Cgroup(field_id) => {
+                model::SingleCgroupModel::get_render_config_builder(&field_id.subquery_id)
+            }
+            Process(model::ProcessModelFieldId::Processes(field_id)) => {
+                model::SingleProcessModel::get_render_config_builder(&field_id.subquery_id)
+            }
+            Network(field_id) => model::NetworkModel::get_render_config_builder(field_id),
+            // No render config for GPU in open source
+            Gpu(_) => RenderConfigBuilder::new(),
+            Resctrl(field_id) => model::ResctrlModel::get_render_config_builder(field_id),
+        }
+    }
+}
+
 impl HasRenderConfig for model::SingleCgroupModel {
     fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
         use model::SingleCgroupModelFieldId::*;
@@ -45,6 +64,8 @@ impl HasRenderConfig for model::SingleCgroupModel {
             }
             Props(field_id) => model::CgroupProperties::get_render_config_builder(field_id),
             Pids(field_id) => model::CgroupPidsModel::get_render_config_builder(field_id),
+            Procs => rc.title("Procs").width(50),
+            Exited(field_id) => model::CgroupExitedModel::get_render_config_builder(field_id),
         }
     }
 }
@@ -66,6 +87,8 @@ impl HasRenderConfigForDump for model::SingleCgroupModel {
         use model::CgroupIoModelFieldId::WiosPerSec;
         use model::CgroupMemoryModelFieldId::Anon;
         use model::CgroupMemoryModelFieldId::File;
+        use model::CgroupMemoryModelFieldId::Hugetlb;
+        use model::CgroupMemoryModelFieldId::Percpu;
         use model::CgroupMemoryModelFieldId::Pgactivate;
         use model::CgroupMemoryModelFieldId::Pgdeactivate;
         use model::CgroupMemoryModelFieldId::Pgfault;
@@ -74,13 +97,18 @@ impl HasRenderConfigForDump for model::SingleCgroupModel {
         use model::CgroupMemoryModelFieldId::Pgmajfault;
         use model::CgroupMemoryModelFieldId::Pgrefill;
         use model::CgroupMemoryModelFieldId::Pgscan;
+        use model::CgroupMemoryModelFieldId::PgscanDirect;
+        use model::CgroupMemoryModelFieldId::PgscanKswapd;
         use model::CgroupMemoryModelFieldId::Pgsteal;
+        use model::CgroupMemoryModelFieldId::PgstealKhugepaged;
+        use model::CgroupMemoryModelFieldId::SecPagetables;
         use model::CgroupMemoryModelFieldId::Shmem;
         use model::CgroupMemoryModelFieldId::Slab;
         use model::CgroupMemoryModelFieldId::Sock;
         use model::CgroupMemoryModelFieldId::Swap;
         use model::CgroupMemoryModelFieldId::ThpCollapseAlloc;
         use model::CgroupMemoryModelFieldId::ThpFaultAlloc;
+        use model::CgroupMemoryModelFieldId::ThpSwpout;
         use model::CgroupMemoryModelFieldId::Total;
         use model::CgroupMemoryModelFieldId::WorkingsetActivateAnon;
         use model::CgroupMemoryModelFieldId::WorkingsetActivateFile;
@@ -91,6 +119,8 @@ impl HasRenderConfigForDump for model::SingleCgroupModel {
         use model::CgroupMemoryModelFieldId::WorkingsetRestoreFile;
         use model::CgroupMemoryModelFieldId::Zswap;
         use model::CgroupMemoryModelFieldId::Zswapped;
+        use model::CgroupMemoryModelFieldId::Zswpin;
+        use model::CgroupMemoryModelFieldId::Zswpout;
         use model::CgroupPressureModelFieldId::MemoryFullPct;
         use model::CgroupPressureModelFieldId::MemorySomePct;
         use model::SingleCgroupModelFieldId::Cpu;
@@ -141,6 +171,15 @@ impl HasRenderConfigForDump for model::SingleCgroupModel {
             Mem(Pglazyfreed) => rc.title("Pglazyfreed"),
             Mem(ThpFaultAlloc) => rc.title("THP Fault Alloc"),
             Mem(ThpCollapseAlloc) => rc.title("THP Collapse Alloc"),
+            Mem(PgscanKswapd) => rc.title("Pgscan Kswapd"),
+            Mem(PgscanDirect) => rc.title("Pgscan Direct"),
+            Mem(PgstealKhugepaged) => rc.title("Pgsteal Khugepaged"),
+            Mem(ThpSwpout) => rc.title("THP Swpout"),
+            Mem(Zswpin) => rc.title("Zswpin"),
+            Mem(Zswpout) => rc.title("Zswpout"),
+            Mem(Percpu) => rc.title("Mem Percpu"),
+            Mem(SecPagetables) => rc.title("Mem Sec Pagetables"),
+            Mem(Hugetlb) => rc.title("Mem Hugetlb"),
             Pressure(MemorySomePct) => rc.title("Mem Some Pressure"),
             Pressure(MemoryFullPct) => rc.title("Mem Pressure"),
             _ => rc,
@@ -153,6 +192,7 @@ impl HasRenderConfigForDump for model::SingleCgroupModel {
         field_id: &Self::FieldId,
     ) -> Option<RenderOpenMetricsConfigBuilder> {
         use model::CgroupCpuModelFieldId::*;
+        use model::CgroupExitedModelFieldId::*;
         use model::CgroupIoModelFieldId::*;
         use model::CgroupMemoryModelFieldId::*;
         use model::CgroupPidsModelFieldId::*;
@@ -179,6 +219,8 @@ impl HasRenderConfigForDump for model::SingleCgroupModel {
             },
             Pids(field_id) => match field_id {
                 TidsCurrent => Some(counter.unit("count")),
+                NrProcs => Some(gauge.unit("count")),
+                NrThreads => Some(gauge.unit("count")),
             },
             Io(field_id) => match field_id {
                 RbytesPerSec => Some(gauge.unit("bytes_per_second")),
@@ -241,6 +283,17 @@ impl HasRenderConfigForDump for model::SingleCgroupModel {
                 Pglazyfreed => Some(gauge.help("Pglazyfreed per second")),
                 ThpFaultAlloc => Some(gauge.help("THP Fault Alloc per second")),
                 ThpCollapseAlloc => Some(gauge.help("THP Collapse Alloc per second")),
+                PgscanKswapd => Some(gauge.help("Pgscan kswapd per second")),
+                PgscanDirect => Some(gauge.help("Pgscan direct per second")),
+                PgstealKhugepaged => Some(gauge.help("Pgsteal khugepaged per second")),
+                ThpSwpout => Some(gauge.help("THP Swpout per second")),
+                Zswpin => Some(gauge.help("Zswpin per second")),
+                Zswpout => Some(gauge.help("Zswpout per second")),
+                Percpu => Some(gauge.unit("bytes")),
+                SecPagetables => Some(gauge.unit("bytes")),
+                Hugetlb => Some(gauge.unit("bytes")),
+                // Unknown keys may be either counters or gauges
+                RawStats => None,
             },
             Pressure(field_id) => match field_id {
                 CpuSomePct => Some(gauge.unit("percent")),
@@ -262,10 +315,142 @@ impl HasRenderConfigForDump for model::SingleCgroupModel {
             // Looks like these represent child IO data. Not sure it's necessary to report this
             // as dump does not even pretend to form a hierarchy.
             IoDetails(_) => None,
+            // Member pids are a list rather than a value
+            Procs => None,
+            Exited(field_id) => match field_id {
+                Count => Some(gauge.help("Processes exited in the interval")),
+                CpuUsagePct => Some(gauge.unit("percent")),
+                CpuUserPct => Some(gauge.unit("percent")),
+                CpuSystemPct => Some(gauge.unit("percent")),
+                IoRbytesPerSec => Some(gauge.unit("bytes_per_second")),
+                IoWbytesPerSec => Some(gauge.unit("bytes_per_second")),
+                MinorfaultsPerSec => Some(gauge.help("Minor page faults per second")),
+                MajorfaultsPerSec => Some(gauge.help("Major page faults per second")),
+            },
+        }
+    }
+}
+
+/// Columns of a diff model that are derived from a field of the underlying
+/// model, see `get_diff_render_config`.
+enum DiffColumn {
+    Before,
+    After,
+    Delta,
+    DeltaPct,
+}
+
+/// Derives the config of a diff column from the config of the field it is
+/// based on.
+fn get_diff_render_config(column: DiffColumn, mut rc: RenderConfig) -> RenderConfig {
+    let title = rc.get_title().to_owned();
+    match column {
+        DiffColumn::After => rc,
+        DiffColumn::Before => {
+            rc.title = Some(format!("Before {}", title));
+            rc
+        }
+        DiffColumn::Delta => {
+            rc.title = Some(format!("Δ {}", title));
+            // Limits are irrelevant for a change, so -1 must not become "max"
+            rc.format = match rc.format {
+                Some(MaxOrReadableSize) => Some(ReadableSize),
+                Some(MaxOrDuration) => Some(Duration),
+                format => format,
+            };
+            rc
+        }
+        DiffColumn::DeltaPct => RenderConfigBuilder::new()
+            .title(format!("Δ% {}", title))
+            .format(Precision(2))
+            .suffix("%")
+            .get(),
+    }
+}
+
+/// Derives the config of a cgroup diff column from the config of the
+/// SingleCgroupModel field it is based on.
+fn get_cgroup_diff_render_config(
+    field_id: &model::SingleCgroupDiffModelFieldId,
+    rc: RenderConfig,
+) -> RenderConfig {
+    use model::SingleCgroupDiffModelFieldId::*;
+    match field_id {
+        Name | FullPath | State => rc,
+        Before(_) => get_diff_render_config(DiffColumn::Before, rc),
+        After(_) => get_diff_render_config(DiffColumn::After, rc),
+        Delta(_) => get_diff_render_config(DiffColumn::Delta, rc),
+        DeltaPct(_) => get_diff_render_config(DiffColumn::DeltaPct, rc),
+    }
+}
+
+impl HasRenderConfig for model::SingleCgroupDiffModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::SingleCgroupDiffModelFieldId::*;
+        let rc = RenderConfigBuilder::new();
+        match field_id {
+            Name => rc.title("Name").width(50),
+            FullPath => rc.title("Full Path").width(50),
+            State => rc.title("State").width(10),
+            Before(subquery_id)
+            | After(subquery_id)
+            | Delta(subquery_id)
+            | DeltaPct(subquery_id) => get_cgroup_diff_render_config(
+                field_id,
+                model::SingleCgroupModel::get_render_config(subquery_id),
+            )
+            .into(),
         }
     }
 }
 
+impl HasRenderConfigForDump for model::SingleCgroupDiffModel {
+    fn get_render_config_for_dump(field_id: &Self::FieldId) -> RenderConfig {
+        use common::util::get_prefix;
+        use model::SingleCgroupDiffModelFieldId::*;
+        match field_id {
+            Name => Self::get_render_config_builder(field_id)
+                .indented_prefix(get_prefix(false))
+                .get(),
+            FullPath | State => Self::get_render_config(field_id),
+            Before(subquery_id)
+            | After(subquery_id)
+            | Delta(subquery_id)
+            | DeltaPct(subquery_id) => get_cgroup_diff_render_config(
+                field_id,
+                model::SingleCgroupModel::get_render_config_for_dump(subquery_id),
+            ),
+        }
+    }
+
+    fn get_openmetrics_config_for_dump(
+        &self,
+        _field_id: &Self::FieldId,
+    ) -> Option<RenderOpenMetricsConfigBuilder> {
+        // A diff is not a time series
+        None
+    }
+}
+
+impl HasRenderConfig for model::SingleProcessDiffModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::SingleProcessDiffModelFieldId::*;
+        let (column, subquery_id) = match field_id {
+            Comm => return RenderConfigBuilder::new().title("Comm").width(30),
+            State => return RenderConfigBuilder::new().title("State").width(10),
+            Before(subquery_id) => (DiffColumn::Before, subquery_id),
+            After(subquery_id) => (DiffColumn::After, subquery_id),
+            Delta(subquery_id) => (DiffColumn::Delta, subquery_id),
+            DeltaPct(subquery_id) => (DiffColumn::DeltaPct, subquery_id),
+        };
+        get_diff_render_config(
+            column,
+            model::ProcessGroupModel::get_render_config(subquery_id),
+        )
+        .into()
+    }
+}
+
 impl HasRenderConfig for model::CgroupCpuModel {
     fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
         use model::CgroupCpuModelFieldId::*;
@@ -287,6 +472,25 @@ impl HasRenderConfig for model::CgroupPidsModel {
         let rc = RenderConfigBuilder::new();
         match field_id {
             TidsCurrent => rc.title("Tids Current").format(Precision(1)),
+            NrProcs => rc.title("Nr Procs"),
+            NrThreads => rc.title("Nr Threads"),
+        }
+    }
+}
+
+impl HasRenderConfig for model::CgroupExitedModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::CgroupExitedModelFieldId::*;
+        let rc = RenderConfigBuilder::new();
+        match field_id {
+            Count => rc.title("Exited"),
+            CpuUsagePct => rc.title("Exited CPU").suffix("%").format(Precision(2)),
+            CpuUserPct => rc.title("Exited CPU User").suffix("%").format(Precision(2)),
+            CpuSystemPct => rc.title("Exited CPU Sys").suffix("%").format(Precision(2)),
+            IoRbytesPerSec => rc.title("Exited Reads").suffix("/s").format(ReadableSize),
+            IoWbytesPerSec => rc.title("Exited Writes").suffix("/s").format(ReadableSize),
+            MinorfaultsPerSec => rc.title("Exited Minflt").suffix("/s").format(Precision(2)),
+            MajorfaultsPerSec => rc.title("Exited Majflt").suffix("/s").format(Precision(2)),
         }
     }
 }
@@ -361,6 +565,16 @@ impl HasRenderConfig for model::CgroupMemoryModel {
             Pglazyfreed => rc.title("Pglazyfreed/s"),
             ThpFaultAlloc => rc.title("THP Fault Alloc/s"),
             ThpCollapseAlloc => rc.title("THP Collapse Alloc/s"),
+            PgscanKswapd => rc.title("Pgscan Kswapd/s"),
+            PgscanDirect => rc.title("Pgscan Direct/s"),
+            PgstealKhugepaged => rc.title("Pgsteal Khugepaged/s"),
+            ThpSwpout => rc.title("THP Swpout/s"),
+            Zswpin => rc.title("Zswpin/s"),
+            Zswpout => rc.title("Zswpout/s"),
+            Percpu => rc.title("Percpu").format(ReadableSize),
+            SecPagetables => rc.title("Sec Pagetables").format(ReadableSize),
+            Hugetlb => rc.title("Hugetlb").format(ReadableSize),
+            RawStats => rc.title("Raw Stats"),
         }
     }
 }
@@ -980,6 +1194,81 @@ impl HasRenderConfigForDump for model::SingleProcessModel {
     }
 }
 
+impl HasRenderConfig for model::ProcessGroupModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::ProcessGroupModelFieldId::*;
+        match field_id {
+            Members => RenderConfigBuilder::new().title("Members"),
+            Process(field_id) => model::SingleProcessModel::get_render_config_builder(field_id),
+        }
+    }
+}
+
+impl HasRenderConfigForDump for model::ProcessGroupModel {
+    fn get_render_config_for_dump(field_id: &Self::FieldId) -> RenderConfig {
+        use model::ProcessGroupModelFieldId::*;
+        match field_id {
+            Members => Self::get_render_config(field_id),
+            Process(field_id) => model::SingleProcessModel::get_render_config_for_dump(field_id),
+        }
+    }
+
+    fn get_openmetrics_config_for_dump(
+        &self,
+        field_id: &Self::FieldId,
+    ) -> Option<RenderOpenMetricsConfigBuilder> {
+        use model::ProcessGroupModelFieldId::*;
+        // Label with the group as folded processes have no pid
+        let group_by = self.group_by.to_string();
+        match field_id {
+            Members => Some(gauge()),
+            Process(field_id) => self.process.get_openmetrics_config_for_dump(field_id),
+        }
+        .map(|config| config.label(&group_by, &self.key))
+    }
+}
+
+impl HasRenderConfig for model::ProcessTreeModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::ProcessTreeModelFieldId::*;
+        match field_id {
+            Members => RenderConfigBuilder::new().title("Members"),
+            Process(field_id) => model::SingleProcessModel::get_render_config_builder(field_id),
+        }
+    }
+}
+
+impl HasRenderConfigForDump for model::ProcessTreeModel {
+    fn get_render_config_for_dump(field_id: &Self::FieldId) -> RenderConfig {
+        use common::util::get_prefix;
+        use model::ProcessTreeModelFieldId::*;
+        use model::SingleProcessModelFieldId::Comm;
+        match field_id {
+            Members => Self::get_render_config(field_id),
+            Process(Comm) => model::SingleProcessModel::get_render_config_for_dump(&Comm)
+                .update(RenderConfigBuilder::new().indented_prefix(get_prefix(false))),
+            Process(field_id) => model::SingleProcessModel::get_render_config_for_dump(field_id),
+        }
+    }
+
+    fn get_openmetrics_config_for_dump(
+        &self,
+        field_id: &Self::FieldId,
+    ) -> Option<RenderOpenMetricsConfigBuilder> {
+        use model::ProcessTreeModelFieldId::*;
+        match field_id {
+            Members => {
+                let gauge = gauge();
+                match self.process.pid {
+                    Some(pid) => Some(gauge.label("pid", &pid.to_string())),
+                    None => Some(gauge),
+                }
+            }
+            Process(field_id) => self.process.get_openmetrics_config_for_dump(field_id),
+        }
+    }
+}
+
 impl HasRenderConfig for model::ProcessIoModel {
     fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
         use model::ProcessIoModelFieldId::*;
@@ -1415,6 +1704,177 @@ impl HasRenderConfigForDump for model::BtrfsModel {
     }
 }
 
+impl HasRenderConfig for model::ResctrlModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::ResctrlModelFieldId::*;
+        let rc = RenderConfigBuilder::new();
+        match field_id {
+            Cpuset => rc.title("Cpuset").width(30),
+            Mode => rc.title("Mode"),
+            Mon(field_id) => model::ResctrlMonModel::get_render_config_builder(field_id),
+            MonGroups(field_id) => {
+                model::ResctrlMonGroupModel::get_render_config_builder(&field_id.subquery_id)
+            }
+            CtrlMonGroups(field_id) => {
+                model::ResctrlCtrlMonGroupModel::get_render_config_builder(&field_id.subquery_id)
+            }
+        }
+    }
+}
+
+impl HasRenderConfig for model::ResctrlCtrlMonGroupModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::ResctrlCtrlMonGroupModelFieldId::*;
+        let rc = RenderConfigBuilder::new();
+        match field_id {
+            Name => rc.title("Name").width(30),
+            FullPath => rc.title("Full Path").width(50).fold(FoldOption::Path),
+            Cpuset => rc.title("Cpuset").width(30),
+            Mode => rc.title("Mode"),
+            Mon(field_id) => model::ResctrlMonModel::get_render_config_builder(field_id),
+            MonGroups(field_id) => {
+                model::ResctrlMonGroupModel::get_render_config_builder(&field_id.subquery_id)
+            }
+        }
+    }
+}
+
+impl HasRenderConfigForDump for model::ResctrlCtrlMonGroupModel {
+    fn get_openmetrics_config_for_dump(
+        &self,
+        field_id: &Self::FieldId,
+    ) -> Option<RenderOpenMetricsConfigBuilder> {
+        use model::ResctrlCtrlMonGroupModelFieldId::*;
+        match field_id {
+            // We label the other metrics with the group path
+            Name => None,
+            FullPath => None,
+            Cpuset => None,
+            Mode => None,
+            Mon(field_id) => self
+                .mon
+                .get_openmetrics_config_for_dump(field_id)
+                .map(|config| config.label("group", &self.full_path)),
+            MonGroups(field_id) => self
+                .mon_groups
+                .get(field_id.key.as_ref()?)?
+                .get_openmetrics_config_for_dump(&field_id.subquery_id),
+        }
+    }
+}
+
+impl HasRenderConfig for model::ResctrlMonGroupModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::ResctrlMonGroupModelFieldId::*;
+        let rc = RenderConfigBuilder::new();
+        match field_id {
+            Name => rc.title("Name").width(30),
+            FullPath => rc.title("Full Path").width(50).fold(FoldOption::Path),
+            Mon(field_id) => model::ResctrlMonModel::get_render_config_builder(field_id),
+        }
+    }
+}
+
+impl HasRenderConfigForDump for model::ResctrlMonGroupModel {
+    fn get_openmetrics_config_for_dump(
+        &self,
+        field_id: &Self::FieldId,
+    ) -> Option<RenderOpenMetricsConfigBuilder> {
+        use model::ResctrlMonGroupModelFieldId::*;
+        match field_id {
+            // We label the other metrics with the group path
+            Name => None,
+            FullPath => None,
+            Mon(field_id) => self
+                .mon
+                .get_openmetrics_config_for_dump(field_id)
+                .map(|config| config.label("group", &self.full_path)),
+        }
+    }
+}
+
+impl HasRenderConfig for model::ResctrlMonModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::ResctrlMonModelFieldId::*;
+        match field_id {
+            Total(field_id) => model::ResctrlL3MonModel::get_render_config_builder(field_id),
+            PerL3(field_id) => {
+                BTreeMap::<u64, model::ResctrlL3MonModel>::get_render_config_builder(field_id)
+            }
+        }
+    }
+}
+
+impl HasRenderConfigForDump for model::ResctrlMonModel {
+    fn get_openmetrics_config_for_dump(
+        &self,
+        field_id: &Self::FieldId,
+    ) -> Option<RenderOpenMetricsConfigBuilder> {
+        use model::ResctrlMonModelFieldId::*;
+        match field_id {
+            Total(field_id) => self.total.get_openmetrics_config_for_dump(field_id),
+            PerL3(field_id) => self.per_l3.get_openmetrics_config_for_dump(field_id),
+        }
+    }
+}
+
+impl HasRenderConfig for BTreeMap<u64, model::ResctrlL3MonModel> {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        let mut rc =
+            model::ResctrlL3MonModel::get_render_config_builder(&field_id.subquery_id).get();
+        rc.title = rc.title.map(|title| {
+            format!(
+                "L3 {} {}",
+                field_id
+                    .key
+                    .expect("BTreeMapFieldId without key should not have render config"),
+                title
+            )
+        });
+        rc.into()
+    }
+}
+
+impl HasRenderConfigForDump for BTreeMap<u64, model::ResctrlL3MonModel> {
+    fn get_openmetrics_config_for_dump(
+        &self,
+        field_id: &Self::FieldId,
+    ) -> Option<RenderOpenMetricsConfigBuilder> {
+        let key = field_id
+            .key
+            .expect("BTreeMapFieldId without key should not have render config");
+        self.get(&key)
+            .and_then(|l3| l3.get_openmetrics_config_for_dump(&field_id.subquery_id))
+            .map(|config| config.label("l3", &key.to_string()))
+    }
+}
+
+impl HasRenderConfig for model::ResctrlL3MonModel {
+    fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
+        use model::ResctrlL3MonModelFieldId::*;
+        let rc = RenderConfigBuilder::new();
+        match field_id {
+            LlcOccupancyBytes => rc.title("LLC Occupancy").format(ReadableSize),
+            MbmTotalBytesPerSec => rc.title("MBM Total").format(ReadableSize).suffix("/s"),
+            MbmLocalBytesPerSec => rc.title("MBM Local").format(ReadableSize).suffix("/s"),
+        }
+    }
+}
+
+impl HasRenderConfigForDump for model::ResctrlL3MonModel {
+    fn get_openmetrics_config_for_dump(
+        &self,
+        field_id: &Self::FieldId,
+    ) -> Option<RenderOpenMetricsConfigBuilder> {
+        use model::ResctrlL3MonModelFieldId::*;
+        match field_id {
+            LlcOccupancyBytes => Some(gauge().unit("bytes")),
+            MbmTotalBytesPerSec => Some(gauge().unit("bytes_per_second")),
+            MbmLocalBytesPerSec => Some(gauge().unit("bytes_per_second")),
+        }
+    }
+}
+
 impl HasRenderConfig for model::CgroupStatModel {
     fn get_render_config_builder(field_id: &Self::FieldId) -> RenderConfigBuilder {
         use model::CgroupStatModelFieldId::*;
@@ -25,6 +25,7 @@ use common::util::convert_duration;
 use common::util::convert_freq;
 use common::util::fold_string;
 use model::Field;
+use model::FieldUnit;
 use model::Queriable;
 
 open_source_shim!();
@@ -143,6 +144,12 @@ impl RenderOpenMetricsConfigBuilder {
         }
     }
 
+    /// Override the type of the metric, e.g. for a statistic of a counter
+    pub fn ty(mut self, ty: OpenMetricsType) -> Self {
+        self.config.ty = ty;
+        self
+    }
+
     /// Help text for the metric
     pub fn help(mut self, help: &str) -> Self {
         self.config.help = Some(help.to_owned());
@@ -159,19 +166,7 @@ impl RenderOpenMetricsConfigBuilder {
     ///
     /// Note multiple labels for a single metric is supported
     pub fn label(mut self, key: &str, value: &str) -> Self {
-        // Escape value according to spec:
-        // https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md#escaping
-        let mut value_escaped = String::with_capacity(value.len());
-        for c in value.chars() {
-            match c {
-                '\\' => value_escaped.push_str("\\\\"),
-                '\"' => value_escaped.push_str("\\\""),
-                '\n' => value_escaped.push_str("\\n"),
-                _ => value_escaped.push(c),
-            }
-        }
-
-        self.config.labels.insert(key.to_owned(), value_escaped);
+        self.config.labels.insert(key.to_owned(), value.to_owned());
         self
     }
 
@@ -181,6 +176,21 @@ impl RenderOpenMetricsConfigBuilder {
     }
 }
 
+/// Escape label value according to spec:
+/// https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md#escaping
+fn escape_label_value(value: &str) -> String {
+    let mut value_escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => value_escaped.push_str("\\\\"),
+            '\"' => value_escaped.push_str("\\\""),
+            '\n' => value_escaped.push_str("\\n"),
+            _ => value_escaped.push(c),
+        }
+    }
+    value_escaped
+}
+
 fn gauge() -> RenderOpenMetricsConfigBuilder {
     RenderOpenMetricsConfigBuilder::new(OpenMetricsType::Gauge)
 }
@@ -203,6 +213,11 @@ impl RenderOpenMetricsConfig {
         ret
     }
 
+    /// Labels of the metric, unescaped
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+
     fn render_field(&self, key: &str, field: Field, timestamp: i64) -> String {
         let mut res = String::new();
 
@@ -214,7 +229,7 @@ impl RenderOpenMetricsConfig {
             let body = self
                 .labels
                 .iter()
-                .map(|(k, v)| format!("{}=\"{}\"", k, v))
+                .map(|(k, v)| format!("{}=\"{}\"", k, escape_label_value(v)))
                 .collect::<Vec<_>>()
                 .join(",");
             format!("{{{}}}", body)
@@ -315,6 +330,19 @@ impl RenderConfig {
         self.title.as_deref().unwrap_or("unknown")
     }
 
+    /// Unit of the raw value of the Field as implied by the format, for
+    /// converting numbers with a unit in filter expressions.
+    pub fn get_field_unit(&self) -> FieldUnit {
+        use RenderFormat::*;
+        match &self.format {
+            Some(ReadableSize) | Some(MaxOrReadableSize) => FieldUnit::Bytes(1.0),
+            Some(PageReadableSize) => FieldUnit::Bytes(4096.0),
+            Some(SectorReadableSize) => FieldUnit::Bytes(512.0),
+            Some(Duration) | Some(MaxOrDuration) => FieldUnit::Usecs,
+            Some(Precision(_)) | Some(ReadableFrequency) | None => FieldUnit::Other,
+        }
+    }
+
     /// Value for fixed-width rendering, with default as title width + 2 and
     /// minimum width 10.
     fn get_width(&self) -> usize {
@@ -333,6 +361,14 @@ impl RenderConfig {
     /// Applies format to render a Field into a String.
     fn format(&self, field: Field) -> String {
         use RenderFormat::*;
+        // Negative values (e.g. deltas between two Models) are rendered as
+        // their magnitude with a leading sign, as the unit conversions below
+        // expect non-negative numbers.
+        if let (Some(format), Field::F64(v)) = (&self.format, &field) {
+            if *v < 0.0 && !matches!(format, Precision(_)) {
+                return format!("-{}", self.format(Field::F64(-v)));
+            }
+        }
         match &self.format {
             Some(format) => match format {
                 Precision(precision) => format!("{:.precision$}", field, precision = precision),
@@ -347,9 +383,9 @@ impl RenderConfig {
                         convert_bytes(field as f64)
                     }
                 }
-                ReadableFrequency => convert_freq(u64::from(field)),
+                ReadableFrequency => convert_freq(f64::from(field) as u64),
                 Duration => {
-                    let field = u64::from(field);
+                    let field = f64::from(field) as u64;
                     convert_duration(field)
                 }
                 MaxOrDuration => {
@@ -588,3 +624,19 @@ my_key{label1="value1",label2="value2",label3="zzz"} 1.23 1234
 "#;
     assert_eq!(text, expected);
 }
+
+#[test]
+fn test_render_negative() {
+    let config = RenderConfigBuilder::new()
+        .format(RenderFormat::ReadableSize)
+        .get();
+    assert_eq!(config.render(Some(Field::F64(-2048.0)), false), "-2 KB");
+    let config = RenderConfigBuilder::new()
+        .format(RenderFormat::Duration)
+        .get();
+    assert_eq!(config.render(Some(Field::F64(-1500.0)), false), "-1.5 ms");
+    let config = RenderConfigBuilder::new()
+        .format(RenderFormat::Precision(1))
+        .get();
+    assert_eq!(config.render(Some(Field::F64(-1.23)), false), "-1.2");
+}
@@ -39,6 +39,7 @@ pub struct Metadata {
     pub sid: i32,
     pub cpu: i32,
     pub comm: [u8; 16],
+    pub cgroup_id: u64,
 }
 
 // See bpf prog for comments on what each field is
@@ -130,10 +131,10 @@ impl ExitstatDriver {
                 rbytes: Some(event.stats.io_read_bytes),
                 wbytes: Some(event.stats.io_write_bytes),
             },
-            // It seems to be somewhat tricky to get a cgroup name using bpf. It might be possible
-            // with the bpf_get_current_cgroup_id() helper, but that returns what looks like an
-            // inode number. I'm not sure if it's easy/possible to translate an inode # to a path.
+            // bpf can only give us the cgroup id, which is the inode number of the cgroup
+            // directory. The collector translates it into a path using the cgroup sample.
             cgroup: "?".to_string(),
+            cgroup_id: Some(event.meta.cgroup_id),
             // We can't access cmdline b/c it requires taking mmap_sem and a
             // bunch of memory management helpers.
             ..Default::default()
@@ -17,6 +17,7 @@
 use std::cell::RefCell;
 use std::fs;
 use std::io;
+use std::net::IpAddr;
 use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
 use std::process::exit;
@@ -56,6 +57,7 @@ use uzers::get_current_uid;
 use uzers::get_user_by_uid;
 
 mod exitstat;
+mod metrics;
 #[cfg(test)]
 mod test;
 
@@ -202,6 +204,16 @@ enum Command {
         /// Options for compression
         #[clap(flatten)]
         compress_opts: CompressOpts,
+        /// Serve the latest sample on /metrics at this port, in the
+        /// OpenMetrics text format
+        #[clap(long)]
+        metrics_port: Option<u16>,
+        /// Address to serve /metrics on, e.g. 0.0.0.0 for all interfaces
+        #[clap(long, default_value = "127.0.0.1", requires("metrics_port"))]
+        metrics_address: IpAddr,
+        /// Number of processes using the most CPU to serve on /metrics
+        #[clap(long, default_value = "20", requires("metrics_port"))]
+        metrics_top_processes: u32,
     },
     /// Replay historical data (interactive)
     Replay {
@@ -212,8 +224,8 @@ enum Command {
         /// Absolute: "Jan 01 23:59", "01/01/1970 11:59PM", "1970-01-01 23:59:59"{n}
         /// Unix Epoch: 1589808367
         /// _
-        #[clap(short, long, verbatim_doc_comment)]
-        time: String,
+        #[clap(short, long, verbatim_doc_comment, required_unless_present("view"))]
+        time: Option<String>,
         /// Supply hostname to activate remote viewing
         #[clap(short = 's', long)]
         host: Option<String>,
@@ -231,6 +243,23 @@ enum Command {
         /// command instead of from the store directory.
         #[clap(long, conflicts_with("host"))]
         snapshot: Option<String>,
+        /// Restore a view saved with the save_view command, given by its
+        /// name, file or below://view?... string. Its sample is shown
+        /// unless --time is given.
+        #[clap(long)]
+        view: Option<String>,
+        /// Compare side by side with another host at the same time. Press
+        /// 'V' in the cgroup or process view to show the comparison.
+        #[clap(long)]
+        compare_host: Option<String>,
+        /// Override default port to connect to --compare-host
+        #[clap(long, requires("compare_host"))]
+        compare_port: Option<u16>,
+        /// Compare side by side with another time, same format as --time
+        /// and also adjusted by -r. It is on --compare-host if given, or
+        /// else on the replayed host.
+        #[clap(long)]
+        compare_time: Option<String>,
     },
     /// Debugging facilities (for development use)
     Debug {
@@ -277,6 +306,38 @@ enum Command {
         #[clap(long, requires("host"))]
         port: Option<u16>,
     },
+    /// Export historical data into Parquet or Arrow tables for offline analysis
+    #[cfg(feature = "export")]
+    Export {
+        /// Supply hostname to export from remote
+        #[clap(short = 's', long)]
+        host: Option<String>,
+        /// Override default port to connect to remote
+        #[clap(long, requires("host"))]
+        port: Option<u16>,
+        /// Export from a snapshot file generated by the snapshot
+        /// command instead of from the store directory.
+        #[clap(long, conflicts_with("host"))]
+        snapshot: Option<String>,
+        #[clap(flatten)]
+        opts: dump::command::ExportOpts,
+    },
+    /// Load historical data into SQLite and query it
+    #[cfg(feature = "sql")]
+    Sql {
+        /// Supply hostname to query from remote
+        #[clap(short = 's', long)]
+        host: Option<String>,
+        /// Override default port to connect to remote
+        #[clap(long, requires("host"))]
+        port: Option<u16>,
+        /// Query a snapshot file generated by the snapshot
+        /// command instead of the store directory.
+        #[clap(long, conflicts_with("host"))]
+        snapshot: Option<String>,
+        #[clap(flatten)]
+        opts: dump::command::SqlOpts,
+    },
     /// Generate a shell completions file
     #[clap(hide = true)]
     GenerateCompletions {
@@ -690,6 +751,9 @@ fn real_main(init: init::InitToken) {
             ref disable_disk_stat,
             ref disable_exitstats,
             ref compress_opts,
+            ref metrics_port,
+            ref metrics_address,
+            ref metrics_top_processes,
         } => {
             logutil::set_current_log_target(logutil::TargetLog::Term);
             run(
@@ -713,6 +777,9 @@ fn real_main(init: init::InitToken) {
                         *disable_disk_stat,
                         *disable_exitstats,
                         compress_opts,
+                        *metrics_port,
+                        *metrics_address,
+                        *metrics_top_processes,
                     )
                 },
             )
@@ -723,12 +790,20 @@ fn real_main(init: init::InitToken) {
             ref port,
             ref yesterdays,
             ref snapshot,
+            ref view,
+            ref compare_host,
+            ref compare_port,
+            ref compare_time,
         } => {
             let time = time.clone();
             let host = host.clone();
             let port = port.clone();
             let days_adjuster = yesterdays.clone();
             let snapshot = snapshot.clone();
+            let view = view.clone();
+            let compare_host = compare_host.clone();
+            let compare_port = compare_port.clone();
+            let compare_time = compare_time.clone();
             run(
                 init,
                 debug,
@@ -745,6 +820,10 @@ fn real_main(init: init::InitToken) {
                         port,
                         days_adjuster,
                         snapshot,
+                        view,
+                        compare_host,
+                        compare_port,
+                        compare_time,
                     )
                 },
             )
@@ -858,6 +937,52 @@ fn real_main(init: init::InitToken) {
                 },
             )
         }
+        #[cfg(feature = "export")]
+        Command::Export {
+            ref host,
+            ref port,
+            ref snapshot,
+            ref opts,
+        } => {
+            let store_dir = below_config.store_dir.clone();
+            let host = host.clone();
+            let port = port.clone();
+            let snapshot = snapshot.clone();
+            let opts = opts.clone();
+            run(
+                init,
+                debug,
+                below_config,
+                Service::Off,
+                RedirectLogOnFail::Off,
+                |_, _below_config, logger, errs| {
+                    dump::export::run(logger, errs, store_dir, host, port, snapshot, opts)
+                },
+            )
+        }
+        #[cfg(feature = "sql")]
+        Command::Sql {
+            ref host,
+            ref port,
+            ref snapshot,
+            ref opts,
+        } => {
+            let store_dir = below_config.store_dir.clone();
+            let host = host.clone();
+            let port = port.clone();
+            let snapshot = snapshot.clone();
+            let opts = opts.clone();
+            run(
+                init,
+                debug,
+                below_config,
+                Service::Off,
+                RedirectLogOnFail::Off,
+                |_, _below_config, logger, errs| {
+                    dump::sql::run(logger, errs, store_dir, host, port, snapshot, opts)
+                },
+            )
+        }
         Command::GenerateCompletions {
             ref shell,
             ref output,
@@ -873,17 +998,38 @@ fn real_main(init: init::InitToken) {
 fn replay(
     logger: slog::Logger,
     errs: Receiver<Error>,
-    time: String,
+    time: Option<String>,
     below_config: &BelowConfig,
     host: Option<String>,
     port: Option<u16>,
     days_adjuster: Option<String>,
     snapshot: Option<String>,
+    saved_view: Option<String>,
+    compare_host: Option<String>,
+    compare_port: Option<u16>,
+    compare_time: Option<String>,
 ) -> Result<()> {
-    let timestamp =
-        cliutil::system_time_from_date_and_adjuster(time.as_str(), days_adjuster.as_deref())?;
+    let mut saved_view = saved_view
+        .map(|saved_view| view::saved_view::SavedView::load(&saved_view))
+        .transpose()?;
+    let timestamp = match (time, saved_view.as_mut()) {
+        (Some(time), saved_view) => {
+            // --time overrides the sample of the saved view
+            if let Some(saved_view) = saved_view {
+                saved_view.timestamp = None;
+            }
+            cliutil::system_time_from_date_and_adjuster(time.as_str(), days_adjuster.as_deref())?
+        }
+        (None, Some(view::saved_view::SavedView {
+            timestamp: Some(timestamp),
+            ..
+        })) => common::util::get_system_time(*timestamp),
+        (None, _) => bail!("The saved view has no timestamp, please provide one with --time"),
+    };
 
-    let mut advance = match (host, snapshot) {
+    // Kept to open the compare side from the same snapshot
+    let mut unpacked_snapshot = None;
+    let mut advance = match (host.clone(), snapshot) {
         (None, None) => {
             new_advance_local(logger.clone(), below_config.store_dir.clone(), timestamp)
         }
@@ -897,6 +1043,7 @@ fn replay(
             for path in fs::read_dir(&snapshot_dir)? {
                 snapshot_dir.push(path.unwrap().file_name());
             }
+            unpacked_snapshot = Some(snapshot_dir.clone());
             new_advance_local(logger.clone(), snapshot_dir, timestamp)
         }
         (Some(_), Some(_)) => {
@@ -920,10 +1067,58 @@ fn replay(
 
     cliutil::check_initial_sample_time_with_requested_time(model.timestamp, timestamp);
 
+    let compare = match (compare_host, compare_time) {
+        (None, None) => None,
+        (compare_host, compare_time) => {
+            let compare_timestamp = match compare_time {
+                Some(time) => cliutil::system_time_from_date_and_adjuster(
+                    time.as_str(),
+                    days_adjuster.as_deref(),
+                )?,
+                None => model.timestamp,
+            };
+            // The replayed host is compared with on the same port
+            let compare_remote = match &compare_host {
+                Some(compare_host) => Some((compare_host, compare_port)),
+                None => host.as_ref().map(|host| (host, port)),
+            };
+            let mut compare_advance = match (compare_remote, unpacked_snapshot) {
+                (Some((host, port)), _) => {
+                    new_advance_remote(logger.clone(), host.clone(), port, compare_timestamp)?
+                }
+                (None, Some(snapshot_dir)) => {
+                    new_advance_local(logger.clone(), snapshot_dir, compare_timestamp)
+                }
+                (None, None) => new_advance_local(
+                    logger.clone(),
+                    below_config.store_dir.clone(),
+                    compare_timestamp,
+                ),
+            };
+            compare_advance.initialize();
+            let compare_model = compare_advance
+                .jump_sample_to(compare_timestamp)
+                .ok_or_else(|| anyhow!("No sample to compare with could be found!"))?;
+            let label = compare_host.unwrap_or_else(|| compare_model.system.hostname.clone());
+            Some(view::compare::Compare::new(
+                label,
+                compare_advance,
+                compare_model,
+                model.timestamp,
+            ))
+        }
+    };
+
     let mut view = view::View::new_with_advance(
         model,
         view::ViewMode::Replay(Rc::new(RefCell::new(advance))),
     );
+    if let Some(saved_view) = saved_view {
+        view.set_saved_view(saved_view);
+    }
+    if let Some(compare) = compare {
+        view.set_compare(compare);
+    }
     logutil::set_current_log_target(logutil::TargetLog::File);
 
     let sink = view.cb_sink().clone();
@@ -960,6 +1155,9 @@ fn record(
     disable_disk_stat: bool,
     disable_exitstats: bool,
     compress_opts: &CompressOpts,
+    metrics_port: Option<u16>,
+    metrics_address: IpAddr,
+    metrics_top_processes: u32,
 ) -> Result<()> {
     debug!(logger, "Starting up!");
 
@@ -1019,6 +1217,12 @@ fn record(
         },
     );
 
+    let metrics = metrics_port
+        .map(|port| metrics::start_metrics_server(logger.clone(), metrics_address, port))
+        .transpose()?;
+    // The previous sample to calculate the Model served on /metrics
+    let mut last_sample: Option<(SystemTime, model::Sample)> = None;
+
     loop {
         if !disable_exitstats {
             // Anything that comes over the error channel is an error
@@ -1069,12 +1273,30 @@ fn record(
                 if below_config.enable_gpu_stats {
                     stats.report_nr_accelerators(&frame.sample);
                 }
+                if let Some(metrics) = metrics.as_ref() {
+                    if let Some((last_time, last)) = last_sample.as_ref() {
+                        let duration = post_collect_sys_time
+                            .duration_since(*last_time)
+                            .unwrap_or_default();
+                        let model = model::Model::new(
+                            post_collect_sys_time,
+                            &frame.sample,
+                            Some((last, duration)),
+                        );
+                        match dump::metrics::get_metrics(&model, metrics_top_processes) {
+                            Ok(text) => *metrics.lock().unwrap() = text,
+                            Err(e) => error!(logger, "{:#}", e.context("Failed to render metrics")),
+                        }
+                    }
+                    last_sample = Some((post_collect_sys_time, frame.sample));
+                }
             }
             Err(e) => {
                 // Handle cgroupfs errors
                 match e.downcast_ref::<cgroupfs::Error>() {
-                    // Unrecoverable error -- below only supports cgroup2
-                    Some(cgroupfs::Error::NotCgroup2(_)) => bail!(e),
+                    // Unrecoverable error -- neither cgroup2 nor any
+                    // cgroup1 hierarchy is mounted
+                    Some(cgroupfs::Error::NotCgroup1(_)) => bail!(e),
                     _ => {}
                 };
 
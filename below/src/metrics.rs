@@ -0,0 +1,164 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::net::IpAddr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use dump::metrics::METRICS_CONTENT_TYPE;
+use slog::warn;
+
+/// Time for a client to send its request and receive the response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Size limit of the request line and headers together.
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// Starts a thread serving the metrics in the returned buffer on /metrics.
+/// The buffer is expected to be updated by `record` with each new sample,
+/// and is served as is. An empty buffer means there are no metrics yet.
+/// Each connection is served on its own thread, so that a slow client does
+/// not hold up others.
+pub fn start_metrics_server(
+    logger: slog::Logger,
+    address: IpAddr,
+    port: u16,
+) -> Result<Arc<Mutex<String>>> {
+    let listener = TcpListener::bind((address, port)).with_context(|| {
+        format!(
+            "Failed to bind metrics server to address {} port {}",
+            address, port
+        )
+    })?;
+    let metrics = Arc::new(Mutex::new(String::new()));
+    let server_metrics = metrics.clone();
+
+    thread::Builder::new()
+        .name("metrics_server".to_owned())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!(logger, "Failed to accept metrics connection: {:#}", e);
+                        continue;
+                    }
+                };
+                let conn_logger = logger.clone();
+                let metrics = server_metrics.clone();
+                let res = thread::Builder::new()
+                    .name("metrics_conn".to_owned())
+                    .spawn(move || {
+                        if let Err(e) = handle_connection(stream, &metrics) {
+                            warn!(conn_logger, "Failed to serve metrics: {:#}", e);
+                        }
+                    });
+                if let Err(e) = res {
+                    warn!(logger, "Failed to spawn metrics connection thread: {:#}", e);
+                }
+            }
+        })
+        .context("Failed to spawn metrics server thread")?;
+
+    Ok(metrics)
+}
+
+/// Reads from a TcpStream until a deadline, after which reads fail. Unlike
+/// a read timeout, this bounds the total time of a client trickling bytes.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Request not received in time",
+            ));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Reads a single HTTP request and responds to it, closing the connection.
+fn handle_connection(stream: TcpStream, metrics: &Mutex<String>) -> Result<()> {
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    // Requests past the size limit are cut off and answered as they are
+    let mut reader = BufReader::new(
+        DeadlineReader {
+            stream: &stream,
+            deadline: Instant::now() + REQUEST_TIMEOUT,
+        }
+        .take(MAX_REQUEST_BYTES),
+    );
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers, we don't need any of them
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let mut tokens = request_line.split_whitespace();
+    let (status, content_type, body) = match (tokens.next(), tokens.next()) {
+        (Some("GET"), Some(path)) if path.split('?').next() == Some("/metrics") => {
+            let metrics = metrics.lock().unwrap().clone();
+            if metrics.is_empty() {
+                (
+                    "503 Service Unavailable",
+                    "text/plain",
+                    "No sample collected yet\n".to_owned(),
+                )
+            } else {
+                ("200 OK", METRICS_CONTENT_TYPE, metrics)
+            }
+        }
+        (Some("GET"), Some(_)) => ("404 Not Found", "text/plain", "Not found\n".to_owned()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n".to_owned(),
+        ),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+
+    Ok(())
+}
@@ -13,6 +13,9 @@
 // limitations under the License.
 
 use std::collections::BTreeMap;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpStream;
 use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
@@ -376,3 +379,30 @@ fn testing_fold_string() {
         "d/...aaaaa"
     );
 }
+
+#[test]
+fn metrics_server() {
+    let port = portpicker::pick_unused_port().expect("No free port");
+    let metrics =
+        crate::metrics::start_metrics_server(get_logger(), "127.0.0.1".parse().unwrap(), port)
+            .expect("Failed to start server");
+    let get = |path: &str| {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).expect("Failed to connect");
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path)
+            .expect("Failed to send request");
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .expect("Failed to read response");
+        response
+    };
+
+    assert!(get("/metrics").starts_with("HTTP/1.1 503"));
+    // A client that never finishes its request does not block the others
+    let _idle = TcpStream::connect(("127.0.0.1", port)).expect("Failed to connect");
+    *metrics.lock().unwrap() = "system_cpu_usage_pct{hostname=\"h\"} 12.5\n".to_owned();
+    let response = get("/metrics");
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("\r\n\r\nsystem_cpu_usage_pct{hostname=\"h\"} 12.5\n"));
+    assert!(get("/").starts_with("HTTP/1.1 404"));
+}
@@ -283,6 +283,42 @@ impl<FrameType, ModelType> Advance<FrameType, ModelType> {
         }
     }
 
+    /// Generate a Model from the current sample to the last sample at or
+    /// before timestamp, skipping the samples in between.
+    // Since a Model only looks at the two samples it is built from, rates
+    // are calculated over the whole time between them. If there is no
+    // sample up to timestamp (e.g. a gap in recording), we move on to the
+    // next available sample like advance(Forward) does. This always moves
+    // forward, regardless of the current direction.
+    pub fn advance_until(&mut self, timestamp: SystemTime) -> Option<ModelType> {
+        let (next_timestamp, next_sample) =
+            match self
+                .store
+                .extract_sample_and_log(timestamp, Direction::Reverse, &self.logger)
+            {
+                Some((next_timestamp, next_sample)) if next_timestamp > self.target_timestamp => {
+                    (next_timestamp, next_sample)
+                }
+                _ => self.store.extract_sample_and_log(
+                    self.target_timestamp + Duration::from_secs(1),
+                    Direction::Forward,
+                    &self.logger,
+                )?,
+            };
+
+        let sample_package = SamplePackage::<FrameType>::new(
+            self.cached_sample.take(),
+            self.target_timestamp,
+            next_sample,
+            next_timestamp,
+        );
+        let model = self.store.to_model(&sample_package);
+        self.current_direction = Direction::Forward;
+        self.cached_sample = Some(sample_package.newer_sample);
+        self.target_timestamp = next_timestamp;
+        model
+    }
+
     /// jump to the sample at timestamp.
     // We will always use forward jump to make sure we can get two samples before(at)
     // and after the timestamp. One exception here is the timestamp is in future, so
@@ -333,6 +369,156 @@ impl<FrameType, ModelType> Advance<FrameType, ModelType> {
         self.jump_sample_to(self.target_timestamp - gap)
     }
 
+    /// Calls f with the timestamp and Model of each sample at or before
+    /// timestamp, newest first, for as long as it returns true. This does
+    /// not move the current sample.
+    // We reuse the older sample of each package as the newer sample of the
+    // next one, so that each sample is only read once.
+    fn for_each_model_backward(
+        &mut self,
+        timestamp: SystemTime,
+        mut f: impl FnMut(SystemTime, ModelType) -> bool,
+    ) {
+        let mut newer =
+            self.store
+                .extract_sample_and_log(timestamp, Direction::Reverse, &self.logger);
+        while let Some((newer_timestamp, newer_sample)) = newer.take() {
+            let older = newer_timestamp
+                .checked_sub(Duration::from_secs(1))
+                .and_then(|timestamp| {
+                    self.store
+                        .extract_sample_and_log(timestamp, Direction::Reverse, &self.logger)
+                });
+            let (older_timestamp, older_sample) = match older {
+                Some((older_timestamp, older_sample)) => (older_timestamp, Some(older_sample)),
+                None => (newer_timestamp, None),
+            };
+            let sample_package = SamplePackage::<FrameType>::new(
+                older_sample,
+                older_timestamp,
+                newer_sample,
+                newer_timestamp,
+            );
+            if let Some(model) = self.store.to_model(&sample_package) {
+                if !f(newer_timestamp, model) {
+                    return;
+                }
+            }
+            newer = sample_package
+                .older_sample
+                .map(|older_sample| (older_timestamp, older_sample));
+        }
+    }
+
+    /// Generate the Models of up to `count` samples at or before timestamp,
+    /// oldest first. This does not move the current sample.
+    pub fn get_models_until(&mut self, timestamp: SystemTime, count: usize) -> Vec<ModelType> {
+        let mut models = Vec::new();
+        if count > 0 {
+            self.for_each_model_backward(timestamp, |_, model| {
+                models.push(model);
+                models.len() < count
+            });
+        }
+        models.reverse();
+        models
+    }
+
+    /// Apply f to the Models of the samples between start and end, and
+    /// return the results with the sample timestamps, oldest first. Unlike
+    /// get_models_until, this does not keep the Models around, so it works
+    /// for long time ranges. This does not move the current sample.
+    pub fn map_models_between<T>(
+        &mut self,
+        start: SystemTime,
+        end: SystemTime,
+        mut f: impl FnMut(ModelType) -> T,
+    ) -> Vec<(SystemTime, T)> {
+        let mut res = Vec::new();
+        self.for_each_model_backward(end, |timestamp, model| {
+            if timestamp < start {
+                return false;
+            }
+            res.push((timestamp, f(model)));
+            true
+        });
+        res.reverse();
+        res
+    }
+
+    /// Return the timestamps of the first and the last sample in the store.
+    /// This does not move the current sample.
+    pub fn get_sample_range(&mut self) -> Option<(SystemTime, SystemTime)> {
+        let (first, _) = self.store.extract_sample_and_log(
+            SystemTime::UNIX_EPOCH,
+            Direction::Forward,
+            &self.logger,
+        )?;
+        let (last, _) = self.store.extract_sample_and_log(
+            SystemTime::now(),
+            Direction::Reverse,
+            &self.logger,
+        )?;
+        Some((first, last))
+    }
+
+    /// Split the time between start and end into `buckets` equal intervals
+    /// and return whether each of them is covered by samples. An interval is
+    /// covered if it has a sample, or if it lies between two samples that are
+    /// at most max_gap apart. This does not move the current sample.
+    // We only look up the first sample of each interval, and skip the
+    // intervals before it, so this reads at most one sample per interval
+    // plus one per gap.
+    pub fn get_sample_coverage(
+        &mut self,
+        start: SystemTime,
+        end: SystemTime,
+        buckets: usize,
+        max_gap: Duration,
+    ) -> Vec<bool> {
+        let mut covered = vec![false; buckets];
+        let start = util::get_unix_timestamp(start);
+        let end = util::get_unix_timestamp(end);
+        if buckets == 0 || end < start {
+            return covered;
+        }
+        let span = end - start;
+        let bucket_of = |timestamp: u64| {
+            if span == 0 {
+                0
+            } else {
+                (((timestamp - start) as u128 * buckets as u128 / span as u128) as usize)
+                    .min(buckets - 1)
+            }
+        };
+
+        let mut prev: Option<(usize, u64)> = None;
+        let mut idx = 0;
+        while idx < buckets {
+            let bucket_start = start + (span as u128 * idx as u128 / buckets as u128) as u64;
+            let timestamp = match self.store.extract_sample_and_log(
+                util::get_system_time(bucket_start),
+                Direction::Forward,
+                &self.logger,
+            ) {
+                Some((timestamp, _)) if util::get_unix_timestamp(timestamp) <= end => {
+                    util::get_unix_timestamp(timestamp)
+                }
+                _ => break,
+            };
+            let bucket = bucket_of(timestamp);
+            covered[bucket] = true;
+            if let Some((prev_bucket, prev_timestamp)) = prev {
+                if timestamp - prev_timestamp <= max_gap.as_secs() {
+                    covered[prev_bucket..bucket].fill(true);
+                }
+            }
+            prev = Some((bucket, timestamp));
+            idx = bucket.max(idx) + 1;
+        }
+        covered
+    }
+
     // Convenience function will be used by dump and scuba dump
     pub fn get_next_ts(&self) -> SystemTime {
         // timestamp for initial advance if initialize didn't setup cached_sample
@@ -724,6 +910,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn advance_test_advance_until() {
+        // Samples: [3, 10, 20, 50]
+        let mut advance = get_advance_with_fake_store(3);
+        advance.initialize();
+
+        macro_rules! check_advance_until {
+            ($until:tt, $expected_cache:expr, $expected_sample:expr) => {
+                let res = advance.advance_until(util::get_system_time($until));
+                assert_eq!(res, $expected_sample);
+                assert_eq!(advance.current_direction, Direction::Forward);
+                assert_eq!(
+                    advance.target_timestamp,
+                    util::get_system_time($expected_cache)
+                );
+                assert_eq!(advance.cached_sample, Some($expected_cache));
+            };
+        }
+
+        // Skip 10 and take the rates over 3..20
+        check_advance_until!(
+            25,                            /*until*/
+            20,                            /*expected_cache*/
+            Some("3_20_20_17".to_owned())  /*old_new_ts_dur*/
+        );
+
+        // No sample up to 30, move on to the next one
+        check_advance_until!(
+            30,                             /*until*/
+            50,                             /*expected_cache*/
+            Some("20_50_50_30".to_owned())  /*old_new_ts_dur*/
+        );
+
+        // Nothing left
+        check_advance_until!(100 /*until*/, 50 /*expected_cache*/, None);
+
+        // Moves forward from the cached 10 after moving reverse
+        advance.advance(Direction::Reverse);
+        check_advance_until!(
+            60,                             /*until*/
+            50,                             /*expected_cache*/
+            Some("10_50_50_40".to_owned())  /*old_new_ts_dur*/
+        );
+    }
+
     #[test]
     fn advance_test_jump_sample_to() {
         // Samples: [3, 10, 20, 50]
@@ -801,6 +1032,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn advance_test_get_sample_coverage() {
+        // Samples: [3, 10, 20, 50]
+        let mut advance = get_advance_with_fake_store(3);
+        advance.initialize();
+
+        assert_eq!(
+            advance.get_sample_range(),
+            Some((util::get_system_time(3), util::get_system_time(50)))
+        );
+        // The samples fall into buckets 0, 1, 3 and 9, and 20 is more than
+        // 10 seconds away from 50
+        assert_eq!(
+            advance.get_sample_coverage(
+                util::get_system_time(3),
+                util::get_system_time(50),
+                10,
+                Duration::from_secs(10)
+            ),
+            vec![
+                true, true, true, true, false, false, false, false, false, true
+            ]
+        );
+        assert_eq!(
+            advance.get_sample_coverage(
+                util::get_system_time(3),
+                util::get_system_time(50),
+                10,
+                Duration::from_secs(30)
+            ),
+            vec![true; 10]
+        );
+        // No sample after 20 within the range
+        assert_eq!(
+            advance.get_sample_coverage(
+                util::get_system_time(15),
+                util::get_system_time(45),
+                3,
+                Duration::from_secs(30)
+            ),
+            vec![true, false, false]
+        );
+        assert!(
+            advance
+                .get_sample_coverage(
+                    util::get_system_time(50),
+                    util::get_system_time(3),
+                    10,
+                    Duration::from_secs(10)
+                )
+                .iter()
+                .all(|covered| !covered)
+        );
+
+        // The current sample is left as is
+        assert_eq!(advance.get_next_ts(), util::get_system_time(4));
+    }
+
     #[test]
     fn advance_test_get_next_ts() {
         // Samples: [3, 10, 20, 50]
@@ -812,4 +1101,48 @@ mod tests {
         advance.advance(Direction::Reverse);
         assert_eq!(advance.get_next_ts(), util::get_system_time(2));
     }
+
+    #[test]
+    fn advance_test_get_models() {
+        // Samples: [3, 10, 20, 50]
+        let mut advance = get_advance_with_fake_store(3);
+        advance.initialize();
+        advance.advance(Direction::Forward);
+
+        assert_eq!(
+            advance.get_models_until(util::get_system_time(30), 2),
+            vec!["3_10_10_7", "10_20_20_10"] /*old_new_ts_dur*/
+        );
+        // The first sample has no sample before it
+        assert_eq!(
+            advance.get_models_until(util::get_system_time(50), 10),
+            vec!["3_3", "3_10_10_7", "10_20_20_10", "20_50_50_30"]
+        );
+        assert!(
+            advance
+                .get_models_until(util::get_system_time(0), 10)
+                .is_empty()
+        );
+
+        assert_eq!(
+            advance.map_models_between(
+                util::get_system_time(10),
+                util::get_system_time(30),
+                |model| model.len()
+            ),
+            vec![
+                (util::get_system_time(10), "3_10_10_7".len()),
+                (util::get_system_time(20), "10_20_20_10".len()),
+            ]
+        );
+
+        // The current sample is left as is
+        assert_eq!(advance.get_next_ts(), util::get_system_time(11));
+        assert_eq!(
+            advance
+                .advance(Direction::Forward)
+                .expect("Failed to advance"),
+            "10_20_20_10"
+        );
+    }
 }
@@ -0,0 +1,449 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Ref;
+use std::cell::RefCell;
+use std::cell::RefMut;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use base_render::RenderConfig;
+use common::util::systemtime_to_datetime;
+use cursive::utils::markup::StyledString;
+use cursive::view::Nameable;
+use cursive::views::NamedView;
+use cursive::views::SelectView;
+use cursive::views::ViewRef;
+use cursive::Cursive;
+use model::sort_cgroup_diffs;
+use model::CgroupDiffModel;
+use model::CgroupDiffState;
+use model::FilterExpr;
+use model::Queriable;
+use model::SingleCgroupDiffModel;
+use model::SingleCgroupDiffModelFieldId;
+use model::SingleCgroupModelFieldId;
+
+use crate::cgroup_tabs::default_tabs;
+use crate::render::parse_filter_expr;
+use crate::render::ViewItem;
+use crate::stats_view::ColumnTitles;
+use crate::stats_view::StateCommon;
+use crate::stats_view::StatsView;
+use crate::stats_view::ViewBridge;
+use crate::viewrc::ViewRc;
+use crate::ViewState;
+
+pub type ViewType = StatsView<CgroupDiffView>;
+
+/// Renders corresponding Fields From CgroupDiffModel.
+type CgroupDiffViewItem = ViewItem<SingleCgroupDiffModelFieldId>;
+
+#[derive(Default)]
+pub struct CgroupDiffViewState {
+    pub filter_info: Option<(SingleCgroupDiffModelFieldId, String)>,
+    pub filter_expr: Option<FilterExpr<SingleCgroupDiffModelFieldId>>,
+    pub sort_order: Option<SingleCgroupDiffModelFieldId>,
+    pub sort_tags: HashMap<String, Vec<CgroupDiffViewItem>>,
+    pub reverse: bool,
+    pub model: Rc<RefCell<CgroupDiffModel>>,
+    // Time of the sample all other samples are compared to
+    pub base_timestamp: Option<SystemTime>,
+    // Host or store of the compare side in compare mode
+    pub base_source: Option<String>,
+}
+
+/// Shows the State column followed by the absolute and relative change of
+/// each column of a cgroup view tab.
+fn get_diff_items(items: Vec<ViewItem<SingleCgroupModelFieldId>>) -> Vec<CgroupDiffViewItem> {
+    std::iter::once(ViewItem::from_default(SingleCgroupDiffModelFieldId::State))
+        .chain(items.into_iter().flat_map(|item| {
+            [
+                ViewItem::from_default(SingleCgroupDiffModelFieldId::Delta(item.field_id.clone())),
+                ViewItem::from_default(SingleCgroupDiffModelFieldId::DeltaPct(item.field_id)),
+            ]
+        }))
+        .collect()
+}
+
+/// Shows the State column followed by the value on both sides and the
+/// change of each column of a cgroup view tab, for compare mode.
+fn get_paired_items(items: Vec<ViewItem<SingleCgroupModelFieldId>>) -> Vec<CgroupDiffViewItem> {
+    use base_render::RenderConfigBuilder as Rc;
+    std::iter::once(ViewItem::from_default(SingleCgroupDiffModelFieldId::State))
+        .chain(items.into_iter().flat_map(|item| {
+            let title = format!("Cmp {}", item.config.render_config.get_title());
+            [
+                ViewItem::from_default(SingleCgroupDiffModelFieldId::After(item.field_id.clone())),
+                ViewItem::from_default(SingleCgroupDiffModelFieldId::Before(item.field_id.clone()))
+                    .update(Rc::new().title(title)),
+                ViewItem::from_default(SingleCgroupDiffModelFieldId::Delta(item.field_id)),
+            ]
+        }))
+        .collect()
+}
+
+fn get_tabs(
+    get_items: fn(Vec<ViewItem<SingleCgroupModelFieldId>>) -> Vec<CgroupDiffViewItem>,
+) -> Vec<(&'static str, Vec<CgroupDiffViewItem>)> {
+    vec![
+        ("General", get_items(default_tabs::get_general_items())),
+        ("CPU", get_items(default_tabs::get_cpu_items())),
+        ("Mem", get_items(default_tabs::get_mem_items())),
+        ("I/O", get_items(default_tabs::get_io_items())),
+        ("Pressure", get_items(default_tabs::get_pressure_items())),
+    ]
+}
+
+impl StateCommon for CgroupDiffViewState {
+    type ModelType = CgroupDiffModel;
+    type TagType = SingleCgroupDiffModelFieldId;
+    type KeyType = String;
+
+    fn get_filter_info(&self) -> &Option<(Self::TagType, String)> {
+        &self.filter_info
+    }
+
+    fn is_filter_supported_from_tab_idx(&self, _tab: &str, idx: usize) -> bool {
+        // Same as cgroup view, only the name column is filtered by string
+        idx == 0
+    }
+
+    fn get_tag_from_tab_idx(&self, tab: &str, idx: usize) -> Self::TagType {
+        match idx {
+            0 => Self::TagType::Name,
+            _ => self
+                .sort_tags
+                .get(tab)
+                .unwrap_or_else(|| panic!("Fail to find tab: {}", tab))
+                .get(idx - 1)
+                .expect("Out of title scope")
+                .field_id
+                .to_owned(),
+        }
+    }
+
+    fn set_filter_from_tab_idx(&mut self, tab: &str, idx: usize, filter: Option<String>) -> bool {
+        if !self.is_filter_supported_from_tab_idx(tab, idx) {
+            return false;
+        }
+        if let Some(filter_text) = filter {
+            let title = self.get_tag_from_tab_idx(tab, idx);
+            self.filter_info = Some((title, filter_text));
+        } else {
+            self.filter_info = None;
+        }
+        true
+    }
+
+    fn set_filter_expr(&mut self, expr: Option<&str>) -> Result<(), String> {
+        self.filter_expr = expr.map(parse_filter_expr).transpose()?;
+        Ok(())
+    }
+
+    fn set_sort_tag(&mut self, sort_order: Self::TagType, reverse: &mut bool) -> bool {
+        let sort_order = Some(sort_order);
+        if self.sort_order == sort_order {
+            *reverse = !*reverse;
+        } else {
+            *reverse = true;
+            self.sort_order = sort_order;
+        }
+        self.reverse = *reverse;
+        true
+    }
+
+    fn set_sort_tag_from_tab_idx(&mut self, tab: &str, idx: usize, reverse: &mut bool) -> bool {
+        let sort_order = self.get_tag_from_tab_idx(tab, idx);
+        self.set_sort_tag(sort_order, reverse)
+    }
+
+    fn set_sort_string(&mut self, selection: &str, reverse: &mut bool) -> bool {
+        use std::str::FromStr;
+        match Self::TagType::from_str(selection) {
+            Ok(field_id) => self.set_sort_tag(field_id, reverse),
+            Err(_) => false,
+        }
+    }
+
+    fn get_model(&self) -> Ref<Self::ModelType> {
+        self.model.borrow()
+    }
+
+    fn get_model_mut(&self) -> RefMut<Self::ModelType> {
+        self.model.borrow_mut()
+    }
+
+    fn new(model: Rc<RefCell<Self::ModelType>>) -> Self {
+        Self {
+            filter_info: None,
+            filter_expr: None,
+            sort_order: None,
+            sort_tags: get_tabs(get_diff_items)
+                .into_iter()
+                .map(|(tab, items)| (tab.to_owned(), items))
+                .collect(),
+            reverse: false,
+            model,
+            base_timestamp: None,
+            base_source: None,
+        }
+    }
+}
+
+/// Returns the full paths of cgroups that match the filter, along with their
+/// ancestors and descendants.
+fn calculate_filtered_set(
+    cgroup: &CgroupDiffModel,
+    matches_filter: &dyn Fn(&SingleCgroupDiffModel) -> bool,
+) -> HashSet<String> {
+    fn insert_cgroup_and_descendents(set: &mut HashSet<String>, cgroup: &CgroupDiffModel) {
+        set.insert(cgroup.data.full_path.clone());
+        for child in &cgroup.children {
+            insert_cgroup_and_descendents(set, child)
+        }
+    }
+
+    fn should_keep(
+        cgroup: &CgroupDiffModel,
+        matches_filter: &dyn Fn(&SingleCgroupDiffModel) -> bool,
+        set: &mut HashSet<String>,
+    ) -> bool {
+        if matches_filter(&cgroup.data) {
+            insert_cgroup_and_descendents(set, cgroup);
+            return true;
+        }
+
+        let mut keep_cgroup = false;
+        for child in &cgroup.children {
+            if should_keep(child, matches_filter, set) {
+                keep_cgroup = true;
+            }
+        }
+
+        if keep_cgroup {
+            set.insert(cgroup.data.full_path.clone());
+        }
+        keep_cgroup
+    }
+    let mut set = HashSet::new();
+    should_keep(cgroup, matches_filter, &mut set);
+    set
+}
+
+/// Cgroup tree of the difference between the current sample and a base
+/// sample. Added cgroups are green and removed ones are red.
+pub struct CgroupDiffView {
+    view_items: Vec<CgroupDiffViewItem>,
+    cgroup_name: CgroupDiffViewItem,
+}
+
+impl CgroupDiffView {
+    fn new_tab(view_items: Vec<CgroupDiffViewItem>, cgroup_name_config: &RenderConfig) -> Self {
+        use base_render::RenderConfigBuilder as Rc;
+        use common::util::get_prefix;
+        Self {
+            view_items,
+            cgroup_name: ViewItem::from_default(SingleCgroupDiffModelFieldId::Name)
+                .update(cgroup_name_config.clone())
+                .update(Rc::new().indented_prefix(get_prefix(false))),
+        }
+    }
+
+    pub fn new(c: &mut Cursive, viewrc: &ViewRc) -> NamedView<ViewType> {
+        let list = SelectView::new();
+
+        let cgroup_name_config = RenderConfig {
+            width: viewrc.cgroup_name_width,
+            ..Default::default()
+        };
+        let mut tabs = Vec::new();
+        let mut tabs_map: HashMap<String, CgroupDiffView> = HashMap::new();
+        for (tab, items) in get_tabs(get_diff_items) {
+            tabs.push(tab.to_owned());
+            tabs_map.insert(tab.to_owned(), Self::new_tab(items, &cgroup_name_config));
+        }
+        let user_data = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive Object!");
+        StatsView::new(
+            "Cgroup Diff",
+            tabs,
+            tabs_map,
+            list,
+            CgroupDiffViewState::new(user_data.cgroup_diff.clone()),
+            user_data.event_controllers.clone(),
+            user_data.cmd_controllers.clone(),
+        )
+        .feed_data(c)
+        .with_name(Self::get_view_name())
+    }
+
+    pub fn get_cgroup_diff_view(c: &mut Cursive) -> ViewRef<ViewType> {
+        ViewType::get_view(c)
+    }
+
+    pub fn refresh(c: &mut Cursive) {
+        // The compare side may have moved since the last refresh
+        let base = crate::compare::get_active_base(c);
+        let mut view = Self::get_cgroup_diff_view(c);
+        if let Some((timestamp, source)) = base {
+            let mut state = view.state.borrow_mut();
+            state.base_timestamp = Some(timestamp);
+            state.base_source = Some(source);
+        }
+        view.refresh(c);
+    }
+
+    /// Shows the value on both sides next to the change of each column in
+    /// compare mode, or only the change otherwise.
+    pub fn set_compare_mode(c: &mut Cursive, compare: bool) {
+        let mut view = Self::get_cgroup_diff_view(c);
+        let get_items = if compare {
+            get_paired_items
+        } else {
+            get_diff_items
+        };
+        for (tab, items) in get_tabs(get_items) {
+            view.state
+                .borrow_mut()
+                .sort_tags
+                .insert(tab.to_owned(), items.clone());
+            view.update_tab_view(tab, |tab_view| tab_view.view_items = items);
+        }
+        if !compare {
+            view.state.borrow_mut().base_source = None;
+        }
+    }
+
+    fn get_line(&self, model: &SingleCgroupDiffModel, offset: Option<usize>) -> StyledString {
+        let mut line = self.cgroup_name.render_indented(model);
+        line.append_plain(" ");
+
+        for item in self.view_items.iter().skip(offset.unwrap_or(0)) {
+            line.append(item.render(model));
+            line.append_plain(" ");
+        }
+
+        let color = match model.state {
+            CgroupDiffState::Added => cursive::theme::BaseColor::Green,
+            CgroupDiffState::Removed => cursive::theme::BaseColor::Red,
+            CgroupDiffState::Common => return line,
+        };
+        StyledString::styled(line.source(), cursive::theme::Color::Light(color))
+    }
+}
+
+impl ViewBridge for CgroupDiffView {
+    type StateType = CgroupDiffViewState;
+
+    fn get_view_name() -> &'static str {
+        "cgroup_diff_view"
+    }
+
+    fn get_titles(&self) -> ColumnTitles {
+        ColumnTitles {
+            titles: std::iter::once(&self.cgroup_name)
+                .chain(self.view_items.iter())
+                .map(|item| item.config.render_title())
+                .collect(),
+            pinned_titles: 1,
+        }
+    }
+
+    fn get_rows(
+        &mut self,
+        state: &Self::StateType,
+        offset: Option<usize>,
+    ) -> Vec<(StyledString, String)> {
+        let model = state.get_model();
+        let filtered_set = if state.filter_info.is_some() || state.filter_expr.is_some() {
+            Some(calculate_filtered_set(&model, &|cgroup| {
+                state
+                    .filter_info
+                    .as_ref()
+                    .map_or(true, |(_, filter)| cgroup.name.contains(filter))
+                    && state
+                        .filter_expr
+                        .as_ref()
+                        .map_or(true, |expr| expr.eval(cgroup))
+            }))
+        } else {
+            None
+        };
+
+        let mut rows = Vec::new();
+        let mut cgroup_stack = vec![&*model];
+        while let Some(cgroup) = cgroup_stack.pop() {
+            if let Some(set) = &filtered_set {
+                if !set.contains(&cgroup.data.full_path) {
+                    continue;
+                }
+            }
+            rows.push((
+                self.get_line(&cgroup.data, offset),
+                cgroup.data.full_path.clone(),
+            ));
+
+            let mut children = Vec::from_iter(&cgroup.children);
+            if let Some(sort_order) = state.sort_order.as_ref() {
+                sort_cgroup_diffs(&mut children, sort_order, state.reverse);
+            }
+            // Push children in reverse order so the first one will be pop first
+            while let Some(child) = children.pop() {
+                cgroup_stack.push(child);
+            }
+        }
+        rows
+    }
+
+    fn on_select_update_cmd_palette(
+        view: &Self::StateType,
+        selected_key: &String,
+        current_tab: &str,
+        selected_column: usize,
+    ) -> String {
+        let tag = if selected_column == 0 {
+            SingleCgroupDiffModelFieldId::FullPath
+        } else {
+            view.get_tag_from_tab_idx(current_tab, selected_column)
+        };
+        let field_str = selected_key
+            .split('/')
+            // Ignore leading slash
+            .skip(1)
+            // Traverse cgroup diff tree to find matching model, or None
+            .try_fold(view.model.borrow(), |model, cgroup_name| {
+                Ref::filter_map(model, |model| model.children.get(cgroup_name)).ok()
+            })
+            .and_then(|model| model.data.query(&tag))
+            .map_or("?".to_string(), |field| field.to_string());
+        let base = view
+            .base_timestamp
+            .map_or("?".to_string(), systemtime_to_datetime);
+        let source = view
+            .base_source
+            .as_ref()
+            .map_or(String::new(), |source| format!("{} ", source));
+        format!(
+            " vs {}{} | {} : {} ",
+            source,
+            base,
+            tag.to_string(),
+            field_str
+        )
+    }
+}
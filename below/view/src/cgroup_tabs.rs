@@ -162,8 +162,21 @@ impl CgroupTab {
         state: &CgroupState,
         offset: Option<usize>,
     ) -> Vec<(StyledString, String)> {
-        let filtered_set = if let Some((field_id, filter)) = &state.filter_info {
-            Some(calculate_filtered_set(&state.get_model(), field_id, filter))
+        let filtered_set = if state.filter_info.is_some() || state.filter_expr.is_some() {
+            Some(calculate_filtered_set(&state.get_model(), &|cgroup| {
+                let match_filter = match &state.filter_info {
+                    Some((field_id, filter)) => match cgroup.query(field_id) {
+                        None => false,
+                        Some(value) => value.to_string().contains(filter),
+                    },
+                    None => true,
+                };
+                match_filter
+                    && state
+                        .filter_expr
+                        .as_ref()
+                        .map_or(true, |expr| expr.eval(cgroup))
+            }))
         } else {
             None
         };
@@ -173,27 +186,15 @@ impl CgroupTab {
     }
 }
 
-/// Returns a set of full cgroup paths that should be filtered by the filter string.
+/// Returns a set of full cgroup paths of cgroups that match the filter.
 ///
 /// Note that this algorithm recursively whitelists parents of cgroups that are
 /// whitelisted. The reason for this is because cgroups are inherently tree-like
 /// and displaying a lone cgroup without its ancestors doesn't make much sense.
 pub fn calculate_filtered_set(
     cgroup: &CgroupModel,
-    field_id: &SingleCgroupModelFieldId,
-    filter: &str,
+    matches_filter: &dyn Fn(&SingleCgroupModel) -> bool,
 ) -> HashSet<String> {
-    fn field_val_matches_filter(
-        cgroup: &CgroupModel,
-        field_id: &SingleCgroupModelFieldId,
-        filter: &str,
-    ) -> bool {
-        match cgroup.data.query(field_id) {
-            None => false,
-            Some(value) => value.to_string().contains(filter),
-        }
-    }
-
     // insert all descendents of cgroup into set
     fn insert_cgroup_and_descendents(set: &mut HashSet<String>, cgroup: &CgroupModel) {
         set.insert(cgroup.data.full_path.clone());
@@ -204,11 +205,10 @@ pub fn calculate_filtered_set(
 
     fn should_keep(
         cgroup: &CgroupModel,
-        field_id: &SingleCgroupModelFieldId,
-        filter: &str,
+        matches_filter: &dyn Fn(&SingleCgroupModel) -> bool,
         set: &mut HashSet<String>,
     ) -> bool {
-        let match_filter = field_val_matches_filter(cgroup, field_id, filter);
+        let match_filter = matches_filter(&cgroup.data);
         if match_filter {
             insert_cgroup_and_descendents(set, cgroup);
             return match_filter;
@@ -217,7 +217,7 @@ pub fn calculate_filtered_set(
         let mut keep_cgroup = false;
         for child in &cgroup.children {
             // keep children that match filter and children of cgroups that match filter
-            if should_keep(child, field_id, filter, set) {
+            if should_keep(child, matches_filter, set) {
                 // keep parent cgroup if child isn't filtered out
                 keep_cgroup = true;
             }
@@ -229,7 +229,7 @@ pub fn calculate_filtered_set(
         keep_cgroup
     }
     let mut set = HashSet::new();
-    should_keep(cgroup, field_id, filter, &mut set);
+    should_keep(cgroup, matches_filter, &mut set);
     set
 }
 
@@ -241,6 +241,14 @@ pub mod default_tabs {
     use model::CgroupCpuModelFieldId::ThrottledPct;
     use model::CgroupCpuModelFieldId::UsagePct;
     use model::CgroupCpuModelFieldId::UserPct;
+    use model::CgroupExitedModelFieldId::Count;
+    use model::CgroupExitedModelFieldId::CpuSystemPct;
+    use model::CgroupExitedModelFieldId::CpuUsagePct;
+    use model::CgroupExitedModelFieldId::CpuUserPct;
+    use model::CgroupExitedModelFieldId::IoRbytesPerSec;
+    use model::CgroupExitedModelFieldId::IoWbytesPerSec;
+    use model::CgroupExitedModelFieldId::MajorfaultsPerSec;
+    use model::CgroupExitedModelFieldId::MinorfaultsPerSec;
     use model::CgroupIoModelFieldId::CostIndebtPct;
     use model::CgroupIoModelFieldId::CostIndelayPct;
     use model::CgroupIoModelFieldId::CostUsagePct;
@@ -265,10 +273,12 @@ pub mod default_tabs {
     use model::CgroupMemoryModelFieldId::FileDirty;
     use model::CgroupMemoryModelFieldId::FileMapped;
     use model::CgroupMemoryModelFieldId::FileWriteback;
+    use model::CgroupMemoryModelFieldId::Hugetlb;
     use model::CgroupMemoryModelFieldId::InactiveAnon;
     use model::CgroupMemoryModelFieldId::InactiveFile;
     use model::CgroupMemoryModelFieldId::Kernel;
     use model::CgroupMemoryModelFieldId::KernelStack;
+    use model::CgroupMemoryModelFieldId::Percpu;
     use model::CgroupMemoryModelFieldId::Pgactivate;
     use model::CgroupMemoryModelFieldId::Pgdeactivate;
     use model::CgroupMemoryModelFieldId::Pgfault;
@@ -277,7 +287,11 @@ pub mod default_tabs {
     use model::CgroupMemoryModelFieldId::Pgmajfault;
     use model::CgroupMemoryModelFieldId::Pgrefill;
     use model::CgroupMemoryModelFieldId::Pgscan;
+    use model::CgroupMemoryModelFieldId::PgscanDirect;
+    use model::CgroupMemoryModelFieldId::PgscanKswapd;
     use model::CgroupMemoryModelFieldId::Pgsteal;
+    use model::CgroupMemoryModelFieldId::PgstealKhugepaged;
+    use model::CgroupMemoryModelFieldId::SecPagetables;
     use model::CgroupMemoryModelFieldId::Shmem;
     use model::CgroupMemoryModelFieldId::Slab;
     use model::CgroupMemoryModelFieldId::SlabReclaimable;
@@ -286,6 +300,7 @@ pub mod default_tabs {
     use model::CgroupMemoryModelFieldId::Swap;
     use model::CgroupMemoryModelFieldId::ThpCollapseAlloc;
     use model::CgroupMemoryModelFieldId::ThpFaultAlloc;
+    use model::CgroupMemoryModelFieldId::ThpSwpout;
     use model::CgroupMemoryModelFieldId::Total;
     use model::CgroupMemoryModelFieldId::Unevictable;
     use model::CgroupMemoryModelFieldId::WorkingsetActivateAnon;
@@ -297,6 +312,9 @@ pub mod default_tabs {
     use model::CgroupMemoryModelFieldId::WorkingsetRestoreFile;
     use model::CgroupMemoryModelFieldId::Zswap;
     use model::CgroupMemoryModelFieldId::Zswapped;
+    use model::CgroupMemoryModelFieldId::Zswpin;
+    use model::CgroupMemoryModelFieldId::Zswpout;
+    use model::CgroupPidsModelFieldId::NrProcs;
     use model::CgroupPidsModelFieldId::TidsCurrent;
     use model::CgroupPressureModelFieldId::CpuFullPct;
     use model::CgroupPressureModelFieldId::CpuSomePct;
@@ -321,6 +339,7 @@ pub mod default_tabs {
     use model::CgroupStatModelFieldId::NrDyingDescendants;
     use model::SingleCgroupModelFieldId::CgroupStat;
     use model::SingleCgroupModelFieldId::Cpu;
+    use model::SingleCgroupModelFieldId::Exited;
     use model::SingleCgroupModelFieldId::Io;
     use model::SingleCgroupModelFieldId::Mem;
     use model::SingleCgroupModelFieldId::Pids;
@@ -329,6 +348,19 @@ pub mod default_tabs {
 
     use super::*;
 
+    /// The tabs of the cgroup view with their default columns.
+    pub fn get_tabs() -> Vec<(String, Vec<ViewItem<SingleCgroupModelFieldId>>)> {
+        vec![
+            ("General".into(), get_general_items()),
+            ("CPU".into(), get_cpu_items()),
+            ("Mem".into(), get_mem_items()),
+            ("I/O".into(), get_io_items()),
+            ("Pressure".into(), get_pressure_items()),
+            ("Exited".into(), get_exited_items()),
+            ("Properties".into(), get_properties_items()),
+        ]
+    }
+
     pub fn get_general_items() -> Vec<ViewItem<SingleCgroupModelFieldId>> {
         vec![
             ViewItem::from_default(Cpu(UsagePct)).update(Rc::new().title("CPU")),
@@ -342,6 +374,7 @@ pub mod default_tabs {
             ViewItem::from_default(CgroupStat(NrDescendants)),
             ViewItem::from_default(CgroupStat(NrDyingDescendants)),
             ViewItem::from_default(Pids(TidsCurrent)),
+            ViewItem::from_default(Pids(NrProcs)),
         ]
     }
 
@@ -398,6 +431,15 @@ pub mod default_tabs {
             ViewItem::from_default(Mem(Pglazyfreed)),
             ViewItem::from_default(Mem(ThpFaultAlloc)),
             ViewItem::from_default(Mem(ThpCollapseAlloc)),
+            ViewItem::from_default(Mem(PgscanKswapd)),
+            ViewItem::from_default(Mem(PgscanDirect)),
+            ViewItem::from_default(Mem(PgstealKhugepaged)),
+            ViewItem::from_default(Mem(ThpSwpout)),
+            ViewItem::from_default(Mem(Zswpin)),
+            ViewItem::from_default(Mem(Zswpout)),
+            ViewItem::from_default(Mem(Percpu)),
+            ViewItem::from_default(Mem(SecPagetables)),
+            ViewItem::from_default(Mem(Hugetlb)),
             ViewItem::from_default(Mem(EventsLow)),
             ViewItem::from_default(Mem(EventsHigh)),
             ViewItem::from_default(Mem(EventsMax)),
@@ -433,6 +475,19 @@ pub mod default_tabs {
         ]
     }
 
+    pub fn get_exited_items() -> Vec<ViewItem<SingleCgroupModelFieldId>> {
+        vec![
+            ViewItem::from_default(Exited(Count)),
+            ViewItem::from_default(Exited(CpuUsagePct)),
+            ViewItem::from_default(Exited(CpuUserPct)),
+            ViewItem::from_default(Exited(CpuSystemPct)),
+            ViewItem::from_default(Exited(IoRbytesPerSec)),
+            ViewItem::from_default(Exited(IoWbytesPerSec)),
+            ViewItem::from_default(Exited(MinorfaultsPerSec)),
+            ViewItem::from_default(Exited(MajorfaultsPerSec)),
+        ]
+    }
+
     pub fn get_properties_items() -> Vec<ViewItem<SingleCgroupModelFieldId>> {
         vec![
             ViewItem::from_default(Props(MemoryMin)),
@@ -29,12 +29,20 @@ use model::CgroupCpuModelFieldId;
 use model::CgroupIoModelFieldId;
 use model::CgroupMemoryModelFieldId;
 use model::CgroupModel;
+use model::CgroupModelFieldId;
+use model::Field;
+use model::FilterExpr;
+use model::Model;
+use model::ModelFieldId;
 use model::Queriable;
 use model::SingleCgroupModelFieldId;
 
 use crate::cgroup_tabs::default_tabs;
 use crate::cgroup_tabs::CgroupTab;
+use crate::render::parse_filter_expr;
 use crate::render::ViewItem;
+use crate::saved_view::parse_field;
+use crate::saved_view::SavedView;
 use crate::stats_view::ColumnTitles;
 use crate::stats_view::StateCommon;
 use crate::stats_view::StatsView;
@@ -55,6 +63,7 @@ pub struct CgroupState {
     // moved to the cgroup
     pub cgroup_to_focus: Option<String>,
     pub filter_info: Option<(SingleCgroupModelFieldId, String)>,
+    pub filter_expr: Option<FilterExpr<SingleCgroupModelFieldId>>,
     pub sort_order: Option<SingleCgroupModelFieldId>,
     pub sort_tags: HashMap<String, Vec<ViewItem<SingleCgroupModelFieldId>>>,
     pub reverse: bool,
@@ -106,6 +115,11 @@ impl StateCommon for CgroupState {
         true
     }
 
+    fn set_filter_expr(&mut self, expr: Option<&str>) -> Result<(), String> {
+        self.filter_expr = expr.map(parse_filter_expr).transpose()?;
+        Ok(())
+    }
+
     fn set_sort_tag(&mut self, sort_order: Self::TagType, reverse: &mut bool) -> bool {
         let sort_order = Some(sort_order);
         if self.sort_order == sort_order {
@@ -131,6 +145,33 @@ impl StateCommon for CgroupState {
         }
     }
 
+    fn save_view(&self, saved: &mut SavedView) {
+        saved.sort = self.sort_order.as_ref().map(ToString::to_string);
+        saved.reverse = self.reverse;
+        if let Some((field, filter)) = &self.filter_info {
+            saved.filter_field = Some(field.to_string());
+            saved.filter = Some(filter.clone());
+        }
+        let mut collapsed_cgroups = self
+            .collapsed_cgroups
+            .borrow()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        collapsed_cgroups.sort();
+        saved.collapsed_cgroups = collapsed_cgroups;
+    }
+
+    fn restore_view(&mut self, saved: &SavedView) -> Result<(), String> {
+        self.sort_order = parse_field(saved.sort.as_deref())?;
+        self.reverse = saved.reverse;
+        self.filter_info = parse_field(saved.filter_field.as_deref())?.zip(saved.filter.clone());
+        self.collapse_all_top_level_cgroup = false;
+        self.collapsed_cgroups
+            .replace(saved.collapsed_cgroups.iter().cloned().collect());
+        Ok(())
+    }
+
     fn get_model(&self) -> Ref<Self::ModelType> {
         self.model.borrow()
     }
@@ -140,20 +181,14 @@ impl StateCommon for CgroupState {
     }
 
     fn new(model: Rc<RefCell<Self::ModelType>>) -> Self {
-        let mut sort_tags = HashMap::new();
-        sort_tags.insert("General".into(), default_tabs::get_general_items());
-        sort_tags.insert("CPU".into(), default_tabs::get_cpu_items());
-        sort_tags.insert("Mem".into(), default_tabs::get_mem_items());
-        sort_tags.insert("I/O".into(), default_tabs::get_io_items());
-        sort_tags.insert("Pressure".into(), default_tabs::get_pressure_items());
-        sort_tags.insert("Properties".into(), default_tabs::get_properties_items());
         Self {
             collapsed_cgroups: Rc::new(RefCell::new(HashSet::new())),
             current_selected_cgroup: "<root>".into(),
             cgroup_to_focus: None,
             filter_info: None,
+            filter_expr: None,
             sort_order: None,
-            sort_tags,
+            sort_tags: default_tabs::get_tabs().into_iter().collect(),
             reverse: false,
             model,
             collapse_all_top_level_cgroup: false,
@@ -192,6 +227,34 @@ impl CgroupState {
         self.uncollapse_cgroup(cgroup.as_str());
         self.cgroup_to_focus = Some(cgroup);
     }
+
+    /// Pids of all processes in the subtree of the selected cgroup, as listed
+    /// in cgroup.procs. None if cgroup.procs was not collected for the
+    /// selected cgroup.
+    pub fn get_procs_for_selected_cgroup(&self) -> Option<Vec<i32>> {
+        let model = self.get_model();
+        let mut cgroup = &*model;
+        for part in self
+            .current_selected_cgroup
+            .split('/')
+            .filter(|part| !part.is_empty())
+        {
+            cgroup = cgroup.children.get(part)?;
+        }
+        if cgroup.data.procs.is_none() {
+            return None;
+        }
+
+        let mut procs = Vec::new();
+        let mut cgroup_stack = vec![cgroup];
+        while let Some(cgroup) = cgroup_stack.pop() {
+            if let Some(cgroup_procs) = &cgroup.data.procs {
+                procs.extend(cgroup_procs.iter().map(|pid| *pid as i32));
+            }
+            cgroup_stack.extend(cgroup.children.iter());
+        }
+        Some(procs)
+    }
 }
 
 // TODO: Make CgroupView a collection of CgroupTab
@@ -245,55 +308,25 @@ impl CgroupView {
             width: viewrc.cgroup_name_width,
             ..Default::default()
         };
-        let tabs = vec![
-            "General".into(),
-            "CPU".into(),
-            "Mem".into(),
-            "I/O".into(),
-            "Pressure".into(),
-            "Properties".into(),
-        ];
+        let tab_items = viewrc
+            .get_cgroup_tabs()
+            .unwrap_or_else(|_| default_tabs::get_tabs());
+        let mut tabs = Vec::new();
         let mut tabs_map: HashMap<String, CgroupView> = HashMap::new();
-        tabs_map.insert(
-            "General".into(),
-            CgroupView {
-                tab: CgroupTab::new(default_tabs::get_general_items(), &cgroup_name_config),
-            },
-        );
-        tabs_map.insert(
-            "CPU".into(),
-            CgroupView {
-                tab: CgroupTab::new(default_tabs::get_cpu_items(), &cgroup_name_config),
-            },
-        );
-        tabs_map.insert(
-            "Mem".into(),
-            CgroupView {
-                tab: CgroupTab::new(default_tabs::get_mem_items(), &cgroup_name_config),
-            },
-        );
-        tabs_map.insert(
-            "I/O".into(),
-            CgroupView {
-                tab: CgroupTab::new(default_tabs::get_io_items(), &cgroup_name_config),
-            },
-        );
-        tabs_map.insert(
-            "Pressure".into(),
-            CgroupView {
-                tab: CgroupTab::new(default_tabs::get_pressure_items(), &cgroup_name_config),
-            },
-        );
-        tabs_map.insert(
-            "Properties".into(),
-            CgroupView {
-                tab: CgroupTab::new(default_tabs::get_properties_items(), &cgroup_name_config),
-            },
-        );
+        for (tab, items) in &tab_items {
+            tabs.push(tab.clone());
+            tabs_map.insert(
+                tab.clone(),
+                CgroupView {
+                    tab: CgroupTab::new(items.clone(), &cgroup_name_config),
+                },
+            );
+        }
         let user_data = c
             .user_data::<ViewState>()
             .expect("No data stored in Cursive Object!");
         let mut cgroup_state = CgroupState::new(user_data.cgroup.clone());
+        cgroup_state.sort_tags = tab_items.into_iter().collect();
         if viewrc.collapse_cgroups == Some(true) {
             cgroup_state.collapse_all_top_level_cgroup = true;
         }
@@ -344,6 +377,21 @@ impl CgroupView {
         ViewType::get_view(c)
     }
 
+    /// Replaces the columns of the tab.
+    pub fn set_tab_columns(
+        c: &mut Cursive,
+        tab: &str,
+        items: Vec<ViewItem<SingleCgroupModelFieldId>>,
+    ) {
+        let mut view = Self::get_cgroup_view(c);
+        view.state
+            .borrow_mut()
+            .sort_tags
+            .insert(tab.to_owned(), items.clone());
+        view.update_tab_view(tab, |cgroup_view| cgroup_view.tab.view_items = items);
+        view.refresh(c);
+    }
+
     pub fn refresh(c: &mut Cursive) {
         let mut view = Self::get_cgroup_view(c);
         let cgroup_to_focus = view.state.borrow_mut().cgroup_to_focus.take();
@@ -407,4 +455,33 @@ impl ViewBridge for CgroupView {
             .map_or("?".to_string(), |field| field.to_string());
         format!(" {} : {} ", tag.to_string(), field_str)
     }
+
+    fn get_history_field(
+        state: &Self::StateType,
+        model: &Model,
+        selected_key: &String,
+        current_tab: &str,
+        selected_column: usize,
+    ) -> Option<Field> {
+        model.cgroup.query(&CgroupModelFieldId {
+            // Ignore leading slash
+            path: Some(selected_key.split('/').skip(1).map(str::to_owned).collect()),
+            subquery_id: state.get_tag_from_tab_idx(current_tab, selected_column),
+        })
+    }
+
+    fn get_model_field_id(
+        _state: &Self::StateType,
+        selected_key: &String,
+        _current_tab: &str,
+        field: &str,
+    ) -> Option<ModelFieldId> {
+        use std::str::FromStr;
+
+        Some(ModelFieldId::Cgroup(CgroupModelFieldId {
+            // Ignore leading slash
+            path: Some(selected_key.split('/').skip(1).map(str::to_owned).collect()),
+            subquery_id: SingleCgroupModelFieldId::from_str(field).ok()?,
+        }))
+    }
 }
@@ -0,0 +1,399 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The chart view plots chosen fields over a time window from the store in
+//! a full screen layer. Moving its cursor moves the viewed sample in pause
+//! and replay mode.
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use base_render::HasRenderConfig;
+use base_render::RenderConfig;
+use common::util::systemtime_to_datetime;
+use cursive::event::Event;
+use cursive::event::EventResult;
+use cursive::event::EventTrigger;
+use cursive::event::Key;
+use cursive::theme::BaseColor;
+use cursive::theme::Color;
+use cursive::theme::Effect;
+use cursive::vec::Vec2;
+use cursive::views::OnEventView;
+use cursive::views::ResizedView;
+use cursive::Cursive;
+use cursive::Printer;
+use cursive::View;
+use model::Field;
+use model::Model;
+use model::ModelFieldId;
+use model::Queriable;
+
+use crate::ViewMode;
+use crate::ViewState;
+
+/// Time window charted when none is given
+pub const DEFAULT_CHART_WINDOW: Duration = Duration::from_secs(10 * 60);
+/// Maximum number of fields charted at once
+pub const MAX_CHART_FIELDS: usize = 4;
+
+const SERIES_COLORS: [BaseColor; MAX_CHART_FIELDS] = [
+    BaseColor::Green,
+    BaseColor::Cyan,
+    BaseColor::Yellow,
+    BaseColor::Magenta,
+];
+
+/// Values of one field over the charted samples
+struct ChartSeries {
+    title: String,
+    render_config: RenderConfig,
+    fields: Vec<Option<Field>>,
+    values: Vec<Option<f64>>,
+}
+
+impl ChartSeries {
+    fn render(&self, field: Option<&Field>) -> String {
+        self.render_config.render(field.cloned(), false)
+    }
+
+    /// Returns the fields with the lowest and highest numeric values.
+    fn min_max(&self) -> Option<(&Field, &Field, f64, f64)> {
+        let numeric = || {
+            self.values
+                .iter()
+                .zip(self.fields.iter())
+                .filter_map(|(value, field)| Some(((*value)?, field.as_ref()?)))
+        };
+        let (min, min_field) = numeric().min_by(|a, b| a.0.total_cmp(&b.0))?;
+        let (max, max_field) = numeric().max_by(|a, b| a.0.total_cmp(&b.0))?;
+        Some((min_field, max_field, min, max))
+    }
+}
+
+/// Full screen chart of fields over the samples of a time window
+pub struct ChartView {
+    window: Duration,
+    timestamps: Vec<SystemTime>,
+    series: Vec<ChartSeries>,
+    /// Index of the sample under the cursor
+    cursor: usize,
+}
+
+impl ChartView {
+    /// Creates a chart of the given fields over the samples of the window
+    /// ending at the current sample. The cursor starts at the last sample.
+    pub fn new(c: &mut Cursive, window: Duration, field_ids: Vec<ModelFieldId>) -> Self {
+        let view_state = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!");
+        let end = view_state.timestamp;
+        let advance = match &view_state.mode {
+            ViewMode::Live(adv) | ViewMode::Pause(adv) | ViewMode::Replay(adv) => adv.clone(),
+        };
+        let start = end.checked_sub(window).unwrap_or(SystemTime::UNIX_EPOCH);
+        let samples = advance
+            .borrow_mut()
+            .map_models_between(start, end, |model| {
+                field_ids
+                    .iter()
+                    .map(|field_id| model.query(field_id))
+                    .collect::<Vec<_>>()
+            });
+
+        let series = field_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, field_id)| {
+                let fields: Vec<Option<Field>> = samples
+                    .iter()
+                    .map(|(_, fields)| fields[idx].clone())
+                    .collect();
+                ChartSeries {
+                    title: field_id.to_string(),
+                    render_config: Model::get_render_config(field_id),
+                    values: fields
+                        .iter()
+                        .map(|field| field.clone().and_then(model::numeric_value))
+                        .collect(),
+                    fields,
+                }
+            })
+            .collect();
+
+        let timestamps: Vec<SystemTime> = samples.iter().map(|(timestamp, _)| *timestamp).collect();
+        Self {
+            window,
+            cursor: timestamps.len().saturating_sub(1),
+            timestamps,
+            series,
+        }
+    }
+
+    /// Column of the given sample in a plot of the given width
+    fn column_of(&self, sample: usize, width: usize) -> usize {
+        if self.timestamps.len() <= 1 {
+            return width.saturating_sub(1);
+        }
+        sample * (width - 1) / (self.timestamps.len() - 1)
+    }
+
+    fn draw_header(&self, printer: &Printer) {
+        let cursor_time = self
+            .timestamps
+            .get(self.cursor)
+            .map_or("-".to_owned(), |t| systemtime_to_datetime(*t));
+        printer.with_effect(Effect::Bold, |printer| {
+            printer.print(
+                (0, 0),
+                &format!(
+                    "Chart of the last {} ({} samples), cursor at {}",
+                    humantime::format_duration(self.window),
+                    self.timestamps.len(),
+                    cursor_time
+                ),
+            );
+        });
+        printer.print(
+            (0, 1),
+            "<Left>/<Right>/<Home>/<End>: move cursor and viewed sample, q: close",
+        );
+    }
+
+    /// Draws one series as a title line followed by its plot area.
+    fn draw_series(
+        &self,
+        printer: &Printer,
+        series: &ChartSeries,
+        color: BaseColor,
+        top: usize,
+        height: usize,
+        label_width: usize,
+    ) {
+        printer.with_color(Color::Light(color).into(), |printer| {
+            printer.print(
+                (0, top),
+                &format!(
+                    "{}: {}",
+                    series.title,
+                    series
+                        .render(series.fields.get(self.cursor).and_then(Option::as_ref))
+                        .trim()
+                ),
+            );
+        });
+
+        let plot_height = height.saturating_sub(1);
+        let plot_width = printer.size.x.saturating_sub(label_width + 1);
+        if plot_height == 0 || plot_width == 0 {
+            return;
+        }
+        let plot_top = top + 1;
+        let min_max = series.min_max();
+        if let Some((min_field, max_field, _, _)) = min_max {
+            printer.print((0, plot_top), series.render(Some(max_field)).trim());
+            printer.print(
+                (0, plot_top + plot_height - 1),
+                series.render(Some(min_field)).trim(),
+            );
+        }
+        printer.print_vline((label_width, plot_top), plot_height, "│");
+
+        let mut grid = vec![vec![' '; plot_width]; plot_height];
+        if let Some((_, _, min, max)) = min_max {
+            let row_of = |v: f64| {
+                let level = if max > min {
+                    ((v - min) / (max - min) * (plot_height - 1) as f64).round() as usize
+                } else {
+                    0
+                };
+                plot_height - 1 - level
+            };
+            let mut prev_row = None;
+            for (idx, value) in series.values.iter().enumerate() {
+                let row = match value {
+                    Some(v) => row_of(*v),
+                    None => {
+                        prev_row = None;
+                        continue;
+                    }
+                };
+                let col = self.column_of(idx, plot_width);
+                // Connect to the previous point so steep changes stay visible
+                if let Some(prev_row) = prev_row {
+                    for connector in grid
+                        .iter_mut()
+                        .take(row.max(prev_row))
+                        .skip(row.min(prev_row) + 1)
+                    {
+                        connector[col] = '│';
+                    }
+                }
+                grid[row][col] = '•';
+                prev_row = Some(row);
+            }
+        }
+
+        let cursor_col = self.column_of(self.cursor, plot_width);
+        let printer = printer.offset((label_width + 1, plot_top));
+        for (row, line) in grid.iter().enumerate() {
+            printer.with_color(Color::Light(color).into(), |printer| {
+                printer.print((0, row), &line.iter().collect::<String>());
+            });
+            printer.with_effect(Effect::Reverse, |printer| {
+                printer.print((cursor_col, row), &line[cursor_col].to_string());
+            });
+        }
+    }
+
+    fn draw_time_axis(&self, printer: &Printer, row: usize, label_width: usize) {
+        let plot_width = printer.size.x.saturating_sub(label_width + 1);
+        let (first, last) = match (self.timestamps.first(), self.timestamps.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return,
+        };
+        let printer = printer.offset((label_width + 1, row));
+        printer.print_hline((0, 0), plot_width, "─");
+        let start = systemtime_to_datetime(first);
+        let end = systemtime_to_datetime(last);
+        printer.print((0, 1), &start);
+        printer.print((plot_width.saturating_sub(end.len()), 1), &end);
+        let middle = self.timestamps[self.timestamps.len() / 2];
+        let middle_col = self.column_of(self.timestamps.len() / 2, plot_width);
+        let middle = systemtime_to_datetime(middle);
+        // Only show the middle time if it fits between the other two
+        if middle_col > start.len() + middle.len() / 2 + 1
+            && middle_col + middle.len() / 2 + 1 < plot_width.saturating_sub(end.len())
+        {
+            printer.print((middle_col - middle.len() / 2, 1), &middle);
+        }
+    }
+
+    /// Moves the cursor to the given sample and, when not live, the
+    /// viewed sample along with it.
+    fn move_cursor(&mut self, cursor: usize) -> EventResult {
+        if self.timestamps.is_empty() {
+            return EventResult::Consumed(None);
+        }
+        self.cursor = cursor.min(self.timestamps.len() - 1);
+        let timestamp = self.timestamps[self.cursor];
+        EventResult::with_cb(move |c| {
+            let mode = c
+                .user_data::<ViewState>()
+                .expect("No data stored in Cursive object!")
+                .mode
+                .clone();
+            match mode {
+                ViewMode::Pause(adv) | ViewMode::Replay(adv) => {
+                    let model = adv.borrow_mut().jump_sample_to(timestamp);
+                    if let Some(model) = model {
+                        c.user_data::<ViewState>()
+                            .expect("No data stored in Cursive object!")
+                            .update(model);
+                        crate::refresh(c);
+                    }
+                }
+                ViewMode::Live(_) => {}
+            }
+        })
+    }
+}
+
+impl View for ChartView {
+    fn draw(&self, printer: &Printer) {
+        self.draw_header(printer);
+        if self.timestamps.is_empty() {
+            printer.print((0, 3), "No data available in the chart window");
+            return;
+        }
+
+        // Leave room for the header and the time axis
+        let chart_height = printer.size.y.saturating_sub(4);
+        let series_height = chart_height / self.series.len().max(1);
+        let label_width = self
+            .series
+            .iter()
+            .filter_map(|series| {
+                let (min, max, _, _) = series.min_max()?;
+                let width = |field| series.render(Some(field)).trim().len();
+                Some(width(min).max(width(max)))
+            })
+            .max()
+            .unwrap_or(0);
+        for (idx, series) in self.series.iter().enumerate() {
+            self.draw_series(
+                printer,
+                series,
+                SERIES_COLORS[idx % SERIES_COLORS.len()],
+                2 + idx * series_height,
+                series_height,
+                label_width,
+            );
+        }
+        self.draw_time_axis(printer, 2 + chart_height, label_width);
+    }
+
+    fn required_size(&mut self, constraint: Vec2) -> Vec2 {
+        constraint
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::Key(Key::Left) => self.move_cursor(self.cursor.saturating_sub(1)),
+            Event::Key(Key::Right) => self.move_cursor(self.cursor + 1),
+            Event::Key(Key::Home) => self.move_cursor(0),
+            Event::Key(Key::End) => self.move_cursor(usize::MAX),
+            _ => EventResult::Ignored,
+        }
+    }
+}
+
+/// Parses the arguments of the chart command, "[-w <duration>] <field>...",
+/// into the window and the fields.
+pub fn parse_args<'a>(args: &[&'a str]) -> Result<(Duration, Vec<&'a str>), String> {
+    let mut window = DEFAULT_CHART_WINDOW;
+    let mut fields = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if *arg == "-w" {
+            let input = args.next().ok_or("Missing window after -w")?;
+            window = input
+                .parse::<humantime::Duration>()
+                .map_err(|_| format!("Invalid window: {}", input))?
+                .into();
+        } else {
+            fields.push(*arg);
+        }
+    }
+    if fields.is_empty() {
+        return Err("No field to chart".to_owned());
+    }
+    if fields.len() > MAX_CHART_FIELDS {
+        return Err(format!(
+            "Cannot chart more than {} fields",
+            MAX_CHART_FIELDS
+        ));
+    }
+    Ok((window, fields))
+}
+
+/// Opens the chart of the given fields as a full screen layer.
+pub fn open(c: &mut Cursive, window: Duration, field_ids: Vec<ModelFieldId>) {
+    let chart = ChartView::new(c, window, field_ids);
+    c.add_fullscreen_layer(ResizedView::with_full_screen(
+        OnEventView::new(chart).on_event(EventTrigger::from('q').or(Key::Esc), |c| {
+            c.pop_layer();
+        }),
+    ));
+}
@@ -0,0 +1,267 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The column picker chooses and orders the columns of the current tab of
+//! the cgroup or process view, and saves them to belowrc.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use base_render::HasRenderConfig;
+use cursive::event::Key;
+use cursive::view::Nameable;
+use cursive::view::Resizable;
+use cursive::view::Scrollable;
+use cursive::views::Dialog;
+use cursive::views::LinearLayout;
+use cursive::views::OnEventView;
+use cursive::views::SelectView;
+use cursive::views::TextView;
+use cursive::Cursive;
+use enum_iterator::Sequence;
+use model::FieldId;
+use model::Queriable;
+use model::SingleCgroupModelFieldId;
+use model::SingleProcessModelFieldId;
+
+use crate::cgroup_view::CgroupView;
+use crate::process_view::ProcessView;
+use crate::render::HasViewStyle;
+use crate::render::ViewItem;
+use crate::stats_view::StatsView;
+use crate::stats_view::ViewBridge;
+use crate::viewrc::save_tab_columns;
+use crate::viewrc::ColumnRc;
+use crate::MainViewState;
+use crate::ViewState;
+
+const COLUMN_PICKER_LIST: &str = "column_picker_list";
+
+struct Column<F: FieldId> {
+    item: ViewItem<F>,
+    selected: bool,
+}
+
+/// Columns of a tab being picked. The selected ones come first, in the
+/// order they are shown.
+struct Columns<F: FieldId> {
+    columns: Vec<Column<F>>,
+}
+
+impl<T, F> Columns<F>
+where
+    T: Queriable<FieldId = F> + HasRenderConfig + HasViewStyle,
+    F: FieldId<Queriable = T> + Clone + PartialEq + Sequence + ToString,
+{
+    /// The shown columns followed by the rest of the fields, except for the
+    /// pinned ones that are always shown.
+    fn new(items: Vec<ViewItem<F>>, pinned: &[F]) -> Self {
+        let mut columns: Vec<_> = items
+            .into_iter()
+            .map(|item| Column {
+                item,
+                selected: true,
+            })
+            .collect();
+        for field_id in enum_iterator::all::<F>() {
+            if pinned.contains(&field_id)
+                || columns
+                    .iter()
+                    .any(|column| column.item.field_id == field_id)
+            {
+                continue;
+            }
+            columns.push(Column {
+                item: ViewItem::from_default(field_id),
+                selected: false,
+            });
+        }
+        Self { columns }
+    }
+
+    fn num_selected(&self) -> usize {
+        self.columns.iter().filter(|column| column.selected).count()
+    }
+
+    /// Selects or unselects the column, moving it to the end of the
+    /// selected ones or to the start of the others. Returns its new index.
+    fn toggle(&mut self, idx: usize) -> usize {
+        let mut column = self.columns.remove(idx);
+        column.selected = !column.selected;
+        let new_idx = self.num_selected();
+        self.columns.insert(new_idx, column);
+        new_idx
+    }
+
+    /// Moves a selected column up (-1) or down (1) among the selected ones.
+    /// Returns its new index.
+    fn move_by(&mut self, idx: usize, delta: isize) -> usize {
+        let new_idx = idx as isize + delta;
+        if !self.columns[idx].selected || new_idx < 0 || new_idx as usize >= self.num_selected() {
+            return idx;
+        }
+        self.columns.swap(idx, new_idx as usize);
+        new_idx as usize
+    }
+
+    fn get_selected_items(&self) -> Vec<ViewItem<F>> {
+        self.columns
+            .iter()
+            .filter(|column| column.selected)
+            .map(|column| column.item.clone())
+            .collect()
+    }
+
+    fn fill_select_view(&self, select_view: &mut SelectView<usize>, selected_idx: usize) {
+        select_view.clear();
+        for (idx, column) in self.columns.iter().enumerate() {
+            select_view.add_item(
+                format!(
+                    "[{}] {:<40} {}",
+                    if column.selected { 'x' } else { ' ' },
+                    column.item.field_id.to_string(),
+                    column.item.config.render_config.get_title(),
+                ),
+                idx,
+            );
+        }
+        select_view.set_selection(selected_idx);
+    }
+}
+
+fn update_list<T, F>(
+    c: &mut Cursive,
+    columns: &Rc<RefCell<Columns<F>>>,
+    update: impl FnOnce(&mut Columns<F>, usize) -> usize,
+) where
+    T: Queriable<FieldId = F> + HasRenderConfig + HasViewStyle,
+    F: FieldId<Queriable = T> + Clone + PartialEq + Sequence + ToString,
+{
+    c.call_on_name(COLUMN_PICKER_LIST, |select_view: &mut SelectView<usize>| {
+        if let Some(idx) = select_view.selected_id() {
+            let new_idx = update(&mut columns.borrow_mut(), idx);
+            columns.borrow().fill_select_view(select_view, new_idx);
+        }
+    });
+}
+
+fn new_column_picker<V, T, F>(
+    view: &'static str,
+    tab: String,
+    items: Vec<ViewItem<F>>,
+    pinned: &[F],
+    set_tab_columns: fn(&mut Cursive, &str, Vec<ViewItem<F>>),
+) -> impl cursive::View
+where
+    V: 'static + ViewBridge,
+    T: Queriable<FieldId = F> + HasRenderConfig + HasViewStyle,
+    F: 'static + FieldId<Queriable = T> + Clone + PartialEq + Sequence + ToString,
+{
+    let columns = Rc::new(RefCell::new(Columns::new(items, pinned)));
+    let mut select_view = SelectView::<usize>::new();
+    columns.borrow().fill_select_view(&mut select_view, 0);
+
+    let toggle_columns = columns.clone();
+    let select_view = select_view.on_submit(move |c, _: &usize| {
+        update_list(c, &toggle_columns, Columns::toggle);
+    });
+    let up_columns = columns.clone();
+    let down_columns = columns.clone();
+    let list = OnEventView::new(select_view.with_name(COLUMN_PICKER_LIST))
+        .on_event('K', move |c| {
+            update_list(c, &up_columns, |columns, idx| columns.move_by(idx, -1))
+        })
+        .on_event('J', move |c| {
+            update_list(c, &down_columns, |columns, idx| columns.move_by(idx, 1))
+        });
+
+    let title = format!("Columns of {} {} tab", view, tab);
+    OnEventView::new(
+        Dialog::around(
+            LinearLayout::vertical()
+                .child(TextView::new(
+                    "<Enter>: show/hide column, K/J: move column up/down",
+                ))
+                .child(list.scrollable().max_height(30)),
+        )
+        .title(title)
+        .button("Save", move |c| {
+            c.pop_layer();
+            let items = columns.borrow().get_selected_items();
+            let columns_rc: Vec<_> = items.iter().map(ColumnRc::from_view_item).collect();
+            set_tab_columns(c, &tab, items);
+            if let Err(e) = save_tab_columns(view, &tab, &columns_rc) {
+                StatsView::<V>::cp_warn(c, &format!("Failed to save columns to belowrc: {}", e));
+            }
+        })
+        .dismiss_button("Cancel"),
+    )
+    .on_event(Key::Esc, |c| {
+        c.pop_layer();
+    })
+}
+
+/// Opens the column picker for the current tab of the cgroup or process
+/// view.
+pub fn open(c: &mut Cursive) {
+    let main_view_state = c
+        .user_data::<ViewState>()
+        .expect("No data stored in Cursive object!")
+        .main_view_state
+        .clone();
+    match main_view_state {
+        MainViewState::Cgroup => {
+            let mut view = CgroupView::get_cgroup_view(c);
+            let tab = view.get_tab_view().get_cur_selected().to_string();
+            let items = view.state.borrow().sort_tags.get(&tab).cloned();
+            drop(view);
+            if let Some(items) = items {
+                c.add_layer(new_column_picker::<CgroupView, _, _>(
+                    "cgroup",
+                    tab,
+                    items,
+                    &[SingleCgroupModelFieldId::Name],
+                    CgroupView::set_tab_columns,
+                ));
+            }
+        }
+        MainViewState::Process(_) => {
+            let mut view = ProcessView::get_process_view(c);
+            let tab = view.get_tab_view().get_cur_selected().to_string();
+            let items = view
+                .state
+                .borrow()
+                .sort_tags
+                .get(&tab)
+                .map(|process_tab| process_tab.view_items.clone());
+            drop(view);
+            if let Some(items) = items {
+                c.add_layer(new_column_picker::<ProcessView, _, _>(
+                    "process",
+                    tab,
+                    items,
+                    &[
+                        SingleProcessModelFieldId::Comm,
+                        SingleProcessModelFieldId::Cgroup,
+                    ],
+                    ProcessView::set_tab_columns,
+                ));
+            }
+        }
+        _ => view_warn!(
+            c,
+            "Columns can only be picked in the cgroup and process views"
+        ),
+    }
+}
@@ -54,7 +54,9 @@ enum CPMode {
 pub struct CommandPalette {
     content: String,
     filter_info: Option<(String, String)>,
+    filter_expr: Option<String>,
     fold: bool,
+    tree: bool,
     mode: CPMode,
     cmd_view: RefCell<EditView>,
     cmd_controllers: Rc<RefCell<HashMap<&'static str, Controllers>>>,
@@ -77,12 +79,24 @@ impl View for CommandPalette {
             printer.print((max_x, 0), &output);
         }
 
+        if let Some(expr) = &self.filter_expr {
+            let output = format!("|| Filter Expr: {:>20.20} ||", expr);
+            max_x -= output.len();
+            printer.print((max_x, 0), &output);
+        }
+
         if self.fold {
             let text = "| Fold |";
             max_x -= text.len();
             printer.print((max_x, 0), text);
         }
 
+        if self.tree {
+            let text = "| Tree |";
+            max_x -= text.len();
+            printer.print((max_x, 0), text);
+        }
+
         match self.mode {
             CPMode::Command => {
                 printer.print((0, 1), ":");
@@ -135,7 +149,9 @@ impl CommandPalette {
         Self {
             content: content.into(),
             filter_info: None,
+            filter_expr: None,
             fold: false,
+            tree: false,
             mode: CPMode::Info,
             cmd_view: RefCell::new(
                 EditView::new()
@@ -238,6 +254,13 @@ impl CommandPalette {
         self.content = "".into()
     }
 
+    /// Turn cmd_palette into command input mode with the given command
+    /// prefilled, e.g. to ask for its arguments
+    pub fn invoke_cmd_with(&mut self, cmd: &str) {
+        self.invoke_cmd();
+        self.cmd_view.borrow_mut().set_content(cmd);
+    }
+
     /// Check if command palette is in command mode
     pub fn is_cmd_mode(&self) -> bool {
         self.mode == CPMode::Command
@@ -269,10 +292,22 @@ impl CommandPalette {
         self.filter_info = filter_info;
     }
 
+    pub fn set_filter_expr(&mut self, filter_expr: Option<String>) {
+        self.filter_expr = filter_expr;
+    }
+
+    pub fn get_filter_expr(&self) -> Option<&str> {
+        self.filter_expr.as_deref()
+    }
+
     pub fn toggle_fold(&mut self) {
         self.fold = !self.fold;
     }
 
+    pub fn toggle_tree(&mut self) {
+        self.tree = !self.tree;
+    }
+
     fn print_info(&self, printer: &Printer, pos: Vec2, idx: usize) {
         if idx + printer.size.x > self.content.len() {
             printer.print(pos, &self.content[idx..]);
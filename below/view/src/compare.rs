@@ -0,0 +1,168 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compare mode shows the cgroups or processes of the current sample side
+//! by side with those of a sample from a second source, which is either
+//! another host or the same host at another time. The compared side either
+//! moves along with the current one or is navigated on its own.
+
+use std::time::SystemTime;
+
+use common::util::systemtime_to_datetime;
+use cursive::Cursive;
+use model::CgroupModel;
+use model::Model;
+use model::ProcessModel;
+use store::Advance;
+use store::Direction;
+
+use crate::ViewState;
+
+pub struct Compare {
+    advance: Advance,
+    /// Host or store the compared samples come from
+    pub label: String,
+    pub timestamp: SystemTime,
+    pub cgroup: CgroupModel,
+    pub process: ProcessModel,
+    /// Whether this side moves along with the current one
+    pub locked: bool,
+    /// Whether the diff views compare to this side rather than to a fixed
+    /// sample
+    pub active: bool,
+    /// Times of the current side and this side when they were last moved
+    /// independently. Locked sides keep the distance between them.
+    anchor: (SystemTime, SystemTime),
+    /// Last time this side followed the current one to
+    target: SystemTime,
+}
+
+/// Returns the time that is as far from `timestamp` as `anchor.1` is from
+/// `anchor.0`.
+fn get_target(anchor: (SystemTime, SystemTime), timestamp: SystemTime) -> SystemTime {
+    match timestamp.duration_since(anchor.0) {
+        Ok(d) => anchor.1 + d,
+        Err(e) => anchor.1 - e.duration(),
+    }
+}
+
+impl Compare {
+    /// `model` is the first compared sample and `timestamp` the time of the
+    /// current sample, the two sides start locked.
+    pub fn new(label: String, advance: Advance, model: Model, timestamp: SystemTime) -> Self {
+        Self {
+            advance,
+            label,
+            timestamp: model.timestamp,
+            cgroup: model.cgroup,
+            process: model.process,
+            locked: true,
+            active: false,
+            anchor: (timestamp, model.timestamp),
+            target: model.timestamp,
+        }
+    }
+
+    fn set_model(&mut self, model: Option<Model>) -> bool {
+        match model {
+            Some(model) => {
+                self.timestamp = model.timestamp;
+                self.cgroup = model.cgroup;
+                self.process = model.process;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves to the sample as far from the current one at `timestamp` as when
+    /// the sides were locked. Does nothing if they are not.
+    pub fn follow(&mut self, timestamp: SystemTime) {
+        if !self.locked {
+            return;
+        }
+        let target = get_target(self.anchor, timestamp);
+        // Refreshing without moving the current side must not reload samples
+        if target != self.target {
+            self.target = target;
+            let model = self.advance.jump_sample_to(target);
+            self.set_model(model);
+        }
+    }
+
+    /// Moves this side to the next or previous sample, keeping the new
+    /// distance to the current side at `timestamp` if locked. Returns false
+    /// if there is no such sample.
+    pub fn step(&mut self, direction: Direction, timestamp: SystemTime) -> bool {
+        let model = self.advance.advance(direction);
+        let moved = self.set_model(model);
+        self.set_anchor(timestamp);
+        moved
+    }
+
+    /// Moves this side to the sample at `to`, see `step`.
+    pub fn jump_to(&mut self, to: SystemTime, timestamp: SystemTime) -> bool {
+        let model = self.advance.jump_sample_to(to);
+        let moved = self.set_model(model);
+        self.set_anchor(timestamp);
+        moved
+    }
+
+    pub fn set_locked(&mut self, locked: bool, timestamp: SystemTime) {
+        self.locked = locked;
+        self.set_anchor(timestamp);
+    }
+
+    fn set_anchor(&mut self, timestamp: SystemTime) {
+        self.anchor = (timestamp, self.timestamp);
+        self.target = self.timestamp;
+    }
+
+    pub fn get_status_str(&self) -> String {
+        format!(
+            "vs {} {}{}",
+            self.label,
+            systemtime_to_datetime(self.timestamp),
+            if self.locked { " (locked)" } else { "" }
+        )
+    }
+}
+
+/// Returns the time and source of the compared sample if the diff views
+/// compare to it.
+pub fn get_active_base(c: &mut Cursive) -> Option<(SystemTime, String)> {
+    c.user_data::<ViewState>()
+        .expect("No data stored in Cursive object!")
+        .compare
+        .as_ref()
+        .filter(|compare| compare.active)
+        .map(|compare| (compare.timestamp, compare.label.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn locked_target() {
+        let t = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        // Compare side one hour behind
+        let anchor = (t(10000), t(6400));
+        assert_eq!(get_target(anchor, t(10000)), t(6400));
+        assert_eq!(get_target(anchor, t(10060)), t(6460));
+        assert_eq!(get_target(anchor, t(9940)), t(6340));
+    }
+}
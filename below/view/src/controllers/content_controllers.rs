@@ -101,7 +101,12 @@ make_event_controller!(
     "clear_filter",
     "cf",
     vec![Event::CtrlChar('l')],
-    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |view: &mut StatsView<T>, _cmd_vec: &[&str]| {
+        // Views without filter expression support have nothing to clear
+        if view.state.borrow_mut().set_filter_expr(None).is_ok() {
+            view.get_cmd_palette().set_filter_expr(None);
+        }
+    },
     |c: &mut Cursive, _cmd_vec: &[&str]| {
         let state = StatsView::<T>::get_view(c).state.clone();
         state.borrow_mut().set_filter_from_tab_idx("", 0, None); // clear filter
@@ -109,3 +114,92 @@ make_event_controller!(
         StatsView::<T>::refresh_myself(c);
     }
 );
+
+// Filter by a boolean expression over fields, e.g. "cpu.usage_pct > 50"
+make_event_controller!(
+    FilterExprImpl,
+    "filter_expr",
+    "fe",
+    vec![Event::Char('F')],
+    |view: &mut StatsView<T>, cmd_vec: &[&str]| {
+        // Hot key asks for the expression in the command palette
+        if cmd_vec.is_empty() {
+            view.get_cmd_palette().invoke_cmd_with("filter_expr ");
+            return;
+        }
+        let expr = (cmd_vec.len() > 1).then(|| cmd_vec[1..].join(" "));
+        let res = view.state.borrow_mut().set_filter_expr(expr.as_deref());
+        match res {
+            Ok(()) => view.get_cmd_palette().set_filter_expr(expr),
+            Err(e) => view.get_cmd_palette().set_alert(e),
+        }
+    },
+    |c: &mut Cursive, _cmd_vec: &[&str]| {
+        StatsView::<T>::refresh_myself(c);
+    }
+);
+
+// Toggle the history pane of the selected row, optionally with the number
+// of samples, e.g. "history 120"
+make_event_controller!(
+    HistoryImpl,
+    "history",
+    "hist",
+    vec![Event::Char('H')],
+    |view: &mut StatsView<T>, cmd_vec: &[&str]| {
+        match cmd_vec.get(1).map(|samples| samples.parse::<usize>()) {
+            Some(Ok(samples)) if samples > 0 => view.toggle_history(Some(samples)),
+            Some(_) => view
+                .get_cmd_palette()
+                .set_alert(format!("Invalid number of samples: {}", cmd_vec[1])),
+            None => view.toggle_history(None),
+        }
+    },
+    |c: &mut Cursive, _cmd_vec: &[&str]| {
+        StatsView::<T>::refresh_myself(c);
+    }
+);
+
+// Chart fields over a time window in a full screen layer. Fields of the
+// selected row can be given by their name in the view, e.g.
+// "chart -w 30m cpu.usage_pct pressure.memory_full_pct", other fields by
+// their full id, e.g. "chart system.cpu.usage_pct".
+make_event_controller!(
+    ChartImpl,
+    "chart",
+    "",
+    vec![],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        use std::str::FromStr;
+
+        let (window, fields) = match crate::chart_view::parse_args(cmd_vec.get(1..).unwrap_or(&[]))
+        {
+            Ok(args) => args,
+            Err(msg) => return StatsView::<T>::cp_warn(c, &msg),
+        };
+        let field_ids = {
+            let mut view = StatsView::<T>::get_view(c);
+            let cur_tab = view.get_tab_view().get_cur_selected().to_string();
+            let selection = view.get_detail_view().selection();
+            let state = view.state.borrow();
+            fields
+                .iter()
+                .map(|field| {
+                    model::ModelFieldId::from_str(field)
+                        .ok()
+                        .or_else(|| {
+                            selection.as_ref().and_then(|selected_key| {
+                                T::get_model_field_id(&state, selected_key, &cur_tab, field)
+                            })
+                        })
+                        .ok_or_else(|| format!("Unknown field: {}", field))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        match field_ids {
+            Ok(field_ids) => crate::chart_view::open(c, window, field_ids),
+            Err(msg) => StatsView::<T>::cp_warn(c, &msg),
+        }
+    }
+);
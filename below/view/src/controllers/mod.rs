@@ -116,24 +116,45 @@ make_controllers!(
     SortCol: SortByColumn,
     Filter: FilterPopup,
     CFilter: ClearFilter,
+    FilterExpr: FilterExprImpl,
+    History: HistoryImpl,
+    Chart: ChartImpl,
     JForward: JumpForward,
     JBackward: JumpBackward,
     NSample: NextSample,
     PSample: PrevSample,
     Pause: PauseImpl,
+    PlaySpeed: PlaySpeed,
+    Seek: SeekImpl,
+    SeekForward: SeekForward,
+    SeekBackward: SeekBackward,
+    Bookmark: BookmarkImpl,
+    Bookmarks: BookmarksImpl,
     Quit: QuitImpl,
     Help: HelpMenu,
     Process: ProcessView,
     Cgroup: CgroupView,
+    CgroupDiff: CgroupDiffView,
+    Compare: CompareView,
+    CompareLock: CompareLockImpl,
+    CompareNext: CompareNextImpl,
+    ComparePrev: ComparePrevImpl,
     System: SystemView,
+    Network: NetworkView,
+    Resctrl: ResctrlView,
     Gpu: GpuView,
     GpuProcess: GpuProcessView,
     GpuZoom: GpuZoomView,
     Zoom: ZoomView,
+    ZoomProcs: ZoomProcsView,
     Fold: FoldProcessView,
+    Tree: TreeProcessView,
     NextPage: NextPageImpl,
     PrevPage: PrevPageImpl,
     NextSelection: NextSelectionImpl,
     PrevSelection: PrevSelectionImpl,
     Url: URLPopup,
+    SaveView: SaveViewImpl,
+    LoadView: LoadViewImpl,
+    Columns: ColumnsImpl,
 );
@@ -145,6 +145,128 @@ make_event_controller!(
                 _ => {}
             };
         }
+        // In replay, start or stop playback instead
+        crate::playback::toggle(c);
         crate::status_bar::refresh(c);
     }
 );
+
+// Bookmark the current sample
+make_event_controller!(
+    BookmarkImpl,
+    "bookmark",
+    "bm",
+    vec![Event::Char('m')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        let view_state = c.user_data::<ViewState>().expect("user data not set");
+        let bookmark = crate::saved_view::Bookmark {
+            timestamp: common::util::get_unix_timestamp(view_state.timestamp),
+            hostname: view_state.system.borrow().hostname.clone(),
+            note: cmd_vec.get(1..).unwrap_or_default().join(" "),
+        };
+        match crate::saved_view::add_bookmark(bookmark) {
+            Ok(path) => view_warn!(c, "Bookmark saved to {}", path.display()),
+            Err(e) => view_warn!(c, "{:#}", e),
+        }
+    }
+);
+
+// List bookmarks
+make_event_controller!(
+    BookmarksImpl,
+    "bookmarks",
+    "bms",
+    vec![Event::Char('L')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, _cmd_vec: &[&str]| {
+        match crate::saved_view::load_bookmarks() {
+            Ok(bookmarks) if bookmarks.is_empty() => view_warn!(c, "No bookmarks saved"),
+            Ok(bookmarks) => c.add_layer(crate::saved_view::new_bookmarks_popup(bookmarks)),
+            Err(e) => view_warn!(c, "{:#}", e),
+        }
+    }
+);
+
+// Playback speed
+make_event_controller!(
+    PlaySpeed,
+    "play_speed",
+    "speed",
+    vec![Event::Char('>')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        let speed = match cmd_vec.get(1) {
+            Some(arg) => match arg.trim_end_matches('x').parse::<u32>() {
+                Ok(speed) if speed > 0 => Some(speed),
+                _ => {
+                    view_warn!(c, "Invalid playback speed: {}", arg);
+                    return;
+                }
+            },
+            None => None,
+        };
+        crate::playback::set_speed(c, speed);
+        crate::status_bar::refresh(c);
+    }
+);
+
+// Seek to a position of the timeline
+make_event_controller!(
+    SeekImpl,
+    "seek",
+    "",
+    vec![],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        let arg = match cmd_vec.get(1) {
+            Some(arg) => arg,
+            None => {
+                view_warn!(c, "Missing percentage of the timeline to seek to");
+                return;
+            }
+        };
+        match crate::playback::parse_percent(arg) {
+            Ok(percent) => crate::playback::seek_to_percent(c, percent),
+            Err(e) => view_warn!(c, "{}", e),
+        }
+    }
+);
+
+// Seek forward
+make_event_controller!(
+    SeekForward,
+    "seek_forward",
+    "sf",
+    vec![Event::Char(']')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        match cmd_vec
+            .get(1)
+            .map_or(Ok(crate::playback::DEFAULT_SEEK_PERCENT), |arg| {
+                crate::playback::parse_percent(arg)
+            }) {
+            Ok(percent) => crate::playback::seek_by_percent(c, percent),
+            Err(e) => view_warn!(c, "{}", e),
+        }
+    }
+);
+
+// Seek backward
+make_event_controller!(
+    SeekBackward,
+    "seek_backward",
+    "sb",
+    vec![Event::Char('[')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        match cmd_vec
+            .get(1)
+            .map_or(Ok(crate::playback::DEFAULT_SEEK_PERCENT), |arg| {
+                crate::playback::parse_percent(arg)
+            }) {
+            Ok(percent) => crate::playback::seek_by_percent(c, -percent),
+            Err(e) => view_warn!(c, "{}", e),
+        }
+    }
+);
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::io::prelude::*;
 
 use tempfile::TempDir;
@@ -196,6 +197,25 @@ fn test_event_controller_override_failed() {
     );
 }
 
+#[test]
+fn test_default_events_unique() {
+    // Controllers reachable by shortcut appear twice in the cmd map
+    let controllers: HashSet<Controllers> = make_cmd_controller_map().into_values().collect();
+    let mut events: HashMap<Event, Controllers> = HashMap::new();
+    for controller in controllers {
+        for event in controller.default_events() {
+            if let Some(existing) = events.insert(event.clone(), controller.clone()) {
+                panic!(
+                    "Default event {} of {} is also used by {}",
+                    event_to_string(&event),
+                    controller.command(),
+                    existing.command()
+                );
+            }
+        }
+    }
+}
+
 #[test]
 fn test_str_to_event_valid() {
     assert_eq!(str_to_event("c").unwrap(), Event::Char('c'));
@@ -12,13 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::SystemTime;
+
+use common::dateutil;
 use cursive::views::OnEventView;
 use cursive::views::ResizedView;
+use model::CgroupModel;
 
 use super::*;
 use crate::set_active_screen;
 use crate::MainViewState;
 use crate::ProcessZoomState;
+use crate::ViewMode;
 
 // Invoke command palette
 make_event_controller!(
@@ -243,6 +248,64 @@ make_event_controller!(
     }
 );
 
+// Invoke Network View
+make_event_controller!(
+    NetworkView,
+    "network",
+    "",
+    vec![Event::Char('I')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, _cmd_vec: &[&str]| {
+        set_active_screen(c, "network_view_panel");
+
+        let current_state = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state
+            .clone();
+
+        // If the previous state is zoom state, we need to clear the zoom state
+        if current_state.is_process_zoom_state() {
+            crate::process_view::ProcessView::get_process_view(c)
+                .state
+                .borrow_mut()
+                .reset_state_for_quiting_zoom();
+        }
+        c.user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state = MainViewState::Network;
+    }
+);
+
+// Invoke Resctrl View
+make_event_controller!(
+    ResctrlView,
+    "resctrl",
+    "",
+    vec![Event::Char('R')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, _cmd_vec: &[&str]| {
+        set_active_screen(c, "resctrl_view_panel");
+
+        let current_state = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state
+            .clone();
+
+        // If the previous state is zoom state, we need to clear the zoom state
+        if current_state.is_process_zoom_state() {
+            crate::process_view::ProcessView::get_process_view(c)
+                .state
+                .borrow_mut()
+                .reset_state_for_quiting_zoom();
+        }
+        c.user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state = MainViewState::Resctrl;
+    }
+);
+
 // Zoom in View
 make_event_controller!(
     ZoomView,
@@ -295,6 +358,11 @@ make_event_controller!(
                     .handle_state_for_entering_zoom(current_selection);
                 MainViewState::Process(ProcessZoomState::Cgroup)
             }
+            // Zooming into the queues of an interface stays in network view
+            MainViewState::Network => {
+                crate::network_view::NetworkView::toggle_zoom(c);
+                MainViewState::Network
+            }
             _ => return,
         };
 
@@ -307,7 +375,11 @@ make_event_controller!(
                 // Bring cgroup_view to front
                 set_active_screen(c, "cgroup_view_panel");
             }
-            MainViewState::Core => {}
+            MainViewState::CgroupDiff
+            | MainViewState::ProcessDiff
+            | MainViewState::Core
+            | MainViewState::Network
+            | MainViewState::Resctrl => {}
             #[cfg(fbcode_build)]
             MainViewState::Gpu => {}
         }
@@ -322,6 +394,316 @@ make_event_controller!(
     }
 );
 
+// Zoom into process view with the member processes of the selected cgroup
+make_event_controller!(
+    ZoomProcsView,
+    "zoom_procs",
+    "",
+    vec![Event::Char('Z')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, _cmd_vec: &[&str]| {
+        let current_state = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state
+            .clone();
+        if current_state != MainViewState::Cgroup {
+            return;
+        }
+
+        let (current_selection, procs) = {
+            let cgroup_view = crate::cgroup_view::CgroupView::get_cgroup_view(c);
+            let state = cgroup_view.state.borrow();
+            (
+                state.current_selected_cgroup.clone(),
+                state.get_procs_for_selected_cgroup(),
+            )
+        };
+        // Without cgroup.procs (e.g. store written by an older below), fall
+        // back to matching the cgroup path of each process
+        let next_state = {
+            let process_view = crate::process_view::ProcessView::get_process_view(c);
+            let mut state = process_view.state.borrow_mut();
+            match procs {
+                Some(procs) => {
+                    state.handle_state_for_entering_pids_zoom(procs);
+                    MainViewState::Process(ProcessZoomState::Pids)
+                }
+                None => {
+                    state.handle_state_for_entering_zoom(current_selection);
+                    MainViewState::Process(ProcessZoomState::Cgroup)
+                }
+            }
+        };
+
+        set_active_screen(c, "process_view_panel");
+        c.user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state = next_state;
+
+        // Redraw screen now so we don't have to wait until next tick
+        refresh(c)
+    }
+);
+
+// Returns the given time, which is either relative to the viewing time
+// (e.g. 10m) or absolute.
+fn parse_time_at(view_time: SystemTime, input: &str) -> Result<SystemTime, String> {
+    match input.parse::<humantime::Duration>() {
+        Ok(d) => view_time
+            .checked_sub(d.into())
+            .ok_or_else(|| format!("Time out of range: {}", input)),
+        Err(_) => match dateutil::HgTime::parse_time_of_day(input) {
+            Some(time_of_day) => {
+                dateutil::HgTime::time_of_day_relative_to_system_time(view_time, time_of_day)
+                    .ok_or_else(|| format!("Failed to parse time of day value: {}", input))
+            }
+            None => dateutil::HgTime::parse(input)
+                .map(|pt| std::time::UNIX_EPOCH + std::time::Duration::from_secs(pt.unixtime))
+                .ok_or_else(|| format!("Failed to parse time value: {}", input)),
+        },
+    }
+}
+
+// Returns the timestamp and cgroup tree of the sample at the given time,
+// which is either relative to the viewing time (e.g. 10m) or absolute.
+fn get_cgroup_sample_at(c: &mut Cursive, input: &str) -> Result<(SystemTime, CgroupModel), String> {
+    let view_state = c.user_data::<ViewState>().expect("user data not set");
+    let view_time = view_state.timestamp;
+    let adv = match &view_state.mode {
+        ViewMode::Live(adv) | ViewMode::Pause(adv) | ViewMode::Replay(adv) => adv.clone(),
+    };
+    let timestamp = parse_time_at(view_time, input)?;
+
+    let mut adv = adv.borrow_mut();
+    let sample = adv.jump_sample_to(timestamp);
+    // Move the cursor back so the next sample follows the viewed one
+    adv.jump_sample_to(view_time);
+    sample
+        .map(|model| (model.timestamp, model.cgroup))
+        .ok_or_else(|| "Cannot find available data sample".to_owned())
+}
+
+// Compare cgroups against a base sample
+make_event_controller!(
+    CgroupDiffView,
+    "cgroup_diff",
+    "cd",
+    vec![Event::Char('X')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        let current_state = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state
+            .clone();
+
+        let (timestamp, base) = if cmd_vec.len() > 1 {
+            match get_cgroup_sample_at(c, &cmd_vec[1..].join(" ")) {
+                Ok(sample) => sample,
+                Err(msg) => {
+                    view_warn!(c, "{}", msg);
+                    return;
+                }
+            }
+        } else if current_state == MainViewState::CgroupDiff {
+            // Pressing 'x' again in cgroup diff view goes back to cgroup view
+            set_active_screen(c, "cgroup_view_panel");
+            c.user_data::<ViewState>()
+                .expect("No data stored in Cursive object!")
+                .main_view_state = MainViewState::Cgroup;
+            return refresh(c);
+        } else {
+            let view_state = c
+                .user_data::<ViewState>()
+                .expect("No data stored in Cursive object!");
+            (view_state.timestamp, view_state.cgroup.borrow().clone())
+        };
+
+        // If the previous state is zoom state, we need to clear the zoom state
+        if current_state.is_process_zoom_state() {
+            crate::process_view::ProcessView::get_process_view(c)
+                .state
+                .borrow_mut()
+                .reset_state_for_quiting_zoom();
+        }
+
+        c.user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .set_cgroup_diff_base(base);
+        crate::cgroup_diff_view::CgroupDiffView::set_compare_mode(c, false);
+        crate::cgroup_diff_view::CgroupDiffView::get_cgroup_diff_view(c)
+            .state
+            .borrow_mut()
+            .base_timestamp = Some(timestamp);
+
+        set_active_screen(c, "cgroup_diff_view_panel");
+        c.user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state = MainViewState::CgroupDiff;
+
+        // Redraw screen now so we don't have to wait until next tick
+        refresh(c)
+    }
+);
+
+// Returns the compare side, or warns that there is none
+macro_rules! get_compare {
+    ($c:ident) => {
+        match $c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .compare
+            .as_mut()
+        {
+            Some(compare) => compare,
+            None => {
+                view_warn!(
+                    $c,
+                    "No compare side, start below replay with --compare-host or --compare-time"
+                );
+                return;
+            }
+        }
+    };
+}
+
+// Compare cgroups or processes side by side with the compare side
+make_event_controller!(
+    CompareView,
+    "compare",
+    "cmp",
+    vec![Event::Char('V')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        let view_state = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!");
+        let current_state = view_state.main_view_state.clone();
+        let timestamp = view_state.timestamp;
+        let compare = get_compare!(c);
+        let compare_active = compare.active;
+
+        if cmd_vec.len() > 1 {
+            let moved = parse_time_at(timestamp, &cmd_vec[1..].join(" ")).and_then(|to| {
+                if compare.jump_to(to, timestamp) {
+                    Ok(())
+                } else {
+                    Err("Cannot find available data sample".to_owned())
+                }
+            });
+            if let Err(msg) = moved {
+                view_warn!(c, "{}", msg);
+                return;
+            }
+        } else if compare_active
+            && (current_state == MainViewState::CgroupDiff
+                || current_state == MainViewState::ProcessDiff)
+        {
+            // Pressing 'V' again in compare mode goes back to where it came from
+            compare.active = false;
+            let (screen, next_state) = match current_state {
+                MainViewState::ProcessDiff => (
+                    "process_view_panel",
+                    MainViewState::Process(ProcessZoomState::NoZoom),
+                ),
+                _ => ("cgroup_view_panel", MainViewState::Cgroup),
+            };
+            set_active_screen(c, screen);
+            c.user_data::<ViewState>()
+                .expect("No data stored in Cursive object!")
+                .main_view_state = next_state;
+            return refresh(c);
+        }
+
+        let (screen, next_state) = match current_state {
+            MainViewState::Process(_) | MainViewState::ProcessDiff => {
+                ("process_diff_view_panel", MainViewState::ProcessDiff)
+            }
+            _ => ("cgroup_diff_view_panel", MainViewState::CgroupDiff),
+        };
+        // If the previous state is zoom state, we need to clear the zoom state
+        if current_state.is_process_zoom_state() {
+            crate::process_view::ProcessView::get_process_view(c)
+                .state
+                .borrow_mut()
+                .reset_state_for_quiting_zoom();
+        }
+
+        let view_state = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!");
+        if let Some(compare) = view_state.compare.as_mut() {
+            compare.active = true;
+        }
+        view_state.update_diffs();
+        crate::cgroup_diff_view::CgroupDiffView::set_compare_mode(c, true);
+
+        set_active_screen(c, screen);
+        c.user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state = next_state;
+
+        // Redraw screen now so we don't have to wait until next tick
+        refresh(c)
+    }
+);
+
+// Lock or unlock the compare side to the current one
+make_event_controller!(
+    CompareLockImpl,
+    "compare_lock",
+    "",
+    vec![Event::Char('|')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, _cmd_vec: &[&str]| {
+        let timestamp = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .timestamp;
+        let compare = get_compare!(c);
+        let locked = !compare.locked;
+        compare.set_locked(locked, timestamp);
+        refresh(c)
+    }
+);
+
+fn step_compare(c: &mut Cursive, direction: store::Direction) {
+    let timestamp = c
+        .user_data::<ViewState>()
+        .expect("No data stored in Cursive object!")
+        .timestamp;
+    let compare = get_compare!(c);
+    if !compare.step(direction, timestamp) {
+        view_warn!(c, "Data is not available on the compare side.");
+        return;
+    }
+    c.user_data::<ViewState>()
+        .expect("No data stored in Cursive object!")
+        .update_diffs();
+    refresh(c)
+}
+
+// Move the compare side to its next sample
+make_event_controller!(
+    CompareNextImpl,
+    "compare_next",
+    "",
+    vec![Event::Char('}')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, _cmd_vec: &[&str]| { step_compare(c, store::Direction::Forward) }
+);
+
+// Move the compare side to its previous sample
+make_event_controller!(
+    ComparePrevImpl,
+    "compare_prev",
+    "",
+    vec![Event::Char('{')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, _cmd_vec: &[&str]| { step_compare(c, store::Direction::Reverse) }
+);
+
 // Fold processes in process view
 make_event_controller!(
     FoldProcessView,
@@ -341,6 +723,41 @@ make_event_controller!(
             let mut process_view = crate::process_view::ProcessView::get_process_view(c);
             process_view.get_cmd_palette().toggle_fold();
             process_view.state.borrow_mut().toggle_fold();
+            // Folded processes have no tree
+            if process_view.state.borrow().tree {
+                process_view.get_cmd_palette().toggle_tree();
+                process_view.state.borrow_mut().toggle_tree();
+            }
+        }
+
+        // Redraw screen now so we don't have to wait until next tick
+        refresh(c)
+    }
+);
+
+// Show processes as a tree in process view
+make_event_controller!(
+    TreeProcessView,
+    "tree",
+    "",
+    vec![Event::Char('E')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, _cmd_vec: &[&str]| {
+        let current_state = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .main_view_state
+            .clone();
+
+        // NB: scope the borrowing to refresh() doesn't re-borrow and panic
+        if let MainViewState::Process(_) = current_state {
+            let mut process_view = crate::process_view::ProcessView::get_process_view(c);
+            process_view.get_cmd_palette().toggle_tree();
+            process_view.state.borrow_mut().toggle_tree();
+            if process_view.state.borrow().fold {
+                process_view.get_cmd_palette().toggle_fold();
+                process_view.state.borrow_mut().toggle_fold();
+            }
         }
 
         // Redraw screen now so we don't have to wait until next tick
@@ -429,3 +846,65 @@ make_event_controller!(
         StatsView::<T>::refresh_myself(c);
     }
 );
+
+// Save the current view, or show it as a string without a name
+make_event_controller!(
+    SaveViewImpl,
+    "save_view",
+    "sv",
+    vec![],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        let saved = match crate::saved_view::get_current_view(c) {
+            Ok(saved) => saved,
+            Err(e) => {
+                view_warn!(c, "{}", e);
+                return;
+            }
+        };
+        let path = match cmd_vec.get(1) {
+            Some(name) => match saved.save(name) {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    view_warn!(c, "{:#}", e);
+                    return;
+                }
+            },
+            None => None,
+        };
+        c.add_layer(crate::saved_view::new_saved_popup(path.as_deref(), &saved));
+    }
+);
+
+// Load a saved view by name, file or string
+make_event_controller!(
+    LoadViewImpl,
+    "load_view",
+    "lv",
+    vec![],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, cmd_vec: &[&str]| {
+        if cmd_vec.len() < 2 {
+            view_warn!(c, "Missing name of the view to load");
+            return;
+        }
+        let res = crate::saved_view::SavedView::load(&cmd_vec[1..].join(" "))
+            .map_err(|e| format!("{:#}", e))
+            .and_then(|saved| crate::saved_view::restore(c, &saved));
+        if let Err(e) = res {
+            view_warn!(c, "{}", e);
+        }
+    }
+);
+
+// Pick the columns of the current tab
+make_event_controller!(
+    ColumnsImpl,
+    "columns",
+    "col",
+    vec![Event::Char('o')],
+    |_view: &mut StatsView<T>, _cmd_vec: &[&str]| {},
+    |c: &mut Cursive, _cmd_vec: &[&str]| {
+        crate::column_picker::open(c);
+    }
+);
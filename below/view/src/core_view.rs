@@ -26,10 +26,16 @@ use cursive::views::SelectView;
 use cursive::views::ViewRef;
 use cursive::Cursive;
 use model::system::SystemModel;
+use model::BTreeMapFieldId;
 use model::BtrfsModelFieldId;
+use model::Field;
 use model::MemoryModelFieldId;
+use model::Model;
+use model::ModelFieldId;
+use model::Queriable;
 use model::SingleCpuModelFieldId;
 use model::SingleDiskModelFieldId;
+use model::SystemModelFieldId;
 use model::VmModelFieldId;
 
 use crate::core_tabs::*;
@@ -292,4 +298,55 @@ impl ViewBridge for CoreView {
     ) -> String {
         selected_key.clone()
     }
+
+    fn get_history_field(
+        _state: &Self::StateType,
+        model: &Model,
+        selected_key: &String,
+        current_tab: &str,
+        selected_column: usize,
+    ) -> Option<Field> {
+        // Only disks have history. They are keyed by "major.minor".
+        if current_tab != "Disk" {
+            return None;
+        }
+        let field_id = enum_iterator::all::<SingleDiskModelFieldId>().nth(selected_column)?;
+        model
+            .system
+            .disks
+            .values()
+            .find(|sdm| {
+                format!("{}.{}", sdm.major.unwrap_or(0), sdm.minor.unwrap_or(0)) == *selected_key
+            })
+            .and_then(|sdm| sdm.query(&field_id))
+    }
+
+    fn get_model_field_id(
+        state: &Self::StateType,
+        selected_key: &String,
+        current_tab: &str,
+        field: &str,
+    ) -> Option<ModelFieldId> {
+        use std::str::FromStr;
+
+        // Only disk rows have fields of their own. They are keyed by
+        // "major.minor" while the model keys them by name.
+        if current_tab != "Disk" {
+            return None;
+        }
+        let name = state
+            .get_model()
+            .disks
+            .iter()
+            .find(|(_, sdm)| {
+                format!("{}.{}", sdm.major.unwrap_or(0), sdm.minor.unwrap_or(0)) == *selected_key
+            })
+            .map(|(name, _)| name.clone())?;
+        Some(ModelFieldId::System(SystemModelFieldId::Disks(
+            BTreeMapFieldId {
+                key: Some(name),
+                subquery_id: SingleDiskModelFieldId::from_str(field).ok()?,
+            },
+        )))
+    }
 }
@@ -12,24 +12,95 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::str::FromStr;
+
+use once_cell::sync::OnceCell;
+
 use crate::render::HasViewStyle;
+use crate::render::Thresholds;
 use crate::render::ViewStyle;
 use crate::render::CPU_HIGHLIGHT;
 use crate::render::MEM_HIGHLIGHT;
 use crate::render::PRESSURE_HIGHLIGHT;
+use crate::viewrc::HighlightView;
+
+/// A highlight rule from belowrc, see viewrc::HighlightRc.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighlightRule {
+    pub view: HighlightView,
+    /// Field id of the view, e.g. "mem.total"
+    pub field: String,
+    pub relative_to: Option<String>,
+    pub thresholds: Thresholds,
+}
+
+static HIGHLIGHT_RULES: OnceCell<Vec<HighlightRule>> = OnceCell::new();
+
+/// Sets the highlight rules, which take precedence over the default
+/// styles. Must be called before any ViewItem is created.
+pub fn set_highlight_rules(rules: Vec<HighlightRule>) {
+    // Ignored if already set, the rules are only read once
+    let _ = HIGHLIGHT_RULES.set(rules);
+}
+
+/// The last rule for the field, given as its id in the model of the view
+/// prefixed with the prefix of that model, e.g. "mem." for the system view.
+fn get_highlight_rule(
+    view: HighlightView,
+    prefix: &str,
+    field_id: &impl ToString,
+) -> Option<&'static HighlightRule> {
+    let field = format!("{}{}", prefix, field_id.to_string());
+    HIGHLIGHT_RULES
+        .get()?
+        .iter()
+        .rev()
+        .find(|rule| rule.view == view && rule.field == field)
+}
+
+fn get_rule_view_style(
+    view: HighlightView,
+    prefix: &str,
+    field_id: &impl ToString,
+) -> Option<ViewStyle> {
+    get_highlight_rule(view, prefix, field_id)
+        .map(|rule| ViewStyle::Thresholds(rule.thresholds.clone()))
+}
+
+fn get_rule_reference_field_id<F: FromStr + ToString>(
+    view: HighlightView,
+    prefix: &str,
+    field_id: &F,
+) -> Option<F> {
+    let relative_to = get_highlight_rule(view, prefix, field_id)?
+        .relative_to
+        .as_deref()?;
+    F::from_str(relative_to.strip_prefix(prefix)?).ok()
+}
 
 impl HasViewStyle for model::SingleCgroupModel {
     fn get_view_style(field_id: &Self::FieldId) -> Option<ViewStyle> {
         use model::SingleCgroupModelFieldId::Cpu;
         use model::SingleCgroupModelFieldId::Pressure;
+        if let Some(view_style) = get_rule_view_style(HighlightView::Cgroup, "", field_id) {
+            return Some(view_style);
+        }
         match field_id {
             Cpu(field_id) => model::CgroupCpuModel::get_view_style(field_id),
             Pressure(field_id) => model::CgroupPressureModel::get_view_style(field_id),
             _ => None,
         }
     }
+
+    fn get_reference_field_id(field_id: &Self::FieldId) -> Option<Self::FieldId> {
+        get_rule_reference_field_id(HighlightView::Cgroup, "", field_id)
+    }
 }
 
+impl HasViewStyle for model::SingleCgroupDiffModel {}
+
+impl HasViewStyle for model::SingleProcessDiffModel {}
+
 impl HasViewStyle for model::CgroupCpuModel {
     fn get_view_style(field_id: &Self::FieldId) -> Option<ViewStyle> {
         use model::CgroupCpuModelFieldId::SystemPct;
@@ -50,14 +121,39 @@ impl HasViewStyle for model::CgroupPressureModel {
 
 impl HasViewStyle for model::SingleNetModel {}
 
+impl HasViewStyle for model::SingleQueueModel {}
+
+impl HasViewStyle for model::TcpModel {}
+
+impl HasViewStyle for model::UdpModel {}
+
+impl HasViewStyle for model::Udp6Model {}
+
+impl HasViewStyle for model::IpModel {}
+
+impl HasViewStyle for model::Ip6Model {}
+
+impl HasViewStyle for model::IcmpModel {}
+
+impl HasViewStyle for model::Icmp6Model {}
+
+impl HasViewStyle for model::ResctrlCtrlMonGroupModel {}
+
 impl HasViewStyle for model::SingleProcessModel {
     fn get_view_style(field_id: &Self::FieldId) -> Option<ViewStyle> {
         use model::SingleProcessModelFieldId::Cpu;
+        if let Some(view_style) = get_rule_view_style(HighlightView::Process, "", field_id) {
+            return Some(view_style);
+        }
         match field_id {
             Cpu(field_id) => model::ProcessCpuModel::get_view_style(field_id),
             _ => None,
         }
     }
+
+    fn get_reference_field_id(field_id: &Self::FieldId) -> Option<Self::FieldId> {
+        get_rule_reference_field_id(HighlightView::Process, "", field_id)
+    }
 }
 
 impl HasViewStyle for model::ProcessCpuModel {
@@ -72,11 +168,29 @@ impl HasViewStyle for model::ProcessCpuModel {
     }
 }
 
+// Rules of the system view are looked up by the models of the system core
+// view, so that they apply to both.
 impl HasViewStyle for model::SystemModel {
     fn get_view_style(field_id: &Self::FieldId) -> Option<ViewStyle> {
+        use model::SystemModelFieldId::Cpu;
         use model::SystemModelFieldId::Mem;
+        use model::SystemModelFieldId::Vm;
         match field_id {
+            Cpu(field_id) => model::SingleCpuModel::get_view_style(field_id),
             Mem(field_id) => model::MemoryModel::get_view_style(field_id),
+            Vm(field_id) => model::VmModel::get_view_style(field_id),
+            _ => None,
+        }
+    }
+
+    fn get_reference_field_id(field_id: &Self::FieldId) -> Option<Self::FieldId> {
+        use model::SystemModelFieldId::Cpu;
+        use model::SystemModelFieldId::Mem;
+        use model::SystemModelFieldId::Vm;
+        match field_id {
+            Cpu(field_id) => model::SingleCpuModel::get_reference_field_id(field_id).map(Cpu),
+            Mem(field_id) => model::MemoryModel::get_reference_field_id(field_id).map(Mem),
+            Vm(field_id) => model::VmModel::get_reference_field_id(field_id).map(Vm),
             _ => None,
         }
     }
@@ -85,16 +199,39 @@ impl HasViewStyle for model::SystemModel {
 impl HasViewStyle for model::MemoryModel {
     fn get_view_style(field_id: &Self::FieldId) -> Option<ViewStyle> {
         use model::MemoryModelFieldId::Free;
+        if let Some(view_style) = get_rule_view_style(HighlightView::System, "mem.", field_id) {
+            return Some(view_style);
+        }
         match field_id {
             Free => Some(MEM_HIGHLIGHT.clone()),
             _ => None,
         }
     }
+
+    fn get_reference_field_id(field_id: &Self::FieldId) -> Option<Self::FieldId> {
+        get_rule_reference_field_id(HighlightView::System, "mem.", field_id)
+    }
+}
+
+impl HasViewStyle for model::SingleCpuModel {
+    fn get_view_style(field_id: &Self::FieldId) -> Option<ViewStyle> {
+        get_rule_view_style(HighlightView::System, "cpu.", field_id)
+    }
+
+    fn get_reference_field_id(field_id: &Self::FieldId) -> Option<Self::FieldId> {
+        get_rule_reference_field_id(HighlightView::System, "cpu.", field_id)
+    }
 }
 
-impl HasViewStyle for model::SingleCpuModel {}
+impl HasViewStyle for model::VmModel {
+    fn get_view_style(field_id: &Self::FieldId) -> Option<ViewStyle> {
+        get_rule_view_style(HighlightView::System, "vm.", field_id)
+    }
 
-impl HasViewStyle for model::VmModel {}
+    fn get_reference_field_id(field_id: &Self::FieldId) -> Option<Self::FieldId> {
+        get_rule_reference_field_id(HighlightView::System, "vm.", field_id)
+    }
+}
 
 impl HasViewStyle for model::SingleDiskModel {}
 
@@ -36,8 +36,12 @@ fn set_cp_filter(c: &mut Cursive, field_info: Option<(String, String)>) {
         .clone();
     match state {
         MainViewState::Cgroup => crate::cgroup_view::ViewType::cp_filter(c, field_info),
+        MainViewState::CgroupDiff => crate::cgroup_diff_view::ViewType::cp_filter(c, field_info),
         MainViewState::Process(_) => crate::process_view::ViewType::cp_filter(c, field_info),
+        MainViewState::ProcessDiff => crate::process_diff_view::ViewType::cp_filter(c, field_info),
         MainViewState::Core => crate::core_view::ViewType::cp_filter(c, field_info),
+        MainViewState::Network => crate::network_view::ViewType::cp_filter(c, field_info),
+        MainViewState::Resctrl => crate::resctrl_view::ViewType::cp_filter(c, field_info),
         #[cfg(fbcode_build)]
         MainViewState::Gpu => crate::gpu_view::ViewType::cp_filter(c, field_info),
     }
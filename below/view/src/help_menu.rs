@@ -37,6 +37,18 @@ pub struct ControllerHelper {
     args: &'static str,
 }
 
+impl ControllerHelper {
+    fn new(controller: &Controllers, events: Vec<Event>) -> Self {
+        Self {
+            events,
+            cmd: controller.command(),
+            cmd_short: controller.cmd_shortcut(),
+            description: get_description(controller),
+            args: get_args(controller),
+        }
+    }
+}
+
 impl std::fmt::Display for ControllerHelper {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
@@ -77,6 +89,15 @@ fn get_description(controller: &Controllers) -> &'static str {
         }
         Controllers::Filter => "Filter by selected column.",
         Controllers::CFilter => "Clear the current filter.",
+        Controllers::FilterExpr => {
+            "Filter by a boolean expression over fields, e.g. cpu.usage_pct > 50 and name =~ /foo/. No argument clears it."
+        }
+        Controllers::History => {
+            "Toggle a sparkline of the selected column of the selected row over the last 60 samples, or the given number of samples (cgroup, process and disk only)."
+        }
+        Controllers::Chart => {
+            "Chart up to 4 fields over the last 10m, or the window given with -w, e.g. chart -w 30m cpu.usage_pct. Fields of the selected row are given by name (cgroup, process and disk only), others by full id, e.g. system.cpu.usage_pct. Move the cursor with <Left>/<Right> to move the viewed sample (replay and live-paused mode)."
+        }
         Controllers::JForward => {
             "Jump time by a specific amount forward or to a specific timestamp (replay and live-paused mode)."
         }
@@ -86,20 +107,64 @@ fn get_description(controller: &Controllers) -> &'static str {
         Controllers::NSample => "Show next sample (replay and live-paused mode).",
         Controllers::PSample => "Show previous sample (replay and live-paused mode).",
         Controllers::Pause => {
-            "pause/resume the live mode. While pausing, use the above commands to go forwards or backwards in time"
+            "pause/resume the live mode. While pausing, use the above commands to go forwards or backwards in time. In replay mode, start/stop playback."
+        }
+        Controllers::PlaySpeed => {
+            "Play back at the next of 1x, 2x, 10x and 60x real time, or at the given speed (replay mode)."
+        }
+        Controllers::Seek => {
+            "Jump to the given percentage of the timeline of recorded samples, e.g. seek 40% (replay and live-paused mode)."
+        }
+        Controllers::SeekForward => {
+            "Jump forward by 5%, or by the given percentage, of the timeline of recorded samples (replay and live-paused mode)."
+        }
+        Controllers::SeekBackward => {
+            "Jump backward by 5%, or by the given percentage, of the timeline of recorded samples (replay and live-paused mode)."
+        }
+        Controllers::Bookmark => {
+            "Bookmark the current sample with an optional note, saved in ~/.config/below/bookmarks.toml."
+        }
+        Controllers::Bookmarks => "List bookmarks. Press Enter to jump to one.",
+        Controllers::SaveView => {
+            "Save the view, tab, sort column, filters, collapsed cgroups and selected row under ~/.config/below/views/ by name, and show it as a shareable string. Sort column and filters are saved for the cgroup and process views only."
+        }
+        Controllers::LoadView => {
+            "Load a view saved by name, from a file or from its shareable string, e.g. for below replay --view."
+        }
+        Controllers::Columns => {
+            "Pick and order the columns of the current tab of the cgroup or process view, and save them to belowrc."
         }
         Controllers::Quit => "Quit.",
         Controllers::Help => "Toggle help menu.",
         Controllers::Process => "Show process view.",
         Controllers::Cgroup => "Show cgroup view.",
+        Controllers::CgroupDiff => {
+            "Compare cgroups against the current sample, or the sample at the given time. Press again to go back to cgroup view."
+        }
+        Controllers::Compare => {
+            "Compare cgroups, or processes by comm, side by side with the compare side given to below replay, optionally moving it to the given time first. Press again to go back."
+        }
+        Controllers::CompareLock => {
+            "Lock or unlock the compare side. A locked side moves along with the current one, keeping the time between them."
+        }
+        Controllers::CompareNext => "Move the compare side to its next sample.",
+        Controllers::ComparePrev => "Move the compare side to its previous sample.",
         Controllers::System => "Show system core view.",
+        Controllers::Network => "Show network view.",
+        Controllers::Resctrl => "Show resctrl view. Press Enter to collapse or expand a group.",
         Controllers::Gpu => "Show GPU view.",
         Controllers::GpuZoom => "Zoom into process view filtered by selected GPU.",
         Controllers::GpuProcess => "Zoom into process view for all GPU processes.",
         Controllers::Zoom => {
-            "If in cgroup view, zoom into process view filtered by cgroup. If in process view, zoom into cgroup view, selected on cgroup of process."
+            "If in cgroup view, zoom into process view filtered by cgroup. If in process view, zoom into cgroup view, selected on cgroup of process. If in network view, zoom into the ethtool queues of the interface."
+        }
+        Controllers::ZoomProcs => {
+            "If in cgroup view, zoom into process view filtered by the member processes (cgroup.procs) of the cgroup subtree."
         }
         Controllers::Fold => "Fold processes (post filter) and display aggregated values.",
+        Controllers::Tree => {
+            "Show processes (post filter) as a tree by parent pid, with values aggregated over each subtree. Press Enter to collapse or expand a process."
+        }
         Controllers::NextPage => "Scroll down 15 lines primary display.",
         Controllers::PrevPage => "Scroll up 15 lines primary display.",
         Controllers::Url => "Show Corresponding Below Web URL.",
@@ -113,6 +178,18 @@ fn get_args(controller: &Controllers) -> &'static str {
         Controllers::Filter => "Substring",
         Controllers::JForward => "Time",
         Controllers::JBackward => "Time",
+        Controllers::FilterExpr => "Expression",
+        Controllers::History => "Samples",
+        Controllers::Chart => "Fields",
+        Controllers::CgroupDiff => "Time",
+        Controllers::Compare => "Time",
+        Controllers::PlaySpeed => "Speed",
+        Controllers::Seek => "Percent",
+        Controllers::SeekForward => "Percent",
+        Controllers::SeekBackward => "Percent",
+        Controllers::Bookmark => "Note",
+        Controllers::SaveView => "Name",
+        Controllers::LoadView => "Name",
         _ => "-",
     }
 }
@@ -142,16 +219,24 @@ fn fill_controllers(
             Some(ref mut item) => item.events.push(event.clone()),
             None => drop(cmd_map.insert(
                 controller.clone(),
-                ControllerHelper {
-                    events: vec![event.clone()],
-                    cmd: controller.command(),
-                    cmd_short: controller.cmd_shortcut(),
-                    description: get_description(controller),
-                    args: get_args(controller),
-                },
+                ControllerHelper::new(controller, vec![event.clone()]),
             )),
         }
     }
+    // Commands without a default hot key are only in event_controllers if
+    // the user bound one.
+    cmd_map
+        .entry(Controllers::Chart)
+        .or_insert_with(|| ControllerHelper::new(&Controllers::Chart, vec![]));
+    cmd_map
+        .entry(Controllers::Seek)
+        .or_insert_with(|| ControllerHelper::new(&Controllers::Seek, vec![]));
+    cmd_map
+        .entry(Controllers::SaveView)
+        .or_insert_with(|| ControllerHelper::new(&Controllers::SaveView, vec![]));
+    cmd_map
+        .entry(Controllers::LoadView)
+        .or_insert_with(|| ControllerHelper::new(&Controllers::LoadView, vec![]));
 
     // Unwrap in this vec! must be success, otherwise we may have lost
     // controller(s) and should be detected by unit test.
@@ -170,14 +255,35 @@ fn fill_controllers(
         cmd_map.get(&Controllers::NSample).unwrap().to_string(),
         cmd_map.get(&Controllers::PSample).unwrap().to_string(),
         cmd_map.get(&Controllers::Pause).unwrap().to_string(),
+        cmd_map.get(&Controllers::PlaySpeed).unwrap().to_string(),
+        cmd_map.get(&Controllers::Seek).unwrap().to_string(),
+        cmd_map.get(&Controllers::SeekForward).unwrap().to_string(),
+        cmd_map.get(&Controllers::SeekBackward).unwrap().to_string(),
+        cmd_map.get(&Controllers::Bookmark).unwrap().to_string(),
+        cmd_map.get(&Controllers::Bookmarks).unwrap().to_string(),
+        cmd_map.get(&Controllers::SaveView).unwrap().to_string(),
+        cmd_map.get(&Controllers::LoadView).unwrap().to_string(),
+        cmd_map.get(&Controllers::Columns).unwrap().to_string(),
         cmd_map.get(&Controllers::SortCol).unwrap().to_string(),
         cmd_map.get(&Controllers::Filter).unwrap().to_string(),
         cmd_map.get(&Controllers::CFilter).unwrap().to_string(),
+        cmd_map.get(&Controllers::FilterExpr).unwrap().to_string(),
+        cmd_map.get(&Controllers::History).unwrap().to_string(),
+        cmd_map.get(&Controllers::Chart).unwrap().to_string(),
         cmd_map.get(&Controllers::Zoom).unwrap().to_string(),
+        cmd_map.get(&Controllers::ZoomProcs).unwrap().to_string(),
         cmd_map.get(&Controllers::Fold).unwrap().to_string(),
+        cmd_map.get(&Controllers::Tree).unwrap().to_string(),
         cmd_map.get(&Controllers::Process).unwrap().to_string(),
         cmd_map.get(&Controllers::Cgroup).unwrap().to_string(),
+        cmd_map.get(&Controllers::CgroupDiff).unwrap().to_string(),
+        cmd_map.get(&Controllers::Compare).unwrap().to_string(),
+        cmd_map.get(&Controllers::CompareLock).unwrap().to_string(),
+        cmd_map.get(&Controllers::CompareNext).unwrap().to_string(),
+        cmd_map.get(&Controllers::ComparePrev).unwrap().to_string(),
         cmd_map.get(&Controllers::System).unwrap().to_string(),
+        cmd_map.get(&Controllers::Network).unwrap().to_string(),
+        cmd_map.get(&Controllers::Resctrl).unwrap().to_string(),
         cmd_map.get(&Controllers::NextPage).unwrap().to_string(),
         cmd_map.get(&Controllers::PrevPage).unwrap().to_string(),
     ];
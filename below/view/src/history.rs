@@ -0,0 +1,121 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The history pane of the stats views plots the selected field of the
+//! selected row over the last samples as a sparkline.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use cursive::utils::markup::StyledString;
+use cursive::Cursive;
+use model::Field;
+use model::Model;
+
+use crate::ViewMode;
+use crate::ViewState;
+
+/// Number of samples shown when the history pane is opened without a count
+pub const DEFAULT_HISTORY_SAMPLES: usize = 60;
+
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Models of the last samples up to the current one, oldest first.
+#[derive(Default)]
+pub struct ModelHistory {
+    /// Timestamp of the current sample when the models were fetched
+    timestamp: Option<SystemTime>,
+    count: usize,
+    pub models: Vec<Model>,
+}
+
+/// Returns the models of the last `count` samples up to the current one.
+/// They are only fetched from the store again when the current sample or
+/// the count changes.
+pub fn get_model_history(c: &mut Cursive, count: usize) -> Rc<RefCell<ModelHistory>> {
+    let view_state = c
+        .user_data::<ViewState>()
+        .expect("No data stored in Cursive object!");
+    let history = view_state.history.clone();
+    let timestamp = view_state.timestamp;
+    if history.borrow().timestamp == Some(timestamp) && history.borrow().count == count {
+        return history;
+    }
+
+    let advance = match &view_state.mode {
+        ViewMode::Live(adv) | ViewMode::Pause(adv) | ViewMode::Replay(adv) => adv.clone(),
+    };
+    let models = advance.borrow_mut().get_models_until(timestamp, count);
+    history.replace(ModelHistory {
+        timestamp: Some(timestamp),
+        count,
+        models,
+    });
+    history
+}
+
+/// Renders numeric values as a sparkline scaled between their min and max.
+/// Missing values are left blank.
+pub fn render_sparkline(values: &[Option<f64>]) -> String {
+    let (min, max) = values
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(*v), max.max(*v))
+        });
+    values
+        .iter()
+        .map(|value| match value {
+            Some(v) if max > min => {
+                let idx = ((v - min) / (max - min) * (SPARKLINE_BARS.len() - 1) as f64).round();
+                SPARKLINE_BARS[idx as usize]
+            }
+            Some(_) => SPARKLINE_BARS[0],
+            None => ' ',
+        })
+        .collect()
+}
+
+/// Renders the content of the history pane for the field titled `title`.
+pub fn render_history(title: &str, fields: Vec<Option<Field>>) -> StyledString {
+    let title = title.trim();
+    let values: Vec<Option<f64>> = fields
+        .iter()
+        .map(|field| field.clone().and_then(model::numeric_value))
+        .collect();
+    if values.iter().all(Option::is_none) {
+        return StyledString::plain(format!("History: no numeric history for {}", title));
+    }
+
+    let numeric: Vec<(f64, &Field)> = fields
+        .iter()
+        .flatten()
+        .filter_map(|field| model::numeric_value(field.clone()).map(|v| (v, field)))
+        .collect();
+    let min = numeric.iter().min_by(|a, b| a.0.total_cmp(&b.0));
+    let max = numeric.iter().max_by(|a, b| a.0.total_cmp(&b.0));
+    let summary = |value: Option<&Field>| value.map_or("?".to_owned(), |v| v.to_string());
+    let mut content = StyledString::plain(format!(
+        "History of {} over {} samples: min {} max {} last {}\n",
+        title,
+        fields.len(),
+        summary(min.map(|(_, field)| *field)),
+        summary(max.map(|(_, field)| *field)),
+        summary(fields.last().and_then(Option::as_ref)),
+    ));
+    content.append_plain(render_sparkline(&values));
+    content
+}
+
@@ -26,7 +26,8 @@
 /// |                      Stats View                            |
 ///  ------------------------------------------------------------
 /// ```
-/// * Status Bar: Displays datetime, elapsed time, hostname, and below version.
+/// * Status Bar: Displays datetime, elapsed time, hostname, and below version. In replay and
+///   live-paused mode, it also displays a timeline of the recorded samples.
 /// * System View: Displays overall system stats including cpu, mem, io, iface, transport, and network.
 /// * Stats View: Display the detailed stats. Please check the stats view section for more details.
 ///
@@ -79,12 +80,15 @@ use cursive::views::ScreensView;
 use cursive::Cursive;
 use cursive::CursiveRunnable;
 use cursive::ScreenId;
+use model::CgroupDiffModel;
 use model::CgroupModel;
 #[cfg(fbcode_build)]
 use model::GpuModel;
 use model::Model;
 use model::NetworkModel;
+use model::ProcessDiffModel;
 use model::ProcessModel;
+use model::ResctrlModel;
 use model::SystemModel;
 use store::Advance;
 use toml::value::Value;
@@ -92,17 +96,25 @@ extern crate render as base_render;
 
 open_source_shim!();
 
+mod cgroup_diff_view;
 mod cgroup_tabs;
 pub mod cgroup_view;
+mod chart_view;
 pub mod command_palette;
+pub mod compare;
 mod core_tabs;
 mod core_view;
 mod default_styles;
 mod filter_popup;
 mod help_menu;
+mod history;
+mod network_tabs;
+mod network_view;
+mod process_diff_view;
 mod process_tabs;
 mod process_view;
 mod render;
+mod resctrl_view;
 pub mod stats_view;
 mod status_bar;
 mod system_view;
@@ -110,6 +122,8 @@ mod tab_view;
 
 pub struct View {
     inner: CursiveRunnable,
+    /// View to restore once the views are set up
+    saved_view: Option<saved_view::SavedView>,
 }
 
 macro_rules! advance {
@@ -144,9 +158,15 @@ macro_rules! view_warn {
         let msg = format!($($args)*);
         match state {
             crate::MainViewState::Cgroup => crate::cgroup_view::ViewType::cp_warn($c, &msg),
+            crate::MainViewState::CgroupDiff =>
+                crate::cgroup_diff_view::ViewType::cp_warn($c, &msg),
             crate::MainViewState::Process(_) =>
                 crate::process_view::ViewType::cp_warn($c, &msg),
+            crate::MainViewState::ProcessDiff =>
+                crate::process_diff_view::ViewType::cp_warn($c, &msg),
             crate::MainViewState::Core => crate::core_view::ViewType::cp_warn($c, &msg),
+            crate::MainViewState::Network => crate::network_view::ViewType::cp_warn($c, &msg),
+            crate::MainViewState::Resctrl => crate::resctrl_view::ViewType::cp_warn($c, &msg),
             #[cfg(fbcode_build)]
             crate::MainViewState::Gpu => crate::gpu_view::ViewType::cp_warn($c, &msg),
         }
@@ -156,8 +176,11 @@ macro_rules! view_warn {
 // controllers depends on Advance
 pub mod controllers;
 pub mod viewrc;
-// Jump popup depends on view_warn
+// Modules below depend on view_warn
+mod column_picker;
 mod jump_popup;
+mod playback;
+pub mod saved_view;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ProcessZoomState {
@@ -169,8 +192,12 @@ pub enum ProcessZoomState {
 #[derive(Clone, Debug, PartialEq)]
 pub enum MainViewState {
     Cgroup,
+    CgroupDiff,
     Process(ProcessZoomState),
+    ProcessDiff,
     Core,
+    Network,
+    Resctrl,
     #[cfg(fbcode_build)]
     Gpu,
 }
@@ -203,8 +230,12 @@ fn refresh(c: &mut Cursive) {
         .clone();
     match current_state {
         MainViewState::Cgroup => cgroup_view::CgroupView::refresh(c),
+        MainViewState::CgroupDiff => cgroup_diff_view::CgroupDiffView::refresh(c),
         MainViewState::Process(_) => process_view::ProcessView::refresh(c),
+        MainViewState::ProcessDiff => process_diff_view::ProcessDiffView::refresh(c),
         MainViewState::Core => core_view::CoreView::refresh(c),
+        MainViewState::Network => network_view::NetworkView::refresh(c),
+        MainViewState::Resctrl => resctrl_view::ResctrlView::refresh(c),
         #[cfg(fbcode_build)]
         MainViewState::Gpu => gpu_view::GpuView::refresh(c),
     }
@@ -241,8 +272,22 @@ pub struct ViewState {
     pub timestamp: SystemTime,
     pub system: Rc<RefCell<SystemModel>>,
     pub cgroup: Rc<RefCell<CgroupModel>>,
+    /// Difference between the current cgroup tree and cgroup_diff_base
+    pub cgroup_diff: Rc<RefCell<CgroupDiffModel>>,
+    /// Cgroup tree the current sample is compared to in cgroup diff view
+    pub cgroup_diff_base: Option<CgroupModel>,
     pub process: Rc<RefCell<ProcessModel>>,
+    /// Difference between the current processes and those of the compare
+    /// side
+    pub process_diff: Rc<RefCell<ProcessDiffModel>>,
+    /// Second source of samples in compare mode
+    pub compare: Option<compare::Compare>,
     pub network: Rc<RefCell<NetworkModel>>,
+    pub resctrl: Rc<RefCell<Option<ResctrlModel>>>,
+    /// Models of the last samples for the history pane
+    pub history: Rc<RefCell<history::ModelHistory>>,
+    /// Autoplay state and timeline of replay
+    pub playback: Rc<RefCell<playback::Playback>>,
     #[cfg(fbcode_build)]
     pub gpu: Rc<RefCell<Option<GpuModel>>>,
     pub main_view_state: MainViewState,
@@ -263,8 +308,27 @@ impl ViewState {
         self.cgroup.replace(model.cgroup);
         self.process.replace(model.process);
         self.network.replace(model.network);
+        self.resctrl.replace(model.resctrl);
         #[cfg(fbcode_build)]
         self.gpu.replace(model.gpu);
+        self.update_diffs();
+    }
+
+    /// Compares the current sample to the compare side if the diff views
+    /// show compare mode, or else to cgroup_diff_base.
+    pub fn update_diffs(&mut self) {
+        if let Some(compare) = self.compare.as_mut().filter(|compare| compare.active) {
+            compare.follow(self.timestamp);
+            self.cgroup_diff_base = Some(compare.cgroup.clone());
+            self.process_diff.replace(ProcessDiffModel::new(
+                &compare.process,
+                &self.process.borrow(),
+            ));
+        }
+        if let Some(base) = &self.cgroup_diff_base {
+            self.cgroup_diff
+                .replace(CgroupDiffModel::new(base, &self.cgroup.borrow()));
+        }
     }
 
     pub fn new_with_advance(main_view_state: MainViewState, model: Model, mode: ViewMode) -> Self {
@@ -274,8 +338,15 @@ impl ViewState {
             timestamp: model.timestamp,
             system: Rc::new(RefCell::new(model.system)),
             cgroup: Rc::new(RefCell::new(model.cgroup)),
+            cgroup_diff: Default::default(),
+            cgroup_diff_base: None,
             process: Rc::new(RefCell::new(model.process)),
+            process_diff: Default::default(),
+            compare: None,
             network: Rc::new(RefCell::new(model.network)),
+            resctrl: Rc::new(RefCell::new(model.resctrl)),
+            history: Default::default(),
+            playback: Default::default(),
             #[cfg(fbcode_build)]
             gpu: Rc::new(RefCell::new(model.gpu)),
             main_view_state,
@@ -286,6 +357,16 @@ impl ViewState {
         }
     }
 
+    pub fn set_cgroup_diff_base(&mut self, base: CgroupModel) {
+        // A fixed base replaces the compare side
+        if let Some(compare) = self.compare.as_mut() {
+            compare.active = false;
+        }
+        self.cgroup_diff
+            .replace(CgroupDiffModel::new(&base, &self.cgroup.borrow()));
+        self.cgroup_diff_base = Some(base);
+    }
+
     pub fn view_mode_str(&self) -> &'static str {
         match self.mode {
             ViewMode::Live(_) => "live",
@@ -317,7 +398,23 @@ impl View {
             model,
             mode,
         ));
-        View { inner }
+        View {
+            inner,
+            saved_view: None,
+        }
+    }
+
+    /// Restore the saved view, e.g. from `below replay --view`, on start.
+    pub fn set_saved_view(&mut self, saved_view: saved_view::SavedView) {
+        self.saved_view = Some(saved_view);
+    }
+
+    /// Open compare mode against the given second source of samples.
+    pub fn set_compare(&mut self, compare: compare::Compare) {
+        self.inner
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive object!")
+            .compare = Some(compare);
     }
 
     pub fn cb_sink(&mut self) -> &::cursive::CbSink {
@@ -353,6 +450,7 @@ impl View {
 
     pub fn run(&mut self) -> Result<()> {
         let (viewrc, viewrc_error) = viewrc::ViewRc::new();
+        default_styles::set_highlight_rules(viewrc.get_highlight_rules());
 
         let mut theme = self.inner.current_theme().clone();
         theme.palette[PaletteColor::Background] = Color::TerminalDefault;
@@ -392,6 +490,7 @@ impl View {
                 c.on_event(Event::WindowResize);
             });
         self.inner.add_global_callback(Event::Refresh, |c| {
+            playback::tick(c);
             refresh(c);
         });
         self.inner.add_global_callback(Event::CtrlChar('r'), |c| {
@@ -405,8 +504,12 @@ impl View {
         let status_bar = status_bar::new(&mut self.inner);
         let system_view = system_view::new(&mut self.inner);
         let cgroup_view = cgroup_view::CgroupView::new(&mut self.inner, &viewrc);
-        let process_view = process_view::ProcessView::new(&mut self.inner);
+        let cgroup_diff_view = cgroup_diff_view::CgroupDiffView::new(&mut self.inner, &viewrc);
+        let process_view = process_view::ProcessView::new(&mut self.inner, &viewrc);
+        let process_diff_view = process_diff_view::ProcessDiffView::new(&mut self.inner);
         let core_view = core_view::CoreView::new(&mut self.inner);
+        let network_view = network_view::NetworkView::new(&mut self.inner);
+        let resctrl_view = resctrl_view::ResctrlView::new(&mut self.inner);
         #[cfg(fbcode_build)]
         let gpu_view = gpu_view::GpuView::new(&mut self.inner);
 
@@ -420,16 +523,40 @@ impl View {
             "cgroup_view_panel".to_owned(),
             screens_view.add_screen(BoxedView::boxed(ResizedView::with_full_screen(cgroup_view))),
         );
+        main_view_screens.insert(
+            "cgroup_diff_view_panel".to_owned(),
+            screens_view.add_screen(BoxedView::boxed(ResizedView::with_full_screen(
+                cgroup_diff_view,
+            ))),
+        );
         main_view_screens.insert(
             "process_view_panel".to_owned(),
             screens_view.add_screen(BoxedView::boxed(ResizedView::with_full_screen(
                 process_view,
             ))),
         );
+        main_view_screens.insert(
+            "process_diff_view_panel".to_owned(),
+            screens_view.add_screen(BoxedView::boxed(ResizedView::with_full_screen(
+                process_diff_view,
+            ))),
+        );
         main_view_screens.insert(
             "core_view_panel".to_owned(),
             screens_view.add_screen(BoxedView::boxed(ResizedView::with_full_screen(core_view))),
         );
+        main_view_screens.insert(
+            "network_view_panel".to_owned(),
+            screens_view.add_screen(BoxedView::boxed(ResizedView::with_full_screen(
+                network_view,
+            ))),
+        );
+        main_view_screens.insert(
+            "resctrl_view_panel".to_owned(),
+            screens_view.add_screen(BoxedView::boxed(ResizedView::with_full_screen(
+                resctrl_view,
+            ))),
+        );
         #[cfg(fbcode_build)]
         main_view_screens.insert(
             "gpu_view_panel".to_owned(),
@@ -484,6 +611,12 @@ impl View {
             let c = &mut self.inner;
             view_warn!(c, "{}", msg);
         }
+        if let Some(saved) = self.saved_view.take() {
+            let c = &mut self.inner;
+            if let Err(e) = saved_view::restore(c, &saved) {
+                view_warn!(c, "Failed to restore saved view: {}", e);
+            }
+        }
         self.inner.run();
 
         Ok(())
@@ -537,8 +670,12 @@ pub mod fake_view {
             );
             user_data.main_view_screens = [
                 ("cgroup_view_panel".to_owned(), 0),
+                ("cgroup_diff_view_panel".to_owned(), 0),
                 ("process_view_panel".to_owned(), 0),
+                ("process_diff_view_panel".to_owned(), 0),
                 ("core_view_panel".to_owned(), 0),
+                ("network_view_panel".to_owned(), 0),
+                ("resctrl_view_panel".to_owned(), 0),
             ]
             .into();
             inner.set_user_data(user_data);
@@ -0,0 +1,355 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use base_render::get_fixed_width;
+use base_render::HasRenderConfig;
+use base_render::RenderConfigBuilder as Rc;
+use cursive::utils::markup::StyledString;
+use enum_iterator::Sequence;
+use model::Queriable;
+use model::SingleNetModel;
+use model::SingleQueueModel;
+
+use crate::network_view::NetworkState;
+use crate::network_view::NetworkStateFieldId;
+use crate::render::HasViewStyle;
+use crate::render::ViewItem;
+use crate::stats_view::ColumnTitles;
+use crate::stats_view::StateCommon;
+
+const FIELD_NAME_WIDTH: usize = 20;
+const FIELD_WIDTH: usize = 20;
+
+pub trait NetworkTab {
+    fn get_titles(&self) -> ColumnTitles {
+        ColumnTitles {
+            titles: vec![
+                get_fixed_width("Field", FIELD_NAME_WIDTH),
+                get_fixed_width("Value", FIELD_WIDTH),
+            ],
+            pinned_titles: 1,
+        }
+    }
+
+    fn get_rows(&self, state: &NetworkState, offset: Option<usize>) -> Vec<(StyledString, String)>;
+}
+
+/// Renders a (Field, Value) line per field of the model.
+fn get_field_lines<T>(model: &T) -> impl Iterator<Item = StyledString> + '_
+where
+    T: Queriable + HasRenderConfig + HasViewStyle,
+    T::FieldId: Sequence,
+{
+    enum_iterator::all::<T::FieldId>().map(move |field_id| {
+        let mut line = StyledString::new();
+        let item = ViewItem::from_default(field_id).update(Rc::new().width(FIELD_NAME_WIDTH));
+        line.append_plain(item.config.render_title());
+        line.append_plain(" ");
+        line.append(item.update(Rc::new().width(FIELD_WIDTH)).render(model));
+        line
+    })
+}
+
+/// Keeps the lines containing the filter, if any.
+fn filter_field_lines(
+    state: &NetworkState,
+    lines: impl Iterator<Item = StyledString>,
+) -> Vec<(StyledString, String)> {
+    lines
+        .filter(|s| {
+            if let Some((_, filter)) = &state.filter_info {
+                s.source().contains(filter)
+            } else {
+                true
+            }
+        })
+        .map(|s| (s, "".into()))
+        .collect()
+}
+
+/// Interfaces passing the filter expression.
+fn get_ifaces<'a>(state: &NetworkState, model: &'a model::NetworkModel) -> Vec<&'a SingleNetModel> {
+    model
+        .interfaces
+        .values()
+        .filter(|snm| {
+            state
+                .filter_expr
+                .as_ref()
+                .map_or(true, |expr| expr.eval(snm))
+        })
+        .collect()
+}
+
+/// Renders corresponding Fields From SingleNetModel.
+type IfaceViewItem = ViewItem<model::SingleNetModelFieldId>;
+
+#[derive(Clone)]
+pub struct NetworkIface {
+    pub view_items: Vec<IfaceViewItem>,
+}
+
+impl NetworkIface {
+    fn new(view_items: Vec<IfaceViewItem>) -> Self {
+        Self { view_items }
+    }
+}
+
+impl NetworkTab for NetworkIface {
+    fn get_titles(&self) -> ColumnTitles {
+        ColumnTitles {
+            titles: self
+                .view_items
+                .iter()
+                .map(|item| item.config.render_title())
+                .collect(),
+            pinned_titles: 1,
+        }
+    }
+
+    fn get_rows(&self, state: &NetworkState, offset: Option<usize>) -> Vec<(StyledString, String)> {
+        let model = state.get_model();
+        let mut ifaces = get_ifaces(state, &model);
+
+        if let Some(NetworkStateFieldId::Iface(sort_order)) = state.sort_order.as_ref() {
+            model::sort_queriables(&mut ifaces, sort_order, state.reverse);
+        }
+
+        ifaces
+            .iter()
+            .filter(|snm| {
+                if let Some((NetworkStateFieldId::Iface(field), filter)) = &state.filter_info {
+                    match snm.query(field) {
+                        None => true,
+                        Some(value) => value.to_string().contains(filter),
+                    }
+                } else {
+                    true
+                }
+            })
+            .map(|snm| {
+                let mut line = StyledString::new();
+                for item in self
+                    .view_items
+                    .iter()
+                    .take(1)
+                    .chain(self.view_items.iter().skip(1 + offset.unwrap_or(0)))
+                {
+                    line.append(item.render(snm));
+                    line.append_plain(" ");
+                }
+                (line, snm.interface.clone())
+            })
+            .collect()
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct NetworkTransport;
+
+impl NetworkTab for NetworkTransport {
+    fn get_rows(
+        &self,
+        state: &NetworkState,
+        _offset: Option<usize>,
+    ) -> Vec<(StyledString, String)> {
+        let model = state.get_model();
+        filter_field_lines(
+            state,
+            get_field_lines(&model.tcp)
+                .chain(get_field_lines(&model.udp))
+                .chain(get_field_lines(&model.udp6)),
+        )
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct NetworkIp;
+
+impl NetworkTab for NetworkIp {
+    fn get_rows(
+        &self,
+        state: &NetworkState,
+        _offset: Option<usize>,
+    ) -> Vec<(StyledString, String)> {
+        let model = state.get_model();
+        filter_field_lines(
+            state,
+            get_field_lines(&model.ip)
+                .chain(get_field_lines(&model.ip6))
+                .chain(get_field_lines(&model.icmp))
+                .chain(get_field_lines(&model.icmp6)),
+        )
+    }
+}
+
+/// Renders corresponding Fields From SingleQueueModel.
+type QueueViewItem = ViewItem<model::SingleQueueModelFieldId>;
+
+#[derive(Clone)]
+pub struct NetworkEthtool {
+    pub view_items: Vec<QueueViewItem>,
+}
+
+impl NetworkEthtool {
+    fn new(view_items: Vec<QueueViewItem>) -> Self {
+        Self { view_items }
+    }
+}
+
+impl NetworkTab for NetworkEthtool {
+    fn get_titles(&self) -> ColumnTitles {
+        ColumnTitles {
+            titles: self
+                .view_items
+                .iter()
+                .map(|item| item.config.render_title())
+                .collect(),
+            pinned_titles: 1,
+        }
+    }
+
+    fn get_rows(&self, state: &NetworkState, offset: Option<usize>) -> Vec<(StyledString, String)> {
+        let model = state.get_model();
+        // The filter expression and zoom select queues by their interface
+        let mut queues: Vec<&SingleQueueModel> = get_ifaces(state, &model)
+            .into_iter()
+            .filter(|snm| {
+                state
+                    .iface_filter
+                    .as_ref()
+                    .map_or(true, |iface| &snm.interface == iface)
+            })
+            .flat_map(|snm| snm.queues.iter())
+            .collect();
+
+        if let Some(NetworkStateFieldId::Queue(sort_order)) = state.sort_order.as_ref() {
+            model::sort_queriables(&mut queues, sort_order, state.reverse);
+        }
+
+        queues
+            .iter()
+            .filter(|sqm| {
+                if let Some((NetworkStateFieldId::Queue(field), filter)) = &state.filter_info {
+                    match sqm.query(field) {
+                        None => true,
+                        Some(value) => value.to_string().contains(filter),
+                    }
+                } else {
+                    true
+                }
+            })
+            .map(|sqm| {
+                let mut line = StyledString::new();
+                for item in self
+                    .view_items
+                    .iter()
+                    .take(1)
+                    .chain(self.view_items.iter().skip(1 + offset.unwrap_or(0)))
+                {
+                    line.append(item.render(sqm));
+                    line.append_plain(" ");
+                }
+                (line, format!("{}/{}", sqm.interface, sqm.queue_id))
+            })
+            .collect()
+    }
+}
+
+pub mod default_tabs {
+    use model::SingleNetModelFieldId::Collisions;
+    use model::SingleNetModelFieldId::Interface;
+    use model::SingleNetModelFieldId::Multicast;
+    use model::SingleNetModelFieldId::RxBytes;
+    use model::SingleNetModelFieldId::RxBytesPerSec;
+    use model::SingleNetModelFieldId::RxCompressed;
+    use model::SingleNetModelFieldId::RxCrcErrors;
+    use model::SingleNetModelFieldId::RxDropped;
+    use model::SingleNetModelFieldId::RxErrors;
+    use model::SingleNetModelFieldId::RxFifoErrors;
+    use model::SingleNetModelFieldId::RxFrameErrors;
+    use model::SingleNetModelFieldId::RxLengthErrors;
+    use model::SingleNetModelFieldId::RxMissedErrors;
+    use model::SingleNetModelFieldId::RxNohandler;
+    use model::SingleNetModelFieldId::RxOverErrors;
+    use model::SingleNetModelFieldId::RxPackets;
+    use model::SingleNetModelFieldId::RxPacketsPerSec;
+    use model::SingleNetModelFieldId::ThroughputPerSec;
+    use model::SingleNetModelFieldId::TxAbortedErrors;
+    use model::SingleNetModelFieldId::TxBytes;
+    use model::SingleNetModelFieldId::TxBytesPerSec;
+    use model::SingleNetModelFieldId::TxCarrierErrors;
+    use model::SingleNetModelFieldId::TxCompressed;
+    use model::SingleNetModelFieldId::TxDropped;
+    use model::SingleNetModelFieldId::TxErrors;
+    use model::SingleNetModelFieldId::TxFifoErrors;
+    use model::SingleNetModelFieldId::TxHeartbeatErrors;
+    use model::SingleNetModelFieldId::TxPackets;
+    use model::SingleNetModelFieldId::TxPacketsPerSec;
+    use model::SingleNetModelFieldId::TxTimeoutPerSec;
+    use model::SingleNetModelFieldId::TxWindowErrors;
+    use model::SingleQueueModelFieldId;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    pub static NETWORK_IFACE_TAB: Lazy<NetworkIface> = Lazy::new(|| {
+        NetworkIface::new(vec![
+            ViewItem::from_default(Interface),
+            ViewItem::from_default(RxBytesPerSec),
+            ViewItem::from_default(TxBytesPerSec),
+            ViewItem::from_default(ThroughputPerSec),
+            ViewItem::from_default(RxPacketsPerSec),
+            ViewItem::from_default(TxPacketsPerSec),
+            ViewItem::from_default(TxTimeoutPerSec),
+            ViewItem::from_default(RxErrors),
+            ViewItem::from_default(TxErrors),
+            ViewItem::from_default(RxDropped),
+            ViewItem::from_default(TxDropped),
+            ViewItem::from_default(Collisions),
+            ViewItem::from_default(Multicast),
+            ViewItem::from_default(RxBytes),
+            ViewItem::from_default(TxBytes),
+            ViewItem::from_default(RxPackets),
+            ViewItem::from_default(TxPackets),
+            ViewItem::from_default(RxCompressed),
+            ViewItem::from_default(TxCompressed),
+            ViewItem::from_default(RxCrcErrors),
+            ViewItem::from_default(RxFifoErrors),
+            ViewItem::from_default(RxFrameErrors),
+            ViewItem::from_default(RxLengthErrors),
+            ViewItem::from_default(RxMissedErrors),
+            ViewItem::from_default(RxNohandler),
+            ViewItem::from_default(RxOverErrors),
+            ViewItem::from_default(TxAbortedErrors),
+            ViewItem::from_default(TxCarrierErrors),
+            ViewItem::from_default(TxFifoErrors),
+            ViewItem::from_default(TxHeartbeatErrors),
+            ViewItem::from_default(TxWindowErrors),
+        ])
+    });
+
+    pub static NETWORK_ETHTOOL_TAB: Lazy<NetworkEthtool> = Lazy::new(|| {
+        NetworkEthtool::new(vec![
+            ViewItem::from_default(SingleQueueModelFieldId::Interface),
+            ViewItem::from_default(SingleQueueModelFieldId::QueueId),
+            ViewItem::from_default(SingleQueueModelFieldId::RxBytesPerSec),
+            ViewItem::from_default(SingleQueueModelFieldId::TxBytesPerSec),
+            ViewItem::from_default(SingleQueueModelFieldId::RxCountPerSec),
+            ViewItem::from_default(SingleQueueModelFieldId::TxCountPerSec),
+            ViewItem::from_default(SingleQueueModelFieldId::TxMissedTx),
+            ViewItem::from_default(SingleQueueModelFieldId::TxUnmaskInterrupt),
+        ])
+    });
+}
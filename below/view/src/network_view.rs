@@ -0,0 +1,296 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Ref;
+use std::cell::RefCell;
+use std::cell::RefMut;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use cursive::utils::markup::StyledString;
+use cursive::view::Nameable;
+use cursive::views::NamedView;
+use cursive::views::SelectView;
+use cursive::views::ViewRef;
+use cursive::Cursive;
+use model::FilterExpr;
+use model::NetworkModel;
+use model::SingleNetModelFieldId;
+use model::SingleQueueModelFieldId;
+
+use crate::network_tabs::default_tabs::NETWORK_ETHTOOL_TAB;
+use crate::network_tabs::default_tabs::NETWORK_IFACE_TAB;
+use crate::network_tabs::*;
+use crate::render::parse_filter_expr;
+use crate::stats_view::ColumnTitles;
+use crate::stats_view::StateCommon;
+use crate::stats_view::StatsView;
+use crate::stats_view::ViewBridge;
+use crate::ViewState;
+
+pub type ViewType = StatsView<NetworkView>;
+
+#[derive(Default)]
+pub struct NetworkState {
+    pub filter_info: Option<(NetworkStateFieldId, String)>,
+    /// Interface whose queues the Ethtool tab is zoomed into
+    pub iface_filter: Option<String>,
+    pub filter_expr: Option<FilterExpr<SingleNetModelFieldId>>,
+    pub sort_order: Option<NetworkStateFieldId>,
+    pub reverse: bool,
+    pub model: Rc<RefCell<NetworkModel>>,
+}
+
+#[derive(PartialEq)]
+pub enum NetworkStateFieldId {
+    Iface(SingleNetModelFieldId),
+    Queue(SingleQueueModelFieldId),
+    /// The 'Field' column of the Transport and IP tabs
+    Field,
+}
+
+impl std::string::ToString for NetworkStateFieldId {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Iface(field) => field.to_string(),
+            Self::Queue(field) => field.to_string(),
+            Self::Field => "field".to_owned(),
+        }
+    }
+}
+
+impl StateCommon for NetworkState {
+    type ModelType = NetworkModel;
+    type TagType = NetworkStateFieldId;
+    type KeyType = String;
+
+    fn get_filter_info(&self) -> &Option<(Self::TagType, String)> {
+        &self.filter_info
+    }
+
+    fn is_filter_supported_from_tab_idx(&self, _tab: &str, idx: usize) -> bool {
+        // Interface name for the Iface and Ethtool tabs, field name otherwise
+        idx == 0
+    }
+
+    fn get_tag_from_tab_idx(&self, tab: &str, idx: usize) -> Self::TagType {
+        match tab {
+            "Iface" => NetworkStateFieldId::Iface(
+                NETWORK_IFACE_TAB
+                    .view_items
+                    .get(idx)
+                    .expect("Out of title scope")
+                    .field_id
+                    .to_owned(),
+            ),
+            "Ethtool" => NetworkStateFieldId::Queue(
+                NETWORK_ETHTOOL_TAB
+                    .view_items
+                    .get(idx)
+                    .expect("Out of title scope")
+                    .field_id
+                    .to_owned(),
+            ),
+            "Transport" | "IP" => NetworkStateFieldId::Field,
+            _ => panic!("bug: got unsupported tab {}", tab),
+        }
+    }
+
+    fn set_filter_from_tab_idx(&mut self, tab: &str, idx: usize, filter: Option<String>) -> bool {
+        if !self.is_filter_supported_from_tab_idx(tab, idx) {
+            return false;
+        }
+
+        if let Some(filter_text) = filter {
+            let title = self.get_tag_from_tab_idx(tab, idx);
+            self.filter_info = Some((title, filter_text));
+        } else {
+            self.filter_info = None;
+        }
+        true
+    }
+
+    fn set_filter_expr(&mut self, expr: Option<&str>) -> Result<(), String> {
+        self.filter_expr = expr.map(parse_filter_expr).transpose()?;
+        Ok(())
+    }
+
+    fn set_sort_tag(&mut self, sort_order: Self::TagType, reverse: &mut bool) -> bool {
+        let sort_order = Some(sort_order);
+        if self.sort_order == sort_order {
+            *reverse = !*reverse;
+        } else {
+            *reverse = true;
+            self.sort_order = sort_order;
+        }
+        self.reverse = *reverse;
+        true
+    }
+
+    fn set_sort_tag_from_tab_idx(&mut self, tab: &str, idx: usize, reverse: &mut bool) -> bool {
+        match tab {
+            "Iface" | "Ethtool" => {
+                let sort_order = self.get_tag_from_tab_idx(tab, idx);
+                self.set_sort_tag(sort_order, reverse)
+            }
+            // This is to notify that tab is not currently sortable
+            _ => false,
+        }
+    }
+
+    fn set_sort_string(&mut self, selection: &str, reverse: &mut bool) -> bool {
+        use std::str::FromStr;
+        // Interface fields take precedence as they share names with queue fields
+        if let Ok(field_id) = SingleNetModelFieldId::from_str(selection) {
+            return self.set_sort_tag(NetworkStateFieldId::Iface(field_id), reverse);
+        }
+        match SingleQueueModelFieldId::from_str(selection) {
+            Ok(field_id) => self.set_sort_tag(NetworkStateFieldId::Queue(field_id), reverse),
+            Err(_) => false,
+        }
+    }
+
+    fn get_model(&self) -> Ref<Self::ModelType> {
+        self.model.borrow()
+    }
+
+    fn get_model_mut(&self) -> RefMut<Self::ModelType> {
+        self.model.borrow_mut()
+    }
+
+    fn new(model: Rc<RefCell<Self::ModelType>>) -> Self {
+        Self {
+            model,
+            ..Default::default()
+        }
+    }
+}
+
+pub enum NetworkView {
+    Iface(&'static NetworkIface),
+    Transport(NetworkTransport),
+    Ip(NetworkIp),
+    Ethtool(&'static NetworkEthtool),
+}
+
+impl NetworkView {
+    pub fn new(c: &mut Cursive) -> NamedView<ViewType> {
+        let list = SelectView::<String>::new();
+        let tabs = vec![
+            "Iface".into(),
+            "Transport".into(),
+            "IP".into(),
+            "Ethtool".into(),
+        ];
+        let mut tabs_map: HashMap<String, NetworkView> = HashMap::new();
+        tabs_map.insert("Iface".into(), NetworkView::Iface(&*NETWORK_IFACE_TAB));
+        tabs_map.insert(
+            "Transport".into(),
+            NetworkView::Transport(Default::default()),
+        );
+        tabs_map.insert("IP".into(), NetworkView::Ip(Default::default()));
+        tabs_map.insert(
+            "Ethtool".into(),
+            NetworkView::Ethtool(&*NETWORK_ETHTOOL_TAB),
+        );
+        let user_data = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive Object!");
+        StatsView::new(
+            "network",
+            tabs,
+            tabs_map,
+            list,
+            NetworkState::new(user_data.network.clone()),
+            user_data.event_controllers.clone(),
+            user_data.cmd_controllers.clone(),
+        )
+        .feed_data(c)
+        .with_name(Self::get_view_name())
+    }
+
+    pub fn get_network_view(c: &mut Cursive) -> ViewRef<ViewType> {
+        ViewType::get_view(c)
+    }
+
+    pub fn refresh(c: &mut Cursive) {
+        Self::get_network_view(c).refresh(c);
+    }
+
+    /// Zooms from the selected interface of the Iface tab into its queues in
+    /// the Ethtool tab, or back out of it.
+    pub fn toggle_zoom(c: &mut Cursive) {
+        let mut view = Self::get_network_view(c);
+        let zoomed = view.state.borrow().iface_filter.is_some();
+        let next_tab = if zoomed {
+            view.state.borrow_mut().iface_filter = None;
+            "Iface"
+        } else {
+            if view.get_tab_view().get_cur_selected() != "Iface" {
+                return;
+            }
+            let selection = view.get_detail_view().selection();
+            match selection {
+                Some(iface) => view.state.borrow_mut().iface_filter = Some(iface.to_string()),
+                // No interface to zoom into
+                None => return,
+            }
+            "Ethtool"
+        };
+
+        while view.get_tab_view().get_cur_selected() != next_tab {
+            view.get_tab_view().on_tab();
+        }
+        view.update_title();
+    }
+
+    fn get_inner(&self) -> &dyn NetworkTab {
+        match self {
+            Self::Iface(inner) => *inner,
+            Self::Transport(inner) => inner,
+            Self::Ip(inner) => inner,
+            Self::Ethtool(inner) => *inner,
+        }
+    }
+}
+
+impl ViewBridge for NetworkView {
+    type StateType = NetworkState;
+    fn get_view_name() -> &'static str {
+        "network_view"
+    }
+    fn get_titles(&self) -> ColumnTitles {
+        self.get_inner().get_titles()
+    }
+
+    fn get_rows(
+        &mut self,
+        state: &Self::StateType,
+        offset: Option<usize>,
+    ) -> Vec<(StyledString, String)> {
+        self.get_inner().get_rows(state, offset)
+    }
+
+    fn on_select_update_cmd_palette(
+        state: &Self::StateType,
+        selected_key: &String,
+        _current_tab: &str,
+        _selected_column: usize,
+    ) -> String {
+        match &state.iface_filter {
+            Some(iface) => format!("{} (zoomed into {})", selected_key, iface),
+            None => selected_key.clone(),
+        }
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replay can play the recorded samples back at a multiple of real time.
+//! The status bar then shows a timeline of the recorded samples with the
+//! position of the current one, which can be moved with the seek commands.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use chrono::DateTime;
+use chrono::Local;
+use cursive::theme::BaseColor;
+use cursive::theme::Color;
+use cursive::theme::Effect;
+use cursive::utils::markup::StyledString;
+use cursive::Cursive;
+use store::Advance;
+
+use crate::ViewMode;
+use crate::ViewState;
+
+/// Speeds cycled through by the play_speed command, as multiples of real time
+pub const PLAYBACK_SPEEDS: [u32; 4] = [1, 2, 10, 60];
+
+/// Percentage of the recorded time seeked by seek_forward and seek_backward
+/// without an argument
+pub const DEFAULT_SEEK_PERCENT: f64 = 5.0;
+
+/// Samples further apart than this are shown as a gap on the timeline
+const MAX_SAMPLE_GAP: Duration = Duration::from_secs(60);
+
+const TIMELINE_TIME_FORMAT: &str = "%m/%d %H:%M";
+
+/// Where playback is in recorded time.
+struct Playhead {
+    timestamp: SystemTime,
+    /// Timestamp of the sample shown when the playhead was last moved, so
+    /// that we notice when other commands move the current sample
+    shown: SystemTime,
+    last_tick: Instant,
+}
+
+/// Timeline of the recorded samples, see Advance::get_sample_coverage.
+struct Timeline {
+    start: SystemTime,
+    end: SystemTime,
+    covered: Vec<bool>,
+}
+
+impl Timeline {
+    fn position(&self, timestamp: SystemTime) -> usize {
+        let span = self.end.duration_since(self.start).unwrap_or_default();
+        let offset = timestamp.duration_since(self.start).unwrap_or_default();
+        if span.is_zero() || self.covered.is_empty() {
+            return 0;
+        }
+        ((offset.as_secs_f64() / span.as_secs_f64() * self.covered.len() as f64) as usize)
+            .min(self.covered.len() - 1)
+    }
+}
+
+pub struct Playback {
+    speed: u32,
+    /// None while paused
+    playhead: Option<Playhead>,
+    /// Cached since it needs to read a sample for each part of the timeline
+    timeline: Option<Timeline>,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            speed: PLAYBACK_SPEEDS[0],
+            playhead: None,
+            timeline: None,
+        }
+    }
+}
+
+impl Playback {
+    pub fn is_playing(&self) -> bool {
+        self.playhead.is_some()
+    }
+
+    pub fn get_status_str(&self) -> String {
+        if self.is_playing() {
+            format!("playing {}x", self.speed)
+        } else {
+            format!("paused {}x", self.speed)
+        }
+    }
+}
+
+fn get_replay_advance(c: &mut Cursive) -> Option<Rc<RefCell<Advance>>> {
+    match &c.user_data::<ViewState>().expect("user data not set").mode {
+        ViewMode::Replay(adv) => Some(adv.clone()),
+        _ => None,
+    }
+}
+
+fn start(c: &mut Cursive) {
+    let view_state = c.user_data::<ViewState>().expect("user data not set");
+    view_state.playback.borrow_mut().playhead = Some(Playhead {
+        timestamp: view_state.timestamp,
+        shown: view_state.timestamp,
+        last_tick: Instant::now(),
+    });
+}
+
+/// Starts or stops playback (replay mode only).
+pub fn toggle(c: &mut Cursive) {
+    if get_replay_advance(c).is_none() {
+        return;
+    }
+    let playback = c
+        .user_data::<ViewState>()
+        .expect("user data not set")
+        .playback
+        .clone();
+    if playback.borrow().is_playing() {
+        playback.borrow_mut().playhead = None;
+    } else {
+        start(c);
+    }
+}
+
+/// Starts playback at the given speed, or at the next one of PLAYBACK_SPEEDS
+/// if None (replay mode only).
+pub fn set_speed(c: &mut Cursive, speed: Option<u32>) {
+    if get_replay_advance(c).is_none() {
+        view_warn!(c, "Playback is only available in replay mode");
+        return;
+    }
+    let playback = c
+        .user_data::<ViewState>()
+        .expect("user data not set")
+        .playback
+        .clone();
+    let speed = speed.unwrap_or_else(|| {
+        let current = playback.borrow().speed;
+        PLAYBACK_SPEEDS
+            .iter()
+            .find(|speed| **speed > current)
+            .copied()
+            .unwrap_or(PLAYBACK_SPEEDS[0])
+    });
+    playback.borrow_mut().speed = speed;
+    if !playback.borrow().is_playing() {
+        start(c);
+    }
+}
+
+/// Moves the playhead by the real time passed since the last tick times the
+/// speed, and shows the last sample it passed. Gaps in the recorded data
+/// are skipped.
+pub fn tick(c: &mut Cursive) {
+    let adv = match get_replay_advance(c) {
+        Some(adv) => adv,
+        None => return,
+    };
+    let view_state = c.user_data::<ViewState>().expect("user data not set");
+    let playback = view_state.playback.clone();
+    let mut playback = playback.borrow_mut();
+    let speed = playback.speed;
+    let playhead = match playback.playhead.as_mut() {
+        Some(playhead) => playhead,
+        None => return,
+    };
+
+    let now = Instant::now();
+    if playhead.shown != view_state.timestamp {
+        playhead.timestamp = view_state.timestamp;
+    }
+    playhead.timestamp += now.duration_since(playhead.last_tick) * speed;
+    playhead.last_tick = now;
+
+    // Assume the next sample comes after the usual interval, so that we
+    // don't look for it in the store on every tick
+    let next_sample =
+        view_state.timestamp + view_state.lowest_time_elapsed.max(Duration::from_secs(1));
+    if playhead.timestamp < next_sample {
+        return;
+    }
+    let model = adv.borrow_mut().advance_until(playhead.timestamp);
+    match model {
+        Some(model) => {
+            view_state.update(model);
+            // We are ahead of the playhead after skipping a gap
+            playhead.timestamp = playhead.timestamp.max(view_state.timestamp);
+            playhead.shown = view_state.timestamp;
+        }
+        None => {
+            playback.playhead = None;
+            drop(playback);
+            view_warn!(c, "Reached the end of the recorded data");
+        }
+    }
+}
+
+fn seek_to(c: &mut Cursive, get_timestamp: impl FnOnce(SystemTime, Duration) -> SystemTime) {
+    let adv = match &c.user_data::<ViewState>().expect("user data not set").mode {
+        ViewMode::Pause(adv) | ViewMode::Replay(adv) => adv.clone(),
+        ViewMode::Live(_) => return,
+    };
+    let range = adv.borrow_mut().get_sample_range();
+    let (start, end) = match range {
+        Some(range) => range,
+        None => {
+            view_warn!(c, "Cannot find available data sample");
+            return;
+        }
+    };
+    let timestamp =
+        get_timestamp(start, end.duration_since(start).unwrap_or_default()).clamp(start, end);
+    let model = adv.borrow_mut().jump_sample_to(timestamp);
+    match model {
+        Some(model) => c
+            .user_data::<ViewState>()
+            .expect("user data not set")
+            .update(model),
+        None => view_warn!(c, "Cannot find available data sample"),
+    }
+    crate::refresh(c);
+}
+
+/// Jumps to the sample at the given percentage of the recorded time
+/// (replay and live-paused mode).
+pub fn seek_to_percent(c: &mut Cursive, percent: f64) {
+    seek_to(c, |start, span| {
+        start + span.mul_f64(percent.clamp(0.0, 100.0) / 100.0)
+    });
+}
+
+/// Jumps by the given percentage of the recorded time, backwards if
+/// negative (replay and live-paused mode).
+pub fn seek_by_percent(c: &mut Cursive, percent: f64) {
+    let current = c
+        .user_data::<ViewState>()
+        .expect("user data not set")
+        .timestamp;
+    seek_to(c, |start, span| {
+        let offset = span.mul_f64(percent.abs().min(100.0) / 100.0);
+        if percent < 0.0 {
+            current.checked_sub(offset).unwrap_or(start)
+        } else {
+            current + offset
+        }
+    });
+}
+
+/// Parses a percentage given as e.g. "40" or "40%".
+pub fn parse_percent(arg: &str) -> Result<f64, String> {
+    arg.trim_end_matches('%')
+        .parse::<f64>()
+        .ok()
+        .filter(|percent| percent.is_finite() && *percent >= 0.0)
+        .ok_or_else(|| format!("Invalid percentage: {}", arg))
+}
+
+/// Renders the timeline of the recorded samples in the given width, with
+/// the gaps and the position of the current sample (replay and live-paused
+/// mode). Returns None if it does not fit.
+pub fn render_timeline(c: &mut Cursive, width: usize) -> Option<StyledString> {
+    let view_state = c.user_data::<ViewState>().expect("user data not set");
+    let adv = match &view_state.mode {
+        ViewMode::Pause(adv) | ViewMode::Replay(adv) => adv.clone(),
+        ViewMode::Live(_) => return None,
+    };
+    let timestamp = view_state.timestamp;
+    let mut playback = view_state.playback.borrow_mut();
+
+    let format_time = |time: SystemTime| DateTime::<Local>::from(time).format(TIMELINE_TIME_FORMAT);
+    // The start and end times with a space each
+    let label_width = format_time(SystemTime::UNIX_EPOCH).to_string().len() + 1;
+    let bar_width = width.checked_sub(2 * label_width)?;
+    if bar_width < 10 {
+        return None;
+    }
+    let stale = match &playback.timeline {
+        Some(timeline) => {
+            timeline.covered.len() != bar_width
+                || timestamp < timeline.start
+                || timestamp > timeline.end
+        }
+        None => true,
+    };
+    if stale {
+        let mut adv = adv.borrow_mut();
+        let (start, end) = adv.get_sample_range()?;
+        let covered = adv.get_sample_coverage(start, end, bar_width, MAX_SAMPLE_GAP);
+        playback.timeline = Some(Timeline {
+            start,
+            end,
+            covered,
+        });
+    }
+    let timeline = playback.timeline.as_ref()?;
+
+    let position = timeline.position(timestamp);
+    let mut line = StyledString::plain(format!("{} ", format_time(timeline.start)));
+    for (idx, covered) in timeline.covered.iter().enumerate() {
+        if idx == position {
+            line.append_styled("●", Effect::Bold);
+        } else if *covered {
+            line.append_plain("━");
+        } else {
+            line.append_styled("╌", Color::Light(BaseColor::Red));
+        }
+    }
+    line.append_plain(format!(" {}", format_time(timeline.end)));
+    Some(line)
+}
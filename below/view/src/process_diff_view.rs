@@ -0,0 +1,343 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Ref;
+use std::cell::RefCell;
+use std::cell::RefMut;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use common::util::systemtime_to_datetime;
+use cursive::utils::markup::StyledString;
+use cursive::view::Nameable;
+use cursive::views::NamedView;
+use cursive::views::SelectView;
+use cursive::views::ViewRef;
+use cursive::Cursive;
+use model::sort_process_diffs;
+use model::CgroupDiffState;
+use model::FilterExpr;
+use model::ProcessDiffModel;
+use model::ProcessGroupModelFieldId;
+use model::Queriable;
+use model::SingleProcessDiffModel;
+use model::SingleProcessDiffModelFieldId;
+use model::SingleProcessModelFieldId;
+
+use crate::process_tabs::default_tabs;
+use crate::render::parse_filter_expr;
+use crate::render::ViewItem;
+use crate::stats_view::ColumnTitles;
+use crate::stats_view::StateCommon;
+use crate::stats_view::StatsView;
+use crate::stats_view::ViewBridge;
+use crate::ViewState;
+
+pub type ViewType = StatsView<ProcessDiffView>;
+
+/// Renders corresponding Fields From ProcessDiffModel.
+type ProcessDiffViewItem = ViewItem<SingleProcessDiffModelFieldId>;
+
+#[derive(Default)]
+pub struct ProcessDiffViewState {
+    pub filter_info: Option<(SingleProcessDiffModelFieldId, String)>,
+    pub filter_expr: Option<FilterExpr<SingleProcessDiffModelFieldId>>,
+    pub sort_order: Option<SingleProcessDiffModelFieldId>,
+    pub sort_tags: HashMap<String, Vec<ProcessDiffViewItem>>,
+    pub reverse: bool,
+    pub model: Rc<RefCell<ProcessDiffModel>>,
+    // Time of the sample on the compare side
+    pub base_timestamp: Option<SystemTime>,
+    // Host or store of the compare side
+    pub base_source: Option<String>,
+}
+
+/// Shows the value on both sides and the change of a column of the folded
+/// processes.
+fn get_paired_item(field_id: ProcessGroupModelFieldId) -> [ProcessDiffViewItem; 3] {
+    use base_render::RenderConfigBuilder as Rc;
+    let after = ViewItem::from_default(SingleProcessDiffModelFieldId::After(field_id.clone()));
+    let title = format!("Cmp {}", after.config.render_config.get_title());
+    [
+        after,
+        ViewItem::from_default(SingleProcessDiffModelFieldId::Before(field_id.clone()))
+            .update(Rc::new().title(title)),
+        ViewItem::from_default(SingleProcessDiffModelFieldId::Delta(field_id)),
+    ]
+}
+
+/// Shows the State column and the number of processes followed by the
+/// columns of a process view tab that can be summed over processes.
+fn get_paired_items(items: Vec<ViewItem<SingleProcessModelFieldId>>) -> Vec<ProcessDiffViewItem> {
+    use SingleProcessModelFieldId::*;
+    std::iter::once(ViewItem::from_default(SingleProcessDiffModelFieldId::State))
+        .chain(get_paired_item(ProcessGroupModelFieldId::Members))
+        .chain(
+            items
+                .into_iter()
+                .filter(|item| matches!(item.field_id, Cpu(_) | Mem(_) | Io(_)))
+                .flat_map(|item| get_paired_item(ProcessGroupModelFieldId::Process(item.field_id))),
+        )
+        .collect()
+}
+
+fn get_tabs() -> Vec<(String, Vec<ProcessDiffViewItem>)> {
+    default_tabs::get_tabs()
+        .into_iter()
+        .map(|(tab, items)| (tab, get_paired_items(items)))
+        .collect()
+}
+
+impl StateCommon for ProcessDiffViewState {
+    type ModelType = ProcessDiffModel;
+    type TagType = SingleProcessDiffModelFieldId;
+    type KeyType = String;
+
+    fn get_filter_info(&self) -> &Option<(Self::TagType, String)> {
+        &self.filter_info
+    }
+
+    fn is_filter_supported_from_tab_idx(&self, _tab: &str, idx: usize) -> bool {
+        // Only the comm column is filtered by string
+        idx == 0
+    }
+
+    fn get_tag_from_tab_idx(&self, tab: &str, idx: usize) -> Self::TagType {
+        match idx {
+            0 => Self::TagType::Comm,
+            _ => self
+                .sort_tags
+                .get(tab)
+                .unwrap_or_else(|| panic!("Fail to find tab: {}", tab))
+                .get(idx - 1)
+                .expect("Out of title scope")
+                .field_id
+                .to_owned(),
+        }
+    }
+
+    fn set_filter_from_tab_idx(&mut self, tab: &str, idx: usize, filter: Option<String>) -> bool {
+        if !self.is_filter_supported_from_tab_idx(tab, idx) {
+            return false;
+        }
+        if let Some(filter_text) = filter {
+            let title = self.get_tag_from_tab_idx(tab, idx);
+            self.filter_info = Some((title, filter_text));
+        } else {
+            self.filter_info = None;
+        }
+        true
+    }
+
+    fn set_filter_expr(&mut self, expr: Option<&str>) -> Result<(), String> {
+        self.filter_expr = expr.map(parse_filter_expr).transpose()?;
+        Ok(())
+    }
+
+    fn set_sort_tag(&mut self, sort_order: Self::TagType, reverse: &mut bool) -> bool {
+        let sort_order = Some(sort_order);
+        if self.sort_order == sort_order {
+            *reverse = !*reverse;
+        } else {
+            *reverse = true;
+            self.sort_order = sort_order;
+        }
+        self.reverse = *reverse;
+        true
+    }
+
+    fn set_sort_tag_from_tab_idx(&mut self, tab: &str, idx: usize, reverse: &mut bool) -> bool {
+        let sort_order = self.get_tag_from_tab_idx(tab, idx);
+        self.set_sort_tag(sort_order, reverse)
+    }
+
+    fn set_sort_string(&mut self, selection: &str, reverse: &mut bool) -> bool {
+        use std::str::FromStr;
+        match Self::TagType::from_str(selection) {
+            Ok(field_id) => self.set_sort_tag(field_id, reverse),
+            Err(_) => false,
+        }
+    }
+
+    fn get_model(&self) -> Ref<Self::ModelType> {
+        self.model.borrow()
+    }
+
+    fn get_model_mut(&self) -> RefMut<Self::ModelType> {
+        self.model.borrow_mut()
+    }
+
+    fn new(model: Rc<RefCell<Self::ModelType>>) -> Self {
+        Self {
+            filter_info: None,
+            filter_expr: None,
+            sort_order: None,
+            sort_tags: get_tabs().into_iter().collect(),
+            reverse: false,
+            model,
+            base_timestamp: None,
+            base_source: None,
+        }
+    }
+}
+
+/// Processes of the current sample next to those of the compare side,
+/// folded by comm. Comms only seen on the current side are green and those
+/// only seen on the compare side are red.
+pub struct ProcessDiffView {
+    view_items: Vec<ProcessDiffViewItem>,
+    comm: ProcessDiffViewItem,
+}
+
+impl ProcessDiffView {
+    pub fn new(c: &mut Cursive) -> NamedView<ViewType> {
+        let list = SelectView::new();
+
+        let mut tabs = Vec::new();
+        let mut tabs_map: HashMap<String, ProcessDiffView> = HashMap::new();
+        for (tab, view_items) in get_tabs() {
+            tabs.push(tab.clone());
+            tabs_map.insert(
+                tab,
+                Self {
+                    view_items,
+                    comm: ViewItem::from_default(SingleProcessDiffModelFieldId::Comm),
+                },
+            );
+        }
+        let user_data = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive Object!");
+        StatsView::new(
+            "Process Diff",
+            tabs,
+            tabs_map,
+            list,
+            ProcessDiffViewState::new(user_data.process_diff.clone()),
+            user_data.event_controllers.clone(),
+            user_data.cmd_controllers.clone(),
+        )
+        .feed_data(c)
+        .with_name(Self::get_view_name())
+    }
+
+    pub fn get_process_diff_view(c: &mut Cursive) -> ViewRef<ViewType> {
+        ViewType::get_view(c)
+    }
+
+    pub fn refresh(c: &mut Cursive) {
+        // The compare side may have moved since the last refresh
+        let base = crate::compare::get_active_base(c);
+        let mut view = Self::get_process_diff_view(c);
+        if let Some((timestamp, source)) = base {
+            let mut state = view.state.borrow_mut();
+            state.base_timestamp = Some(timestamp);
+            state.base_source = Some(source);
+        }
+        view.refresh(c);
+    }
+
+    fn get_line(&self, model: &SingleProcessDiffModel, offset: Option<usize>) -> StyledString {
+        let mut line = self.comm.render(model);
+        line.append_plain(" ");
+
+        for item in self.view_items.iter().skip(offset.unwrap_or(0)) {
+            line.append(item.render(model));
+            line.append_plain(" ");
+        }
+
+        let color = match model.state {
+            CgroupDiffState::Added => cursive::theme::BaseColor::Green,
+            CgroupDiffState::Removed => cursive::theme::BaseColor::Red,
+            CgroupDiffState::Common => return line,
+        };
+        StyledString::styled(line.source(), cursive::theme::Color::Light(color))
+    }
+}
+
+impl ViewBridge for ProcessDiffView {
+    type StateType = ProcessDiffViewState;
+
+    fn get_view_name() -> &'static str {
+        "process_diff_view"
+    }
+
+    fn get_titles(&self) -> ColumnTitles {
+        ColumnTitles {
+            titles: std::iter::once(&self.comm)
+                .chain(self.view_items.iter())
+                .map(|item| item.config.render_title())
+                .collect(),
+            pinned_titles: 1,
+        }
+    }
+
+    fn get_rows(
+        &mut self,
+        state: &Self::StateType,
+        offset: Option<usize>,
+    ) -> Vec<(StyledString, String)> {
+        let model = state.get_model();
+        let mut processes: Vec<_> = model
+            .processes
+            .iter()
+            .filter(|process| {
+                state
+                    .filter_info
+                    .as_ref()
+                    .map_or(true, |(_, filter)| process.comm.contains(filter))
+                    && state
+                        .filter_expr
+                        .as_ref()
+                        .map_or(true, |expr| expr.eval(process))
+            })
+            .collect();
+        if let Some(sort_order) = state.sort_order.as_ref() {
+            sort_process_diffs(&mut processes, sort_order, state.reverse);
+        }
+        processes
+            .into_iter()
+            .map(|process| (self.get_line(process, offset), process.comm.clone()))
+            .collect()
+    }
+
+    fn on_select_update_cmd_palette(
+        view: &Self::StateType,
+        selected_key: &String,
+        current_tab: &str,
+        selected_column: usize,
+    ) -> String {
+        let tag = view.get_tag_from_tab_idx(current_tab, selected_column);
+        let field_str = view
+            .model
+            .borrow()
+            .processes
+            .iter()
+            .find(|process| &process.comm == selected_key)
+            .and_then(|process| process.query(&tag))
+            .map_or("?".to_string(), |field| field.to_string());
+        let base = view
+            .base_timestamp
+            .map_or("?".to_string(), systemtime_to_datetime);
+        let source = view.base_source.as_deref().unwrap_or("?");
+        format!(
+            " vs {} {} | {} : {} ",
+            source,
+            base,
+            tag.to_string(),
+            field_str
+        )
+    }
+}
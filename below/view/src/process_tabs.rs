@@ -14,6 +14,8 @@
 
 use cursive::utils::markup::StyledString;
 use itertools::Itertools;
+use model::ProcessTreeModel;
+use model::ProcessTreeModelFieldId;
 use model::Queriable;
 use model::SingleProcessModel;
 
@@ -33,7 +35,7 @@ pub struct ProcessTab {
 
 // Defines how to iterate through the process stats and generate get_rows for ViewBridge
 impl ProcessTab {
-    fn new(view_items: Vec<ProcessViewItem>) -> Self {
+    pub fn new(view_items: Vec<ProcessViewItem>) -> Self {
         Self { view_items }
     }
 
@@ -45,6 +47,38 @@ impl ProcessTab {
         let mut line = StyledString::new();
         line.append(default_tabs::COMM_VIEW_ITEM.render(model));
         line.append_plain(" ");
+        self.append_fields(line, model, offset)
+    }
+
+    fn get_process_tree_line(
+        &self,
+        tree: &ProcessTreeModel,
+        collapsed: bool,
+        offset: Option<usize>,
+    ) -> StyledString {
+        let mut line = StyledString::new();
+        line.append(
+            if collapsed {
+                &*default_tabs::COMM_TREE_COLLAPSED_VIEW_ITEM
+            } else {
+                &*default_tabs::COMM_TREE_VIEW_ITEM
+            }
+            .config
+            .render_indented(
+                tree.process.query(&default_tabs::COMM_VIEW_ITEM.field_id),
+                tree.depth,
+            ),
+        );
+        line.append_plain(" ");
+        self.append_fields(line, &tree.process, offset)
+    }
+
+    fn append_fields(
+        &self,
+        mut line: StyledString,
+        model: &SingleProcessModel,
+        offset: Option<usize>,
+    ) -> StyledString {
 
         for item in std::iter::once(&*default_tabs::CGROUP_VIEW_ITEM)
             .chain(self.view_items.iter())
@@ -82,7 +116,7 @@ impl ProcessTab {
         if let Some(sort_order) = state.sort_order.as_ref() {
             model::sort_queriables(&mut processes, sort_order, state.reverse);
         }
-        processes
+        let processes = processes
             .iter()
             .filter(|spm| {
                 // If we're in zoomed pids mode, only show processes belonging
@@ -113,7 +147,20 @@ impl ProcessTab {
                     true
                 }
             })
-            .copied()
+            .filter(|spm| {
+                // Only show processes matching the filter expression
+                state
+                    .filter_expr
+                    .as_ref()
+                    .map_or(true, |expr| expr.eval(spm))
+            })
+            .copied();
+
+        if state.tree {
+            return self.get_tree_rows(state, ProcessTreeModel::build(processes), offset);
+        }
+
+        processes
             // Abuse batching() to conditionally fold iter
             .batching(|it| {
                 if state.fold {
@@ -136,9 +183,47 @@ impl ProcessTab {
             })
             .collect()
     }
+
+    /// Rows of the process trees in pre-order, with the children of each
+    /// process sorted and the subtrees of collapsed processes left out.
+    fn get_tree_rows(
+        &self,
+        state: &ProcessState,
+        roots: Vec<ProcessTreeModel>,
+        offset: Option<usize>,
+    ) -> Vec<(StyledString, i32)> {
+        let sort = |trees: &mut Vec<&ProcessTreeModel>| {
+            if let Some(sort_order) = state.sort_order.as_ref() {
+                model::sort_queriables(
+                    trees,
+                    &ProcessTreeModelFieldId::Process(sort_order.clone()),
+                    state.reverse,
+                );
+            }
+        };
+        let mut rows = Vec::new();
+        let mut stack = Vec::from_iter(&roots);
+        sort(&mut stack);
+        // Pop the first one first
+        stack.reverse();
+        while let Some(tree) = stack.pop() {
+            let pid = tree.process.pid.unwrap_or(0);
+            let collapsed = state.collapsed_pids.contains(&pid);
+            rows.push((self.get_process_tree_line(tree, collapsed, offset), pid));
+            if collapsed {
+                continue;
+            }
+            let mut children = Vec::from_iter(&tree.children);
+            sort(&mut children);
+            stack.extend(children.into_iter().rev());
+        }
+        rows
+    }
 }
 
 pub mod default_tabs {
+    use base_render::RenderConfigBuilder as Rc;
+    use common::util::get_prefix;
     use model::ProcessCpuModelFieldId::NumThreads;
     use model::ProcessCpuModelFieldId::SystemPct;
     use model::ProcessCpuModelFieldId::UsagePct;
@@ -174,6 +259,12 @@ pub mod default_tabs {
     use super::*;
 
     pub static COMM_VIEW_ITEM: Lazy<ProcessViewItem> = Lazy::new(|| ViewItem::from_default(Comm));
+    pub static COMM_TREE_VIEW_ITEM: Lazy<ProcessViewItem> = Lazy::new(|| {
+        ViewItem::from_default(Comm).update(Rc::new().indented_prefix(get_prefix(false)))
+    });
+    pub static COMM_TREE_COLLAPSED_VIEW_ITEM: Lazy<ProcessViewItem> = Lazy::new(|| {
+        ViewItem::from_default(Comm).update(Rc::new().indented_prefix(get_prefix(true)))
+    });
     pub static CGROUP_VIEW_ITEM: Lazy<ProcessViewItem> =
         Lazy::new(|| ViewItem::from_default(Cgroup));
 
@@ -231,4 +322,14 @@ pub mod default_tabs {
             ViewItem::from_default(Io(RwbytesPerSec)),
         ])
     });
+
+    /// The tabs of the process view with their default columns.
+    pub fn get_tabs() -> Vec<(String, Vec<ProcessViewItem>)> {
+        vec![
+            ("General".into(), PROCESS_GENERAL_TAB.view_items.clone()),
+            ("CPU".into(), PROCESS_CPU_TAB.view_items.clone()),
+            ("Mem".into(), PROCESS_MEM_TAB.view_items.clone()),
+            ("I/O".into(), PROCESS_IO_TAB.view_items.clone()),
+        ]
+    }
 }
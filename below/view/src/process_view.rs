@@ -16,6 +16,7 @@ use std::cell::Ref;
 use std::cell::RefCell;
 use std::cell::RefMut;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::rc::Rc;
 
 use cursive::utils::markup::StyledString;
@@ -24,22 +25,30 @@ use cursive::views::NamedView;
 use cursive::views::SelectView;
 use cursive::views::ViewRef;
 use cursive::Cursive;
+use model::BTreeMapFieldId;
+use model::Field;
+use model::FilterExpr;
+use model::Model;
+use model::ModelFieldId;
 use model::ProcessCpuModelFieldId;
 use model::ProcessIoModelFieldId;
 use model::ProcessMemoryModelFieldId;
 use model::ProcessModel;
+use model::ProcessModelFieldId;
 use model::Queriable;
 use model::SingleProcessModelFieldId;
 
-use crate::process_tabs::default_tabs::PROCESS_CPU_TAB;
-use crate::process_tabs::default_tabs::PROCESS_GENERAL_TAB;
-use crate::process_tabs::default_tabs::PROCESS_IO_TAB;
-use crate::process_tabs::default_tabs::PROCESS_MEM_TAB;
+use crate::process_tabs::default_tabs;
 use crate::process_tabs::ProcessTab;
+use crate::render::parse_filter_expr;
+use crate::render::ViewItem;
+use crate::saved_view::parse_field;
+use crate::saved_view::SavedView;
 use crate::stats_view::ColumnTitles;
 use crate::stats_view::StateCommon;
 use crate::stats_view::StatsView;
 use crate::stats_view::ViewBridge;
+use crate::viewrc::ViewRc;
 use crate::ViewState;
 
 pub type ViewType = StatsView<ProcessView>;
@@ -52,11 +61,16 @@ pub struct ProcessState {
     // For zoomed view, we should save current filter to here and reset the
     // filter when go back to cgroup or process view.
     pub filter_cache_for_zoom: Option<(SingleProcessModelFieldId, String)>,
+    pub filter_expr: Option<FilterExpr<SingleProcessModelFieldId>>,
     pub current_selected_pid: Option<i32>,
     pub sort_order: Option<SingleProcessModelFieldId>,
-    pub sort_tags: HashMap<String, &'static ProcessTab>,
+    pub sort_tags: HashMap<String, ProcessTab>,
     pub reverse: bool,
     pub fold: bool,
+    /// Show processes as a tree built from ppid
+    pub tree: bool,
+    /// Pids of processes whose subtree is collapsed in tree mode
+    pub collapsed_pids: HashSet<i32>,
     pub model: Rc<RefCell<ProcessModel>>,
 }
 
@@ -109,6 +123,11 @@ impl StateCommon for ProcessState {
         true
     }
 
+    fn set_filter_expr(&mut self, expr: Option<&str>) -> Result<(), String> {
+        self.filter_expr = expr.map(parse_filter_expr).transpose()?;
+        Ok(())
+    }
+
     fn set_sort_tag(&mut self, sort_order: Self::TagType, reverse: &mut bool) -> bool {
         let sort_order = Some(sort_order);
         if self.sort_order == sort_order {
@@ -134,6 +153,22 @@ impl StateCommon for ProcessState {
         }
     }
 
+    fn save_view(&self, saved: &mut SavedView) {
+        saved.sort = self.sort_order.as_ref().map(ToString::to_string);
+        saved.reverse = self.reverse;
+        if let Some((field, filter)) = &self.filter_info {
+            saved.filter_field = Some(field.to_string());
+            saved.filter = Some(filter.clone());
+        }
+    }
+
+    fn restore_view(&mut self, saved: &SavedView) -> Result<(), String> {
+        self.sort_order = parse_field(saved.sort.as_deref())?;
+        self.reverse = saved.reverse;
+        self.filter_info = parse_field(saved.filter_field.as_deref())?.zip(saved.filter.clone());
+        Ok(())
+    }
+
     fn get_model(&self) -> Ref<Self::ModelType> {
         self.model.borrow()
     }
@@ -143,21 +178,19 @@ impl StateCommon for ProcessState {
     }
 
     fn new(model: Rc<RefCell<Self::ModelType>>) -> Self {
-        let mut sort_tags = HashMap::new();
-        sort_tags.insert("General".into(), &*PROCESS_GENERAL_TAB);
-        sort_tags.insert("CPU".into(), &*PROCESS_CPU_TAB);
-        sort_tags.insert("Mem".into(), &*PROCESS_MEM_TAB);
-        sort_tags.insert("I/O".into(), &*PROCESS_IO_TAB);
         Self {
             filter_info: None,
             cgroup_filter: None,
             pids_filter: None,
             filter_cache_for_zoom: None,
+            filter_expr: None,
             current_selected_pid: None,
             sort_order: None,
-            sort_tags,
+            sort_tags: get_sort_tags(default_tabs::get_tabs()),
             reverse: false,
             fold: false,
+            tree: false,
+            collapsed_pids: HashSet::new(),
             model,
         }
     }
@@ -176,6 +209,16 @@ impl ProcessState {
         self.fold = !self.fold;
     }
 
+    pub fn toggle_tree(&mut self) {
+        self.tree = !self.tree;
+    }
+
+    fn toggle_collapse(&mut self, pid: i32) {
+        if !self.collapsed_pids.remove(&pid) {
+            self.collapsed_pids.insert(pid);
+        }
+    }
+
     pub fn handle_state_for_entering_zoom(&mut self, current_selection: String) {
         self.cgroup_filter = Some(current_selection);
         std::mem::swap(&mut self.filter_cache_for_zoom, &mut self.filter_info);
@@ -190,7 +233,6 @@ impl ProcessState {
         self.pids_filter = None;
     }
 
-    #[allow(dead_code)]
     pub fn handle_state_for_entering_pids_zoom(&mut self, current_selection: Vec<i32>) {
         self.pids_filter = Some(current_selection);
         std::mem::swap(&mut self.filter_cache_for_zoom, &mut self.filter_info);
@@ -206,48 +248,54 @@ impl ProcessState {
     }
 }
 
+fn get_sort_tags(
+    tab_items: Vec<(String, Vec<ViewItem<SingleProcessModelFieldId>>)>,
+) -> HashMap<String, ProcessTab> {
+    tab_items
+        .into_iter()
+        .map(|(tab, items)| (tab, ProcessTab::new(items)))
+        .collect()
+}
+
 pub struct ProcessView {
-    tab: &'static ProcessTab,
+    tab: ProcessTab,
 }
 
 impl ProcessView {
-    pub fn new(c: &mut Cursive) -> NamedView<ViewType> {
-        let list = SelectView::<i32>::new();
-        let tabs = vec!["General".into(), "CPU".into(), "Mem".into(), "I/O".into()];
+    pub fn new(c: &mut Cursive, viewrc: &ViewRc) -> NamedView<ViewType> {
+        let mut list = SelectView::<i32>::new();
+        list.set_on_submit(|c, pid: &i32| {
+            let mut view = Self::get_process_view(c);
+            if view.state.borrow().tree {
+                view.state.borrow_mut().toggle_collapse(*pid);
+                view.refresh(c);
+            }
+        });
+        let tab_items = viewrc
+            .get_process_tabs()
+            .unwrap_or_else(|_| default_tabs::get_tabs());
+        let mut tabs = Vec::new();
         let mut tabs_map: HashMap<String, ProcessView> = HashMap::new();
-        tabs_map.insert(
-            "General".into(),
-            Self {
-                tab: &*PROCESS_GENERAL_TAB,
-            },
-        );
-        tabs_map.insert(
-            "CPU".into(),
-            Self {
-                tab: &*PROCESS_CPU_TAB,
-            },
-        );
-        tabs_map.insert(
-            "Mem".into(),
-            Self {
-                tab: &*PROCESS_MEM_TAB,
-            },
-        );
-        tabs_map.insert(
-            "I/O".into(),
-            Self {
-                tab: &*PROCESS_IO_TAB,
-            },
-        );
+        for (tab, items) in &tab_items {
+            tabs.push(tab.clone());
+            tabs_map.insert(
+                tab.clone(),
+                Self {
+                    tab: ProcessTab::new(items.clone()),
+                },
+            );
+        }
         let user_data = c
             .user_data::<ViewState>()
             .expect("No data stored in Cursive Object!");
+        let mut process_state = ProcessState::new(user_data.process.clone());
+        process_state.sort_tags = get_sort_tags(tab_items);
         StatsView::new(
             "process",
             tabs,
             tabs_map,
             list,
-            ProcessState::new(user_data.process.clone()),
+            process_state,
             user_data.event_controllers.clone(),
             user_data.cmd_controllers.clone(),
         )
@@ -305,6 +353,21 @@ impl ProcessView {
         ViewType::get_view(c)
     }
 
+    /// Replaces the columns of the tab.
+    pub fn set_tab_columns(
+        c: &mut Cursive,
+        tab: &str,
+        items: Vec<ViewItem<SingleProcessModelFieldId>>,
+    ) {
+        let mut view = Self::get_process_view(c);
+        view.state
+            .borrow_mut()
+            .sort_tags
+            .insert(tab.to_owned(), ProcessTab::new(items.clone()));
+        view.update_tab_view(tab, |process_view| process_view.tab.view_items = items);
+        view.refresh(c);
+    }
+
     pub fn refresh(c: &mut Cursive) {
         let mut view = Self::get_process_view(c);
         view.refresh(c);
@@ -351,4 +414,34 @@ impl ViewBridge for ProcessView {
             .map_or("?".to_string(), |field| field.to_string());
         format!(" {} : {} ", tag.to_string(), field_str)
     }
+
+    fn get_history_field(
+        state: &Self::StateType,
+        model: &Model,
+        selected_key: &i32,
+        current_tab: &str,
+        selected_column: usize,
+    ) -> Option<Field> {
+        model
+            .process
+            .processes
+            .get(selected_key /* pid */)
+            .and_then(|spm| spm.query(&state.get_tag_from_tab_idx(current_tab, selected_column)))
+    }
+
+    fn get_model_field_id(
+        _state: &Self::StateType,
+        selected_key: &i32,
+        _current_tab: &str,
+        field: &str,
+    ) -> Option<ModelFieldId> {
+        use std::str::FromStr;
+
+        Some(ModelFieldId::Process(ProcessModelFieldId::Processes(
+            BTreeMapFieldId {
+                key: Some(*selected_key /* pid */),
+                subquery_id: SingleProcessModelFieldId::from_str(field).ok()?,
+            },
+        )))
+    }
 }
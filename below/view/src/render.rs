@@ -14,11 +14,27 @@
 
 use base_render::HasRenderConfig;
 use base_render::RenderConfig;
+use cursive::theme::Color;
 use cursive::utils::markup::StyledString;
 use model::Field;
 use model::FieldId;
+use model::FilterExpr;
 use model::Queriable;
 use model::Recursive;
+use serde::Deserialize;
+
+/// Parses a filter expression, converting numbers with a unit into the raw
+/// unit of each Field as implied by its render config.
+pub fn parse_filter_expr<F>(expr: &str) -> Result<FilterExpr<F>, String>
+where
+    F: FieldId + std::str::FromStr,
+    F::Queriable: HasRenderConfig,
+{
+    FilterExpr::parse(expr, |field_id| {
+        F::Queriable::get_render_config(field_id).get_field_unit()
+    })
+    .map_err(|e| format!("{:#}", e))
+}
 
 /// Details for generating StyledString from a Field.
 #[derive(Clone)]
@@ -27,6 +43,69 @@ pub enum ViewStyle {
     HighlightAbove(Field),
     /// Highlight the Field if its value is below some threshold.
     HighlightBelow(Field),
+    /// Highlight the Field by the thresholds of a rule in belowrc.
+    Thresholds(Thresholds),
+}
+
+/// How a value is compared against the thresholds of a highlight rule.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum Comparator {
+    #[default]
+    #[serde(rename = ">")]
+    Above,
+    #[serde(rename = ">=")]
+    AboveOrEqual,
+    #[serde(rename = "<")]
+    Below,
+    #[serde(rename = "<=")]
+    BelowOrEqual,
+}
+
+impl Comparator {
+    fn matches(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparator::Above => value > threshold,
+            Comparator::AboveOrEqual => value >= threshold,
+            Comparator::Below => value < threshold,
+            Comparator::BelowOrEqual => value <= threshold,
+        }
+    }
+}
+
+/// Warn and crit thresholds with their colors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Thresholds {
+    pub comparator: Comparator,
+    pub warn: Option<(f64, Color)>,
+    pub crit: Option<(f64, Color)>,
+    /// Compare the value as a percentage of the reference field of the
+    /// ViewItem instead.
+    pub relative: bool,
+}
+
+impl Thresholds {
+    /// Color of the value, crit taking precedence over warn.
+    pub fn get_color(&self, value: f64) -> Option<Color> {
+        [&self.crit, &self.warn]
+            .into_iter()
+            .flatten()
+            .find(|(threshold, _)| self.comparator.matches(value, *threshold))
+            .map(|(_, color)| *color)
+    }
+
+    fn get_field_color(&self, field: Option<&Field>, reference: Option<Field>) -> Option<Color> {
+        let value = model::numeric_value(field?.clone())?;
+        let value = if self.relative {
+            let reference = model::numeric_value(reference?)?;
+            if reference <= 0.0 {
+                return None;
+            }
+            value / reference * 100.0
+        } else {
+            value
+        };
+        self.get_color(value)
+    }
 }
 
 pub const PRESSURE_HIGHLIGHT: ViewStyle = ViewStyle::HighlightAbove(Field::F64(40.0));
@@ -50,7 +129,12 @@ impl ViewConfig {
         self
     }
 
-    fn apply_style(&self, rendered: String, field: Option<Field>) -> StyledString {
+    fn apply_style(
+        &self,
+        rendered: String,
+        field: Option<Field>,
+        reference: Option<Field>,
+    ) -> StyledString {
         match &self.view_style {
             Some(view_style) => match view_style {
                 ViewStyle::HighlightAbove(threshold) => {
@@ -73,6 +157,12 @@ impl ViewConfig {
                         StyledString::plain(rendered)
                     }
                 }
+                ViewStyle::Thresholds(thresholds) => {
+                    match thresholds.get_field_color(field.as_ref(), reference) {
+                        Some(color) => StyledString::styled(rendered, color),
+                        None => StyledString::plain(rendered),
+                    }
+                }
             },
             None => StyledString::plain(rendered),
         }
@@ -83,15 +173,34 @@ impl ViewConfig {
     }
 
     pub fn render(&self, field: Option<Field>) -> StyledString {
+        self.render_with_reference(field, None)
+    }
+
+    /// Renders the field, with the value of the reference field for
+    /// relative highlight rules.
+    pub fn render_with_reference(
+        &self,
+        field: Option<Field>,
+        reference: Option<Field>,
+    ) -> StyledString {
         let rendered = self.render_config.render(field.clone(), true);
-        self.apply_style(rendered, field)
+        self.apply_style(rendered, field, reference)
     }
 
     pub fn render_indented(&self, field: Option<Field>, depth: usize) -> StyledString {
+        self.render_indented_with_reference(field, None, depth)
+    }
+
+    pub fn render_indented_with_reference(
+        &self,
+        field: Option<Field>,
+        reference: Option<Field>,
+        depth: usize,
+    ) -> StyledString {
         let rendered = self
             .render_config
             .render_indented(field.clone(), true, depth);
-        self.apply_style(rendered, field)
+        self.apply_style(rendered, field, reference)
     }
 }
 
@@ -100,6 +209,9 @@ impl ViewConfig {
 pub struct ViewItem<F: FieldId> {
     /// For retrieving Field from a model.
     pub field_id: F,
+    /// For retrieving the Field that a relative highlight rule compares
+    /// against.
+    pub reference_field_id: Option<F>,
     /// For rendering a Field into a StyledString.
     pub config: ViewConfig,
 }
@@ -108,6 +220,12 @@ pub trait HasViewStyle: Queriable {
     fn get_view_style(_field_id: &Self::FieldId) -> Option<ViewStyle> {
         None
     }
+
+    /// The field that the relative highlight rule of the field compares
+    /// it against, if any.
+    fn get_reference_field_id(_field_id: &Self::FieldId) -> Option<Self::FieldId> {
+        None
+    }
 }
 
 impl<T, F> ViewItem<F>
@@ -120,7 +238,11 @@ where
             render_config: T::get_render_config(&field_id),
             view_style: T::get_view_style(&field_id),
         };
-        Self { field_id, config }
+        Self {
+            reference_field_id: T::get_reference_field_id(&field_id),
+            field_id,
+            config,
+        }
     }
 }
 
@@ -136,7 +258,12 @@ impl<F: FieldId> ViewItem<F> {
     }
 
     pub fn render(&self, model: &F::Queriable) -> StyledString {
-        self.config.render(model.query(&self.field_id))
+        self.config
+            .render_with_reference(model.query(&self.field_id), self.query_reference(model))
+    }
+
+    fn query_reference(&self, model: &F::Queriable) -> Option<Field> {
+        model.query(self.reference_field_id.as_ref()?)
     }
 }
 
@@ -146,7 +273,10 @@ where
     F: FieldId<Queriable = T>,
 {
     pub fn render_indented(&self, model: &T) -> StyledString {
-        self.config
-            .render_indented(model.query(&self.field_id), model.get_depth())
+        self.config.render_indented_with_reference(
+            model.query(&self.field_id),
+            self.query_reference(model),
+            model.get_depth(),
+        )
     }
 }
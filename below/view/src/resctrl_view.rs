@@ -0,0 +1,409 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Ref;
+use std::cell::RefCell;
+use std::cell::RefMut;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use common::util::get_prefix;
+use cursive::utils::markup::StyledString;
+use cursive::view::Nameable;
+use cursive::views::NamedView;
+use cursive::views::SelectView;
+use cursive::views::ViewRef;
+use cursive::Cursive;
+use model::Queriable;
+use model::ResctrlCtrlMonGroupModel;
+use model::ResctrlCtrlMonGroupModelFieldId;
+use model::ResctrlL3MonModelFieldId;
+use model::ResctrlModel;
+use model::ResctrlMonGroupModel;
+use model::ResctrlMonGroupModelFieldId;
+use model::ResctrlMonModelFieldId;
+use once_cell::sync::Lazy;
+
+use crate::render::ViewItem;
+use crate::stats_view::ColumnTitles;
+use crate::stats_view::StateCommon;
+use crate::stats_view::StatsView;
+use crate::stats_view::ViewBridge;
+use crate::ViewState;
+
+pub type ViewType = StatsView<ResctrlView>;
+
+/// Renders corresponding Fields From ResctrlCtrlMonGroupModel. MON groups
+/// are rendered with the same columns.
+type ResctrlViewItem = ViewItem<ResctrlCtrlMonGroupModelFieldId>;
+
+static RESCTRL_GENERAL_ITEMS: Lazy<Vec<ResctrlViewItem>> = Lazy::new(|| {
+    use ResctrlCtrlMonGroupModelFieldId::Cpuset;
+    use ResctrlCtrlMonGroupModelFieldId::Mode;
+    use ResctrlCtrlMonGroupModelFieldId::Mon;
+    use ResctrlCtrlMonGroupModelFieldId::Name;
+    use ResctrlL3MonModelFieldId::LlcOccupancyBytes;
+    use ResctrlL3MonModelFieldId::MbmLocalBytesPerSec;
+    use ResctrlL3MonModelFieldId::MbmTotalBytesPerSec;
+    use ResctrlMonModelFieldId::Total;
+    vec![
+        ViewItem::from_default(Name),
+        ViewItem::from_default(Cpuset),
+        ViewItem::from_default(Mode),
+        ViewItem::from_default(Mon(Total(LlcOccupancyBytes))),
+        ViewItem::from_default(Mon(Total(MbmTotalBytesPerSec))),
+        ViewItem::from_default(Mon(Total(MbmLocalBytesPerSec))),
+    ]
+});
+
+/// The field of MON groups matching the given CTRL_MON group field, if any.
+/// MON groups have no cpuset or mode of their own.
+fn get_mon_group_field_id(
+    field_id: &ResctrlCtrlMonGroupModelFieldId,
+) -> Option<ResctrlMonGroupModelFieldId> {
+    match field_id {
+        ResctrlCtrlMonGroupModelFieldId::Name => Some(ResctrlMonGroupModelFieldId::Name),
+        ResctrlCtrlMonGroupModelFieldId::FullPath => Some(ResctrlMonGroupModelFieldId::FullPath),
+        ResctrlCtrlMonGroupModelFieldId::Mon(field_id) => {
+            Some(ResctrlMonGroupModelFieldId::Mon(field_id.clone()))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+pub struct ResctrlState {
+    pub filter_info: Option<(ResctrlCtrlMonGroupModelFieldId, String)>,
+    /// Full paths of the CTRL_MON groups whose MON groups are hidden
+    pub collapsed_groups: HashSet<String>,
+    pub sort_order: Option<ResctrlCtrlMonGroupModelFieldId>,
+    pub reverse: bool,
+    pub model: Rc<RefCell<Option<ResctrlModel>>>,
+}
+
+impl StateCommon for ResctrlState {
+    type ModelType = Option<ResctrlModel>;
+    type TagType = ResctrlCtrlMonGroupModelFieldId;
+    type KeyType = String;
+
+    fn get_filter_info(&self) -> &Option<(Self::TagType, String)> {
+        &self.filter_info
+    }
+
+    fn is_filter_supported_from_tab_idx(&self, _tab: &str, idx: usize) -> bool {
+        // Only the name column is filtered by string
+        idx == 0
+    }
+
+    fn get_tag_from_tab_idx(&self, _tab: &str, idx: usize) -> Self::TagType {
+        RESCTRL_GENERAL_ITEMS
+            .get(idx)
+            .expect("Out of title scope")
+            .field_id
+            .clone()
+    }
+
+    fn set_filter_from_tab_idx(&mut self, tab: &str, idx: usize, filter: Option<String>) -> bool {
+        if !self.is_filter_supported_from_tab_idx(tab, idx) {
+            return false;
+        }
+
+        if let Some(filter_text) = filter {
+            let title = self.get_tag_from_tab_idx(tab, idx);
+            self.filter_info = Some((title, filter_text));
+        } else {
+            self.filter_info = None;
+        }
+        true
+    }
+
+    fn set_sort_tag(&mut self, sort_order: Self::TagType, reverse: &mut bool) -> bool {
+        let sort_order = Some(sort_order);
+        if self.sort_order == sort_order {
+            *reverse = !*reverse;
+        } else {
+            *reverse = true;
+            self.sort_order = sort_order;
+        }
+        self.reverse = *reverse;
+        true
+    }
+
+    fn set_sort_tag_from_tab_idx(&mut self, tab: &str, idx: usize, reverse: &mut bool) -> bool {
+        let sort_order = self.get_tag_from_tab_idx(tab, idx);
+        self.set_sort_tag(sort_order, reverse)
+    }
+
+    fn set_sort_string(&mut self, selection: &str, reverse: &mut bool) -> bool {
+        use std::str::FromStr;
+        match Self::TagType::from_str(selection) {
+            Ok(field_id) => self.set_sort_tag(field_id, reverse),
+            Err(_) => false,
+        }
+    }
+
+    fn get_model(&self) -> Ref<Self::ModelType> {
+        self.model.borrow()
+    }
+
+    fn get_model_mut(&self) -> RefMut<Self::ModelType> {
+        self.model.borrow_mut()
+    }
+
+    fn new(model: Rc<RefCell<Self::ModelType>>) -> Self {
+        Self {
+            model,
+            ..Default::default()
+        }
+    }
+}
+
+impl ResctrlState {
+    fn toggle_collapse(&mut self, full_path: &str) {
+        if !self.collapsed_groups.remove(full_path) {
+            self.collapsed_groups.insert(full_path.to_owned());
+        }
+    }
+
+    fn is_match(&self, name: &str) -> bool {
+        self.filter_info
+            .as_ref()
+            .map_or(true, |(_, filter)| name.contains(filter))
+    }
+}
+
+/// Resctrl groups as a tree: the root group on top, with the top-level MON
+/// groups and the CTRL_MON groups below it, each CTRL_MON group followed by
+/// its MON groups. Press Enter to collapse or expand a group.
+pub struct ResctrlView {
+    view_items: &'static [ResctrlViewItem],
+}
+
+impl ResctrlView {
+    pub fn new(c: &mut Cursive) -> NamedView<ViewType> {
+        let mut list = SelectView::<String>::new();
+        list.set_on_submit(|c, full_path: &String| {
+            let mut view = Self::get_resctrl_view(c);
+            view.state.borrow_mut().toggle_collapse(full_path);
+            view.refresh(c);
+        });
+
+        let tabs = vec!["General".into()];
+        let mut tabs_map: HashMap<String, ResctrlView> = HashMap::new();
+        tabs_map.insert(
+            "General".into(),
+            Self {
+                view_items: &RESCTRL_GENERAL_ITEMS,
+            },
+        );
+        let user_data = c
+            .user_data::<ViewState>()
+            .expect("No data stored in Cursive Object!");
+        StatsView::new(
+            "resctrl",
+            tabs,
+            tabs_map,
+            list,
+            ResctrlState::new(user_data.resctrl.clone()),
+            user_data.event_controllers.clone(),
+            user_data.cmd_controllers.clone(),
+        )
+        .feed_data(c)
+        .with_name(Self::get_view_name())
+    }
+
+    pub fn get_resctrl_view(c: &mut Cursive) -> ViewRef<ViewType> {
+        ViewType::get_view(c)
+    }
+
+    pub fn refresh(c: &mut Cursive) {
+        Self::get_resctrl_view(c).refresh(c);
+    }
+
+    fn get_name_line(
+        &self,
+        field: Option<model::Field>,
+        depth: usize,
+        collapsed: bool,
+    ) -> StyledString {
+        use base_render::RenderConfigBuilder as Rc;
+        self.view_items[0]
+            .config
+            .clone()
+            .update(Rc::new().indented_prefix(get_prefix(collapsed)))
+            .render_indented(field, depth)
+    }
+
+    fn get_ctrl_mon_group_line(
+        &self,
+        group: &ResctrlCtrlMonGroupModel,
+        depth: usize,
+        collapsed: bool,
+        offset: Option<usize>,
+    ) -> StyledString {
+        let mut line =
+            self.get_name_line(group.query(&self.view_items[0].field_id), depth, collapsed);
+        line.append_plain(" ");
+        for item in self.view_items.iter().skip(1 + offset.unwrap_or(0)) {
+            line.append(item.render(group));
+            line.append_plain(" ");
+        }
+        line
+    }
+
+    fn get_mon_group_line(
+        &self,
+        group: &ResctrlMonGroupModel,
+        depth: usize,
+        offset: Option<usize>,
+    ) -> StyledString {
+        let query = |item: &ResctrlViewItem| {
+            match get_mon_group_field_id(&item.field_id) {
+                Some(field_id) => group.query(&field_id),
+                // Left blank rather than unknown
+                None => Some(String::new().into()),
+            }
+        };
+        let mut line = self.get_name_line(query(&self.view_items[0]), depth, false);
+        line.append_plain(" ");
+        for item in self.view_items.iter().skip(1 + offset.unwrap_or(0)) {
+            line.append(item.config.render(query(item)));
+            line.append_plain(" ");
+        }
+        line
+    }
+
+    /// Appends the rows of a CTRL_MON group at the given depth, followed by
+    /// the rows of its MON groups.
+    fn push_ctrl_mon_group_rows(
+        &self,
+        state: &ResctrlState,
+        group: &ResctrlCtrlMonGroupModel,
+        depth: usize,
+        offset: Option<usize>,
+        rows: &mut Vec<(StyledString, String)>,
+    ) {
+        let group_matches = state.is_match(&group.name);
+        let mut mon_groups: Vec<&ResctrlMonGroupModel> = group
+            .mon_groups
+            .values()
+            .filter(|mon_group| group_matches || state.is_match(&mon_group.name))
+            .collect();
+        if !group_matches && mon_groups.is_empty() {
+            return;
+        }
+
+        let collapsed = state.collapsed_groups.contains(&group.full_path);
+        rows.push((
+            self.get_ctrl_mon_group_line(group, depth, collapsed, offset),
+            group.full_path.clone(),
+        ));
+        if collapsed {
+            return;
+        }
+
+        if let Some(field_id) = state.sort_order.as_ref().and_then(get_mon_group_field_id) {
+            model::sort_queriables(&mut mon_groups, &field_id, state.reverse);
+        }
+        for mon_group in mon_groups {
+            rows.push((
+                self.get_mon_group_line(mon_group, depth + 1, offset),
+                mon_group.full_path.clone(),
+            ));
+        }
+    }
+}
+
+impl ViewBridge for ResctrlView {
+    type StateType = ResctrlState;
+
+    fn get_view_name() -> &'static str {
+        "resctrl_view"
+    }
+
+    fn get_titles(&self) -> ColumnTitles {
+        ColumnTitles {
+            titles: self
+                .view_items
+                .iter()
+                .map(|item| item.config.render_title())
+                .collect(),
+            pinned_titles: 1,
+        }
+    }
+
+    fn get_rows(
+        &mut self,
+        state: &Self::StateType,
+        offset: Option<usize>,
+    ) -> Vec<(StyledString, String)> {
+        let model = state.get_model();
+        let resctrl = match model.as_ref() {
+            Some(resctrl) => resctrl,
+            // Not collected without enable_resctrl_stats
+            None => return Vec::new(),
+        };
+
+        let root = resctrl.get_root_group();
+        let mut rows = Vec::new();
+        let root_collapsed = state.collapsed_groups.contains(&root.full_path);
+        rows.push((
+            self.get_ctrl_mon_group_line(&root, 0, root_collapsed, offset),
+            root.full_path.clone(),
+        ));
+        if root_collapsed {
+            return rows;
+        }
+
+        // The MON groups of the root group come first, as in dump resctrl
+        let mut root_mon_groups: Vec<&ResctrlMonGroupModel> = root
+            .mon_groups
+            .values()
+            .filter(|mon_group| state.is_match(&mon_group.name))
+            .collect();
+        if let Some(field_id) = state.sort_order.as_ref().and_then(get_mon_group_field_id) {
+            model::sort_queriables(&mut root_mon_groups, &field_id, state.reverse);
+        }
+        for mon_group in root_mon_groups {
+            rows.push((
+                self.get_mon_group_line(mon_group, 1, offset),
+                mon_group.full_path.clone(),
+            ));
+        }
+
+        let mut ctrl_mon_groups: Vec<&ResctrlCtrlMonGroupModel> =
+            resctrl.ctrl_mon_groups.values().collect();
+        if let Some(field_id) = state.sort_order.as_ref() {
+            model::sort_queriables(&mut ctrl_mon_groups, field_id, state.reverse);
+        }
+        for group in ctrl_mon_groups {
+            self.push_ctrl_mon_group_rows(state, group, 1, offset, &mut rows);
+        }
+        rows
+    }
+
+    fn on_select_update_cmd_palette(
+        _state: &Self::StateType,
+        selected_key: &String,
+        _current_tab: &str,
+        _selected_column: usize,
+    ) -> String {
+        if selected_key.is_empty() {
+            "/".to_owned()
+        } else {
+            format!("/{}", selected_key)
+        }
+    }
+}
@@ -0,0 +1,496 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Saved views and bookmarks.
+//!
+//! A saved view is what is shown on screen: the sample, the active view,
+//! its tab, sort order, filters and selected row. Views are saved by name
+//! under `$HOME/.config/below/views/` and can also be shared as a
+//! `below://view?...` string, e.g. for `below replay --view`.
+//!
+//! Bookmarks mark timestamps with a note and are saved in
+//! `$HOME/.config/below/bookmarks.toml`.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::bail;
+use anyhow::Context;
+use anyhow::Result;
+use chrono::DateTime;
+use chrono::Local;
+use common::util::get_below_config_dir;
+use common::util::get_system_time;
+use common::util::get_unix_timestamp;
+use cursive::event::Key;
+use cursive::views::Dialog;
+use cursive::views::OnEventView;
+use cursive::views::SelectView;
+use cursive::views::TextView;
+use cursive::Cursive;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::controllers::Controllers;
+use crate::stats_view::StatsView;
+use crate::stats_view::ViewBridge;
+use crate::MainViewState;
+use crate::ViewMode;
+use crate::ViewState;
+
+/// Prefix of the shareable string form of a saved view
+pub const VIEW_URL_PREFIX: &str = "below://view?";
+
+const VIEWS_DIR: &str = "views";
+const BOOKMARKS_FILE: &str = "bookmarks.toml";
+
+/// What is shown on screen, see the module documentation.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedView {
+    /// Unix timestamp of the sample
+    pub timestamp: Option<u64>,
+    /// Command of the view, e.g. "cgroup" or "process"
+    pub view: String,
+    pub tab: Option<String>,
+    /// Field id of the sort column, e.g. "cpu.usage_pct"
+    pub sort: Option<String>,
+    #[serde(default)]
+    pub reverse: bool,
+    /// Field id of the column filtered by `filter`
+    pub filter_field: Option<String>,
+    pub filter: Option<String>,
+    pub filter_expr: Option<String>,
+    #[serde(default)]
+    pub collapsed_cgroups: Vec<String>,
+    /// Key of the selected row, e.g. the cgroup path or pid
+    pub selected: Option<String>,
+}
+
+/// Encodes everything but unreserved URL characters as %XX.
+fn percent_encode(s: &str) -> String {
+    let mut res = String::new();
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                res.push(b as char)
+            }
+            _ => res.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    res
+}
+
+fn percent_decode(s: &str) -> Result<String> {
+    let mut bytes = Vec::new();
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [
+                iter.next().unwrap_or_default(),
+                iter.next().unwrap_or_default(),
+            ];
+            let hex = std::str::from_utf8(&hex).context("Invalid percent encoding")?;
+            bytes.push(u8::from_str_radix(hex, 16).context("Invalid percent encoding")?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).context("Invalid percent encoding")
+}
+
+impl SavedView {
+    /// Returns the shareable `below://view?...` string of the view.
+    pub fn to_url(&self) -> String {
+        let mut params = vec![format!("view={}", percent_encode(&self.view))];
+        let mut push = |key: &str, value: &Option<String>| {
+            if let Some(value) = value {
+                params.push(format!("{}={}", key, percent_encode(value)));
+            }
+        };
+        push("timestamp", &self.timestamp.map(|t| t.to_string()));
+        push("tab", &self.tab);
+        push("sort", &self.sort);
+        push("reverse", &self.reverse.then(|| "true".to_owned()));
+        push("filter_field", &self.filter_field);
+        push("filter", &self.filter);
+        push("filter_expr", &self.filter_expr);
+        push("selected", &self.selected);
+        if !self.collapsed_cgroups.is_empty() {
+            params.push(format!(
+                "collapsed_cgroups={}",
+                self.collapsed_cgroups
+                    .iter()
+                    .map(|cgroup| percent_encode(cgroup))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ));
+        }
+        format!("{}{}", VIEW_URL_PREFIX, params.join("&"))
+    }
+
+    /// Parses the string returned by to_url.
+    pub fn from_url(url: &str) -> Result<Self> {
+        let query = match url.strip_prefix(VIEW_URL_PREFIX) {
+            Some(query) => query,
+            None => bail!("Saved view string should start with {}", VIEW_URL_PREFIX),
+        };
+        let mut saved = SavedView::default();
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            if key == "collapsed_cgroups" {
+                saved.collapsed_cgroups = value
+                    .split(',')
+                    .filter(|cgroup| !cgroup.is_empty())
+                    .map(percent_decode)
+                    .collect::<Result<_>>()?;
+                continue;
+            }
+            let value = percent_decode(value)?;
+            match key {
+                "view" => saved.view = value,
+                "timestamp" => saved.timestamp = Some(value.parse().context("Invalid timestamp")?),
+                "tab" => saved.tab = Some(value),
+                "sort" => saved.sort = Some(value),
+                "reverse" => saved.reverse = value.parse().context("Invalid reverse")?,
+                "filter_field" => saved.filter_field = Some(value),
+                "filter" => saved.filter = Some(value),
+                "filter_expr" => saved.filter_expr = Some(value),
+                "selected" => saved.selected = Some(value),
+                _ => bail!("Unknown saved view parameter: {}", key),
+            }
+        }
+        if saved.view.is_empty() {
+            bail!("Saved view string has no view");
+        }
+        Ok(saved)
+    }
+
+    fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read saved view {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse saved view {}", path.display()))
+    }
+
+    /// Loads a view by the name it was saved with, from a file or from the
+    /// string returned by to_url.
+    pub fn load(arg: &str) -> Result<Self> {
+        if arg.starts_with(VIEW_URL_PREFIX) {
+            Self::from_url(arg)
+        } else if arg.contains('/') || arg.ends_with(".toml") {
+            Self::from_file(Path::new(arg))
+        } else {
+            Self::from_file(&get_view_path(arg))
+        }
+    }
+
+    /// Saves the view by name and returns the path of its file.
+    pub fn save(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty() || name.contains('/') {
+            bail!("Invalid view name: {}", name);
+        }
+        let path = get_view_path(name);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+fn get_view_path(name: &str) -> PathBuf {
+    Path::new(&get_below_config_dir())
+        .join(VIEWS_DIR)
+        .join(format!("{}.toml", name))
+}
+
+/// Parses an optional field id saved with to_string.
+pub fn parse_field<F: FromStr>(field: Option<&str>) -> Result<Option<F>, String> {
+    field
+        .map(|field| {
+            field
+                .parse()
+                .map_err(|_| format!("Unknown field in saved view: {}", field))
+        })
+        .transpose()
+}
+
+/// A timestamp marked with a note.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Unix timestamp of the sample
+    pub timestamp: u64,
+    /// Hostname of the recorded system
+    #[serde(default)]
+    pub hostname: String,
+    #[serde(default)]
+    pub note: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Bookmarks {
+    #[serde(default)]
+    bookmark: Vec<Bookmark>,
+}
+
+fn get_bookmarks_path() -> PathBuf {
+    Path::new(&get_below_config_dir()).join(BOOKMARKS_FILE)
+}
+
+/// Returns the saved bookmarks sorted by timestamp.
+pub fn load_bookmarks() -> Result<Vec<Bookmark>> {
+    let path = get_bookmarks_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let bookmarks: Bookmarks =
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(bookmarks.bookmark)
+}
+
+/// Saves a new bookmark and returns the path of the bookmarks file.
+pub fn add_bookmark(bookmark: Bookmark) -> Result<PathBuf> {
+    let mut bookmarks = Bookmarks {
+        bookmark: load_bookmarks()?,
+    };
+    bookmarks.bookmark.push(bookmark);
+    bookmarks
+        .bookmark
+        .sort_by_key(|bookmark| bookmark.timestamp);
+    let path = get_bookmarks_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(&path, toml::to_string(&bookmarks)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn save_stats_view<V: 'static + ViewBridge>(c: &mut Cursive, saved: &mut SavedView) {
+    StatsView::<V>::get_view(c).save_view(saved);
+}
+
+/// Returns what is currently shown on screen.
+pub fn get_current_view(c: &mut Cursive) -> Result<SavedView, String> {
+    let view_state = c.user_data::<ViewState>().expect("user data not set");
+    let mut saved = SavedView {
+        timestamp: Some(get_unix_timestamp(view_state.timestamp)),
+        ..Default::default()
+    };
+    match view_state.main_view_state.clone() {
+        MainViewState::Cgroup => {
+            saved.view = "cgroup".to_owned();
+            save_stats_view::<crate::cgroup_view::CgroupView>(c, &mut saved);
+        }
+        MainViewState::Process(_) => {
+            saved.view = "process".to_owned();
+            save_stats_view::<crate::process_view::ProcessView>(c, &mut saved);
+        }
+        MainViewState::Core => {
+            saved.view = "system".to_owned();
+            save_stats_view::<crate::core_view::CoreView>(c, &mut saved);
+        }
+        MainViewState::Network => {
+            saved.view = "network".to_owned();
+            save_stats_view::<crate::network_view::NetworkView>(c, &mut saved);
+        }
+        MainViewState::Resctrl => {
+            saved.view = "resctrl".to_owned();
+            save_stats_view::<crate::resctrl_view::ResctrlView>(c, &mut saved);
+        }
+        MainViewState::CgroupDiff => {
+            return Err("Saving the cgroup diff view is not supported".to_owned());
+        }
+        MainViewState::ProcessDiff => {
+            return Err("Saving the process diff view is not supported".to_owned());
+        }
+        #[cfg(fbcode_build)]
+        MainViewState::Gpu => return Err("Saving the GPU view is not supported".to_owned()),
+    }
+    Ok(saved)
+}
+
+/// Shows the sample at timestamp. Live mode is paused first.
+pub fn jump_to_timestamp(c: &mut Cursive, timestamp: u64) {
+    let view_state = c.user_data::<ViewState>().expect("user data not set");
+    let adv = match view_state.mode.clone() {
+        ViewMode::Live(adv) => {
+            view_state.mode = ViewMode::Pause(adv.clone());
+            adv
+        }
+        ViewMode::Pause(adv) | ViewMode::Replay(adv) => adv,
+    };
+    let model = adv.borrow_mut().jump_sample_to(get_system_time(timestamp));
+    match model {
+        Some(model) => c
+            .user_data::<ViewState>()
+            .expect("user data not set")
+            .update(model),
+        None => view_warn!(c, "Cannot find available data sample"),
+    }
+}
+
+fn restore_stats_view<V: 'static + ViewBridge>(
+    c: &mut Cursive,
+    controller: Controllers,
+    saved: &SavedView,
+) -> Result<(), String> {
+    controller.callback::<V>(c, &[controller.command()]);
+    StatsView::<V>::get_view(c).restore_view(saved)?;
+    crate::refresh(c);
+    if let Some(selected) = &saved.selected {
+        StatsView::<V>::get_view(c).select_key(c, selected);
+    }
+    Ok(())
+}
+
+/// Shows the saved view, including its sample if it has one.
+pub fn restore(c: &mut Cursive, saved: &SavedView) -> Result<(), String> {
+    if let Some(timestamp) = saved.timestamp {
+        jump_to_timestamp(c, timestamp);
+    }
+    match saved.view.as_str() {
+        "cgroup" => {
+            restore_stats_view::<crate::cgroup_view::CgroupView>(c, Controllers::Cgroup, saved)
+        }
+        "process" => {
+            restore_stats_view::<crate::process_view::ProcessView>(c, Controllers::Process, saved)
+        }
+        "system" => restore_stats_view::<crate::core_view::CoreView>(c, Controllers::System, saved),
+        "network" => {
+            restore_stats_view::<crate::network_view::NetworkView>(c, Controllers::Network, saved)
+        }
+        "resctrl" => {
+            restore_stats_view::<crate::resctrl_view::ResctrlView>(c, Controllers::Resctrl, saved)
+        }
+        view => Err(format!("Unknown view in saved view: {}", view)),
+    }
+}
+
+/// Shows the shareable string of a saved view, so that it can be copied.
+pub fn new_saved_popup(path: Option<&Path>, saved: &SavedView) -> impl cursive::View {
+    let mut content = String::new();
+    if let Some(path) = path {
+        content.push_str(&format!("Saved to {}\n\n", path.display()));
+    }
+    content.push_str(&saved.to_url());
+    OnEventView::new(
+        Dialog::around(TextView::new(content))
+            .title("Saved view")
+            .dismiss_button("Close"),
+    )
+    .on_event(Key::Esc, |c| {
+        c.pop_layer();
+    })
+}
+
+/// Lists the bookmarks. Enter jumps to the selected one.
+pub fn new_bookmarks_popup(bookmarks: Vec<Bookmark>) -> impl cursive::View {
+    let mut select_view = SelectView::<u64>::new();
+    for bookmark in bookmarks {
+        let datetime = DateTime::<Local>::from(get_system_time(bookmark.timestamp));
+        select_view.add_item(
+            format!(
+                "{}  {:<20.20}  {}",
+                datetime.format("%m/%d/%Y %H:%M:%S"),
+                bookmark.hostname,
+                bookmark.note
+            ),
+            bookmark.timestamp,
+        );
+    }
+    let select_view = select_view.on_submit(|c, timestamp: &u64| {
+        c.pop_layer();
+        jump_to_timestamp(c, *timestamp);
+        crate::refresh(c);
+    });
+    OnEventView::new(
+        Dialog::around(select_view)
+            .title("Bookmarks")
+            .dismiss_button("Close"),
+    )
+    .on_event(Key::Esc, |c| {
+        c.pop_layer();
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_saved_view() -> SavedView {
+        SavedView {
+            timestamp: Some(1700000000),
+            view: "cgroup".to_owned(),
+            tab: Some("CPU".to_owned()),
+            sort: Some("cpu.usage_pct".to_owned()),
+            reverse: true,
+            filter_field: Some("name".to_owned()),
+            filter: Some("foo bar&baz".to_owned()),
+            filter_expr: Some("memory.total > 1G".to_owned()),
+            collapsed_cgroups: vec!["/system.slice".to_owned(), "/a,b".to_owned()],
+            selected: Some("/workload.slice/foo.service".to_owned()),
+        }
+    }
+
+    #[test]
+    fn saved_view_url_roundtrip() {
+        let saved = get_saved_view();
+        let url = saved.to_url();
+        assert!(url.starts_with(VIEW_URL_PREFIX));
+        assert!(!url[VIEW_URL_PREFIX.len()..].contains(' '));
+        assert_eq!(
+            SavedView::from_url(&url).expect("Failed to parse url"),
+            saved
+        );
+
+        let saved = SavedView {
+            view: "process".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(saved.to_url(), "below://view?view=process");
+        assert_eq!(
+            SavedView::from_url(&saved.to_url()).expect("Failed to parse url"),
+            saved
+        );
+
+        assert!(SavedView::from_url("below://view?tab=CPU").is_err());
+        assert!(SavedView::from_url("below://view?view=cgroup&foo=bar").is_err());
+        assert!(SavedView::from_url("below://view?view=%G1").is_err());
+        assert!(SavedView::from_url("view=cgroup").is_err());
+    }
+
+    #[test]
+    fn saved_view_file_roundtrip() {
+        let dir = tempfile::TempDir::with_prefix("below_saved_view.").expect("tempdir");
+        let path = dir.path().join("view.toml");
+        let saved = get_saved_view();
+        std::fs::write(&path, toml::to_string(&saved).expect("Failed to serialize"))
+            .expect("Failed to write");
+        assert_eq!(
+            SavedView::load(path.to_str().unwrap()).expect("Failed to load"),
+            saved
+        );
+        assert_eq!(
+            SavedView::load(&saved.to_url()).expect("Failed to load"),
+            saved
+        );
+    }
+}
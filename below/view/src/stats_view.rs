@@ -28,6 +28,7 @@ use cursive::view::Nameable;
 use cursive::view::Scrollable;
 use cursive::view::View;
 use cursive::view::ViewWrapper;
+use cursive::views::HideableView;
 use cursive::views::LinearLayout;
 use cursive::views::NamedView;
 use cursive::views::OnEventView;
@@ -35,11 +36,17 @@ use cursive::views::Panel;
 use cursive::views::ResizedView;
 use cursive::views::ScrollView;
 use cursive::views::SelectView;
+use cursive::views::TextView;
 use cursive::views::ViewRef;
 use cursive::Cursive;
+use model::Field;
+use model::Model;
+use model::ModelFieldId;
 
 use crate::command_palette::CommandPalette;
 use crate::controllers::Controllers;
+use crate::history;
+use crate::saved_view::SavedView;
 use crate::tab_view::TabView;
 
 pub struct ColumnTitles {
@@ -55,7 +62,7 @@ pub struct ColumnTitles {
 pub trait StateCommon {
     type ModelType;
     type TagType: ToString;
-    type KeyType: Clone;
+    type KeyType: Clone + ToString;
 
     /// Expose filter data for StatsView to set fields in filter popup
     fn get_filter_info(&self) -> &Option<(Self::TagType, String)>;
@@ -78,6 +85,12 @@ pub trait StateCommon {
         false
     }
 
+    /// Set the filter expression over the fields of the model, or clear it
+    /// with None. Return an error message on failure.
+    fn set_filter_expr(&mut self, _expr: Option<&str>) -> Result<(), String> {
+        Err("Filter expression is not supported in this view".to_owned())
+    }
+
     /// Set the sorting tag to common state
     /// Return true on success, false if current tab doest support sorting.
     fn set_sort_tag(&mut self, _tag: Self::TagType, _reverse: &mut bool) -> bool {
@@ -90,6 +103,16 @@ pub trait StateCommon {
         false
     }
 
+    /// Save the sort order, filter and other view specific state for a
+    /// saved view.
+    fn save_view(&self, _saved: &mut SavedView) {}
+
+    /// Restore the state saved with save_view. Return an error message on
+    /// failure.
+    fn restore_view(&mut self, _saved: &SavedView) -> Result<(), String> {
+        Ok(())
+    }
+
     fn get_model(&self) -> Ref<Self::ModelType>;
     fn get_model_mut(&self) -> RefMut<Self::ModelType>;
     fn new(model: Rc<RefCell<Self::ModelType>>) -> Self;
@@ -138,6 +161,31 @@ pub trait ViewBridge {
     ) -> String {
         "".to_owned()
     }
+
+    /// Optional callback to get the value of the selected column of the
+    /// selected row from the model of a past sample, for the history pane.
+    /// Returns None if the row or the value is not in that sample.
+    fn get_history_field(
+        _state: &Self::StateType,
+        _model: &Model,
+        _selected_key: &<<Self as ViewBridge>::StateType as StateCommon>::KeyType,
+        _current_tab: &str,
+        _selected_column: usize,
+    ) -> Option<Field> {
+        None
+    }
+
+    /// Optional callback to resolve a field of the selected row, e.g.
+    /// "cpu.usage_pct", to its id in the Model, for the chart view.
+    /// Returns None if the view does not know the field.
+    fn get_model_field_id(
+        _state: &Self::StateType,
+        _selected_key: &<<Self as ViewBridge>::StateType as StateCommon>::KeyType,
+        _current_tab: &str,
+        _field: &str,
+    ) -> Option<ModelFieldId> {
+        None
+    }
 }
 
 /// StatsView is a view wrapper that wraps tabs, titles, and list of stats.
@@ -169,7 +217,8 @@ pub trait ViewBridge {
 ///            --> LinearLayout::Vertical
 ///            --> child 0: A TabView that represent the title header tab
 ///            --> child 1: A SelectView that represents the detail stats
-///          --> child 2: Command palette
+///          --> child 2: A hideable history pane of the selected row
+///          --> child 3: Command palette
 ///
 /// `state` defines the state of a view. Filters, sorting orders will be defined here.
 pub struct StatsView<V: 'static + ViewBridge> {
@@ -179,6 +228,8 @@ pub struct StatsView<V: 'static + ViewBridge> {
     pub state: Rc<RefCell<V::StateType>>,
     pub reverse_sort: bool,
     pub event_controllers: Rc<RefCell<HashMap<Event, Controllers>>>,
+    /// Number of samples shown in the history pane, None if it is hidden
+    history_samples: Option<usize>,
 }
 
 impl<V: 'static + ViewBridge> ViewWrapper for StatsView<V> {
@@ -253,6 +304,9 @@ impl<V: 'static + ViewBridge> StatsView<V> {
                         selected_column,
                     ));
                 });
+                if let Some(mut view) = c.find_name::<StatsView<V>>(V::get_view_name()) {
+                    view.refresh_history(c);
+                }
             });
 
         let detailed_view = OnEventView::new(Panel::new(
@@ -282,6 +336,7 @@ impl<V: 'static + ViewBridge> StatsView<V> {
                         .scroll_x(true)
                         .scroll_y(false),
                 )
+                .child(HideableView::new(TextView::new("")).hidden())
                 .child(
                     CommandPalette::new::<V>(name, "<root>", cmd_controllers)
                         .with_name(format!("{}_cmd_palette", &name)),
@@ -295,6 +350,7 @@ impl<V: 'static + ViewBridge> StatsView<V> {
             state: Rc::new(RefCell::new(state)),
             reverse_sort: true,
             event_controllers,
+            history_samples: None,
         }
     }
 
@@ -315,6 +371,21 @@ impl<V: 'static + ViewBridge> StatsView<V> {
         title_view.cur_length = title_view.tabs[0].len();
     }
 
+    /// Updates the concrete view of a tab, e.g. to change its columns, and
+    /// its titles.
+    pub fn update_tab_view(&mut self, tab: &str, update: impl FnOnce(&mut V)) {
+        let bridge = match self.tab_view_map.get_mut(tab) {
+            Some(bridge) => bridge,
+            None => return,
+        };
+        update(bridge);
+        self.tab_titles_map
+            .insert(tab.to_owned(), bridge.get_titles());
+        if self.get_tab_view().get_cur_selected() == tab {
+            self.update_title();
+        }
+    }
+
     // Expose the OnEventView API.
     pub fn on_event<F, E>(mut self, trigger: E, cb: F) -> Self
     where
@@ -395,7 +466,7 @@ impl<V: 'static + ViewBridge> StatsView<V> {
             .detailed_view // OnEventView
             .get_inner_mut() // PanelView
             .get_inner_mut() // LinearLayout
-            .get_child_mut(2) // NamedView
+            .get_child_mut(3) // NamedView
             .expect("Fail to get cmd palette, StatsView may not properly init")
             .downcast_mut()
             .expect("Fail to downcast to cmd palette, StatsView may not properly init");
@@ -403,6 +474,131 @@ impl<V: 'static + ViewBridge> StatsView<V> {
         cmd_palette.get_mut()
     }
 
+    // A convenience function to get the history pane
+    fn get_history_pane(&mut self) -> &mut HideableView<TextView> {
+        self.detailed_view // OnEventView
+            .get_inner_mut() // PanelView
+            .get_inner_mut() // LinearLayout
+            .get_child_mut(2) // HideableView
+            .expect("Fail to get history pane, StatsView may not properly init")
+            .downcast_mut()
+            .expect("Fail to downcast to history pane, StatsView may not properly init")
+    }
+
+    /// Shows the history pane with the given number of samples, or toggles
+    /// it with the default number of samples if None.
+    pub fn toggle_history(&mut self, samples: Option<usize>) {
+        self.history_samples = match (samples, self.history_samples) {
+            (Some(samples), _) => Some(samples),
+            (None, Some(_)) => None,
+            (None, None) => Some(history::DEFAULT_HISTORY_SAMPLES),
+        };
+        let visible = self.history_samples.is_some();
+        self.get_history_pane().set_visible(visible);
+    }
+
+    /// Plots the selected column of the selected row over the last samples
+    /// in the history pane, if it is shown.
+    pub fn refresh_history(&mut self, c: &mut Cursive) {
+        let samples = match self.history_samples {
+            Some(samples) => samples,
+            None => return,
+        };
+        let selection = self.get_detail_view().selection();
+        let content = match selection {
+            Some(selected_key) => {
+                let cur_tab = self.get_tab_view().get_cur_selected().to_string();
+                let title_view = self.get_title_view();
+                let selected_column = title_view.current_selected;
+                let title = title_view.get_cur_selected().to_string();
+                let history = history::get_model_history(c, samples);
+                let state = self.state.borrow();
+                let fields = history
+                    .borrow()
+                    .models
+                    .iter()
+                    .map(|model| {
+                        V::get_history_field(
+                            &state,
+                            model,
+                            &selected_key,
+                            &cur_tab,
+                            selected_column,
+                        )
+                    })
+                    .collect();
+                history::render_history(&title, fields)
+            }
+            None => StyledString::plain("History: no row selected"),
+        };
+        self.get_history_pane().get_inner_mut().set_content(content);
+    }
+
+    /// Saves the tab, selected row and filter expression, and the state of
+    /// the concrete view.
+    pub fn save_view(&mut self, saved: &mut SavedView) {
+        saved.tab = Some(self.get_tab_view().get_cur_selected().trim().to_owned());
+        saved.selected = self
+            .get_detail_view()
+            .selection()
+            .map(|key| key.to_string());
+        saved.filter_expr = self.get_cmd_palette().get_filter_expr().map(str::to_owned);
+        self.state.borrow().save_view(saved);
+    }
+
+    /// Restores what save_view saved, except for the selected row, which can
+    /// only be selected after a refresh.
+    pub fn restore_view(&mut self, saved: &SavedView) -> Result<(), String> {
+        if let Some(tab) = &saved.tab {
+            let mut tab_view = self.get_tab_view();
+            let mut found = false;
+            for _ in 0..tab_view.tabs.len() {
+                if tab_view.get_cur_selected().trim() == tab {
+                    found = true;
+                    break;
+                }
+                tab_view.on_tab();
+            }
+            drop(tab_view);
+            self.update_title();
+            if !found {
+                return Err(format!("Unknown tab in saved view: {}", tab));
+            }
+        }
+
+        let filter_info = {
+            let mut state = self.state.borrow_mut();
+            // Views without filter expression support fail to clear it too
+            if let Err(e) = state.set_filter_expr(saved.filter_expr.as_deref()) {
+                if saved.filter_expr.is_some() {
+                    return Err(e);
+                }
+            }
+            state.restore_view(saved)?;
+            self.reverse_sort = saved.reverse;
+            state
+                .get_filter_info()
+                .as_ref()
+                .map(|(field, text)| (field.to_string(), text.clone()))
+        };
+        let mut cmd_palette = self.get_cmd_palette();
+        cmd_palette.set_filter_expr(saved.filter_expr.clone());
+        cmd_palette.set_filter(filter_info);
+        Ok(())
+    }
+
+    /// Selects the row with the given key if it is shown.
+    pub fn select_key(&mut self, c: &mut Cursive, key: &str) {
+        let pos = self
+            .get_detail_view()
+            .iter()
+            .position(|(_row, row_key)| row_key.to_string() == key);
+        if let Some(pos) = pos {
+            self.get_detail_view().set_selection(pos)(c);
+            self.get_list_scroll_view().scroll_to_important_area();
+        }
+    }
+
     // convenience function to get screen width
     pub fn get_screen_width(&mut self) -> usize {
         self.get_scroll_view().content_viewport().width()
@@ -466,6 +662,7 @@ impl<V: 'static + ViewBridge> StatsView<V> {
             }
         }
         self.get_list_scroll_view().scroll_to_important_area();
+        self.refresh_history(c);
     }
 
     // Chaining call. Use for construction to get initial data.
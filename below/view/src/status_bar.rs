@@ -20,6 +20,7 @@ use cursive::view::View;
 use cursive::views::TextView;
 use cursive::Cursive;
 
+use crate::ViewMode;
 use crate::ViewState;
 
 fn get_spacing() -> &'static str {
@@ -61,6 +62,23 @@ fn get_content(c: &mut Cursive) -> impl Into<StyledString> {
     header_str.append_plain(crate::get_version_str());
     header_str.append_plain(get_spacing());
     header_str.append_plain(view_state.view_mode_str());
+    if let ViewMode::Replay(_) = view_state.mode {
+        header_str.append_plain(format!(
+            "{}{}",
+            get_spacing(),
+            view_state.playback.borrow().get_status_str()
+        ));
+    }
+    if let Some(compare) = view_state.compare.as_ref().filter(|compare| compare.active) {
+        header_str.append_plain(format!("{}{}", get_spacing(), compare.get_status_str()));
+    }
+
+    // Leave out the borders of the panel
+    let width = c.screen_size().x.saturating_sub(2);
+    if let Some(timeline) = crate::playback::render_timeline(c, width) {
+        header_str.append_plain("\n");
+        header_str.append(timeline);
+    }
 
     header_str
 }
@@ -12,10 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use base_render::HasRenderConfig;
+use base_render::RenderConfigBuilder as Rc;
+use cursive::theme::BaseColor;
+use cursive::theme::Color;
+use model::FieldId;
+use model::Queriable;
+use model::SingleCgroupModelFieldId;
+use model::SingleProcessModelFieldId;
+use model::SystemModelFieldId;
 use serde::Deserialize;
 
 use super::get_belowrc_filename;
 use super::get_belowrc_view_section_key;
+use crate::cgroup_tabs;
+use crate::default_styles::HighlightRule;
+use crate::process_tabs;
+use crate::render::Comparator;
+use crate::render::HasViewStyle;
+use crate::render::Thresholds;
+use crate::render::ViewItem;
 
 /// Enum of supported front view.
 // We didn't re-use the MainViewState because we don't want to
@@ -28,6 +49,56 @@ pub enum DefaultFrontView {
     System,
 }
 
+/// A column of a tab in belowrc, e.g. `{ field = "cpu.usage_pct", width = 10 }`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ColumnRc {
+    pub field: String,
+    // Overrides the default width of the column.
+    pub width: Option<usize>,
+}
+
+/// The columns of a tab in belowrc, in the order they are shown.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct TabRc {
+    pub columns: Vec<ColumnRc>,
+}
+
+/// Views that highlight rules apply to.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightView {
+    Cgroup,
+    Process,
+    // The system view and the system core view
+    System,
+}
+
+/// Models of the system view that highlight rules can apply to, as the
+/// prefixes of their field ids.
+pub const SYSTEM_HIGHLIGHT_MODELS: [&str; 3] = ["cpu", "mem", "vm"];
+
+/// A rule in belowrc that highlights a field by its value, e.g.
+/// `{ view = "cgroup", field = "mem.events_oom_kill", crit = 0 }`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct HighlightRc {
+    pub view: HighlightView,
+    // Field id as in below dump, e.g. "cpu.usage_pct".
+    pub field: String,
+    // One of ">" (default), ">=", "<" and "<=".
+    #[serde(default)]
+    pub comparator: Comparator,
+    pub warn: Option<f64>,
+    pub crit: Option<f64>,
+    // Color names as in cursive, e.g. "yellow", "light red" or "#ff8000".
+    // Defaults to "light yellow".
+    pub warn_color: Option<String>,
+    // Defaults to "light red".
+    pub crit_color: Option<String>,
+    // Compare the value as a percentage of this field of the same model,
+    // e.g. "props.memory_max" for "mem.total".
+    pub relative_to: Option<String>,
+}
+
 /// Runtime configuration on the below view.
 #[derive(Default, Deserialize)]
 pub struct ViewRc {
@@ -39,6 +110,168 @@ pub struct ViewRc {
     pub collapse_cgroups: Option<bool>,
     // Overrides cgroup name column width.
     pub cgroup_name_width: Option<usize>,
+    // Columns of the cgroup view tabs, keyed by tab name as in
+    // [view.cgroup.general]. Tabs not in the cgroup view are added to it.
+    #[serde(default)]
+    pub cgroup: BTreeMap<String, TabRc>,
+    // Columns of the process view tabs, see cgroup.
+    #[serde(default)]
+    pub process: BTreeMap<String, TabRc>,
+    // Rules highlighting fields by their values, as [[view.highlight]]. The
+    // last rule for a field wins.
+    #[serde(default)]
+    pub highlight: Vec<HighlightRc>,
+}
+
+/// Tab names with their columns, in the order of the tabs.
+pub type TabItems<F> = Vec<(String, Vec<ViewItem<F>>)>;
+
+/// Key of a tab in belowrc, e.g. "io" for the "I/O" tab.
+pub fn get_tab_key(tab: &str) -> String {
+    tab.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+impl ColumnRc {
+    fn get_view_item<T, F>(&self) -> Result<ViewItem<F>, String>
+    where
+        T: Queriable<FieldId = F> + HasRenderConfig + HasViewStyle,
+        F: FieldId<Queriable = T> + FromStr,
+    {
+        let field_id =
+            F::from_str(&self.field).map_err(|_| format!("Unknown field: {}", self.field))?;
+        let item = ViewItem::from_default(field_id);
+        Ok(match self.width {
+            Some(width) => item.update(Rc::new().width(width)),
+            None => item,
+        })
+    }
+
+    /// Column of the view item, with its width only if it is not the
+    /// default one.
+    pub fn from_view_item<T, F>(item: &ViewItem<F>) -> Self
+    where
+        T: Queriable<FieldId = F> + HasRenderConfig + HasViewStyle,
+        F: FieldId<Queriable = T> + Clone + ToString,
+    {
+        let default_width = ViewItem::from_default(item.field_id.clone())
+            .config
+            .render_config
+            .width;
+        let width = item.config.render_config.width;
+        Self {
+            field: item.field_id.to_string(),
+            width: width.filter(|_| width != default_width),
+        }
+    }
+}
+
+/// Canonical field id of the view, e.g. "mem.total".
+fn parse_highlight_field(view: HighlightView, field: &str) -> Result<String, String> {
+    let unknown_field = |_| format!("Unknown field: {}", field);
+    match view {
+        HighlightView::Cgroup => SingleCgroupModelFieldId::from_str(field)
+            .map(|field_id| field_id.to_string())
+            .map_err(unknown_field),
+        HighlightView::Process => SingleProcessModelFieldId::from_str(field)
+            .map(|field_id| field_id.to_string())
+            .map_err(unknown_field),
+        HighlightView::System => {
+            let field = SystemModelFieldId::from_str(field)
+                .map_err(unknown_field)?
+                .to_string();
+            if !SYSTEM_HIGHLIGHT_MODELS.contains(&get_highlight_model(&field)) {
+                return Err(format!(
+                    "Only {} fields can be highlighted in the system view: {}",
+                    SYSTEM_HIGHLIGHT_MODELS.join(", "),
+                    field
+                ));
+            }
+            Ok(field)
+        }
+    }
+}
+
+/// Prefix of a field id, e.g. "mem" for "mem.total".
+pub fn get_highlight_model(field: &str) -> &str {
+    field.split('.').next().unwrap_or_default()
+}
+
+impl HighlightRc {
+    fn get_rule(&self) -> Result<HighlightRule, String> {
+        let field = parse_highlight_field(self.view, &self.field)?;
+        let relative_to = self
+            .relative_to
+            .as_deref()
+            .map(|relative_to| parse_highlight_field(self.view, relative_to))
+            .transpose()?;
+        if let Some(relative_to) = &relative_to {
+            // Only the fields of the model of the row are at hand
+            if self.view == HighlightView::System
+                && get_highlight_model(relative_to) != get_highlight_model(&field)
+            {
+                return Err(format!(
+                    "{} can only be relative to a {} field",
+                    field,
+                    get_highlight_model(&field)
+                ));
+            }
+        }
+        let get_threshold = |threshold: Option<f64>, color: &Option<String>, default: Color| {
+            let color = match color {
+                Some(color) => {
+                    Color::parse(color).ok_or_else(|| format!("Unknown color: {}", color))?
+                }
+                None => default,
+            };
+            Ok::<_, String>(threshold.map(|threshold| (threshold, color)))
+        };
+        let thresholds = Thresholds {
+            comparator: self.comparator,
+            warn: get_threshold(self.warn, &self.warn_color, Color::Light(BaseColor::Yellow))?,
+            crit: get_threshold(self.crit, &self.crit_color, Color::Light(BaseColor::Red))?,
+            relative: relative_to.is_some(),
+        };
+        if thresholds.warn.is_none() && thresholds.crit.is_none() {
+            return Err(format!("Missing warn or crit threshold for {}", field));
+        }
+        Ok(HighlightRule {
+            view: self.view,
+            field,
+            relative_to,
+            thresholds,
+        })
+    }
+}
+
+/// Replaces the columns of the tabs configured in belowrc and adds the tabs
+/// that are only in belowrc.
+fn apply_tabs_rc<T, F>(
+    mut tabs: TabItems<F>,
+    tabs_rc: &BTreeMap<String, TabRc>,
+) -> Result<TabItems<F>, String>
+where
+    T: Queriable<FieldId = F> + HasRenderConfig + HasViewStyle,
+    F: FieldId<Queriable = T> + FromStr,
+{
+    for (tab, tab_rc) in tabs_rc {
+        let items = tab_rc
+            .columns
+            .iter()
+            .map(ColumnRc::get_view_item)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("{}: {}", tab, e))?;
+        match tabs
+            .iter_mut()
+            .find(|(name, _)| get_tab_key(name) == get_tab_key(tab))
+        {
+            Some((_, tab_items)) => *tab_items = items,
+            None => tabs.push((tab.clone(), items)),
+        }
+    }
+    Ok(tabs)
 }
 
 impl ViewRc {
@@ -54,7 +287,10 @@ impl ViewRc {
                     if let Some(viewrc_val) = belowrc_val.get(get_belowrc_view_section_key()) {
                         // Got the [view] section, let's see if we can deserialize it to ViewRc
                         match viewrc_val.to_owned().try_into::<ViewRc>() {
-                            Ok(viewrc) => (viewrc, None),
+                            Ok(mut viewrc) => {
+                                let error = viewrc.check();
+                                (viewrc, error)
+                            }
                             Err(e) => (
                                 Default::default(),
                                 Some(format!(
@@ -76,4 +312,371 @@ impl ViewRc {
             _ => (Default::default(), None),
         }
     }
+
+    /// Tabs of the cgroup view with their columns.
+    pub fn get_cgroup_tabs(&self) -> Result<TabItems<SingleCgroupModelFieldId>, String> {
+        apply_tabs_rc(cgroup_tabs::default_tabs::get_tabs(), &self.cgroup)
+            .map_err(|e| format!("cgroup.{}", e))
+    }
+
+    /// Tabs of the process view with their columns.
+    pub fn get_process_tabs(&self) -> Result<TabItems<SingleProcessModelFieldId>, String> {
+        apply_tabs_rc(process_tabs::default_tabs::get_tabs(), &self.process)
+            .map_err(|e| format!("process.{}", e))
+    }
+
+    /// Highlight rules of all views.
+    pub fn get_highlight_rules(&self) -> Vec<HighlightRule> {
+        self.highlight
+            .iter()
+            .filter_map(|highlight| highlight.get_rule().ok())
+            .collect()
+    }
+
+    /// Falls back to the default columns of a view if any of its configured
+    /// columns is invalid, and drops invalid highlight rules. Returns the
+    /// errors.
+    fn check(&mut self) -> Option<String> {
+        let mut errors = Vec::new();
+        if let Err(e) = self.get_cgroup_tabs() {
+            self.cgroup.clear();
+            errors.push(e);
+        }
+        if let Err(e) = self.get_process_tabs() {
+            self.process.clear();
+            errors.push(e);
+        }
+        self.highlight
+            .retain(|highlight| match highlight.get_rule() {
+                Ok(_) => true,
+                Err(e) => {
+                    errors.push(format!("highlight: {}", e));
+                    false
+                }
+            });
+        if errors.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Failed to parse belowrc::{}.{}",
+            get_belowrc_view_section_key(),
+            errors.join(", ")
+        ))
+    }
+}
+
+fn get_or_insert_table<'a>(
+    table: &'a mut toml_edit::Table,
+    key: &str,
+) -> Result<&'a mut toml_edit::Table> {
+    table
+        .entry(key)
+        .or_insert_with(|| {
+            let mut table = toml_edit::Table::new();
+            table.set_implicit(true);
+            toml_edit::Item::Table(table)
+        })
+        .as_table_mut()
+        .ok_or_else(|| anyhow!("{} is not a table", key))
+}
+
+/// Sets the columns of a tab of the given view ("cgroup" or "process") in
+/// the content of belowrc, keeping the rest of it as it is.
+fn set_tab_columns(
+    belowrc_str: &str,
+    view: &str,
+    tab: &str,
+    columns: &[ColumnRc],
+) -> Result<String> {
+    let mut doc = belowrc_str.parse::<toml_edit::Document>()?;
+    let view_table = get_or_insert_table(
+        get_or_insert_table(doc.as_table_mut(), get_belowrc_view_section_key())?,
+        view,
+    )?;
+    // Keep the key of the tab as the user wrote it
+    let key = view_table
+        .iter()
+        .map(|(key, _)| key)
+        .find(|key| get_tab_key(key) == get_tab_key(tab))
+        .map_or_else(|| get_tab_key(tab), str::to_owned);
+
+    let mut array = toml_edit::Array::new();
+    for column in columns {
+        let mut inline_table = toml_edit::InlineTable::new();
+        inline_table.insert("field", column.field.as_str().into());
+        if let Some(width) = column.width {
+            inline_table.insert("width", (width as i64).into());
+        }
+        array.push_formatted(toml_edit::Value::from(inline_table).decorated("\n    ", ""));
+    }
+    array.set_trailing("\n");
+    array.set_trailing_comma(true);
+    get_or_insert_table(view_table, &key)?.insert("columns", toml_edit::value(array));
+    Ok(doc.to_string())
+}
+
+/// Writes the columns of a tab of the given view to belowrc.
+pub fn save_tab_columns(view: &str, tab: &str, columns: &[ColumnRc]) -> Result<()> {
+    let filename = get_belowrc_filename();
+    let belowrc_str = match std::fs::read_to_string(&filename) {
+        Ok(belowrc_str) => belowrc_str,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let belowrc_str = set_tab_columns(&belowrc_str, view, tab, columns)?;
+    if let Some(dir) = std::path::Path::new(&filename).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&filename, belowrc_str)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_viewrc(belowrc_str: &str) -> ViewRc {
+        belowrc_str
+            .parse::<toml::value::Value>()
+            .expect("Failed to parse belowrc")
+            .get(get_belowrc_view_section_key())
+            .expect("Missing view section")
+            .to_owned()
+            .try_into::<ViewRc>()
+            .expect("Failed to parse view section")
+    }
+
+    #[test]
+    fn viewrc_tab_columns() {
+        let mut viewrc = get_viewrc(
+            r#"
+[view.cgroup.general]
+columns = [
+    { field = "cpu.usage_pct" },
+    { field = "mem.total", width = 20 },
+]
+
+[view.cgroup.team]
+columns = [{ field = "io.rbytes_per_sec" }]
+
+[view.process."I/O"]
+columns = [{ field = "pid" }]
+"#,
+        );
+        assert_eq!(viewrc.check(), None);
+
+        let tabs = viewrc.get_cgroup_tabs().expect("Failed to get cgroup tabs");
+        let titles: Vec<_> = tabs.iter().map(|(tab, _)| tab.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "General",
+                "CPU",
+                "Mem",
+                "I/O",
+                "Pressure",
+                "Exited",
+                "Properties",
+                "team"
+            ]
+        );
+        let general: Vec<_> = tabs[0]
+            .1
+            .iter()
+            .map(|item| (item.field_id.to_string(), item.config.render_config.width))
+            .collect();
+        assert_eq!(general[0].0, "cpu.usage_pct");
+        assert_eq!(general[1], ("mem.total".to_owned(), Some(20)));
+        assert_eq!(general.len(), 2);
+        assert_eq!(
+            ColumnRc::from_view_item(&tabs[0].1[1]),
+            ColumnRc {
+                field: "mem.total".to_owned(),
+                width: Some(20),
+            }
+        );
+        assert_eq!(ColumnRc::from_view_item(&tabs[0].1[0]).width, None);
+
+        let tabs = viewrc
+            .get_process_tabs()
+            .expect("Failed to get process tabs");
+        assert_eq!(tabs.len(), 4);
+        assert_eq!(tabs[3].0, "I/O");
+        assert_eq!(tabs[3].1.len(), 1);
+        assert_eq!(tabs[3].1[0].field_id.to_string(), "pid");
+    }
+
+    #[test]
+    fn viewrc_invalid_tab_columns() {
+        let mut viewrc = get_viewrc(
+            r#"
+[view.cgroup.general]
+columns = [{ field = "cpu.usage_pct" }]
+
+[view.process.general]
+columns = [{ field = "no_such_field" }]
+"#,
+        );
+        let error = viewrc.check().expect("Invalid field not detected");
+        assert!(error.contains("process.general"), "{}", error);
+        assert!(error.contains("no_such_field"), "{}", error);
+        // Only the invalid view falls back to its default columns
+        assert_eq!(viewrc.cgroup.len(), 1);
+        assert!(viewrc.process.is_empty());
+    }
+
+    #[test]
+    fn viewrc_highlight_rules() {
+        let mut viewrc = get_viewrc(
+            r#"
+[[view.highlight]]
+view = "cgroup"
+field = "mem.total"
+warn = 80
+crit = 95
+relative_to = "props.memory_max"
+
+[[view.highlight]]
+view = "system"
+field = "mem.free"
+comparator = "<"
+crit = 1073741824
+crit_color = "magenta"
+
+[[view.highlight]]
+view = "process"
+field = "no_such_field"
+crit = 1
+
+[[view.highlight]]
+view = "system"
+field = "mem.free"
+relative_to = "cpu.usage_pct"
+crit = 1
+
+[[view.highlight]]
+view = "system"
+field = "disks.sda.read_bytes_per_sec"
+crit = 1
+
+[[view.highlight]]
+view = "cgroup"
+field = "cpu.usage_pct"
+crit = 1
+crit_color = "no_such_color"
+
+[[view.highlight]]
+view = "cgroup"
+field = "cpu.usage_pct"
+"#,
+        );
+        let error = viewrc.check().expect("Invalid rules not detected");
+        for expected in [
+            "no_such_field",
+            "mem.free can only be relative to a mem field",
+            "Only cpu, mem, vm fields",
+            "no_such_color",
+            "Missing warn or crit threshold for cpu.usage_pct",
+        ] {
+            assert!(error.contains(expected), "{}", error);
+        }
+
+        let rules = viewrc.get_highlight_rules();
+        assert_eq!(
+            rules,
+            vec![
+                HighlightRule {
+                    view: HighlightView::Cgroup,
+                    field: "mem.total".to_owned(),
+                    relative_to: Some("props.memory_max".to_owned()),
+                    thresholds: Thresholds {
+                        comparator: Comparator::Above,
+                        warn: Some((80.0, Color::Light(BaseColor::Yellow))),
+                        crit: Some((95.0, Color::Light(BaseColor::Red))),
+                        relative: true,
+                    },
+                },
+                HighlightRule {
+                    view: HighlightView::System,
+                    field: "mem.free".to_owned(),
+                    relative_to: None,
+                    thresholds: Thresholds {
+                        comparator: Comparator::Below,
+                        warn: None,
+                        crit: Some((1073741824.0, Color::Dark(BaseColor::Magenta))),
+                        relative: false,
+                    },
+                },
+            ]
+        );
+
+        let thresholds = &rules[0].thresholds;
+        assert_eq!(thresholds.get_color(50.0), None);
+        assert_eq!(
+            thresholds.get_color(90.0),
+            Some(Color::Light(BaseColor::Yellow))
+        );
+        assert_eq!(
+            thresholds.get_color(99.0),
+            Some(Color::Light(BaseColor::Red))
+        );
+        let thresholds = &rules[1].thresholds;
+        assert_eq!(
+            thresholds.get_color(1024.0),
+            Some(Color::Dark(BaseColor::Magenta))
+        );
+        assert_eq!(thresholds.get_color(2147483648.0), None);
+    }
+
+    #[test]
+    fn viewrc_set_tab_columns() {
+        let columns = vec![
+            ColumnRc {
+                field: "cpu.usage_pct".to_owned(),
+                width: None,
+            },
+            ColumnRc {
+                field: "mem.total".to_owned(),
+                width: Some(20),
+            },
+        ];
+        let belowrc_str = r#"# My belowrc
+[cmd]
+next_tab = 'b'
+
+[view]
+# Show process view first
+default_view = "process"
+
+[view.process."I/O"]
+columns = [{ field = "pid" }]
+"#;
+        let belowrc_str =
+            set_tab_columns(belowrc_str, "cgroup", "General", &columns).expect("Failed to set");
+        let belowrc_str =
+            set_tab_columns(&belowrc_str, "process", "I/O", &columns).expect("Failed to set");
+        assert!(belowrc_str.starts_with("# My belowrc\n[cmd]\nnext_tab = 'b'\n"));
+        assert!(belowrc_str.contains("# Show process view first\ndefault_view = \"process\"\n"));
+        assert!(
+            belowrc_str.contains(
+                r#"[view.cgroup.general]
+columns = [
+    { field = "cpu.usage_pct" },
+    { field = "mem.total", width = 20 },
+]
+"#
+            ),
+            "{}",
+            belowrc_str
+        );
+        assert!(
+            !belowrc_str.contains("[view.process.io]"),
+            "{}",
+            belowrc_str
+        );
+
+        let viewrc = get_viewrc(&belowrc_str);
+        assert_eq!(viewrc.cgroup["general"].columns, columns);
+        assert_eq!(viewrc.process["I/O"].columns, columns);
+    }
 }